@@ -0,0 +1,83 @@
+//! Disassembly of module and script blobs and of transaction programs, so users can see what a
+//! script does before signing it.
+
+use std::fmt::Write;
+use vm::{
+    def::{
+        file_format::{CompiledModule, CompiledScript},
+        printers::{ModuleListing, ScriptListing},
+    },
+    types::transaction::Program,
+};
+
+/// Disassembles a serialized module or script.
+pub fn disassemble_blob(data: &[u8]) -> Result<String, String> {
+    if let Ok(module) = CompiledModule::deserialize(data) {
+        return Ok(ModuleListing(&module).to_string());
+    }
+    CompiledScript::deserialize(data)
+        .map(|script| ScriptListing(&script).to_string())
+        .map_err(|e| format!("neither a module nor a script: {:?}", e))
+}
+
+/// Disassembles the script of `program`, the modules it publishes and lists its arguments.
+pub fn disassemble_program(program: &Program) -> Result<String, String> {
+    let mut listing = String::new();
+    for (idx, module) in program.modules().iter().enumerate() {
+        let module = CompiledModule::deserialize(module)
+            .map_err(|e| format!("invalid module {}: {:?}", idx, e))?;
+        writeln!(listing, "{}", ModuleListing(&module)).unwrap();
+    }
+    let script = CompiledScript::deserialize(program.code())
+        .map_err(|e| format!("invalid script: {:?}", e))?;
+    write!(listing, "{}", ScriptListing(&script)).unwrap();
+    writeln!(listing, "\nargs:").unwrap();
+    for (idx, arg) in program.args().iter().enumerate() {
+        writeln!(listing, "    {}: {:?}", idx, arg).unwrap();
+    }
+    Ok(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::{common, compile::compile_program};
+    use vm::types::transaction::TransactionArgument;
+
+    #[test]
+    fn disassemble_transfer_script() {
+        let listing = disassemble_blob(&common::PEER_TO_PEER).unwrap();
+        assert!(listing.starts_with("script\n"));
+        assert!(listing.contains("import 0x0.LibraAccount"));
+        assert!(listing.contains("main(Address, Integer): ()"));
+        assert!(listing.contains("    0: "));
+    }
+
+    #[test]
+    fn disassemble_published_module() {
+        let code = "
+            modules:
+            module M {
+                resource T { value: u64 }
+                public sum(a: u64, b: u64): u64 {
+                    return move(a) + move(b);
+                }
+            }
+            script:
+            main(x: u64) {
+                return;
+            }
+        ";
+        let program = compile_program(code, vec![TransactionArgument::U64(7)]);
+        let listing = disassemble_program(&program).unwrap();
+        assert!(listing.contains("module 0x0.M"));
+        assert!(listing.contains("resource T {\n    value: Integer\n}"));
+        assert!(listing.contains("public sum(Integer, Integer): (Integer)"));
+        assert!(listing.contains("    0: {U64: 7}"));
+    }
+
+    #[test]
+    fn disassemble_garbage() {
+        disassemble_blob(&[0xde, 0xad]).unwrap_err();
+    }
+}
@@ -0,0 +1,121 @@
+//! Gas estimation by dry running transactions against a local genesis state.
+
+use mock::{
+    account::{Account, AccountData},
+    executor::FakeExecutor,
+};
+use std::collections::HashSet;
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    def::{
+        access::ModuleAccess,
+        file_format::CompiledModule,
+        gas_schedule::{self, GasAlgebra},
+    },
+    types::{
+        account_config::association_address,
+        transaction::{Program, TransactionStatus},
+        vm_error::{ExecutionStatus, VMStatus},
+        AccountAddress, ModuleId,
+    },
+};
+
+/// The balance the sender is given in a dry run when its real balance is unknown.
+pub const DRY_RUN_BALANCE: u64 = 1_000_000_000_000;
+
+/// Checks that `gas_price` is accepted by the VM.
+pub fn check_gas_price(gas_price: u64) -> Result<(), String> {
+    let min = gas_schedule::MIN_PRICE_PER_GAS_UNIT.get();
+    let max = gas_schedule::MAX_PRICE_PER_GAS_UNIT.get();
+    if gas_price < min || gas_price > max {
+        return Err(format!(
+            "gas price {} out of range, should be between {} and {}",
+            gas_price, min, max
+        ));
+    }
+    Ok(())
+}
+
+/// Estimates the max gas of a transaction running `program`.
+///
+/// The transaction is executed with the maximum gas against the genesis state plus the `modules`
+/// it does not publish itself, with the sender holding `balance` at `sequence_number`. The gas
+/// used gets a 10% margin, since the state on chain may differ from the local one.
+pub fn estimate_max_gas(
+    sender: AccountAddress,
+    sequence_number: u64,
+    balance: u64,
+    program: Program,
+    modules: &[VerifiedModule],
+) -> Result<u64, String> {
+    let published: HashSet<ModuleId> = program
+        .modules()
+        .iter()
+        .filter_map(|data| CompiledModule::deserialize(data).ok())
+        .map(|module| module.self_id())
+        .collect();
+    let mut executor = FakeExecutor::from_genesis_file();
+    for module in modules {
+        if !published.contains(&module.self_id()) {
+            executor.add_module(&module.self_id(), module.as_inner());
+        }
+    }
+
+    // The dry run is signed by a throwaway key, except for the association whose key is known.
+    let account = if sender == association_address() {
+        Account::new_association()
+    } else {
+        Account::mock_from_address(sender)
+    };
+    let sender_data = AccountData::with_account(account, balance, sequence_number);
+    executor.add_account_data(&sender_data);
+
+    let max_gas = gas_schedule::MAXIMUM_NUMBER_OF_GAS_UNITS.get();
+    let txn =
+        sender_data
+            .account()
+            .create_signed_txn_impl(sender, program, sequence_number, max_gas, 0);
+    let output = executor.execute_transaction(txn);
+    match output.status() {
+        TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed)) => {
+            let gas_used = output.gas_used();
+            Ok((gas_used + gas_used / 10).min(max_gas))
+        }
+        status => Err(format!("dry run failed: {:?}", status)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::common;
+    use vm::types::transaction::TransactionArgument;
+
+    fn transfer(amount: u64) -> Program {
+        let args = vec![
+            TransactionArgument::Address(*Account::new().address()),
+            TransactionArgument::U64(amount),
+        ];
+        Program::new(common::PEER_TO_PEER.clone(), vec![], args)
+    }
+
+    #[test]
+    fn estimate_transfer() {
+        let sender = *Account::new().address();
+        let max_gas = estimate_max_gas(sender, 3, 1_000_000, transfer(100), &[]).unwrap();
+        assert!(max_gas > 0);
+        assert!(max_gas <= gas_schedule::MAXIMUM_NUMBER_OF_GAS_UNITS.get());
+    }
+
+    #[test]
+    fn estimate_failing_transfer() {
+        let sender = *Account::new().address();
+        estimate_max_gas(sender, 0, 10, transfer(100), &[]).unwrap_err();
+    }
+
+    #[test]
+    fn check_price_range() {
+        check_gas_price(0).unwrap();
+        check_gas_price(gas_schedule::MAX_PRICE_PER_GAS_UNIT.get() + 1).unwrap_err();
+    }
+}
@@ -0,0 +1,246 @@
+//! An encrypted keystore of named accounts.
+//!
+//! Every account is kept in its own json file `<name>.json`. The private key is encrypted with
+//! AES-128-CTR under a key derived from the passphrase by scrypt, and authenticated with a
+//! SHA3-256 mac over the second half of the derived key and the cipher text.
+
+use aes_ctr::{
+    stream_cipher::{generic_array::GenericArray, NewStreamCipher, SyncStreamCipher},
+    Aes128Ctr,
+};
+use crypto::{signing::KeyPair, PrivateKey};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use tiny_keccak::Keccak;
+use vm::types::AccountAddress;
+
+const KEYSTORE_DIR: &str = "keystore";
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Secret data encrypted under a passphrase.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EncryptedData {
+    cipher: String,
+    iv: String,
+    ciphertext: String,
+    kdf: String,
+    salt: String,
+    log_n: u8,
+    r: u32,
+    p: u32,
+    mac: String,
+}
+
+impl EncryptedData {
+    /// Encrypts `data` with `passphrase`.
+    pub fn encrypt(data: &[u8], passphrase: &str) -> Result<Self, String> {
+        let mut rng = OsRng::new().map_err(|e| e.to_string())?;
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut iv);
+
+        let derived = derive_key(passphrase, &salt, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)?;
+        let mut ciphertext = data.to_vec();
+        apply_cipher(&derived[..16], &iv, &mut ciphertext);
+        let mac = mac(&derived[16..], &ciphertext);
+
+        Ok(EncryptedData {
+            cipher: "aes-128-ctr".to_string(),
+            iv: hex::encode(iv),
+            ciphertext: hex::encode(ciphertext),
+            kdf: "scrypt".to_string(),
+            salt: hex::encode(salt),
+            log_n: SCRYPT_LOG_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            mac: hex::encode(mac),
+        })
+    }
+
+    /// Decrypts the data with `passphrase`.
+    pub fn decrypt(&self, passphrase: &str) -> Result<Vec<u8>, String> {
+        let salt = hex::decode(&self.salt).map_err(|e| e.to_string())?;
+        let iv = hex::decode(&self.iv).map_err(|e| e.to_string())?;
+        let mut data = hex::decode(&self.ciphertext).map_err(|e| e.to_string())?;
+        let expected_mac = hex::decode(&self.mac).map_err(|e| e.to_string())?;
+
+        let derived = derive_key(passphrase, &salt, self.log_n, self.r, self.p)?;
+        if !constant_time_eq(&mac(&derived[16..], &data), &expected_mac) {
+            return Err("invalid passphrase".to_string());
+        }
+        apply_cipher(&derived[..16], &iv, &mut data);
+        Ok(data)
+    }
+}
+
+/// A named account in the keystore.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KeyFile {
+    pub name: String,
+    pub address: String,
+    pub public_key: String,
+    crypto: EncryptedData,
+}
+
+impl KeyFile {
+    /// Encrypts `key_pair` with `passphrase`.
+    pub fn encrypt(name: &str, key_pair: &KeyPair, passphrase: &str) -> Result<Self, String> {
+        let crypto = EncryptedData::encrypt(&key_pair.private_key().to_slice(), passphrase)?;
+        let public_key = key_pair.public_key();
+        Ok(KeyFile {
+            name: name.to_string(),
+            address: AccountAddress::from(public_key).to_string(),
+            public_key: format!("0x{}", public_key),
+            crypto,
+        })
+    }
+
+    /// Decrypts the key pair with `passphrase`.
+    pub fn decrypt(&self, passphrase: &str) -> Result<KeyPair, String> {
+        let data = self.crypto.decrypt(passphrase)?;
+        let key = PrivateKey::from_slice(&data).map_err(|e| e.to_string())?;
+        Ok(KeyPair::new(key))
+    }
+}
+
+/// The directory holding the key files.
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    /// Opens the keystore under the caster data directory `root`, creating it if needed.
+    pub fn open(root: &PathBuf) -> Self {
+        let dir = root.join(KEYSTORE_DIR);
+        if !dir.exists() {
+            fs::create_dir_all(&dir).expect("should create keystore directory");
+        }
+        KeyStore { dir }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Stores `key_pair` under `name`, failing if the name is taken.
+    pub fn insert(
+        &self,
+        name: &str,
+        key_pair: &KeyPair,
+        passphrase: &str,
+    ) -> Result<KeyFile, String> {
+        check_name(name)?;
+        let path = self.path(name);
+        if path.exists() {
+            return Err(format!("account {} already exists", name));
+        }
+        let key_file = KeyFile::encrypt(name, key_pair, passphrase)?;
+        let data = serde_json::to_vec_pretty(&key_file).map_err(|e| e.to_string())?;
+        fs::write(path, data).map_err(|e| e.to_string())?;
+        Ok(key_file)
+    }
+
+    /// Loads the key file of account `name`.
+    pub fn get(&self, name: &str) -> Result<KeyFile, String> {
+        let data =
+            fs::read(self.path(name)).map_err(|_| format!("account {} does not exist", name))?;
+        serde_json::from_slice(&data).map_err(|e| e.to_string())
+    }
+
+    /// Lists all accounts, ordered by name.
+    pub fn list(&self) -> Vec<KeyFile> {
+        let mut accounts: Vec<KeyFile> = fs::read_dir(&self.dir)
+            .expect("read_dir call failed")
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| fs::read(entry.path()).ok())
+            .filter_map(|data| serde_json::from_slice(&data).ok())
+            .collect();
+        accounts.sort_by(|a, b| a.name.cmp(&b.name));
+        accounts
+    }
+}
+
+/// Checks that `name` can be used as a file name.
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(|c: char| c == '/' || c == '\\' || c == '.') {
+        Err(format!("invalid name {:?}", name))
+    } else {
+        Ok(())
+    }
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<[u8; 32], String> {
+    let params = scrypt::ScryptParams::new(log_n, r, p).map_err(|e| e.to_string())?;
+    let mut derived = [0u8; 32];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut derived)
+        .map_err(|e| e.to_string())?;
+    Ok(derived)
+}
+
+fn apply_cipher(key: &[u8], iv: &[u8], data: &mut [u8]) {
+    let mut cipher = Aes128Ctr::new(GenericArray::from_slice(key), GenericArray::from_slice(iv));
+    cipher.apply_keystream(data);
+}
+
+fn mac(key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut sha3 = Keccak::new_sha3_256();
+    sha3.update(key);
+    sha3.update(ciphertext);
+    let mut mac = [0u8; 32];
+    sha3.finalize(&mut mac);
+    mac
+}
+
+// Compares `a` and `b` in a time that depends only on their lengths, so that a wrong mac does not
+// tell how many of its leading bytes are right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Prompts for a passphrase on the terminal without echoing it.
+pub fn prompt_passphrase(prompt: &str) -> String {
+    rpassword::read_password_from_tty(Some(prompt)).expect("should read passphrase")
+}
+
+/// Prompts for a new passphrase twice and checks that both entries match.
+pub fn prompt_new_passphrase() -> String {
+    let passphrase = prompt_passphrase("New passphrase: ");
+    let repeat = prompt_passphrase("Repeat passphrase: ");
+    if passphrase != repeat {
+        println!("passphrases do not match");
+        std::process::exit(1);
+    }
+    passphrase
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let (private_key, _) = crypto::signing::generate_keypair();
+        let key_pair = KeyPair::new(private_key);
+        let key_file = KeyFile::encrypt("alice", &key_pair, "secret").unwrap();
+
+        assert_eq!(key_file.decrypt("secret").unwrap(), key_pair);
+        key_file.decrypt("wrong").unwrap_err();
+    }
+
+    #[test]
+    fn compare_macs() {
+        assert!(constant_time_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(!constant_time_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!constant_time_eq(&[1, 2, 3], &[1, 2]));
+    }
+}
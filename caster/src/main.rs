@@ -1,10 +1,20 @@
+mod disasm;
+mod gas;
+mod keystore;
+mod node;
+mod offline;
+mod package;
+mod pending;
+mod script_args;
+mod wallet;
+
 use canonical_serialization::{SimpleDeserializer, SimpleSerializer};
-use clap::{App, Arg, ArgMatches, SubCommand};
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
 use compiler;
 use core::convert::TryFrom;
 use crypto::{signing, signing::KeyPair, PrivateKey, PublicKey};
 use hex;
-use mock::{account::Account, common, compile::*, gas_costs};
+use mock::{account::Account, common, compile::*};
 use serde::{Deserialize, Serialize};
 use std::{
     fs,
@@ -15,15 +25,20 @@ use std::{
 };
 use vm::{
     bytecode_verifier::VerifiedModule,
-    def::file_format::CompiledModule,
+    def::{access::ModuleAccess, file_format::CompiledModule},
     types::{
-        account_config::AccountResource,
-        transaction::{Program, SignedTransaction, TransactionArgument},
+        account_config::{association_address, AccountResource},
+        transaction::{Program, RawTransaction, TransactionArgument, TransactionPayload},
         AccessPath, AccountAddress,
     },
 };
 // use num_traits::real::Real;
 use dirs;
+use keystore::KeyStore;
+use node::NodeClient;
+use pending::PendingCache;
+use script_args::{check_script_args, parse_script_args};
+use wallet::WalletStore;
 
 fn main() {
     let args = App::new("bool node move cli")
@@ -36,18 +51,24 @@ fn main() {
         .subcommand(generate_sub_command_get_address())
         .subcommand(generate_sub_command_get_public_key())
         .subcommand(generate_sub_command_account())
+        .subcommand(generate_sub_command_wallet())
+        .subcommand(generate_sub_command_package())
+        .subcommand(generate_sub_command_disasm())
+        .subcommand(generate_sub_command_snapshot())
         .get_matches();
 
-    if let (command, Some(matches)) = args.subcommand() {
-        match command {
-            "tx" => deal_command_make_tx(matches),
-            "account" => deal_command_account(matches),
-            "decode" => deal_command_decode(matches),
-            "get_access_path" => deal_command_get_access_path(matches),
-            "get_address" => deal_command_get_address(matches),
-            "get_public_key" => deal_command_get_public_key(matches),
-            _ => unimplemented!(),
-        }
+    match args.subcommand() {
+        ("tx", Some(matches)) => deal_command_make_tx(matches),
+        ("account", Some(matches)) => deal_command_account(matches),
+        ("wallet", Some(matches)) => deal_command_wallet(matches),
+        ("package", Some(matches)) => deal_command_package(matches),
+        ("disasm", Some(matches)) => deal_command_disasm(matches),
+        ("snapshot", Some(matches)) => deal_command_snapshot(matches),
+        ("decode", Some(matches)) => deal_command_decode(matches),
+        ("get_access_path", Some(matches)) => deal_command_get_access_path(matches),
+        ("get_address", Some(matches)) => deal_command_get_address(matches),
+        ("get_public_key", Some(matches)) => deal_command_get_public_key(matches),
+        _ => exit_with_error(args.usage()),
     }
 }
 
@@ -61,58 +82,163 @@ fn generate_sub_command_tx<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true)
                 .help("faucet account file path."),
         )
-        .arg(
-            Arg::with_name("key")
-                .short("k")
-                .long("key")
-                .takes_value(true)
-                .help("sender's private key. invalid if faucet account file present."),
-        )
-        .arg(
-            Arg::with_name("program")
-                .short("m")
-                .long("program")
-                .takes_value(true)
-                .help("program should one of `create_account`, `mint` or `transfer`."),
-        )
-        .arg(
-            Arg::with_name("recipient")
-                .short("r")
-                .long("recipient")
-                .takes_value(true)
-                .help("pubkey of recipient."),
-        )
-        .arg(
-            Arg::with_name("value")
-                .short("v")
-                .long("value")
-                .takes_value(true)
-                .help("number of coins."),
+        .args(&key_args())
+        .args(&program_args())
+        .args(&txn_params_args())
+        .args(&submit_args())
+        .subcommand(
+            SubCommand::with_name("build")
+                .about("build an unsigned transaction for offline signing")
+                .arg(
+                    Arg::with_name("sender")
+                        .long("sender")
+                        .takes_value(true)
+                        .required(true)
+                        .help("address of the account sending the transaction."),
+                )
+                .args(&program_args())
+                .args(&txn_params_args())
+                .arg(node_arg())
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .required(true)
+                        .help("file to write the raw transaction to."),
+                ),
         )
-        .arg(
-            Arg::with_name("sequence_number")
-                .short("s")
-                .long("sequence_number")
-                .takes_value(true)
-                .help("sender account sequence number."),
-        )
-        .arg(
-            Arg::with_name("compiled_file")
-                .short("cf")
-                .long("compiled_file")
-                .takes_value(true)
-                .help("complied file path."),
+        .subcommand(
+            SubCommand::with_name("sign")
+                .about("sign a raw transaction, producing a detached signature")
+                .args(&key_args())
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .takes_value(true)
+                        .required(true)
+                        .help("raw transaction file."),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .required(true)
+                        .help("file to write the signature to."),
+                ),
         )
-        .arg(
-            Arg::with_name("params")
-                .short("p")
-                .long("params")
-                .takes_value(true)
-                .help("script parameters"),
+        .subcommand(
+            SubCommand::with_name("assemble")
+                .about("combine a raw transaction with its signature")
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .takes_value(true)
+                        .required(true)
+                        .help("raw transaction file."),
+                )
+                .arg(
+                    Arg::with_name("signature")
+                        .long("signature")
+                        .takes_value(true)
+                        .required(true)
+                        .help("signature file produced by `tx sign`."),
+                )
+                .args(&submit_args()),
         );
     subcommand
 }
 
+fn key_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("key")
+            .short("k")
+            .long("key")
+            .takes_value(true)
+            .help("sender's private key. invalid if faucet account file present."),
+        Arg::with_name("from")
+            .long("from")
+            .takes_value(true)
+            .conflicts_with("key")
+            .help("name of the keystore account sending the transaction."),
+    ]
+}
+
+fn program_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("program")
+            .short("m")
+            .long("program")
+            .takes_value(true)
+            .help("program should one of `create_account`, `mint`, `transfer` or `publish`."),
+        Arg::with_name("recipient")
+            .short("r")
+            .long("recipient")
+            .takes_value(true)
+            .help("pubkey of recipient."),
+        Arg::with_name("value")
+            .short("v")
+            .long("value")
+            .takes_value(true)
+            .help("number of coins."),
+        Arg::with_name("compiled_file")
+            .short("cf")
+            .long("compiled_file")
+            .takes_value(true)
+            .help("complied file path."),
+        Arg::with_name("params")
+            .short("p")
+            .long("params")
+            .takes_value(true)
+            .help("script parameters, e.g. '42 0x1 b\"00ff\" \"hello\"'."),
+    ]
+}
+
+fn txn_params_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("sequence_number")
+            .short("s")
+            .long("sequence_number")
+            .takes_value(true)
+            .help("sender account sequence number. read from the node and pending transactions if absent."),
+        Arg::with_name("max_gas")
+            .long("max_gas")
+            .takes_value(true)
+            .help("max gas amount. estimated by a dry run if absent."),
+        Arg::with_name("gas_price")
+            .long("gas_price")
+            .takes_value(true)
+            .default_value("0")
+            .help("gas unit price."),
+    ]
+}
+
+fn node_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("node")
+        .long("node")
+        .takes_value(true)
+        .default_value(node::DEFAULT_NODE_URL)
+        .help("websocket rpc url of the node.")
+}
+
+fn submit_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("submit")
+            .long("submit")
+            .takes_value(false)
+            .help("submit the transaction to a node and wait for its inclusion."),
+        node_arg(),
+        Arg::with_name("suri")
+            .long("suri")
+            .takes_value(true)
+            .default_value("//Alice")
+            .help("secret uri of the substrate key signing the extrinsic."),
+    ]
+}
+
 fn parse_pubkey_coin(args: &ArgMatches) -> (PublicKey, u64) {
     let receiver = args
         .value_of("recipient")
@@ -144,80 +270,264 @@ fn parse_address_coin(args: &ArgMatches) -> (AccountAddress, u64) {
     (receiver, num_coins)
 }
 
+/// Loads the sender key pair, either from the keystore account given by `--from` or from the raw
+/// private key given by `--key`.
+fn load_key_pair(args: &ArgMatches) -> KeyPair {
+    if let Some(name) = args.value_of("from") {
+        let key_file = KeyStore::open(&app_path())
+            .get(name)
+            .unwrap_or_else(|err| exit_with_error(&err));
+        let passphrase = keystore::prompt_passphrase(&format!("Passphrase for {}: ", name));
+        key_file
+            .decrypt(&passphrase)
+            .unwrap_or_else(|err| exit_with_error(&err))
+    } else {
+        args.value_of("key")
+            .map(|input| hex::decode(&input[2..]))
+            .map(|data| PrivateKey::from_slice(&data.unwrap()))
+            .map(|key| KeyPair::new(key.unwrap()))
+            .expect("should provide private key or keystore account")
+    }
+}
+
+fn exit_with_error(err: &str) -> ! {
+    println!("{}", err);
+    std::process::exit(1);
+}
+
 fn deal_command_make_tx(args: &ArgMatches) {
-    let key_pair = args
-        .value_of("key")
-        .map(|input| hex::decode(&input[2..]))
-        .map(|data| PrivateKey::from_slice(&data.unwrap()))
-        .map(|key| KeyPair::new(key.unwrap()))
-        .expect("should private key");
+    match args.subcommand() {
+        ("build", Some(matches)) => return deal_command_build_tx(matches),
+        ("sign", Some(matches)) => return deal_command_sign_tx(matches),
+        ("assemble", Some(matches)) => return deal_command_assemble_tx(matches),
+        _ => (),
+    }
 
-    let sequence_number = args
-        .value_of("sequence_number")
-        .expect("should sequence number")
-        .parse()
-        .unwrap();
+    let key_pair = load_key_pair(args);
+    let address = AccountAddress::from(key_pair.public_key());
+    let (sender, program) = make_program(args, &address);
+    let (raw_txn, resolved_sequence_number) = make_raw_transaction(args, sender, program);
+    let signed_txn = raw_txn
+        .sign(key_pair.private_key(), key_pair.public_key())
+        .expect("should sign transaction")
+        .into_inner();
+
+    let se_txn = SimpleSerializer::<Vec<u8>>::serialize(&signed_txn).unwrap();
+    println!("0x{}", hex::encode(&se_txn));
+    // A sequence number given on the command line is the user's to manage.
+    if submit_if_requested(args, se_txn) && resolved_sequence_number {
+        record_pending(&sender, signed_txn.sequence_number());
+    }
+}
+
+/// Returns the transaction sender and the program selected by `--program`. `address` is the
+/// account the transaction is built for; mint transactions are sent by the association instead.
+fn make_program(args: &ArgMatches, address: &AccountAddress) -> (AccountAddress, Program) {
+    let script = |code: &Vec<u8>| {
+        let (receiver, num_coins) = parse_address_coin(&args);
+        let args = vec![
+            TransactionArgument::Address(receiver),
+            TransactionArgument::U64(num_coins),
+        ];
+        Program::new(code.clone(), vec![], args)
+    };
 
-    let signed_txn = match args
+    match args
         .value_of("program")
-        .expect("should provide program method")
+        .unwrap_or_else(|| exit_with_error("should provide program method"))
     {
-        "create_account" => {
-            let (receiver, num_coins) = parse_address_coin(&args);
-            common::create_account_txn(
-                &Account::from_keypair(key_pair),
-                &Account::mock_from_address(receiver),
-                sequence_number,
-                num_coins,
-            )
-        }
-        "mint" => {
-            let (receiver, num_coins) = parse_address_coin(&args);
-            common::mint_txn(
-                &Account::from_keypair(key_pair),
-                &Account::mock_from_address(receiver),
-                sequence_number,
-                num_coins,
-            )
-        }
-        "transfer" => {
-            let (receiver, num_coins) = parse_address_coin(&args);
-            common::peer_to_peer_txn(
-                &Account::from_keypair(key_pair),
-                &Account::mock_from_address(receiver),
-                sequence_number,
-                num_coins,
-            )
-        }
+        "create_account" => (*address, script(&common::CREATE_ACCOUNT)),
+        "mint" => (association_address(), script(&common::MINT)),
+        "transfer" => (*address, script(&common::PEER_TO_PEER)),
         "publish" => {
             let params = args
                 .value_of("params")
-                .map_or(vec![], |p| parse_script_args(p).expect("invalid params"));
-            compile_and_publish(
-                &Account::from_keypair(key_pair),
+                .map_or(vec![], |p| {
+                    parse_script_args(p)
+                        .unwrap_or_else(|err| exit_with_error(&format!("invalid params: {}", err)))
+                });
+            let program = compile_and_publish(
+                address,
                 args.value_of("compiled_file").expect("should has file"),
                 params,
-                sequence_number,
-            )
+            );
+            (*address, program)
         }
-        _ => unimplemented!(),
+        other => exit_with_error(&format!(
+            "unknown program `{}`, expected one of `create_account`, `mint`, `transfer` or `publish`",
+            other
+        )),
+    }
+}
+
+/// Builds the raw transaction of `program`. The sequence number and max gas not given on the
+/// command line are resolved from the node, the pending transactions and a dry run. Also returns
+/// whether the sequence number was resolved rather than given.
+fn make_raw_transaction(
+    args: &ArgMatches,
+    sender: AccountAddress,
+    program: Program,
+) -> (RawTransaction, bool) {
+    let gas_price = parse_u64_arg(args, "gas_price").unwrap_or(0);
+    gas::check_gas_price(gas_price).unwrap_or_else(|err| exit_with_error(&err));
+    let sequence_number = parse_u64_arg(args, "sequence_number");
+    let resolved_sequence_number = sequence_number.is_none();
+    let max_gas = parse_u64_arg(args, "max_gas");
+
+    let mut client = if sequence_number.is_none() || max_gas.is_none() {
+        connect_node(args)
+    } else {
+        None
     };
+    let sequence_number = sequence_number.unwrap_or_else(|| {
+        let on_chain = client.as_mut().map(|client| {
+            client
+                .sequence_number(&sender)
+                .unwrap_or_else(|err| exit_with_error(&err))
+                .unwrap_or(0)
+        });
+        let mut pending = PendingCache::open(&app_path());
+        let sequence_number = pending
+            .next_sequence_number(&sender, on_chain)
+            .unwrap_or_else(|| {
+                exit_with_error("can not determine the sequence number, pass --sequence_number")
+            });
+        pending.save().unwrap_or_else(|err| exit_with_error(&err));
+        sequence_number
+    });
+    let max_gas = max_gas.unwrap_or_else(|| {
+        let balance = client
+            .as_mut()
+            .and_then(|client| {
+                client
+                    .balance(&sender)
+                    .unwrap_or_else(|err| exit_with_error(&err))
+            })
+            .unwrap_or(gas::DRY_RUN_BALANCE);
+        let modules = load_local_modules(&home_path());
+        gas::estimate_max_gas(sender, sequence_number, balance, program.clone(), &modules)
+            .unwrap_or_else(|err| exit_with_error(&err))
+    });
+
+    let raw_txn =
+        offline::build_raw_transaction(sender, sequence_number, program, max_gas, gas_price);
+    (raw_txn, resolved_sequence_number)
+}
+
+/// Records a submitted transaction of `sender` in the pending transactions, so the next one
+/// does not reuse its sequence number.
+fn record_pending(sender: &AccountAddress, sequence_number: u64) {
+    let mut pending = PendingCache::open(&app_path());
+    pending.record(sender, sequence_number);
+    pending.save().unwrap_or_else(|err| exit_with_error(&err));
+}
+
+/// Connects to the node given by `--node`, or returns `None` with a warning if it is not
+/// reachable.
+fn connect_node(args: &ArgMatches) -> Option<NodeClient> {
+    let url = args.value_of("node")?;
+    match NodeClient::connect(url) {
+        Ok(client) => Some(client),
+        Err(err) => {
+            eprintln!("{}, using local state only", err);
+            None
+        }
+    }
+}
+
+fn parse_u64_arg(args: &ArgMatches, name: &str) -> Option<u64> {
+    args.value_of(name).map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|_| exit_with_error(&format!("invalid {}: {}", name, value)))
+    })
+}
+
+/// Submits the transaction if `--submit` is given, and tells whether it was submitted.
+fn submit_if_requested(args: &ArgMatches, se_txn: Vec<u8>) -> bool {
+    if !args.is_present("submit") {
+        return false;
+    }
+    let url = args.value_of("node").expect("should provide node url");
+    let suri = args.value_of("suri").expect("should provide substrate key");
+    if let Err(err) = node::submit_transaction(url, suri, se_txn) {
+        exit_with_error(&format!("submit transaction failed: {}", err));
+    }
+    true
+}
+
+fn deal_command_build_tx(args: &ArgMatches) {
+    let address = args
+        .value_of("sender")
+        .map(AccountAddress::from_hex_literal)
+        .expect("should provide sender address")
+        .unwrap_or_else(|err| exit_with_error(&format!("invalid sender address: {}", err)));
+    let (sender, program) = make_program(args, &address);
+    let (raw_txn, _) = make_raw_transaction(args, sender, program);
+    let output = args.value_of("output").expect("should provide output file");
+    fs::write(output, offline::encode_raw_transaction(&raw_txn))
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    println!("{}", offline::summary(&raw_txn));
+}
+
+fn deal_command_sign_tx(args: &ArgMatches) {
+    let raw_txn = read_raw_transaction(
+        args.value_of("input")
+            .expect("should provide raw transaction file"),
+    );
+    println!("{}", offline::summary(&raw_txn));
+    if let TransactionPayload::Program(program) = raw_txn.clone().into_payload() {
+        match disasm::disassemble_program(&program) {
+            Ok(listing) => println!("{}", listing),
+            Err(err) => println!("can not disassemble the program: {}", err),
+        }
+    }
+
+    let key_pair = load_key_pair(args);
+    let signature = offline::sign(raw_txn, &key_pair).unwrap_or_else(|err| exit_with_error(&err));
+    let output = args.value_of("output").expect("should provide output file");
+    fs::write(output, serde_json::to_vec_pretty(&signature).unwrap())
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    println!("signature written to {}", output);
+}
 
+fn deal_command_assemble_tx(args: &ArgMatches) {
+    let raw_txn = read_raw_transaction(
+        args.value_of("input")
+            .expect("should provide raw transaction file"),
+    );
+    let path = args
+        .value_of("signature")
+        .expect("should provide signature file");
+    let signature = fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|data| serde_json::from_slice(&data).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| exit_with_error(&format!("invalid signature file: {}", err)));
+
+    let signed_txn =
+        offline::assemble(raw_txn, &signature).unwrap_or_else(|err| exit_with_error(&err));
     let se_txn = SimpleSerializer::<Vec<u8>>::serialize(&signed_txn).unwrap();
-    let hex = hex::encode(se_txn);
-    println!("0x{}", hex);
+    println!("0x{}", hex::encode(&se_txn));
+    submit_if_requested(args, se_txn);
+}
+
+fn read_raw_transaction(path: &str) -> RawTransaction {
+    fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|data| offline::decode_raw_transaction(&data))
+        .unwrap_or_else(|err| exit_with_error(&err))
 }
 
 const APP_DIR: &str = "Caster";
 const MODULE_DIR: &str = "modules";
 
-fn home_path() -> PathBuf {
+fn app_path() -> PathBuf {
     let root = dirs::data_local_dir().expect("should has local directory");
-    root.join(APP_DIR).join(MODULE_DIR)
+    root.join(APP_DIR)
 }
 
-fn parse_script_args(data: &str) -> Result<Vec<TransactionArgument>, &str> {
-    Ok(vec![])
+fn home_path() -> PathBuf {
+    app_path().join(MODULE_DIR)
 }
 
 fn load_local_modules(home: &PathBuf) -> Vec<VerifiedModule> {
@@ -242,11 +552,10 @@ fn load_local_modules(home: &PathBuf) -> Vec<VerifiedModule> {
 }
 
 fn compile_and_publish(
-    sender: &Account,
+    sender: &AccountAddress,
     path: &str,
     args: Vec<TransactionArgument>,
-    seq_num: u64,
-) -> SignedTransaction {
+) -> Program {
     let path = Path::new(path);
     let home_dir = home_path();
     if !home_dir.exists() {
@@ -254,11 +563,16 @@ fn compile_and_publish(
     }
 
     let deps = load_local_modules(&home_dir);
-    let data = fs::read(path).expect("read file error");
-    let program = str::from_utf8(&data).unwrap();
+    let data = fs::read(path)
+        .unwrap_or_else(|err| exit_with_error(&format!("cannot read {}: {}", path.display(), err)));
+    let program = str::from_utf8(&data).unwrap_or_else(|err| {
+        exit_with_error(&format!("{} is not valid UTF-8: {}", path.display(), err))
+    });
 
-    let compiled_program =
-        compile_inner_program_with_deps(sender.address(), &program, deps.clone());
+    let compiled_program = compile_inner_program_with_deps(sender, &program, deps.clone());
+    if let Err(err) = check_script_args(&compiled_program.script, &args) {
+        exit_with_error(&format!("invalid params: {}", err));
+    }
     // save modules
     compiled_program.modules.into_iter().for_each(|module| {
         let mut data: Vec<u8> = vec![];
@@ -269,14 +583,7 @@ fn compile_and_publish(
         file.write_all(&data).expect("should write file");
     });
 
-    let program = compile_program_with_deps(sender.address(), &program, args, deps);
-    // create signed transaction
-    sender.create_signed_txn_with_program(
-        program,
-        seq_num,
-        gas_costs::TXN_RESERVED, // this is a default for gas
-        0,                       // this is a default for gas
-    )
+    compile_program_with_deps(sender, &program, args, deps)
 }
 
 fn generate_sub_command_get_access_path<'a, 'b>() -> App<'a, 'b> {
@@ -398,8 +705,37 @@ fn deal_command_get_public_key(args: &ArgMatches) {
 }
 
 fn generate_sub_command_account<'a, 'b>() -> App<'a, 'b> {
+    let name = Arg::with_name("name")
+        .short("n")
+        .long("name")
+        .takes_value(true)
+        .required(true)
+        .help("account name in the keystore.");
     SubCommand::with_name("account")
         .about("about account operator")
+        .subcommand(
+            SubCommand::with_name("new")
+                .about("create an account and save it encrypted in the keystore")
+                .arg(name.clone()),
+        )
+        .subcommand(SubCommand::with_name("list").about("list keystore accounts"))
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("import a private key into the keystore")
+                .arg(name.clone())
+                .arg(
+                    Arg::with_name("key")
+                        .short("k")
+                        .long("key")
+                        .takes_value(true)
+                        .help("private key to import. prompted for if absent."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("decrypt and print a keystore account")
+                .arg(name),
+        )
         .arg(
             Arg::with_name("create")
                 .short("c")
@@ -417,18 +753,413 @@ fn generate_sub_command_account<'a, 'b>() -> App<'a, 'b> {
 }
 
 fn deal_command_account(args: &ArgMatches) {
-    if args.is_present("create") {
-        println!("{}", Account::new());
-    } else if args.is_present("recover") {
-        let account = args
-            .value_of("recover")
-            .map(|input| hex::decode(&input[2..]))
-            .map(|data| PrivateKey::from_slice(&data.unwrap()))
-            .map(|key| KeyPair::new(key.unwrap()))
-            .map(|pair| Account::from_keypair(pair))
-            .expect("unknown private key");
-        println!("{}", account);
+    let open_keystore = || KeyStore::open(&app_path());
+    match args.subcommand() {
+        ("new", Some(matches)) => {
+            let name = matches
+                .value_of("name")
+                .expect("should provide account name");
+            let (private_key, _) = signing::generate_keypair();
+            let passphrase = keystore::prompt_new_passphrase();
+            let key_file = open_keystore()
+                .insert(name, &KeyPair::new(private_key), &passphrase)
+                .unwrap_or_else(|err| exit_with_error(&err));
+            println!("{}: {}", key_file.name, key_file.address);
+        }
+        ("list", Some(_)) => {
+            for key_file in open_keystore().list() {
+                println!("{}: {}", key_file.name, key_file.address);
+            }
+        }
+        ("import", Some(matches)) => {
+            let name = matches
+                .value_of("name")
+                .expect("should provide account name");
+            let key = match matches.value_of("key") {
+                Some(key) => key.to_string(),
+                None => keystore::prompt_passphrase("Private key: "),
+            };
+            let key = hex::decode(key.trim_start_matches("0x"))
+                .map_err(|e| e.to_string())
+                .and_then(|data| PrivateKey::from_slice(&data).map_err(|e| e.to_string()))
+                .unwrap_or_else(|err| exit_with_error(&err));
+            let passphrase = keystore::prompt_new_passphrase();
+            let key_file = open_keystore()
+                .insert(name, &KeyPair::new(key), &passphrase)
+                .unwrap_or_else(|err| exit_with_error(&err));
+            println!("{}: {}", key_file.name, key_file.address);
+        }
+        ("export", Some(matches)) => {
+            let name = matches
+                .value_of("name")
+                .expect("should provide account name");
+            let key_file = open_keystore()
+                .get(name)
+                .unwrap_or_else(|err| exit_with_error(&err));
+            let passphrase = keystore::prompt_passphrase(&format!("Passphrase for {}: ", name));
+            let key_pair = key_file
+                .decrypt(&passphrase)
+                .unwrap_or_else(|err| exit_with_error(&err));
+            println!("{}", Account::from_keypair(key_pair));
+        }
+        _ => {
+            if args.is_present("create") {
+                println!("{}", Account::new());
+            } else if args.is_present("recover") {
+                let account = args
+                    .value_of("recover")
+                    .map(|input| hex::decode(&input[2..]))
+                    .map(|data| PrivateKey::from_slice(&data.unwrap()))
+                    .map(|key| KeyPair::new(key.unwrap()))
+                    .map(|pair| Account::from_keypair(pair))
+                    .expect("unknown private key");
+                println!("{}", account);
+            } else {
+                println!("{}", Account::new());
+            }
+        }
+    }
+}
+
+fn generate_sub_command_wallet<'a, 'b>() -> App<'a, 'b> {
+    let name = Arg::with_name("name")
+        .short("n")
+        .long("name")
+        .takes_value(true)
+        .required(true)
+        .help("wallet name.");
+    SubCommand::with_name("wallet")
+        .about("hd wallet deriving accounts from a mnemonic along SLIP-0010 paths")
+        .subcommand(
+            SubCommand::with_name("new")
+                .about("generate a mnemonic and save its seed encrypted")
+                .arg(name.clone())
+                .arg(
+                    Arg::with_name("words")
+                        .short("w")
+                        .long("words")
+                        .takes_value(true)
+                        .default_value("24")
+                        .help("number of mnemonic words, one of 12, 15, 18, 21 or 24."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("recover")
+                .about("recover a wallet from its mnemonic")
+                .arg(name.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("derive")
+                .about("derive the account at an index or path")
+                .arg(name.clone())
+                .arg(
+                    Arg::with_name("index")
+                        .short("i")
+                        .long("index")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("account index under the wallet path prefix."),
+                )
+                .arg(
+                    Arg::with_name("path")
+                        .short("p")
+                        .long("path")
+                        .takes_value(true)
+                        .help("full SLIP-0010 path, e.g. m/44/637/0/0/1. overrides index."),
+                )
+                .arg(
+                    Arg::with_name("save")
+                        .long("save")
+                        .takes_value(true)
+                        .help("save the derived account in the keystore under this name."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("scan")
+                .about("list the addresses of a range of account indices")
+                .arg(name)
+                .arg(
+                    Arg::with_name("start")
+                        .long("start")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("first account index."),
+                )
+                .arg(
+                    Arg::with_name("count")
+                        .short("c")
+                        .long("count")
+                        .takes_value(true)
+                        .default_value("10")
+                        .help("number of accounts."),
+                ),
+        )
+}
+
+fn deal_command_wallet(args: &ArgMatches) {
+    let wallets = WalletStore::open(&app_path());
+    let (command, matches) = match args.subcommand() {
+        (command, Some(matches)) => (command, matches),
+        _ => exit_with_error(args.usage()),
+    };
+    let name = matches
+        .value_of("name")
+        .expect("should provide wallet name");
+    match command {
+        "new" | "recover" => {
+            let mnemonic = if command == "new" {
+                let words = matches
+                    .value_of("words")
+                    .expect("should provide number of words")
+                    .parse()
+                    .expect("invalid number of words");
+                wallet::generate_mnemonic(words)
+            } else {
+                wallet::parse_mnemonic(&keystore::prompt_passphrase("Mnemonic: "))
+            }
+            .unwrap_or_else(|err| exit_with_error(&err));
+            let passphrase = keystore::prompt_new_passphrase();
+            wallets
+                .insert(name, &mnemonic, &passphrase)
+                .unwrap_or_else(|err| exit_with_error(&err));
+            if command == "new" {
+                println!("write down the mnemonic, it is the only way to recover the wallet:");
+                println!("{}", mnemonic.phrase());
+            }
+        }
+        "derive" | "scan" => {
+            let wallet_file = wallets
+                .get(name)
+                .unwrap_or_else(|err| exit_with_error(&err));
+            let passphrase = keystore::prompt_passphrase(&format!("Passphrase for {}: ", name));
+            let seed = wallet_file
+                .seed(&passphrase)
+                .unwrap_or_else(|err| exit_with_error(&err));
+            let parse_u32 = |arg: &str| -> u32 {
+                matches
+                    .value_of(arg)
+                    .expect("should has default value")
+                    .parse()
+                    .unwrap_or_else(|_| exit_with_error(&format!("invalid {}", arg)))
+            };
+
+            if command == "derive" {
+                let path = match matches.value_of("path") {
+                    Some(path) => path.to_string(),
+                    None => wallet::account_path(&wallet_file.path_prefix, parse_u32("index")),
+                };
+                let key_pair = wallet::derive_key_pair(&seed, &path)
+                    .unwrap_or_else(|err| exit_with_error(&err));
+                println!("path: {}", path);
+                if let Some(account_name) = matches.value_of("save") {
+                    let passphrase = keystore::prompt_new_passphrase();
+                    let key_file = KeyStore::open(&app_path())
+                        .insert(account_name, &key_pair, &passphrase)
+                        .unwrap_or_else(|err| exit_with_error(&err));
+                    println!("{}: {}", key_file.name, key_file.address);
+                } else {
+                    println!("{}", Account::from_keypair(key_pair));
+                }
+            } else {
+                let start = parse_u32("start");
+                for index in start..start.saturating_add(parse_u32("count")) {
+                    let path = wallet::account_path(&wallet_file.path_prefix, index);
+                    let key_pair = wallet::derive_key_pair(&seed, &path)
+                        .unwrap_or_else(|err| exit_with_error(&err));
+                    println!("{}: {}", path, AccountAddress::from(key_pair.public_key()));
+                }
+            }
+        }
+        _ => exit_with_error(args.usage()),
+    }
+}
+
+fn generate_sub_command_package<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("package")
+        .about("move packages with a manifest and pinned dependencies")
+        .subcommand(
+            SubCommand::with_name("build")
+                .about("compile a package and its local dependencies in dependency order")
+                .arg(
+                    Arg::with_name("path")
+                        .long("path")
+                        .takes_value(true)
+                        .default_value(".")
+                        .help("package directory holding Package.toml."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("fetch")
+                .about("download a published module into the module directory")
+                .arg(
+                    Arg::with_name("module")
+                        .takes_value(true)
+                        .required(true)
+                        .help("module to fetch, as <address>.<Module>."),
+                )
+                .arg(node_arg()),
+        )
+}
+
+fn deal_command_package(args: &ArgMatches) {
+    match args.subcommand() {
+        ("build", Some(matches)) => {
+            let path = matches
+                .value_of("path")
+                .expect("should provide package path");
+            let packages =
+                package::resolve(Path::new(path)).unwrap_or_else(|err| exit_with_error(&err));
+            let modules =
+                package::build(&packages, &home_path()).unwrap_or_else(|err| exit_with_error(&err));
+            for package in &packages {
+                println!("compiled {} ({:?})", package.name(), package.root);
+            }
+            for module in modules {
+                println!("module {}", module.self_id());
+            }
+        }
+        ("fetch", Some(matches)) => {
+            let id = matches
+                .value_of("module")
+                .map(package::parse_module_id)
+                .expect("should provide module")
+                .unwrap_or_else(|err| exit_with_error(&err));
+            let url = matches.value_of("node").expect("should provide node url");
+            let data = node::fetch_module(url, &id).unwrap_or_else(|err| exit_with_error(&err));
+            let module = CompiledModule::deserialize(&data)
+                .map_err(|err| format!("{:?}", err))
+                .and_then(|module| {
+                    VerifiedModule::new(module).map_err(|(_, errors)| format!("{:?}", errors))
+                })
+                .unwrap_or_else(|err| exit_with_error(&format!("invalid module {}: {}", id, err)));
+            if module.self_id() != id {
+                exit_with_error(&format!("node returned module {}", module.self_id()));
+            }
+
+            let home_dir = home_path();
+            fs::create_dir_all(&home_dir).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+            fs::write(home_dir.join(id.to_string()), &data)
+                .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+            println!(
+                "{} = {{ module = \"{}\", hash = \"{}\" }}",
+                id.name(),
+                id,
+                package::module_hash(&data)
+            );
+        }
+        _ => exit_with_error(args.usage()),
+    }
+}
+
+fn generate_sub_command_disasm<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("disasm")
+        .about("disassemble a module, a script or the program of a raw transaction")
+        .arg(
+            Arg::with_name("file")
+                .long("file")
+                .takes_value(true)
+                .help("module or script file."),
+        )
+        .arg(
+            Arg::with_name("hex")
+                .long("hex")
+                .takes_value(true)
+                .help("module or script as hex."),
+        )
+        .arg(
+            Arg::with_name("module")
+                .long("module")
+                .takes_value(true)
+                .help("published module to fetch from the node, as <address>.<Module>."),
+        )
+        .arg(
+            Arg::with_name("txn")
+                .long("txn")
+                .takes_value(true)
+                .help("raw transaction file produced by `tx build`."),
+        )
+        .group(
+            ArgGroup::with_name("source")
+                .args(&["file", "hex", "module", "txn"])
+                .required(true),
+        )
+        .arg(node_arg())
+}
+
+fn deal_command_disasm(args: &ArgMatches) {
+    let listing = if let Some(path) = args.value_of("file") {
+        fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|data| disasm::disassemble_blob(&data))
+    } else if let Some(data) = args.value_of("hex") {
+        hex::decode(data.trim_start_matches("0x"))
+            .map_err(|err| err.to_string())
+            .and_then(|data| disasm::disassemble_blob(&data))
+    } else if let Some(module) = args.value_of("module") {
+        let url = args.value_of("node").expect("should provide node url");
+        package::parse_module_id(module)
+            .and_then(|id| node::fetch_module(url, &id))
+            .and_then(|data| disasm::disassemble_blob(&data))
     } else {
-        println!("{}", Account::new());
+        let raw_txn = read_raw_transaction(
+            args.value_of("txn")
+                .expect("should provide raw transaction file"),
+        );
+        println!("{}", offline::summary(&raw_txn));
+        match raw_txn.into_payload() {
+            TransactionPayload::Program(program) => disasm::disassemble_program(&program),
+            TransactionPayload::WriteSet(_) => {
+                Err("write set transactions have no code".to_string())
+            }
+        }
+    };
+    println!("{}", listing.unwrap_or_else(|err| exit_with_error(&err)));
+}
+
+fn generate_sub_command_snapshot<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("snapshot")
+        .about("export the move state of a node at a block, for replaying transactions locally")
+        .arg(
+            Arg::with_name("block")
+                .long("block")
+                .takes_value(true)
+                .help("block number or hash, the best block by default."),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .required(true)
+                .help("snapshot file."),
+        )
+        .arg(node_arg())
+}
+
+fn deal_command_snapshot(args: &ArgMatches) {
+    let url = args.value_of("node").expect("should provide node url");
+    let mut client = NodeClient::connect(url).unwrap_or_else(|err| exit_with_error(&err));
+    let block_hash = match args.value_of("block") {
+        Some(block) if block.starts_with("0x") => node::parse_block_hash(block),
+        Some(block) => block
+            .parse()
+            .map_err(|_| format!("invalid block: {}", block))
+            .and_then(|number| client.block_hash(Some(number))),
+        None => client.block_hash(None),
     }
+    .unwrap_or_else(|err| exit_with_error(&err));
+
+    let snapshot = client
+        .snapshot(block_hash)
+        .unwrap_or_else(|err| exit_with_error(&err));
+    let output = args.value_of("output").expect("should provide output file");
+    snapshot
+        .save(Path::new(output))
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+    println!(
+        "wrote {} entries at block {} to {}",
+        snapshot.entries.len(),
+        snapshot.block_hash,
+        output
+    );
 }
@@ -0,0 +1,328 @@
+//! A minimal JSON-RPC client for submitting move transactions to a bool node and tracking them
+//! until they are included in a block.
+
+use canonical_serialization::SimpleDeserializer;
+use mock::snapshot::Snapshot;
+use node_template_runtime::{AccountId, Call, Event, Hash, Nonce, UncheckedExtrinsic};
+use parity_codec::{Compact, Decode, Encode};
+use primitives::{blake2_256, sr25519, twox_128, Pair};
+use runtime_primitives::generic::Era;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, net::TcpStream};
+use system::{EventRecord, Phase};
+use vm::types::{contract_event::ContractEvent, AccessPath, AccountAddress, ModuleId};
+use websocket::{sync::Client, ClientBuilder, OwnedMessage};
+
+/// An entry of the `Vmove AccessStorage` linked map: the stored blob and the previous and next
+/// keys.
+type AccessEntry = (Vec<u8>, Option<Vec<u8>>, Option<Vec<u8>>);
+
+/// The default RPC endpoint of a local dev node.
+pub const DEFAULT_NODE_URL: &str = "ws://127.0.0.1:9944";
+
+/// A synchronous JSON-RPC connection to a node.
+pub struct NodeClient {
+    client: Client<TcpStream>,
+    next_id: u64,
+}
+
+impl NodeClient {
+    /// Connects to the node listening on `url`, e.g. `ws://127.0.0.1:9944`.
+    pub fn connect(url: &str) -> Result<Self, String> {
+        let client = ClientBuilder::new(url)
+            .map_err(|e| format!("invalid node url {}: {}", url, e))?
+            .connect_insecure()
+            .map_err(|e| format!("can not connect to {}: {}", url, e))?;
+        Ok(NodeClient { client, next_id: 1 })
+    }
+
+    /// Sends a request and waits for its response, skipping unrelated notifications.
+    pub fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        self.client
+            .send_message(&OwnedMessage::Text(request.to_string()))
+            .map_err(|e| e.to_string())?;
+        loop {
+            let response = self.recv()?;
+            if response["id"] != json!(id) {
+                continue;
+            }
+            if let Some(error) = response.get("error") {
+                return Err(format!("{} failed: {}", method, error));
+            }
+            return Ok(response["result"].clone());
+        }
+    }
+
+    fn recv(&mut self) -> Result<Value, String> {
+        loop {
+            match self.client.recv_message().map_err(|e| e.to_string())? {
+                OwnedMessage::Text(text) => {
+                    return serde_json::from_str(&text).map_err(|e| e.to_string())
+                }
+                OwnedMessage::Ping(data) => self
+                    .client
+                    .send_message(&OwnedMessage::Pong(data))
+                    .map_err(|e| e.to_string())?,
+                OwnedMessage::Close(_) => return Err("connection closed by node".to_string()),
+                _ => (),
+            }
+        }
+    }
+
+    /// Returns the hash of the genesis block, which every signed extrinsic commits to.
+    pub fn genesis_hash(&mut self) -> Result<Hash, String> {
+        let hash = self.request("chain_getBlockHash", json!([0]))?;
+        parse_hash(&hash)
+    }
+
+    /// Reads the raw storage value under `key`, at block `at` or the best block.
+    pub fn storage(&mut self, key: &[u8], at: Option<Hash>) -> Result<Option<Vec<u8>>, String> {
+        let key = format!("0x{}", hex::encode(key));
+        let params = match at {
+            Some(hash) => json!([key, format!("{:?}", hash)]),
+            None => json!([key]),
+        };
+        match self.request("state_getStorage", params)? {
+            Value::Null => Ok(None),
+            value => parse_bytes(&value).map(Some),
+        }
+    }
+
+    /// Returns the substrate nonce of `account`.
+    pub fn account_nonce(&mut self, account: &AccountId) -> Result<Nonce, String> {
+        let mut key = b"System AccountNonce".to_vec();
+        account.encode_to(&mut key);
+        match self.storage(&blake2_256(&key), None)? {
+            Some(data) => Decode::decode(&mut &data[..]).ok_or_else(|| "bad nonce".to_string()),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the move sequence number of `address`, or `None` if the account does not exist.
+    pub fn sequence_number(&mut self, address: &AccountAddress) -> Result<Option<u64>, String> {
+        self.vmove_value(b"Vmove AccessSequence", &address.to_vec(), None)
+    }
+
+    /// Returns the move balance of `address`, or `None` if the account does not exist.
+    pub fn balance(&mut self, address: &AccountAddress) -> Result<Option<u64>, String> {
+        self.vmove_value(b"Vmove AccessBalance", &address.to_vec(), None)
+    }
+
+    /// Returns the move resource or module stored under `access_path`.
+    pub fn access_storage(&mut self, access_path: &AccessPath) -> Result<Option<Vec<u8>>, String> {
+        let key = bincode::serialize(access_path).map_err(|e| e.to_string())?;
+        let entry: Option<AccessEntry> = self.vmove_value(b"Vmove AccessStorage", &key, None)?;
+        Ok(entry.map(|(data, _, _)| data))
+    }
+
+    /// Returns the hash of block `number`, or of the best block.
+    pub fn block_hash(&mut self, number: Option<u64>) -> Result<Hash, String> {
+        let params = match number {
+            Some(number) => json!([number]),
+            None => json!([]),
+        };
+        match self.request("chain_getBlockHash", params)? {
+            Value::Null => Err(format!("block {} not found", number.unwrap_or_default())),
+            hash => parse_hash(&hash),
+        }
+    }
+
+    /// Reads every move resource and module stored at block `at`.
+    pub fn snapshot(&mut self, at: Hash) -> Result<Snapshot, String> {
+        let mut snapshot = Snapshot {
+            block_hash: format!("{:?}", at),
+            entries: BTreeMap::new(),
+        };
+        // `Vmove AccessStorage` is a linked map: its head is stored apart and every entry links to
+        // the next one.
+        let mut next: Option<Vec<u8>> = match self
+            .storage(&blake2_256(b"head of Vmove AccessStorage"), Some(at))?
+        {
+            Some(data) => Some(
+                Decode::decode(&mut &data[..]).ok_or_else(|| "bad access storage".to_string())?,
+            ),
+            None => None,
+        };
+        // Genesis always stores modules, so a chain past genesis without a head runs a runtime
+        // whose access storage can not be enumerated.
+        let has_genesis = self
+            .storage(&twox_128(b"Vmove HasGenesis"), Some(at))?
+            .is_some();
+        if next.is_none() && has_genesis {
+            return Err("the move state of this node can not be enumerated".to_string());
+        }
+        while let Some(key) = next.take() {
+            let (data, _previous, following): AccessEntry = self
+                .vmove_value(b"Vmove AccessStorage", &key, Some(at))?
+                .ok_or_else(|| "broken access storage".to_string())?;
+            next = following;
+
+            let access_path: AccessPath =
+                bincode::deserialize(&key).map_err(|e| format!("bad access path: {}", e))?;
+            snapshot.entries.insert(access_path, data);
+        }
+        Ok(snapshot)
+    }
+
+    /// Reads the value under `key` of the `Vmove` storage map `prefix`, at block `at` or the best
+    /// block.
+    fn vmove_value<T: Decode>(
+        &mut self,
+        prefix: &[u8],
+        key: &[u8],
+        at: Option<Hash>,
+    ) -> Result<Option<T>, String> {
+        let mut storage_key = prefix.to_vec();
+        key.to_vec().encode_to(&mut storage_key);
+        match self.storage(&blake2_256(&storage_key), at)? {
+            Some(data) => Decode::decode(&mut &data[..])
+                .map(Some)
+                .ok_or_else(|| "bad storage value".to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// Submits `xt` and waits until the block including it is finalized. Returns that block's
+    /// hash.
+    pub fn submit_and_watch(&mut self, xt: &UncheckedExtrinsic) -> Result<Hash, String> {
+        let xt = format!("0x{}", hex::encode(xt.encode()));
+        let subscription = self.request("author_submitAndWatchExtrinsic", json!([xt]))?;
+        loop {
+            let notification = self.recv()?;
+            if notification["method"] != "author_extrinsicUpdate"
+                || notification["params"]["subscription"] != subscription
+            {
+                continue;
+            }
+            let status = &notification["params"]["result"];
+            if let Some(hash) = status.get("finalized").or_else(|| status.get("finalised")) {
+                return parse_hash(hash);
+            }
+            if status.get("usurped").is_some() || status == "invalid" || status == "dropped" {
+                return Err(format!("extrinsic was not included: {}", status));
+            }
+            println!("status: {}", status);
+        }
+    }
+
+    /// Returns the runtime events emitted by `xt` in block `block_hash`.
+    pub fn extrinsic_events(
+        &mut self,
+        block_hash: Hash,
+        xt: &UncheckedExtrinsic,
+    ) -> Result<Vec<Event>, String> {
+        let block = self.request("chain_getBlock", json!([format!("{:?}", block_hash)]))?;
+        let xt = format!("0x{}", hex::encode(xt.encode()));
+        let index = block["block"]["extrinsics"]
+            .as_array()
+            .and_then(|extrinsics| extrinsics.iter().position(|e| *e == xt))
+            .ok_or_else(|| "extrinsic not found in block".to_string())?;
+
+        let data = self
+            .storage(&twox_128(b"System Events"), Some(block_hash))?
+            .unwrap_or_default();
+        let records: Vec<EventRecord<Event>> =
+            Decode::decode(&mut &data[..]).ok_or_else(|| "bad events".to_string())?;
+        Ok(records
+            .into_iter()
+            .filter(|record| record.phase == Phase::ApplyExtrinsic(index as u32))
+            .map(|record| record.event)
+            .collect())
+    }
+}
+
+/// Fetches the bytecode of the published module `id` from the node at `url`.
+pub fn fetch_module(url: &str, id: &ModuleId) -> Result<Vec<u8>, String> {
+    NodeClient::connect(url)?
+        .access_storage(&AccessPath::from(id))?
+        .ok_or_else(|| format!("module {} is not published", id))
+}
+
+/// Wraps a serialized `SignedTransaction` into a `Vmove::execute` extrinsic signed by `signer`.
+pub fn execute_extrinsic(
+    signer: &sr25519::Pair,
+    nonce: Nonce,
+    genesis_hash: Hash,
+    transaction: Vec<u8>,
+) -> UncheckedExtrinsic {
+    let call = Call::Vmove(vmove::Call::execute(transaction));
+    let era = Era::immortal();
+    let raw_payload = (Compact(nonce), call, era, genesis_hash);
+    let signature = raw_payload.using_encoded(|payload| {
+        if payload.len() > 256 {
+            signer.sign(&blake2_256(payload)[..])
+        } else {
+            signer.sign(payload)
+        }
+    });
+    UncheckedExtrinsic::new_signed(
+        nonce,
+        raw_payload.1,
+        indices::address::Address::Id(signer.public()),
+        signature.into(),
+        era,
+    )
+}
+
+/// Submits a serialized `SignedTransaction` to the node at `url`, signing the extrinsic with the
+/// substrate key `suri`, and prints the move status, gas used and events once it is included.
+pub fn submit_transaction(url: &str, suri: &str, transaction: Vec<u8>) -> Result<(), String> {
+    let signer = sr25519::Pair::from_string(suri, None)
+        .map_err(|_| format!("invalid substrate key uri {}", suri))?;
+    let mut client = NodeClient::connect(url)?;
+    let genesis_hash = client.genesis_hash()?;
+    let nonce = client.account_nonce(&signer.public())?;
+
+    let xt = execute_extrinsic(&signer, nonce, genesis_hash, transaction);
+    let block_hash = client.submit_and_watch(&xt)?;
+    println!("included in block {:?}", block_hash);
+
+    for event in client.extrinsic_events(block_hash, &xt)? {
+        match event {
+            Event::vmove(vmove::RawEvent::Executed(_, status, gas_used)) => {
+                println!("status: {}", String::from_utf8_lossy(&status));
+                println!("gas used: {}", gas_used);
+            }
+            Event::vmove(vmove::RawEvent::Contract(_, data)) => {
+                match SimpleDeserializer::deserialize::<ContractEvent>(&data) {
+                    Ok(event) => println!("event: {}", event),
+                    Err(_) => println!("event: 0x{}", hex::encode(data)),
+                }
+            }
+            Event::system(system::Event::ExtrinsicFailed) => {
+                return Err("extrinsic failed".to_string());
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn parse_bytes(value: &Value) -> Result<Vec<u8>, String> {
+    let data = value
+        .as_str()
+        .ok_or_else(|| format!("expect hex string, got {}", value))?;
+    let data = data.trim_start_matches("0x");
+    hex::decode(data).map_err(|e| e.to_string())
+}
+
+/// Parses a block hash given as hex.
+pub fn parse_block_hash(data: &str) -> Result<Hash, String> {
+    parse_hash(&Value::String(data.to_string()))
+}
+
+fn parse_hash(value: &Value) -> Result<Hash, String> {
+    let data = parse_bytes(value)?;
+    if data.len() != 32 {
+        return Err(format!("expect 32 bytes hash, got {}", value));
+    }
+    Ok(Hash::from_slice(&data))
+}
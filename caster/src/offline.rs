@@ -0,0 +1,145 @@
+//! Offline signing of move transactions.
+//!
+//! A transaction is built on an online machine as a canonical serialized `RawTransaction`, signed
+//! on an air-gapped machine holding the key, which only hands back a detached signature, and
+//! finally assembled into a `SignedTransaction` next to the raw transaction.
+
+use canonical_serialization::{SimpleDeserializer, SimpleSerializer};
+use crypto::{signing::KeyPair, PublicKey, Signature};
+use mock::common;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use vm::types::{
+    transaction::{Program, RawTransaction, SignedTransaction},
+    AccountAddress,
+};
+
+/// A signature of a raw transaction together with the public key verifying it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DetachedSignature {
+    pub public_key: String,
+    pub signature: String,
+}
+
+/// Builds a raw transaction running `program` that never expires.
+pub fn build_raw_transaction(
+    sender: AccountAddress,
+    sequence_number: u64,
+    program: Program,
+    max_gas_amount: u64,
+    gas_unit_price: u64,
+) -> RawTransaction {
+    RawTransaction::new(
+        sender,
+        sequence_number,
+        program,
+        max_gas_amount,
+        gas_unit_price,
+        Duration::from_secs(u64::max_value()),
+    )
+}
+
+/// Serializes `raw_txn` canonically.
+pub fn encode_raw_transaction(raw_txn: &RawTransaction) -> Vec<u8> {
+    SimpleSerializer::<Vec<u8>>::serialize(raw_txn).expect("serialization failed")
+}
+
+/// Deserializes a canonical serialized raw transaction.
+pub fn decode_raw_transaction(data: &[u8]) -> Result<RawTransaction, String> {
+    SimpleDeserializer::deserialize(data).map_err(|e| format!("invalid raw transaction: {}", e))
+}
+
+/// Signs `raw_txn` with `key_pair`.
+pub fn sign(raw_txn: RawTransaction, key_pair: &KeyPair) -> Result<DetachedSignature, String> {
+    let signed_txn = raw_txn
+        .sign(key_pair.private_key(), key_pair.public_key())
+        .map_err(|e| e.to_string())?;
+    Ok(DetachedSignature {
+        public_key: format!("0x{}", signed_txn.public_key()),
+        signature: format!(
+            "0x{}",
+            hex::encode(&signed_txn.signature().to_compact()[..])
+        ),
+    })
+}
+
+/// Combines `raw_txn` with its detached signature and checks that the signature is valid.
+pub fn assemble(
+    raw_txn: RawTransaction,
+    signature: &DetachedSignature,
+) -> Result<SignedTransaction, String> {
+    let public_key = parse_hex(&signature.public_key)
+        .and_then(|data| PublicKey::from_slice(&data).map_err(|e| e.to_string()))
+        .map_err(|e| format!("invalid public key: {}", e))?;
+    let compact = parse_hex(&signature.signature)
+        .and_then(|data| Signature::from_compact(&data).map_err(|e| e.to_string()))
+        .map_err(|e| format!("invalid signature: {}", e))?;
+
+    let signed_txn =
+        SignedTransaction::craft_signed_transaction_for_client(raw_txn, public_key, compact);
+    signed_txn
+        .check_signature()
+        .map(|checked| checked.into_inner())
+        .map_err(|e| format!("signature does not match the transaction: {}", e))
+}
+
+/// Returns a readable summary of `raw_txn`, naming the standard transaction scripts.
+pub fn summary(raw_txn: &RawTransaction) -> String {
+    raw_txn.format_for_client(transaction_name)
+}
+
+fn transaction_name(code: &[u8]) -> String {
+    if code == &common::CREATE_ACCOUNT[..] {
+        "create_account".to_string()
+    } else if code == &common::MINT[..] {
+        "mint".to_string()
+    } else if code == &common::PEER_TO_PEER[..] {
+        "transfer".to_string()
+    } else if code == &common::ROTATE_KEY[..] {
+        "rotate_key".to_string()
+    } else {
+        format!("script of {} bytes", code.len())
+    }
+}
+
+fn parse_hex(data: &str) -> Result<Vec<u8>, String> {
+    hex::decode(data.trim_start_matches("0x")).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vm::types::transaction::TransactionArgument;
+
+    fn raw_txn(sender: AccountAddress) -> RawTransaction {
+        let program = Program::new(vec![1, 2, 3], vec![], vec![TransactionArgument::U64(42)]);
+        build_raw_transaction(sender, 7, program, 100_000, 0)
+    }
+
+    #[test]
+    fn build_sign_and_assemble() {
+        let (private_key, _) = crypto::signing::generate_keypair();
+        let key_pair = KeyPair::new(private_key);
+        let raw_txn = raw_txn(AccountAddress::from(key_pair.public_key()));
+
+        let data = encode_raw_transaction(&raw_txn);
+        let decoded = decode_raw_transaction(&data).unwrap();
+        assert_eq!(decoded, raw_txn);
+
+        let signature = sign(decoded, &key_pair).unwrap();
+        let signed_txn = assemble(raw_txn, &signature).unwrap();
+        assert_eq!(signed_txn.sequence_number(), 7);
+        assert_eq!(signed_txn.public_key(), key_pair.public_key());
+    }
+
+    #[test]
+    fn assemble_rejects_foreign_signature() {
+        let (private_key, _) = crypto::signing::generate_keypair();
+        let key_pair = KeyPair::new(private_key);
+        let sender = AccountAddress::from(key_pair.public_key());
+
+        let signature = sign(raw_txn(sender), &key_pair).unwrap();
+        let other = build_raw_transaction(sender, 8, Program::new(vec![], vec![], vec![]), 0, 0);
+        assemble(other, &signature).unwrap_err();
+    }
+}
@@ -0,0 +1,326 @@
+//! Local move packages.
+//!
+//! A package is a directory with a `Package.toml` manifest naming the package, the address its
+//! modules are published under and its module sources in compilation order:
+//!
+//! ```toml
+//! [package]
+//! name = "token"
+//! address = "0x44416e28b8545d375a212c44d9719e5c21c4f44123be4993768c899bf3c02826"
+//! modules = ["token.mvir"]
+//!
+//! [dependencies]
+//! math = { path = "../math" }
+//! coin = { module = "0x44416e28b8545d375a212c44d9719e5c21c4f44123be4993768c899bf3c02826.Coin", hash = "9f4c..." }
+//! ```
+//!
+//! A dependency is either another local package or a published module pinned to the sha3-256
+//! hash of its bytecode. Published modules are fetched from a node into the module directory.
+
+use compiler::Compiler;
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+use tiny_keccak::Keccak;
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    def::{access::ModuleAccess, file_format::CompiledModule},
+    types::{AccountAddress, ModuleId},
+};
+
+pub const MANIFEST_FILE: &str = "Package.toml";
+const BUILD_DIR: &str = "build";
+
+/// The contents of a `Package.toml`.
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    pub package: PackageInfo,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PackageInfo {
+    pub name: String,
+    pub address: String,
+    /// Module sources relative to the package root, in compilation order.
+    pub modules: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Dependency {
+    /// A local package, relative to the depending package's root.
+    Local { path: String },
+    /// A published module, as `<address>.<Module>`, with the hash of its bytecode.
+    Published { module: String, hash: String },
+}
+
+/// A package loaded from disk.
+#[derive(Debug)]
+pub struct Package {
+    pub root: PathBuf,
+    pub manifest: Manifest,
+    pub address: AccountAddress,
+}
+
+impl Package {
+    /// Loads the package whose manifest is in `root`.
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let root = root
+            .canonicalize()
+            .map_err(|e| format!("{}: {}", root.display(), e))?;
+        let path = root.join(MANIFEST_FILE);
+        let data = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let manifest: Manifest =
+            toml::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let address = AccountAddress::from_hex_literal(&manifest.package.address)
+            .map_err(|e| format!("{}: invalid address: {}", path.display(), e))?;
+        Ok(Package {
+            root,
+            manifest,
+            address,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.package.name
+    }
+
+    fn local_dependencies(&self) -> Vec<PathBuf> {
+        self.manifest
+            .dependencies
+            .values()
+            .filter_map(|dependency| match dependency {
+                Dependency::Local { path } => Some(self.root.join(path)),
+                Dependency::Published { .. } => None,
+            })
+            .collect()
+    }
+
+    fn published_dependencies(&self) -> Vec<(&str, &str)> {
+        self.manifest
+            .dependencies
+            .values()
+            .filter_map(|dependency| match dependency {
+                Dependency::Local { .. } => None,
+                Dependency::Published { module, hash } => Some((module.as_str(), hash.as_str())),
+            })
+            .collect()
+    }
+}
+
+/// Loads the package in `root` and every package it depends on through local paths, ordered so
+/// that each package comes after its dependencies.
+pub fn resolve(root: &Path) -> Result<Vec<Package>, String> {
+    let mut visiting = vec![];
+    let mut ordered = vec![];
+    visit(Package::load(root)?, &mut visiting, &mut ordered)?;
+    Ok(ordered)
+}
+
+fn visit(
+    package: Package,
+    visiting: &mut Vec<PathBuf>,
+    ordered: &mut Vec<Package>,
+) -> Result<(), String> {
+    if ordered.iter().any(|p| p.root == package.root) {
+        return Ok(());
+    }
+    if visiting.contains(&package.root) {
+        return Err(format!(
+            "dependency cycle through package {}",
+            package.name()
+        ));
+    }
+    visiting.push(package.root.clone());
+    for dir in package.local_dependencies() {
+        visit(Package::load(&dir)?, visiting, ordered)?;
+    }
+    visiting.pop();
+    ordered.push(package);
+    Ok(())
+}
+
+/// Compiles `packages`, which must be ordered as returned by `resolve`, and writes the modules of
+/// each package into its `build` directory.
+///
+/// Every package links against the modules of its local dependencies and against the published
+/// modules it pins, which are read from `module_dir` and must match their pinned hash.
+pub fn build(packages: &[Package], module_dir: &Path) -> Result<Vec<VerifiedModule>, String> {
+    // The modules visible to dependents of a package: its own and those of its dependencies.
+    let mut exports: HashMap<PathBuf, BTreeMap<ModuleId, VerifiedModule>> = HashMap::new();
+    let mut built = vec![];
+
+    for package in packages {
+        let mut deps = BTreeMap::new();
+        for dir in package.local_dependencies() {
+            let dir = dir.canonicalize().map_err(|e| e.to_string())?;
+            let modules = exports
+                .get(&dir)
+                .ok_or_else(|| format!("package {} is not resolved", dir.display()))?;
+            deps.extend(modules.clone());
+        }
+        for (module, hash) in package.published_dependencies() {
+            let module = load_published_module(module_dir, &parse_module_id(module)?, hash)?;
+            deps.insert(module.self_id(), module);
+        }
+
+        let mut modules = vec![];
+        for source in &package.manifest.package.modules {
+            let path = package.root.join(source);
+            let code =
+                fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let mut extra_deps: Vec<VerifiedModule> = deps.values().cloned().collect();
+            extra_deps.extend(modules.iter().cloned());
+            let compiler = Compiler {
+                address: package.address,
+                code: &code,
+                extra_deps,
+                ..Compiler::default()
+            };
+            let program = compiler
+                .into_compiled_program()
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            for module in program.modules {
+                let module = VerifiedModule::new(module)
+                    .map_err(|(_, errors)| format!("{}: {:?}", path.display(), errors))?;
+                modules.push(module);
+            }
+        }
+
+        write_modules(&package.root.join(BUILD_DIR), &modules)?;
+        deps.extend(modules.iter().map(|m| (m.self_id(), m.clone())));
+        exports.insert(package.root.clone(), deps);
+        built = modules;
+    }
+    Ok(built)
+}
+
+fn write_modules(dir: &Path, modules: &[VerifiedModule]) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    for module in modules {
+        let mut data = vec![];
+        module.serialize(&mut data).map_err(|e| e.to_string())?;
+        let path = dir.join(format!("{}.mv", module.self_id().name()));
+        fs::write(path, data).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn load_published_module(
+    module_dir: &Path,
+    id: &ModuleId,
+    hash: &str,
+) -> Result<VerifiedModule, String> {
+    let data = fs::read(module_dir.join(id.to_string())).map_err(|_| {
+        format!(
+            "module {} is missing, run `caster package fetch {}`",
+            id, id
+        )
+    })?;
+    let actual = module_hash(&data);
+    if actual != hash.trim_start_matches("0x") {
+        return Err(format!(
+            "module {} has hash {}, but {} is pinned, run `caster package fetch {}`",
+            id, actual, hash, id
+        ));
+    }
+    let module = CompiledModule::deserialize(&data).map_err(|e| format!("{}: {:?}", id, e))?;
+    VerifiedModule::new(module).map_err(|(_, errors)| format!("{}: {:?}", id, errors))
+}
+
+/// Parses a module id given as `<address>.<Module>`.
+pub fn parse_module_id(data: &str) -> Result<ModuleId, String> {
+    let invalid = || format!("invalid module {}, expect <address>.<Module>", data);
+    let dot = data.rfind('.').ok_or_else(invalid)?;
+    let (address, name) = (&data[..dot], &data[dot + 1..]);
+    if !address.starts_with("0x") || name.is_empty() {
+        return Err(invalid());
+    }
+    let address = AccountAddress::from_hex_literal(address).map_err(|_| invalid())?;
+    Ok(ModuleId::new(address, name.to_string()))
+}
+
+/// Returns the hex encoded sha3-256 hash of a module's bytecode, which packages pin.
+pub fn module_hash(data: &[u8]) -> String {
+    let mut sha3 = Keccak::new_sha3_256();
+    sha3.update(data);
+    let mut hash = [0u8; 32];
+    sha3.finalize(&mut hash);
+    hex::encode(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_package(dir: &Path, manifest: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("caster-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn resolve_orders_dependencies_first() {
+        let root = temp_dir("resolve");
+        write_package(
+            &root.join("app"),
+            "[package]\nname = \"app\"\naddress = \"0x1\"\nmodules = []\n\
+             [dependencies]\nmath = { path = \"../math\" }\nutil = { path = \"../util\" }\n",
+        );
+        write_package(
+            &root.join("util"),
+            "[package]\nname = \"util\"\naddress = \"0x1\"\nmodules = []\n\
+             [dependencies]\nmath = { path = \"../math\" }\n",
+        );
+        write_package(
+            &root.join("math"),
+            "[package]\nname = \"math\"\naddress = \"0x1\"\nmodules = []\n",
+        );
+
+        let names: Vec<String> = resolve(&root.join("app"))
+            .unwrap()
+            .iter()
+            .map(|p| p.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["math", "util", "app"]);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn resolve_rejects_cycles() {
+        let root = temp_dir("cycle");
+        write_package(
+            &root.join("a"),
+            "[package]\nname = \"a\"\naddress = \"0x1\"\nmodules = []\n\
+             [dependencies]\nb = { path = \"../b\" }\n",
+        );
+        write_package(
+            &root.join("b"),
+            "[package]\nname = \"b\"\naddress = \"0x1\"\nmodules = []\n\
+             [dependencies]\na = { path = \"../a\" }\n",
+        );
+
+        resolve(&root.join("a")).unwrap_err();
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn parse_module_ids() {
+        let id = parse_module_id("0x1.Coin").unwrap();
+        assert_eq!(id.name(), "Coin");
+        assert_eq!(parse_module_id(&id.to_string()).unwrap(), id);
+        parse_module_id("0x1").unwrap_err();
+        parse_module_id("Coin").unwrap_err();
+        parse_module_id("0x1.").unwrap_err();
+    }
+}
@@ -0,0 +1,149 @@
+//! A local cache of the sequence numbers of transactions caster has submitted but the chain may
+//! not have executed yet, so consecutive transactions of an account do not collide.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use vm::types::AccountAddress;
+
+const PENDING_FILE: &str = "pending.json";
+
+/// Seconds after the last submission of an account its pending transactions are assumed dropped
+/// by the node, so the sequence number falls back to the on-chain one.
+const PENDING_TIMEOUT_SECS: u64 = 600;
+
+/// The pending transactions of an account.
+#[derive(Debug, Deserialize, Serialize)]
+struct PendingAccount {
+    /// Sequence number following the last submitted transaction.
+    next: u64,
+    /// Unix time in seconds of the last submission.
+    submitted_at: u64,
+}
+
+/// The pending transactions of every account with transactions not yet seen on chain.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct PendingCache {
+    #[serde(skip)]
+    path: PathBuf,
+    accounts: BTreeMap<String, PendingAccount>,
+}
+
+impl PendingCache {
+    /// Opens the cache under the caster data directory `root`.
+    pub fn open(root: &PathBuf) -> Self {
+        let path = root.join(PENDING_FILE);
+        let mut cache: PendingCache = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        cache.path = path;
+        cache
+    }
+
+    /// Resolves the sequence number of the next transaction of `address` from the number
+    /// `on_chain` read from the node, if any, and the pending transactions. Entries the chain
+    /// has caught up with, or older than `PENDING_TIMEOUT_SECS`, are dropped.
+    pub fn next_sequence_number(
+        &mut self,
+        address: &AccountAddress,
+        on_chain: Option<u64>,
+    ) -> Option<u64> {
+        self.next_sequence_number_at(address, on_chain, now())
+    }
+
+    fn next_sequence_number_at(
+        &mut self,
+        address: &AccountAddress,
+        on_chain: Option<u64>,
+        now: u64,
+    ) -> Option<u64> {
+        let key = address.to_string();
+        let pending = match self.accounts.get(&key) {
+            Some(pending) => pending,
+            None => return on_chain,
+        };
+        let caught_up = on_chain.map_or(false, |on_chain| on_chain >= pending.next);
+        let expired = now.saturating_sub(pending.submitted_at) >= PENDING_TIMEOUT_SECS;
+        if caught_up || expired {
+            self.accounts.remove(&key);
+            on_chain
+        } else {
+            Some(pending.next)
+        }
+    }
+
+    /// Records that a transaction of `address` with `sequence_number` has been submitted.
+    pub fn record(&mut self, address: &AccountAddress, sequence_number: u64) {
+        self.record_at(address, sequence_number, now())
+    }
+
+    fn record_at(&mut self, address: &AccountAddress, sequence_number: u64, now: u64) {
+        let pending = self
+            .accounts
+            .entry(address.to_string())
+            .or_insert(PendingAccount {
+                next: 0,
+                submitted_at: now,
+            });
+        pending.next = pending.next.max(sequence_number + 1);
+        pending.submitted_at = now;
+    }
+
+    /// Writes the cache back to disk.
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_vec_pretty(&self).map_err(|e| e.to_string())?;
+        fs::write(&self.path, data).map_err(|e| e.to_string())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_ahead_of_chain() {
+        let address = AccountAddress::random();
+        let mut cache = PendingCache::default();
+        assert_eq!(cache.next_sequence_number(&address, None), None);
+        assert_eq!(cache.next_sequence_number(&address, Some(4)), Some(4));
+
+        cache.record(&address, 4);
+        cache.record(&address, 5);
+        assert_eq!(cache.next_sequence_number(&address, Some(4)), Some(6));
+        assert_eq!(cache.next_sequence_number(&address, None), Some(6));
+
+        assert_eq!(cache.next_sequence_number(&address, Some(6)), Some(6));
+        assert!(cache.accounts.is_empty());
+    }
+
+    #[test]
+    fn pending_expires() {
+        let address = AccountAddress::random();
+        let mut cache = PendingCache::default();
+        cache.record_at(&address, 4, 1000);
+        assert_eq!(
+            cache.next_sequence_number_at(&address, Some(4), 1000),
+            Some(5)
+        );
+        assert_eq!(
+            cache.next_sequence_number_at(&address, Some(4), 1000 + PENDING_TIMEOUT_SECS),
+            Some(4)
+        );
+        assert!(cache.accounts.is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+//! Parsing of script arguments given on the command line and checking them against the
+//! signature of the script's `main` function.
+
+use vm::{
+    def::{
+        access::ScriptAccess,
+        file_format::{CompiledScript, SignatureToken},
+    },
+    types::{parse_as_transaction_argument, transaction::TransactionArgument},
+};
+
+/// Parses a whitespace separated list of script arguments.
+///
+/// Each argument is one of a u64 (`42`), an address (`0x1`), a byte array (`b"deadbeef"`) or a
+/// double-quoted string (`"hello world"`). Strings may contain whitespace.
+pub fn parse_script_args(data: &str) -> Result<Vec<TransactionArgument>, String> {
+    split_args(data)?
+        .iter()
+        .map(|arg| parse_as_transaction_argument(arg).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Checks that `args` match the argument types of the `main` function of `script`.
+pub fn check_script_args(
+    script: &CompiledScript,
+    args: &[TransactionArgument],
+) -> Result<(), String> {
+    let handle = script.function_handle_at(script.main().function);
+    let signature = script.function_signature_at(handle.signature);
+    if signature.arg_types.len() != args.len() {
+        return Err(format!(
+            "main expects {} argument(s), {} given",
+            signature.arg_types.len(),
+            args.len()
+        ));
+    }
+    for (idx, (ty, arg)) in signature.arg_types.iter().zip(args.iter()).enumerate() {
+        match (ty, arg) {
+            (SignatureToken::U8, TransactionArgument::U8(_))
+            | (SignatureToken::U64, TransactionArgument::U64(_))
+            | (SignatureToken::U128, TransactionArgument::U128(_))
+            | (SignatureToken::Address, TransactionArgument::Address(_))
+            | (SignatureToken::ByteArray, TransactionArgument::ByteArray(_))
+            | (SignatureToken::String, TransactionArgument::String(_)) => (),
+            _ => {
+                return Err(format!(
+                    "argument {} type mismatch: main expects {:?}, given {:?}",
+                    idx, ty, arg
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Splits `data` on whitespace, keeping double-quoted sections together.
+fn split_args(data: &str) -> Result<Vec<String>, String> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in data.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    args.push(current.clone());
+                    current.clear();
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("unterminated quote in \"{}\"", data));
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vm::types::{AccountAddress, ByteArray};
+
+    #[test]
+    fn parse_mixed_args() {
+        let args = parse_script_args(r#"42 0x1 b"dead" "hello world""#).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                TransactionArgument::U64(42),
+                TransactionArgument::Address(AccountAddress::new({
+                    let mut addr = [0u8; 32];
+                    addr[31] = 1;
+                    addr
+                })),
+                TransactionArgument::ByteArray(ByteArray::new(vec![0xde, 0xad])),
+                TransactionArgument::String("hello world".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_empty_args() {
+        assert!(parse_script_args("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_invalid_args() {
+        parse_script_args("garbage").unwrap_err();
+        parse_script_args(r#""unterminated"#).unwrap_err();
+    }
+}
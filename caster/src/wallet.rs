@@ -0,0 +1,143 @@
+//! Hierarchical deterministic wallets.
+//!
+//! A wallet is a BIP-0039 mnemonic backed master seed, kept encrypted in `<name>.json`. Move
+//! accounts are ed25519 keys derived from the seed along SLIP-0010 paths, so every account of a
+//! wallet can be recovered from its mnemonic alone.
+
+use crate::keystore::{check_name, EncryptedData};
+use bip39::{Language, Mnemonic, MnemonicType, Seed};
+use crypto::{signing::KeyPair, PrivateKey};
+use nextgen_crypto::slip0010::Slip0010;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+const WALLET_DIR: &str = "wallets";
+
+/// The SLIP-0010 path prefix of move accounts, the account index is appended to it.
+pub const DEFAULT_PATH_PREFIX: &str = "m/44/637/0/0";
+
+/// A named wallet holding an encrypted master seed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WalletFile {
+    pub name: String,
+    /// The path prefix accounts are derived under.
+    pub path_prefix: String,
+    crypto: EncryptedData,
+}
+
+impl WalletFile {
+    /// Decrypts the master seed with `passphrase`.
+    pub fn seed(&self, passphrase: &str) -> Result<Vec<u8>, String> {
+        self.crypto.decrypt(passphrase)
+    }
+}
+
+/// The directory holding the wallet files.
+pub struct WalletStore {
+    dir: PathBuf,
+}
+
+impl WalletStore {
+    /// Opens the wallet store under the caster data directory `root`, creating it if needed.
+    pub fn open(root: &PathBuf) -> Self {
+        let dir = root.join(WALLET_DIR);
+        if !dir.exists() {
+            fs::create_dir_all(&dir).expect("should create wallet directory");
+        }
+        WalletStore { dir }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Stores the seed of `mnemonic` under `name`, failing if the name is taken.
+    pub fn insert(
+        &self,
+        name: &str,
+        mnemonic: &Mnemonic,
+        passphrase: &str,
+    ) -> Result<WalletFile, String> {
+        check_name(name)?;
+        let path = self.path(name);
+        if path.exists() {
+            return Err(format!("wallet {} already exists", name));
+        }
+        let seed = Seed::new(mnemonic, "");
+        let wallet = WalletFile {
+            name: name.to_string(),
+            path_prefix: DEFAULT_PATH_PREFIX.to_string(),
+            crypto: EncryptedData::encrypt(seed.as_bytes(), passphrase)?,
+        };
+        let data = serde_json::to_vec_pretty(&wallet).map_err(|e| e.to_string())?;
+        fs::write(path, data).map_err(|e| e.to_string())?;
+        Ok(wallet)
+    }
+
+    /// Loads the wallet `name`.
+    pub fn get(&self, name: &str) -> Result<WalletFile, String> {
+        let data =
+            fs::read(self.path(name)).map_err(|_| format!("wallet {} does not exist", name))?;
+        serde_json::from_slice(&data).map_err(|e| e.to_string())
+    }
+}
+
+/// Generates a fresh english mnemonic of `words` words.
+pub fn generate_mnemonic(words: usize) -> Result<Mnemonic, String> {
+    let mnemonic_type = MnemonicType::for_word_count(words).map_err(|e| e.to_string())?;
+    Ok(Mnemonic::new(mnemonic_type, Language::English))
+}
+
+/// Parses an english mnemonic phrase.
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, String> {
+    let phrase = phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    Mnemonic::from_phrase(&phrase, Language::English).map_err(|e| e.to_string())
+}
+
+/// Derives the key pair at `path` from `seed`.
+pub fn derive_key_pair(seed: &[u8], path: &str) -> Result<KeyPair, String> {
+    let key = Slip0010::derive_from_path(path, seed).map_err(|e| e.to_string())?;
+    let private_key =
+        PrivateKey::from_slice(key.private_key.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(KeyPair::new(private_key))
+}
+
+/// Returns the derivation path of the account at `index` under `prefix`.
+pub fn account_path(prefix: &str, index: u32) -> String {
+    format!("{}/{}", prefix, index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vm::types::AccountAddress;
+
+    #[test]
+    fn derive_is_reproducible() {
+        let mnemonic = generate_mnemonic(24).unwrap();
+        let recovered = parse_mnemonic(&format!("  {}\n", mnemonic.phrase())).unwrap();
+        let seed = Seed::new(&mnemonic, "");
+        let recovered_seed = Seed::new(&recovered, "");
+
+        for index in 0..3 {
+            let path = account_path(DEFAULT_PATH_PREFIX, index);
+            let key_pair = derive_key_pair(seed.as_bytes(), &path).unwrap();
+            let recovered_pair = derive_key_pair(recovered_seed.as_bytes(), &path).unwrap();
+            assert_eq!(key_pair, recovered_pair);
+        }
+
+        let first = derive_key_pair(seed.as_bytes(), &account_path(DEFAULT_PATH_PREFIX, 0));
+        let second = derive_key_pair(seed.as_bytes(), &account_path(DEFAULT_PATH_PREFIX, 1));
+        assert_ne!(
+            AccountAddress::from(first.unwrap().public_key()),
+            AccountAddress::from(second.unwrap().public_key())
+        );
+    }
+
+    #[test]
+    fn derive_rejects_invalid_path() {
+        let seed = [0u8; 64];
+        derive_key_pair(&seed, "44/637").unwrap_err();
+        derive_key_pair(&seed, "m/2147483648").unwrap_err();
+    }
+}
@@ -5,10 +5,12 @@ use crate::{
     errors::*,
     parser::ast::{
         self, BinOp, Block, Builtin, Cmd, CopyableVal, Exp, Field, Fields, Function, FunctionBody,
-        FunctionCall, FunctionSignature as AstFunctionSignature, FunctionVisibility, IfElse, Loop,
-        ModuleDefinition, ModuleIdent, ModuleName, Program, Statement,
-        StructDefinition as MoveStruct, Tag, Type, UnaryOp, Var, Var_, While,
+        FunctionCall, FunctionSignature as AstFunctionSignature, FunctionVisibility, IfElse,
+        ImportDefinition, Loc, Loop, ModuleDefinition, ModuleIdent, ModuleName, Program, Statement,
+        StructDefinition as MoveStruct, StructDefinitionFields, StructName, Tag, Type, TypeVar,
+        UnaryOp, Var, Var_, While,
     },
+    source_map::{CodeSpans, FunctionSpans, ProgramSpanMap, SpanMap},
 };
 
 use failure::*;
@@ -18,19 +20,20 @@ use std::{
         hash_map::Entry::{Occupied, Vacant},
         HashMap, VecDeque,
     },
+    mem,
 };
 use vm::{
     def::{
         access::ModuleAccess,
         file_format::{
-            AddressPoolIndex, ByteArrayPoolIndex, Bytecode, CodeUnit, CompiledModule,
+            AddressPoolIndex, ByteArrayPoolIndex, Bytecode, CodeOffset, CodeUnit, CompiledModule,
             CompiledModuleMut, CompiledProgram, CompiledScriptMut, FieldDefinition,
             FieldDefinitionIndex, FunctionDefinition, FunctionDefinitionIndex, FunctionHandle,
             FunctionHandleIndex, FunctionSignature, FunctionSignatureIndex, Kind, LocalsSignature,
             LocalsSignatureIndex, MemberCount, ModuleHandle, ModuleHandleIndex, SignatureToken,
             StringPoolIndex, StructDefinition, StructDefinitionIndex, StructFieldInformation,
             StructHandle, StructHandleIndex, TableIndex, TypeSignature, TypeSignatureIndex,
-            NO_TYPE_ACTUALS, SELF_MODULE_NAME,
+            SELF_MODULE_NAME,
         },
         printers::TableAccess,
     },
@@ -81,13 +84,16 @@ enum InferredType {
 
     // Signature tokens
     Bool,
+    U8,
     U64,
+    U128,
     String,
     ByteArray,
     Address,
-    Struct(StructHandleIndex),
+    Struct(StructHandleIndex, Vec<SignatureToken>),
     Reference(Box<InferredType>),
     MutableReference(Box<InferredType>),
+    TypeParameter(TableIndex),
 }
 
 impl InferredType {
@@ -96,11 +102,13 @@ impl InferredType {
         use SignatureToken as S;
         match sig_token {
             S::Bool => I::Bool,
+            S::U8 => I::U8,
             S::U64 => I::U64,
+            S::U128 => I::U128,
             S::String => I::String,
             S::ByteArray => I::ByteArray,
             S::Address => I::Address,
-            S::Struct(si, _) => I::Struct(*si),
+            S::Struct(si, tys) => I::Struct(*si, tys.clone()),
             S::Reference(s_inner) => {
                 let i_inner = Self::from_signature_token(&*s_inner);
                 I::Reference(Box::new(i_inner))
@@ -109,9 +117,7 @@ impl InferredType {
                 let i_inner = Self::from_signature_token(&*s_inner);
                 I::MutableReference(Box::new(i_inner))
             }
-            S::TypeParameter(_) => {
-                unimplemented!();
-            }
+            S::TypeParameter(idx) => I::TypeParameter(*idx),
         }
     }
 
@@ -119,14 +125,27 @@ impl InferredType {
         match self {
             InferredType::Anything => bail!("could not infer struct type"),
             InferredType::Bool => bail!("no struct type for Bool"),
+            InferredType::U8 => bail!("no struct type for U8"),
             InferredType::U64 => bail!("no struct type for U64"),
+            InferredType::U128 => bail!("no struct type for U128"),
             InferredType::String => bail!("no struct type for String"),
             InferredType::ByteArray => bail!("no struct type for ByteArray"),
             InferredType::Address => bail!("no struct type for Address"),
             InferredType::Reference(inner) | InferredType::MutableReference(inner) => {
                 inner.get_struct_handle()
             }
-            InferredType::Struct(idx) => Ok(*idx),
+            InferredType::Struct(idx, _) => Ok(*idx),
+            InferredType::TypeParameter(idx) => bail!("no struct type for type parameter {}", idx),
+        }
+    }
+
+    fn get_type_actuals(&self) -> &[SignatureToken] {
+        match self {
+            InferredType::Reference(inner) | InferredType::MutableReference(inner) => {
+                inner.get_type_actuals()
+            }
+            InferredType::Struct(_, tys) => tys,
+            _ => &[],
         }
     }
 }
@@ -406,6 +425,20 @@ fn add_item<U>(item: U, table: &mut Vec<U>) -> Result<TableIndex> {
     Ok(size as TableIndex)
 }
 
+fn compile_kind(kind: &ast::Kind) -> Kind {
+    match kind {
+        ast::Kind::Value => Kind::Copyable,
+        ast::Kind::Resource => Kind::Resource,
+    }
+}
+
+fn kind_constraints(type_formals: &[(TypeVar, ast::Kind)]) -> Vec<Kind> {
+    type_formals
+        .iter()
+        .map(|(_, kind)| compile_kind(kind))
+        .collect()
+}
+
 trait Scope {
     fn make_string(&mut self, s: String) -> Result<StringPoolIndex>;
     fn make_byte_array(&mut self, buf: ByteArray) -> Result<ByteArrayPoolIndex>;
@@ -424,6 +457,7 @@ trait Scope {
         module_idx: ModuleHandleIndex,
         name_idx: StringPoolIndex,
         kind: Kind,
+        kind_constraints: Vec<Kind>,
     ) -> Result<StructHandleIndex>;
     fn make_function_handle(
         &mut self,
@@ -530,12 +564,13 @@ impl<'a> Scope for ModuleScope<'a> {
         module_idx: ModuleHandleIndex,
         name_idx: StringPoolIndex,
         kind: Kind,
+        kind_constraints: Vec<Kind>,
     ) -> Result<StructHandleIndex> {
         let sh = StructHandle {
             module: module_idx,
             name: name_idx,
             kind,
-            kind_constraints: vec![],
+            kind_constraints,
         };
         let size = self.module.struct_handles.len();
         if size >= TABLE_MAX_SIZE {
@@ -784,12 +819,13 @@ impl<'a> Scope for ScriptScope<'a> {
         module_idx: ModuleHandleIndex,
         name_idx: StringPoolIndex,
         kind: Kind,
+        kind_constraints: Vec<Kind>,
     ) -> Result<StructHandleIndex> {
         let sh = StructHandle {
             module: module_idx,
             name: name_idx,
             kind,
-            kind_constraints: vec![],
+            kind_constraints,
         };
         let size = self.script.struct_handles.len();
         if size >= TABLE_MAX_SIZE {
@@ -923,6 +959,16 @@ struct Compiler<S: Scope + Sized> {
     type_signatures: HashMap<TypeSignature, TypeSignatureIndex>,
     function_signatures: HashMap<FunctionSignature, FunctionSignatureIndex>,
     locals_signatures: HashMap<LocalsSignature, LocalsSignatureIndex>,
+    // kind constraints of the type formals of the structs declared in the module being compiled
+    struct_kind_constraints: HashMap<StructName, Vec<Kind>>,
+    // type formals in scope of the struct or function being compiled
+    type_formals: HashMap<TypeVar, TableIndex>,
+    // source spans of the function being compiled
+    code_spans: CodeSpans,
+    // names of the locals of the function being compiled, arguments first
+    local_names: Vec<String>,
+    // errors found so far, reported together once compilation cannot go any further
+    errors: Vec<SourceError>,
     // resolution scope
     scope: S,
 }
@@ -942,6 +988,15 @@ pub fn compile_module<'a, T: 'a + ModuleAccess>(
     module: &ModuleDefinition,
     modules: impl IntoIterator<Item = &'a T>,
 ) -> Result<CompiledModule> {
+    Ok(compile_module_with_spans(address, module, modules)?.0)
+}
+
+/// Compile a module and return the source spans of its code
+pub fn compile_module_with_spans<'a, T: 'a + ModuleAccess>(
+    address: &AccountAddress,
+    module: &ModuleDefinition,
+    modules: impl IntoIterator<Item = &'a T>,
+) -> Result<(CompiledModule, SpanMap)> {
     // Convert to &CompiledModule as that's what's used throughout internally.
     let modules = modules.into_iter().map(|module| module.as_module());
 
@@ -955,7 +1010,7 @@ fn compile_module_impl<'a>(
     address: &AccountAddress,
     module: &ModuleDefinition,
     scope: ModuleScope<'a>,
-) -> Result<CompiledModule> {
+) -> Result<(CompiledModule, SpanMap)> {
     let mut compiler = Compiler::new(scope);
 
     // Create an empty locals signature with index 0.
@@ -970,40 +1025,64 @@ fn compile_module_impl<'a>(
     let mh_idx = compiler.make_module_handle(addr_idx, name_idx)?;
     assert!(mh_idx.0 == 0);
 
-    for import in &module.imports {
-        compiler.import_module(
-            match &import.ident {
-                ModuleIdent::Transaction(_) => address,
-                ModuleIdent::Qualified(id) => &id.address,
-            },
-            &import.ident.get_name().name_ref(),
-            &import.alias,
-        )?;
+    compiler.import_modules(address, &module.imports);
+    // Structs can refer to each other in any order, so their type formals are known upfront.
+    for struct_ in &module.structs {
+        compiler.struct_kind_constraints.insert(
+            struct_.name.clone(),
+            kind_constraints(&struct_.type_formals),
+        );
     }
     for struct_ in &module.structs {
-        compiler.define_struct(mh_idx, &struct_)?;
+        let result = compiler.define_struct(mh_idx, &struct_);
+        compiler.report(
+            result,
+            struct_.name_span,
+            format!("in struct `{}`", struct_.name),
+        );
     }
     for (name, function) in &module.functions {
-        compiler.define_function(name.name_ref(), &function)?;
+        let result = compiler.define_function(name.name_ref(), &function);
+        compiler.report(
+            result,
+            function.name_span,
+            format!("in the signature of function `{}`", name),
+        );
     }
+    // Function bodies refer to the declarations above, so errors in those would only cascade.
+    compiler.check_errors()?;
+
+    let mut spans = SpanMap::default();
     for (name, function) in &module.functions {
         match &function.body {
             FunctionBody::Move { locals, code } => {
                 debug!("compile move function: {} {}", name, &function.signature);
-                let compiled_code =
-                    compiler.compile_function(&function.signature.formals, locals, code)?;
-                compiler
-                    .scope
-                    .publish_code(name.name_ref(), compiled_code)?;
+                let first_error = compiler.errors.len();
+                let result = compiler.compile_function(&function.signature, locals, code);
+                let note = format!("in function `{}`", name);
+                if let Some(compiled_code) =
+                    compiler.report(result, function.name_span, note.clone())
+                {
+                    compiler
+                        .scope
+                        .publish_code(name.name_ref(), compiled_code)?;
+                }
+                compiler.add_note(first_error, &note);
             }
             FunctionBody::Native => (),
         }
+        spans.functions.push(FunctionSpans {
+            name: name.name_ref().to_string(),
+            locals: mem::replace(&mut compiler.local_names, vec![]),
+            code: mem::replace(&mut compiler.code_spans, CodeSpans::new()),
+        });
     }
-    compiler
-        .scope
-        .module
-        .freeze()
-        .map_err(|errs| InternalCompilerError::BoundsCheckErrors(errs).into())
+    compiler.check_errors()?;
+    let module = match compiler.scope.module.freeze() {
+        Ok(module) => module,
+        Err(errs) => bail_err!(InternalCompilerError::BoundsCheckErrors(errs)),
+    };
+    Ok((module, spans))
 }
 
 //
@@ -1016,6 +1095,15 @@ pub fn compile_program<'a, T: 'a + ModuleAccess>(
     program: &Program,
     deps: impl IntoIterator<Item = &'a T>,
 ) -> Result<CompiledProgram> {
+    Ok(compile_program_with_spans(address, program, deps)?.0)
+}
+
+/// Compile a transaction program and return the source spans of its modules and script
+pub fn compile_program_with_spans<'a, T: 'a + ModuleAccess>(
+    address: &AccountAddress,
+    program: &Program,
+    deps: impl IntoIterator<Item = &'a T>,
+) -> Result<(CompiledProgram, ProgramSpanMap)> {
     // Normalize into a Vec<&CompiledModule>.
     let deps: Vec<&CompiledModule> = deps.into_iter().map(|dep| dep.as_module()).collect();
 
@@ -1027,15 +1115,17 @@ fn compile_program_impl(
     address: &AccountAddress,
     program: &Program,
     deps: Vec<&CompiledModule>,
-) -> Result<CompiledProgram> {
+) -> Result<(CompiledProgram, ProgramSpanMap)> {
     // Compile modules in the program
     let mut modules = vec![];
+    let mut spans = ProgramSpanMap::default();
     for m in &program.modules {
-        let module = {
+        let (module, module_spans) = {
             let deps = deps.iter().copied().chain(&modules);
-            compile_module(address, &m, deps)?
+            compile_module_with_spans(address, &m, deps)?
         };
         modules.push(module);
+        spans.modules.push(module_spans);
     }
 
     // Compile transaction script
@@ -1060,18 +1150,20 @@ fn compile_program_impl(
     let mh_idx = compiler.make_module_handle(addr_idx, name_idx)?;
     assert!(mh_idx.0 == 0);
 
-    for import in &program.script.imports {
-        compiler.import_module(
-            match &import.ident {
-                ModuleIdent::Transaction(_) => address,
-                ModuleIdent::Qualified(id) => &id.address,
-            },
-            &import.ident.get_name().name_ref(),
-            &import.alias,
-        )?;
-    }
-
-    func_def = compiler.compile_main(&program.script.main)?;
+    compiler.import_modules(address, &program.script.imports);
+    compiler.check_errors()?;
+
+    let result = compiler.compile_main(&program.script.main);
+    let note = "in function `main`";
+    let main = compiler.report(result, program.script.main.name_span, note);
+    compiler.add_note(0, note);
+    compiler.check_errors()?;
+    func_def = main.expect("main compiled without errors");
+    spans.script.functions.push(FunctionSpans {
+        name: "main".to_string(),
+        locals: compiler.local_names,
+        code: compiler.code_spans,
+    });
 
     let mut script = compiler.scope.script;
     script.main = func_def;
@@ -1080,7 +1172,7 @@ fn compile_program_impl(
         Err(errs) => bail_err!(InternalCompilerError::BoundsCheckErrors(errs)),
     };
 
-    Ok(CompiledProgram::new(modules, script))
+    Ok((CompiledProgram::new(modules, script), spans))
 }
 
 impl<S: Scope + Sized> Compiler<S> {
@@ -1095,11 +1187,34 @@ impl<S: Scope + Sized> Compiler<S> {
             type_signatures: HashMap::new(),
             function_signatures: HashMap::new(),
             locals_signatures: HashMap::new(),
+            struct_kind_constraints: HashMap::new(),
+            type_formals: HashMap::new(),
+            code_spans: CodeSpans::new(),
+            local_names: vec![],
+            errors: vec![],
             // resolution scope
             scope,
         }
     }
 
+    fn import_modules(&mut self, address: &AccountAddress, imports: &[ImportDefinition]) {
+        for import in imports {
+            let result = self.import_module(
+                match &import.ident {
+                    ModuleIdent::Transaction(_) => address,
+                    ModuleIdent::Qualified(id) => &id.address,
+                },
+                &import.ident.get_name().name_ref(),
+                &import.alias,
+            );
+            self.report(
+                result,
+                Loc::default(),
+                format!("in the import of `{}`", import.ident.get_name()),
+            );
+        }
+    }
+
     fn import_module(
         &mut self,
         address: &AccountAddress,
@@ -1120,16 +1235,19 @@ impl<S: Scope + Sized> Compiler<S> {
     ) -> Result<SignatureToken> {
         match sig_token {
             SignatureToken::Bool
+            | SignatureToken::U8
             | SignatureToken::U64
+            | SignatureToken::U128
             | SignatureToken::String
             | SignatureToken::ByteArray
             | SignatureToken::Address
             | SignatureToken::TypeParameter(_) => Ok(sig_token),
-            SignatureToken::Struct(sh_idx, _) => {
+            SignatureToken::Struct(sh_idx, tys) => {
                 let module = self.scope.get_imported_module(module_name)?;
                 let struct_handle = module.struct_handle_at(sh_idx);
                 let defining_module_handle = module.module_handle_at(struct_handle.module);
                 let kind = struct_handle.kind;
+                let kind_constraints = struct_handle.kind_constraints.clone();
 
                 let struct_name = module.string_at(struct_handle.name).to_string();
                 let defining_module_name =
@@ -1142,9 +1260,17 @@ impl<S: Scope + Sized> Compiler<S> {
                 let defining_module_handle_idx =
                     self.make_module_handle(defining_module_addr_idx, defining_module_name_idx)?;
 
-                let local_sh_idx =
-                    self.make_struct_handle(defining_module_handle_idx, name_idx, kind)?;
-                Ok(SignatureToken::Struct(local_sh_idx, vec![]))
+                let local_sh_idx = self.make_struct_handle(
+                    defining_module_handle_idx,
+                    name_idx,
+                    kind,
+                    kind_constraints,
+                )?;
+                let mut local_tys = vec![];
+                for ty in tys {
+                    local_tys.push(self.import_signature_token(module_name, ty)?);
+                }
+                Ok(SignatureToken::Struct(local_sh_idx, local_tys))
             }
             SignatureToken::Reference(sub_sig_token) => Ok(SignatureToken::Reference(Box::new(
                 self.import_signature_token(module_name, *sub_sig_token)?,
@@ -1176,7 +1302,7 @@ impl<S: Scope + Sized> Compiler<S> {
             Ok(FunctionSignature {
                 return_types,
                 arg_types,
-                kind_constraints: vec![],
+                kind_constraints: func_sig.kind_constraints,
             })
         }
     }
@@ -1192,8 +1318,16 @@ impl<S: Scope + Sized> Compiler<S> {
             } else {
                 Kind::Copyable
             },
+            kind_constraints(&struct_.type_formals),
         )?;
-        let struct_def = self.define_fields(sh_idx, &struct_.fields)?;
+        self.bind_type_formals(&struct_.type_formals)?;
+        let struct_def = match &struct_.fields {
+            StructDefinitionFields::Move { fields } => self.define_fields(sh_idx, fields)?,
+            StructDefinitionFields::Native => StructDefinition {
+                struct_handle: sh_idx,
+                field_information: StructFieldInformation::Native,
+            },
+        };
         self.scope.publish_struct_def(
             name,
             if struct_.resource_kind {
@@ -1245,7 +1379,7 @@ impl<S: Scope + Sized> Compiler<S> {
         // compile script
         let code = match &main.body {
             FunctionBody::Move { code, locals } => {
-                self.compile_function(&main.signature.formals, locals, code)?
+                self.compile_function(&main.signature, locals, code)?
             }
             FunctionBody::Native => bail!("main() cannot be a native function"),
         };
@@ -1413,16 +1547,19 @@ impl<S: Scope + Sized> Compiler<S> {
         module_idx: ModuleHandleIndex,
         name_idx: StringPoolIndex,
         kind: Kind,
+        kind_constraints: Vec<Kind>,
     ) -> Result<StructHandleIndex> {
         let sh = StructHandle {
             module: module_idx,
             name: name_idx,
             kind,
-            kind_constraints: vec![],
+            kind_constraints: kind_constraints.clone(),
         };
         Ok(match self.structs.get(&sh) {
             None => {
-                let idx = self.scope.make_struct_handle(module_idx, name_idx, kind)?;
+                let idx =
+                    self.scope
+                        .make_struct_handle(module_idx, name_idx, kind, kind_constraints)?;
                 self.structs.insert(sh, idx);
                 idx
             }
@@ -1527,6 +1664,7 @@ impl<S: Scope + Sized> Compiler<S> {
         &mut self,
         signature: &AstFunctionSignature,
     ) -> Result<FunctionSignature> {
+        self.bind_type_formals(&signature.type_formals)?;
         let mut ret_sig: Vec<SignatureToken> = Vec::new();
         for t in &signature.return_type {
             ret_sig.push(self.build_signature_token(&t)?);
@@ -1538,10 +1676,65 @@ impl<S: Scope + Sized> Compiler<S> {
         Ok(FunctionSignature {
             return_types: ret_sig,
             arg_types: arg_sig,
-            kind_constraints: vec![],
+            kind_constraints: kind_constraints(&signature.type_formals),
         })
     }
 
+    // Bring the given type formals in scope, replacing the ones of the previous struct or function
+    fn bind_type_formals(&mut self, type_formals: &[(TypeVar, ast::Kind)]) -> Result<()> {
+        self.type_formals.clear();
+        for (idx, (type_var, _)) in type_formals.iter().enumerate() {
+            if self
+                .type_formals
+                .insert(type_var.clone(), idx as TableIndex)
+                .is_some()
+            {
+                bail!("duplicate type parameter {}", type_var);
+            }
+        }
+        Ok(())
+    }
+
+    // Build the type actuals of an instruction, the empty list being at index `NO_TYPE_ACTUALS`
+    fn build_type_actuals(&mut self, tys: &[Type]) -> Result<LocalsSignature> {
+        let mut tokens = vec![];
+        for ty in tys {
+            tokens.push(self.build_signature_token(ty)?);
+        }
+        Ok(LocalsSignature(tokens))
+    }
+
+    // Make the struct handle of a struct declared in the module being compiled
+    fn make_self_struct_handle(
+        &mut self,
+        name: &StructName,
+        kind: Kind,
+    ) -> Result<StructHandleIndex> {
+        let kind_constraints = match self.struct_kind_constraints.get(name) {
+            None => bail!("No struct definition for name {}", name),
+            Some(kind_constraints) => kind_constraints.clone(),
+        };
+        let name_idx = self.make_string(name.name_ref())?;
+        self.make_struct_handle(ModuleHandleIndex::new(0), name_idx, kind, kind_constraints)
+    }
+
+    // Kind constraints of the struct `name` declared in `module_name`, as published in that
+    // module. Unknown structs have none, and are reported when the module is linked.
+    fn imported_struct_kind_constraints(&self, module_name: &str, name: &str) -> Vec<Kind> {
+        let module = match self.scope.get_imported_module(module_name) {
+            Ok(module) => module,
+            Err(_) => return vec![],
+        };
+        module
+            .struct_handles()
+            .iter()
+            .find(|handle| {
+                handle.module == ModuleHandleIndex::new(0) && module.string_at(handle.name) == name
+            })
+            .map(|handle| handle.kind_constraints.clone())
+            .unwrap_or_default()
+    }
+
     fn build_signature_token(&mut self, type_: &Type) -> Result<SignatureToken> {
         match type_ {
             Type::Normal(kind, tag) => self.build_normal_signature_token(&kind, &tag),
@@ -1567,25 +1760,34 @@ impl<S: Scope + Sized> Compiler<S> {
     ) -> Result<SignatureToken> {
         match (kind, tag) {
             (ast::Kind::Value, Tag::Address) => Ok(SignatureToken::Address),
+            (ast::Kind::Value, Tag::U8) => Ok(SignatureToken::U8),
             (ast::Kind::Value, Tag::U64) => Ok(SignatureToken::U64),
+            (ast::Kind::Value, Tag::U128) => Ok(SignatureToken::U128),
             (ast::Kind::Value, Tag::Bool) => Ok(SignatureToken::Bool),
             (ast::Kind::Value, Tag::ByteArray) => Ok(SignatureToken::ByteArray),
-            (kind, Tag::Struct(ctype)) => {
+            (ast::Kind::Value, Tag::String) => Ok(SignatureToken::String),
+            (kind, Tag::Struct(ctype, tys)) => {
                 let module_name = &ctype.module().name();
-                let module_idx = if self.scope.get_name().is_ok() && module_name == ModuleName::SELF
-                {
-                    ModuleHandleIndex::new(0)
+                let kind = compile_kind(kind);
+                let sh_idx = if self.scope.get_name().is_ok() && module_name == ModuleName::SELF {
+                    self.make_self_struct_handle(ctype.name(), kind)?
                 } else {
-                    self.scope.get_imported_module_handle(module_name)?
+                    let module_idx = self.scope.get_imported_module_handle(module_name)?;
+                    let name_idx = self.make_string(&ctype.name().name_ref())?;
+                    let kind_constraints =
+                        self.imported_struct_kind_constraints(module_name, ctype.name().name_ref());
+                    self.make_struct_handle(module_idx, name_idx, kind, kind_constraints)?
                 };
-                let name_idx = self.make_string(&ctype.name().name_ref())?;
-                let kind = match kind {
-                    ast::Kind::Value => Kind::Copyable,
-                    ast::Kind::Resource => Kind::Resource,
-                };
-                let sh_idx = self.make_struct_handle(module_idx, name_idx, kind)?;
-                Ok(SignatureToken::Struct(sh_idx, vec![]))
+                let mut actuals = vec![];
+                for ty in tys {
+                    actuals.push(self.build_signature_token(ty)?);
+                }
+                Ok(SignatureToken::Struct(sh_idx, actuals))
             }
+            (_, Tag::TypeParameter(type_var)) => match self.type_formals.get(type_var) {
+                None => bail!("unbound type parameter {}", type_var),
+                Some(idx) => Ok(SignatureToken::TypeParameter(*idx)),
+            },
             (ast::Kind::Value, _) => bail!("unknown value type {:?}", tag),
             (ast::Kind::Resource, _) => bail!("unknown resource type {:?}", tag),
         }
@@ -1596,13 +1798,21 @@ impl<S: Scope + Sized> Compiler<S> {
     //
     fn compile_function(
         &mut self,
-        formals: &[(Var, Type)],
+        signature: &AstFunctionSignature,
         locals: &[(Var_, Type)],
         body: &Block,
     ) -> Result<CodeUnit> {
         let mut code = CodeUnit::default();
         let mut function_frame = FunctionFrame::new();
-        for (var, t) in formals {
+        self.code_spans.clear();
+        self.local_names = signature
+            .formals
+            .iter()
+            .map(|(var, _)| var.name().to_string())
+            .chain(locals.iter().map(|(var_, _)| var_.value.name().to_string()))
+            .collect();
+        self.bind_type_formals(&signature.type_formals)?;
+        for (var, t) in &signature.formals {
             let type_sig = self.build_signature_token(t)?;
             function_frame.define_local(var, type_sig)?;
         }
@@ -1635,44 +1845,112 @@ impl<S: Scope + Sized> Compiler<S> {
         };
         for stmt in &body.stmts {
             debug!("{}", stmt);
-            let stmt_info;
-            match stmt {
+            // The stack depth and loops in scope are restored when a statement fails, so that
+            // the statements after it can still be checked.
+            let stack_depth = function_frame.cur_stack_depth;
+            let loop_depth = function_frame.loops.len();
+            let (result, loc) = match stmt {
                 Statement::CommandStatement(command) => {
-                    stmt_info = self.compile_command(&command, code, function_frame)?;
-                    debug!("{:?}", code);
+                    let start = code.code.len();
+                    let result = self.compile_command(&command, code, function_frame);
+                    if result.is_ok() {
+                        self.record_span(command.span, start, code);
+                    }
+                    (result, command.span)
                 }
                 Statement::WhileStatement(while_) => {
                     // always assume the loop might not be taken
-                    stmt_info = self.compile_while(&while_, code, function_frame)?;
-                    debug!("{:?}", code);
+                    let result = self.compile_while(&while_, code, function_frame);
+                    (result, while_.cond.span)
                 }
                 Statement::LoopStatement(loop_) => {
-                    stmt_info = self.compile_loop(&loop_, code, function_frame)?;
-                    debug!("{:?}", code);
+                    let result = self.compile_loop(&loop_, code, function_frame);
+                    (result, Loc::default())
                 }
                 Statement::IfElseStatement(if_else) => {
-                    stmt_info = self.compile_if_else(&if_else, code, function_frame)?;
-                    debug!("{:?}", code);
+                    let result = self.compile_if_else(&if_else, code, function_frame);
+                    (result, if_else.cond.span)
                 }
                 Statement::VerifyStatement(_) | Statement::AssumeStatement(_) => continue,
                 Statement::EmptyStatement => continue,
             };
+            debug!("{:?}", code);
+            let stmt_info = match result {
+                Ok(stmt_info) => stmt_info,
+                Err(err) => {
+                    self.errors.push(Self::source_error(err, loc));
+                    function_frame.cur_stack_depth = stack_depth;
+                    function_frame.loops.truncate(loop_depth);
+                    ControlFlowInfo {
+                        reachable_break: false,
+                        terminal_node: false,
+                    }
+                }
+            };
             cf_info = ControlFlowInfo::successor(cf_info, stmt_info);
         }
         Ok(cf_info)
     }
 
+    // Turn an error into a `SourceError` located at `loc`, unless it already is one or the
+    // parser did not give the node a location.
+    fn source_error(err: Error, loc: Loc) -> SourceError {
+        match err.downcast::<SourceError>() {
+            Ok(error) => error,
+            Err(err) => {
+                let loc = if loc == Loc::default() {
+                    None
+                } else {
+                    Some(loc)
+                };
+                SourceError::new(loc, err.to_string())
+            }
+        }
+    }
+
+    // Record the error of `result`, if any, located at `loc` and noting the item it happened in,
+    // and return the value of `result` otherwise.
+    fn report<T>(&mut self, result: Result<T>, loc: Loc, note: impl Into<String>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.errors
+                    .push(Self::source_error(err, loc).with_note(note));
+                None
+            }
+        }
+    }
+
+    // Add `note` to the errors found since there were `first_error` errors.
+    fn add_note(&mut self, first_error: usize, note: &str) {
+        for error in &mut self.errors[first_error..] {
+            if !error.notes.iter().any(|n| n == note) {
+                error.notes.push(note.to_string());
+            }
+        }
+    }
+
+    // Fail with every error found so far, if any.
+    fn check_errors(&mut self) -> Result<()> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        bail_err!(CompilationErrors(mem::replace(&mut self.errors, vec![])))
+    }
+
     fn compile_if_else(
         &mut self,
         if_else: &IfElse,
         code: &mut CodeUnit,
         function_frame: &mut FunctionFrame,
     ) -> Result<ControlFlowInfo> {
+        let cond_start = code.code.len();
         self.compile_expression(&if_else.cond, code, function_frame)?;
 
         let brfalse_ins_loc = code.code.len();
         code.code.push(Bytecode::BrFalse(0)); // placeholder, final branch target replaced later
         function_frame.pop()?;
+        self.record_span(if_else.cond.span, cond_start, code);
         let if_cf_info = self.compile_block(&if_else.if_block, code, function_frame)?;
 
         let mut else_block_location = code.code.len();
@@ -1715,6 +1993,7 @@ impl<S: Scope + Sized> Compiler<S> {
         let brfalse_loc = code.code.len();
         code.code.push(Bytecode::BrFalse(0)); // placeholder, final branch target replaced later
         function_frame.pop()?;
+        self.record_span(while_.cond.span, loop_start_loc, code);
 
         self.compile_block(&while_.block, code, function_frame)?;
         code.code.push(Bytecode::Branch(loop_start_loc as u16));
@@ -1803,11 +2082,13 @@ impl<S: Scope + Sized> Compiler<S> {
                     function_frame.pop()?;
                 }
             }
-            Cmd::Unpack(name, bindings, e) => {
+            Cmd::Unpack(name, tys, bindings, e) => {
                 self.compile_expression(e, code, function_frame)?;
 
                 let (_is_resource, def_idx) = self.scope.get_struct_def(name.name_ref())?;
-                code.code.push(Bytecode::Unpack(def_idx, NO_TYPE_ACTUALS));
+                let type_actuals = self.build_type_actuals(tys)?;
+                let type_actuals_idx = self.make_locals_signature(&type_actuals)?;
+                code.code.push(Bytecode::Unpack(def_idx, type_actuals_idx));
                 function_frame.pop()?;
 
                 for lhs_variable in bindings.values().rev() {
@@ -1855,6 +2136,17 @@ impl<S: Scope + Sized> Compiler<S> {
         })
     }
 
+    // Record `loc` as the source span of the instructions emitted from `start` on that do not
+    // have one yet. Nodes built by the parser without a location are ignored.
+    fn record_span(&mut self, loc: Loc, start: usize, code: &CodeUnit) {
+        if loc == Loc::default() {
+            return;
+        }
+        for offset in start..code.code.len() {
+            self.code_spans.entry(offset as CodeOffset).or_insert(loc);
+        }
+    }
+
     fn make_singleton_vec_deque(&mut self, t: InferredType) -> VecDeque<InferredType> {
         let mut v = VecDeque::new();
         v.push_back(t);
@@ -1881,11 +2173,21 @@ impl<S: Scope + Sized> Compiler<S> {
                     function_frame.push()?;
                     Ok(self.make_singleton_vec_deque(InferredType::Address))
                 }
+                CopyableVal::U8(i) => {
+                    code.code.push(Bytecode::LdU8(*i));
+                    function_frame.push()?;
+                    Ok(self.make_singleton_vec_deque(InferredType::U8))
+                }
                 CopyableVal::U64(i) => {
                     code.code.push(Bytecode::LdConst(*i));
                     function_frame.push()?;
                     Ok(self.make_singleton_vec_deque(InferredType::U64))
                 }
+                CopyableVal::U128(i) => {
+                    code.code.push(Bytecode::LdU128(*i));
+                    function_frame.push()?;
+                    Ok(self.make_singleton_vec_deque(InferredType::U128))
+                }
                 CopyableVal::ByteArray(buf) => {
                     let buf_idx = self.make_byte_array(buf)?;
                     code.code.push(Bytecode::LdByteArray(buf_idx));
@@ -1903,21 +2205,21 @@ impl<S: Scope + Sized> Compiler<S> {
                 }
                 CopyableVal::String(_) => bail!("nice try! come back later {:?}", cv),
             },
-            Exp::Pack(name, fields) => {
-                let module_idx = ModuleHandleIndex::new(0);
-                let name_idx = self.make_string(name.name_ref())?;
+            Exp::Pack(name, tys, fields) => {
                 let (is_resource, def_idx) = self.scope.get_struct_def(name.name_ref())?;
-                let sh = self.make_struct_handle(module_idx, name_idx, is_resource)?;
+                let sh = self.make_self_struct_handle(name, is_resource)?;
+                let type_actuals = self.build_type_actuals(tys)?;
+                let type_actuals_idx = self.make_locals_signature(&type_actuals)?;
                 for (_, exp) in fields.iter() {
                     self.compile_expression(exp, code, function_frame)?;
                 }
 
-                code.code.push(Bytecode::Pack(def_idx, NO_TYPE_ACTUALS));
+                code.code.push(Bytecode::Pack(def_idx, type_actuals_idx));
                 for _ in fields.iter() {
                     function_frame.pop()?;
                 }
                 function_frame.push()?;
-                Ok(self.make_singleton_vec_deque(InferredType::Struct(sh)))
+                Ok(self.make_singleton_vec_deque(InferredType::Struct(sh, type_actuals.0)))
             }
             Exp::UnaryExp(op, e) => {
                 self.compile_expression(e, code, function_frame)?;
@@ -1929,41 +2231,53 @@ impl<S: Scope + Sized> Compiler<S> {
                 }
             }
             Exp::BinopExp(e1, op, e2) => {
-                self.compile_expression(e1, code, function_frame)?;
+                // Integer operators give a result of the type of their left operand
+                let int_type = self
+                    .compile_expression(e1, code, function_frame)?
+                    .pop_front()
+                    .unwrap_or(InferredType::Anything);
                 self.compile_expression(e2, code, function_frame)?;
                 function_frame.pop()?;
                 match op {
                     BinOp::Add => {
                         code.code.push(Bytecode::Add);
-                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                        Ok(self.make_singleton_vec_deque(int_type))
                     }
                     BinOp::Sub => {
                         code.code.push(Bytecode::Sub);
-                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                        Ok(self.make_singleton_vec_deque(int_type))
                     }
                     BinOp::Mul => {
                         code.code.push(Bytecode::Mul);
-                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                        Ok(self.make_singleton_vec_deque(int_type))
                     }
                     BinOp::Mod => {
                         code.code.push(Bytecode::Mod);
-                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                        Ok(self.make_singleton_vec_deque(int_type))
                     }
                     BinOp::Div => {
                         code.code.push(Bytecode::Div);
-                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                        Ok(self.make_singleton_vec_deque(int_type))
                     }
                     BinOp::BitOr => {
                         code.code.push(Bytecode::BitOr);
-                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                        Ok(self.make_singleton_vec_deque(int_type))
                     }
                     BinOp::BitAnd => {
                         code.code.push(Bytecode::BitAnd);
-                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                        Ok(self.make_singleton_vec_deque(int_type))
                     }
                     BinOp::Xor => {
                         code.code.push(Bytecode::Xor);
-                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                        Ok(self.make_singleton_vec_deque(int_type))
+                    }
+                    BinOp::Shl => {
+                        code.code.push(Bytecode::Shl);
+                        Ok(self.make_singleton_vec_deque(int_type))
+                    }
+                    BinOp::Shr => {
+                        code.code.push(Bytecode::Shr);
+                        Ok(self.make_singleton_vec_deque(int_type))
                     }
                     BinOp::Or => {
                         code.code.push(Bytecode::Or);
@@ -2070,7 +2384,8 @@ impl<S: Scope + Sized> Compiler<S> {
             InferredType::Reference(_) => false,
             _ => true,
         };
-        let inner_token = Box::new(InferredType::from_signature_token(&field_type.0));
+        let field_type = field_type.0.substitute(struct_type.get_type_actuals());
+        let inner_token = Box::new(InferredType::from_signature_token(&field_type));
         Ok(if is_mutable {
             if !input_is_mutable {
                 bail!("Unsupported Syntax: Cannot take a mutable field reference in an immutable reference. It is not expressible in the bytecode");
@@ -2162,26 +2477,28 @@ impl<S: Scope + Sized> Compiler<S> {
                         function_frame.push()?;
                         Ok(self.make_singleton_vec_deque(InferredType::Address))
                     }
-                    Builtin::Exists(name) => {
+                    Builtin::Exists(name, tys) => {
                         let (_, def_idx) = self.scope.get_struct_def(name.name_ref())?;
-                        code.code.push(Bytecode::Exists(def_idx, NO_TYPE_ACTUALS));
+                        let type_actuals = self.build_type_actuals(tys)?;
+                        let type_actuals_idx = self.make_locals_signature(&type_actuals)?;
+                        code.code.push(Bytecode::Exists(def_idx, type_actuals_idx));
                         function_frame.pop()?;
                         function_frame.push()?;
                         Ok(self.make_singleton_vec_deque(InferredType::Bool))
                     }
-                    Builtin::BorrowGlobal(name) => {
+                    Builtin::BorrowGlobal(name, tys) => {
                         let (is_resource, def_idx) = self.scope.get_struct_def(name.name_ref())?;
+                        let type_actuals = self.build_type_actuals(tys)?;
+                        let type_actuals_idx = self.make_locals_signature(&type_actuals)?;
                         code.code
-                            .push(Bytecode::BorrowGlobal(def_idx, NO_TYPE_ACTUALS));
+                            .push(Bytecode::BorrowGlobal(def_idx, type_actuals_idx));
                         function_frame.pop()?;
                         function_frame.push()?;
 
-                        let module_idx = ModuleHandleIndex::new(0);
-                        let name_idx = self.make_string(name.name_ref())?;
-                        let sh = self.make_struct_handle(module_idx, name_idx, is_resource)?;
+                        let sh = self.make_self_struct_handle(name, is_resource)?;
                         Ok(
                             self.make_singleton_vec_deque(InferredType::MutableReference(
-                                Box::new(InferredType::Struct(sh)),
+                                Box::new(InferredType::Struct(sh, type_actuals.0)),
                             )),
                         )
                     }
@@ -2204,21 +2521,24 @@ impl<S: Scope + Sized> Compiler<S> {
                         function_frame.pop()?;
                         Ok(VecDeque::new())
                     }
-                    Builtin::MoveFrom(name) => {
+                    Builtin::MoveFrom(name, tys) => {
                         let (is_resource, def_idx) = self.scope.get_struct_def(name.name_ref())?;
-                        code.code.push(Bytecode::MoveFrom(def_idx, NO_TYPE_ACTUALS));
+                        let type_actuals = self.build_type_actuals(tys)?;
+                        let type_actuals_idx = self.make_locals_signature(&type_actuals)?;
+                        code.code
+                            .push(Bytecode::MoveFrom(def_idx, type_actuals_idx));
                         function_frame.pop()?; // pop the address
                         function_frame.push()?; // push the return value
 
-                        let module_idx = ModuleHandleIndex::new(0);
-                        let name_idx = self.make_string(name.name_ref())?;
-                        let sh = self.make_struct_handle(module_idx, name_idx, is_resource)?;
-                        Ok(self.make_singleton_vec_deque(InferredType::Struct(sh)))
+                        let sh = self.make_self_struct_handle(name, is_resource)?;
+                        Ok(self.make_singleton_vec_deque(InferredType::Struct(sh, type_actuals.0)))
                     }
-                    Builtin::MoveToSender(name) => {
+                    Builtin::MoveToSender(name, tys) => {
                         let (_, def_idx) = self.scope.get_struct_def(name.name_ref())?;
+                        let type_actuals = self.build_type_actuals(tys)?;
+                        let type_actuals_idx = self.make_locals_signature(&type_actuals)?;
                         code.code
-                            .push(Bytecode::MoveToSender(def_idx, NO_TYPE_ACTUALS));
+                            .push(Bytecode::MoveToSender(def_idx, type_actuals_idx));
                         function_frame.push()?;
                         Ok(VecDeque::new())
                     }
@@ -2244,10 +2564,32 @@ impl<S: Scope + Sized> Compiler<S> {
                         };
                         Ok(self.make_singleton_vec_deque(InferredType::Reference(inner_token)))
                     }
+                    Builtin::ToU8 => {
+                        code.code.push(Bytecode::CastU8);
+                        function_frame.pop()?;
+                        function_frame.push()?;
+                        Ok(self.make_singleton_vec_deque(InferredType::U8))
+                    }
+                    Builtin::ToU64 => {
+                        code.code.push(Bytecode::CastU64);
+                        function_frame.pop()?;
+                        function_frame.push()?;
+                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                    }
+                    Builtin::ToU128 => {
+                        code.code.push(Bytecode::CastU128);
+                        function_frame.pop()?;
+                        function_frame.push()?;
+                        Ok(self.make_singleton_vec_deque(InferredType::U128))
+                    }
                     _ => bail!("unsupported builtin function: {}", function),
                 }
             }
-            FunctionCall::ModuleFunctionCall { module, name } => {
+            FunctionCall::ModuleFunctionCall {
+                module,
+                name,
+                type_actuals,
+            } => {
                 let scope_name = self.scope.get_name();
 
                 let mh = if scope_name.is_ok() && module.name() == ModuleName::SELF {
@@ -2286,15 +2628,17 @@ impl<S: Scope + Sized> Compiler<S> {
                 let name_idx = self.make_string(name_ref)?;
                 let mut func_sig = self.scope.get_function_signature(mh, name_ref)?.clone();
                 func_sig = self.import_function_signature(module.name_ref(), func_sig)?;
+                let type_actuals = self.build_type_actuals(type_actuals)?;
+                let type_actuals_idx = self.make_locals_signature(&type_actuals)?;
                 let return_types = func_sig
                     .return_types
                     .iter()
-                    .map(InferredType::from_signature_token)
+                    .map(|ty| InferredType::from_signature_token(&ty.substitute(&type_actuals.0)))
                     .collect();
                 let args_count = func_sig.arg_types.len();
                 let sig_idx = self.make_function_signature(&func_sig)?;
                 let fh_idx = self.make_function_handle(mh, name_idx, sig_idx)?;
-                let call = Bytecode::Call(fh_idx, NO_TYPE_ACTUALS);
+                let call = Bytecode::Call(fh_idx, type_actuals_idx);
                 code.code.push(call);
                 for _ in 0..args_count {
                     function_frame.pop()?;
@@ -0,0 +1,118 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Renders parser, compiler and verifier errors as codespan diagnostics, with a label on the
+//! offending source and notes saying which item it is in.
+
+use crate::{
+    errors::{CompilationErrors, SourceError},
+    source_map::{ProgramSourceMap, SourceMap},
+};
+use codespan::{ByteIndex, CodeMap, Span};
+use codespan_reporting::{emit, termcolor::Buffer, Diagnostic, Label, Severity};
+use failure::Error;
+use std::io::Write;
+use vm::def::{
+    errors::{VerificationError, VerificationStatus},
+    file_format::{CodeOffset, FunctionDefinitionIndex, TableIndex},
+    IndexKind,
+};
+
+/// Renders the errors returned by parsing or compiling `source`, the text of `file`. Errors that
+/// are not about the source, such as internal compiler errors, are rendered without a label.
+pub fn render_error(file: &str, source: &str, error: &Error) -> String {
+    if let Some(errors) = error.downcast_ref::<CompilationErrors>() {
+        render(file, source, &errors.0)
+    } else if let Some(error) = error.downcast_ref::<SourceError>() {
+        render(file, source, std::slice::from_ref(error))
+    } else {
+        render(file, source, &[SourceError::new(None, error.to_string())])
+    }
+}
+
+/// Renders `errors`, found in `source`, the text of `file`, one diagnostic after the other.
+pub fn render(file: &str, source: &str, errors: &[SourceError]) -> String {
+    let mut code_map = CodeMap::new();
+    let file_map = code_map.add_filemap(file.to_string().into(), source.to_string());
+    // The code map gives each file its own range of indices, while our spans are offsets into
+    // the source.
+    let base = file_map.span().start().0;
+
+    let mut buffer = Buffer::no_color();
+    for error in errors {
+        let mut diagnostic = Diagnostic::new(Severity::Error, error.message.as_str());
+        if let Some(loc) = error.loc {
+            let span = Span::new(
+                ByteIndex(base + loc.start().0),
+                ByteIndex(base + loc.end().0),
+            );
+            diagnostic = diagnostic.with_label(Label::new_primary(span));
+        }
+        emit(&mut buffer, &code_map, &diagnostic).expect("writing to a buffer cannot fail");
+        for note in &error.notes {
+            writeln!(buffer, "  = {}", note).expect("writing to a buffer cannot fail");
+        }
+    }
+    String::from_utf8_lossy(buffer.as_slice()).into_owned()
+}
+
+/// Turns the errors the verifier found in a module or script compiled from `source` into source
+/// errors. Errors in the code of a function are located with `source_map`, the others are only
+/// described.
+pub fn verification_errors(
+    source: &str,
+    source_map: &SourceMap,
+    errors: &[VerificationError],
+) -> Vec<SourceError> {
+    errors
+        .iter()
+        .map(|error| {
+            let mut source_error = SourceError::new(None, error.err.to_string());
+            match error.kind {
+                IndexKind::FunctionDefinition => {
+                    let function = FunctionDefinitionIndex::new(error.idx as TableIndex);
+                    source_error.loc = error.err.code_offset().and_then(|offset| {
+                        let span = source_map.span(function, offset as CodeOffset)?;
+                        Some(span.to_loc(source))
+                    });
+                    if let Some(function) = source_map.functions.get(error.idx) {
+                        source_error
+                            .notes
+                            .push(format!("in function `{}`", function.name));
+                    }
+                }
+                kind => source_error
+                    .notes
+                    .push(format!("in {} #{}", kind, error.idx)),
+            }
+            source_error
+        })
+        .collect()
+}
+
+/// Turns the errors the verifier found in a program compiled from `source` into source errors,
+/// locating them with `source_map`.
+pub fn verification_statuses(
+    source: &str,
+    source_map: &ProgramSourceMap,
+    statuses: &[VerificationStatus],
+) -> Vec<SourceError> {
+    statuses
+        .iter()
+        .flat_map(|status| match status {
+            VerificationStatus::Script(error) => {
+                verification_errors(source, &source_map.script, std::slice::from_ref(error))
+            }
+            VerificationStatus::Module(idx, error) => match source_map.modules.get(*idx as usize) {
+                Some(module_map) => {
+                    verification_errors(source, module_map, std::slice::from_ref(error))
+                }
+                None => vec![SourceError::new(None, error.err.to_string())],
+            },
+            VerificationStatus::Dependency(module, error) => {
+                vec![SourceError::new(None, error.err.to_string())
+                    .with_note(format!("in dependency `{}`", module))]
+            }
+        })
+        .collect()
+}
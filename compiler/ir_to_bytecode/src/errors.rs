@@ -1,7 +1,9 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::parser::ast::Loc;
 use failure::Fail;
+use std::fmt;
 use vm::def::errors::VerificationError;
 
 #[derive(Clone, Debug, Eq, Fail, Ord, PartialEq, PartialOrd)]
@@ -9,3 +11,45 @@ pub enum InternalCompilerError {
     #[fail(display = "Post-compile bounds check errors: {:?}", _0)]
     BoundsCheckErrors(Vec<VerificationError>),
 }
+
+/// An error in the program being compiled.
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+#[fail(display = "{}", message)]
+pub struct SourceError {
+    /// The span of the offending source, if the compiler knows it.
+    pub loc: Option<Loc>,
+    pub message: String,
+    /// Where the error happened, from the innermost item out.
+    pub notes: Vec<String>,
+}
+
+impl SourceError {
+    pub fn new(loc: Option<Loc>, message: impl Into<String>) -> Self {
+        SourceError {
+            loc,
+            message: message.into(),
+            notes: vec![],
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+}
+
+/// Every error found while compiling a module or a script.
+#[derive(Clone, Debug, Eq, Fail, PartialEq)]
+pub struct CompilationErrors(pub Vec<SourceError>);
+
+impl fmt::Display for CompilationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, error) in self.0.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
@@ -5,7 +5,11 @@
 extern crate log;
 
 pub mod compiler;
+pub mod diagnostics;
 pub mod errors;
+pub mod optimizer;
 pub mod parser;
+pub mod source_map;
+pub mod specs;
 
 // Unit tests for this crate are in the parent "compiler" crate.
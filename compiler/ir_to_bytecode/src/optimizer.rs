@@ -0,0 +1,416 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Peephole optimizations of compiled code, to spend less gas on the naive code the compiler
+//! generates.
+//!
+//! Every pass only rewrites code within a basic block or removes code that cannot run, so code
+//! that passed the bytecode verifier still does after optimization. The passes run until none of
+//! them changes the code:
+//!
+//! - branches to a `Branch` are threaded through to its target, branches to the next
+//!   instruction are removed and branches on a constant condition become unconditional;
+//! - arithmetic, comparisons and logic on constants are folded, unless they would abort;
+//! - a `StLoc` immediately followed by a `MoveLoc` of the same local is removed, unless the local
+//!   holds a reference;
+//! - instructions that cannot be reached, such as those after a `Ret` or `Abort`, are removed.
+
+use crate::{errors::InternalCompilerError, source_map::SourceMap};
+use failure::*;
+use std::collections::{BTreeMap, BTreeSet};
+use vm::def::file_format::{
+    Bytecode, CodeOffset, CompiledModule, CompiledProgram, CompiledScript, FunctionDefinition,
+    LocalIndex, LocalsSignature, SignatureToken,
+};
+
+/// Optimizes the code of every function of `module`, and moves the code offsets of its source
+/// map along.
+pub fn optimize_module(
+    module: CompiledModule,
+    source_map: &mut SourceMap,
+) -> Result<CompiledModule> {
+    let mut module = module.into_inner();
+    for (idx, function) in module.function_defs.iter_mut().enumerate() {
+        let locals = &module.locals_signatures[function.code.locals.0 as usize];
+        optimize_function(function, locals, source_map, idx);
+    }
+    match module.freeze() {
+        Ok(module) => Ok(module),
+        Err(errs) => bail_err!(InternalCompilerError::BoundsCheckErrors(errs)),
+    }
+}
+
+/// Optimizes the code of `script`, and moves the code offsets of its source map along.
+pub fn optimize_script(
+    script: CompiledScript,
+    source_map: &mut SourceMap,
+) -> Result<CompiledScript> {
+    let mut script = script.into_inner();
+    let locals = &script.locals_signatures[script.main.code.locals.0 as usize];
+    optimize_function(&mut script.main, locals, source_map, 0);
+    match script.freeze() {
+        Ok(script) => Ok(script),
+        Err(errs) => bail_err!(InternalCompilerError::BoundsCheckErrors(errs)),
+    }
+}
+
+/// Optimizes the modules and script of `program`. The source maps of the modules are looked up
+/// by index in `module_source_maps`.
+pub fn optimize_program(
+    program: CompiledProgram,
+    module_source_maps: &mut [SourceMap],
+    script_source_map: &mut SourceMap,
+) -> Result<CompiledProgram> {
+    let mut modules = vec![];
+    for (idx, module) in program.modules.into_iter().enumerate() {
+        let mut no_source_map = SourceMap::default();
+        let source_map = module_source_maps
+            .get_mut(idx)
+            .unwrap_or(&mut no_source_map);
+        modules.push(optimize_module(module, source_map)?);
+    }
+    let script = optimize_script(program.script, script_source_map)?;
+    Ok(CompiledProgram::new(modules, script))
+}
+
+fn optimize_function(
+    function: &mut FunctionDefinition,
+    locals: &LocalsSignature,
+    source_map: &mut SourceMap,
+    idx: usize,
+) {
+    if function.is_native() {
+        return;
+    }
+    let (code, origins) = optimize_code(&function.code.code, &locals.0);
+    function.code.code = code;
+    if let Some(function_map) = source_map.functions.get_mut(idx) {
+        function_map.code = remap_offsets(&function_map.code, &origins);
+    }
+}
+
+/// Optimizes `code`, whose locals have the types `locals`, and returns the optimized code along
+/// with the offset in `code` of the instruction each of its instructions comes from.
+pub fn optimize_code(
+    code: &[Bytecode],
+    locals: &[SignatureToken],
+) -> (Vec<Bytecode>, Vec<CodeOffset>) {
+    let mut code = Code {
+        code: code.to_vec(),
+        origins: (0..code.len() as CodeOffset).collect(),
+        references: locals
+            .iter()
+            .enumerate()
+            .filter(|(_, ty)| ty.is_reference())
+            .map(|(idx, _)| idx as LocalIndex)
+            .collect(),
+    };
+    if code.code.is_empty() {
+        return (code.code, code.origins);
+    }
+    let passes: &[fn(&mut Code) -> bool] = &[
+        thread_branches,
+        fold_constants,
+        remove_store_loads,
+        remove_unreachable,
+    ];
+    loop {
+        let mut changed = false;
+        for pass in passes {
+            changed |= pass(&mut code);
+        }
+        if !changed {
+            return (code.code, code.origins);
+        }
+    }
+}
+
+/// Moves the entries of `map`, by code offset, to the offsets of the instructions that come from
+/// them. Like the spans of a source map, an entry applies up to the next one.
+pub fn remap_offsets<T: Clone + PartialEq>(
+    map: &BTreeMap<CodeOffset, T>,
+    origins: &[CodeOffset],
+) -> BTreeMap<CodeOffset, T> {
+    let mut remapped = BTreeMap::new();
+    let mut last = None;
+    for (offset, origin) in origins.iter().enumerate() {
+        let entry = map.range(..=origin).next_back().map(|(_, entry)| entry);
+        if entry.is_some() && entry != last {
+            remapped.insert(offset as CodeOffset, entry.unwrap().clone());
+        }
+        last = entry;
+    }
+    remapped
+}
+
+/// Code being optimized.
+struct Code {
+    code: Vec<Bytecode>,
+    /// The offset in the original code of the instruction each instruction comes from.
+    origins: Vec<CodeOffset>,
+    /// The locals that hold references.
+    references: BTreeSet<LocalIndex>,
+}
+
+impl Code {
+    /// The offsets that branches jump to.
+    fn branch_targets(&self) -> BTreeSet<usize> {
+        self.code
+            .iter()
+            .filter_map(|bytecode| branch_target(bytecode).map(|target| target as usize))
+            .collect()
+    }
+
+    /// Removes the instructions that are not kept and retargets branches to the instruction that
+    /// follows a removed one. Returns whether any instruction was removed.
+    fn retain(&mut self, keep: &[bool]) -> bool {
+        if keep.iter().all(|keep| *keep) {
+            return false;
+        }
+        // The new offset of each instruction, or of the next kept one if it is removed.
+        let mut new_offsets = Vec::with_capacity(self.code.len() + 1);
+        let mut kept = 0;
+        for keep in keep {
+            new_offsets.push(kept as CodeOffset);
+            if *keep {
+                kept += 1;
+            }
+        }
+        new_offsets.push(kept as CodeOffset);
+
+        let code = std::mem::replace(&mut self.code, vec![]);
+        let origins = std::mem::replace(&mut self.origins, vec![]);
+        for ((mut bytecode, origin), keep) in code.into_iter().zip(origins).zip(keep) {
+            if !*keep {
+                continue;
+            }
+            match &mut bytecode {
+                Bytecode::Branch(target) | Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+                    *target = new_offsets[*target as usize]
+                }
+                _ => (),
+            }
+            self.code.push(bytecode);
+            self.origins.push(origin);
+        }
+        true
+    }
+}
+
+fn branch_target(bytecode: &Bytecode) -> Option<CodeOffset> {
+    match bytecode {
+        Bytecode::Branch(target) | Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+            Some(*target)
+        }
+        _ => None,
+    }
+}
+
+/// Retargets branches to a `Branch` to where it goes, removes branches to the next instruction
+/// and turns branches on a constant condition into unconditional ones.
+fn thread_branches(code: &mut Code) -> bool {
+    let len = code.code.len();
+    let mut keep = vec![true; len];
+    let mut rewritten = false;
+    for pc in 0..len {
+        if let Some(target) = branch_target(&code.code[pc]) {
+            let threaded = thread(&code.code, target);
+            if threaded != target {
+                set_branch_target(&mut code.code[pc], threaded);
+                rewritten = true;
+            }
+        }
+    }
+
+    let targets = code.branch_targets();
+    for pc in 0..len {
+        match code.code[pc] {
+            Bytecode::Branch(target) if target as usize == pc + 1 => keep[pc] = false,
+            Bytecode::BrTrue(target) | Bytecode::BrFalse(target)
+                if pc > 0 && keep[pc - 1] && !targets.contains(&pc) =>
+            {
+                let jumps = match (&code.code[pc - 1], &code.code[pc]) {
+                    (Bytecode::LdTrue, Bytecode::BrTrue(_))
+                    | (Bytecode::LdFalse, Bytecode::BrFalse(_)) => true,
+                    (Bytecode::LdTrue, Bytecode::BrFalse(_))
+                    | (Bytecode::LdFalse, Bytecode::BrTrue(_)) => false,
+                    _ => continue,
+                };
+                keep[pc - 1] = false;
+                if jumps {
+                    code.code[pc] = Bytecode::Branch(target);
+                    rewritten = true;
+                } else {
+                    keep[pc] = false;
+                }
+            }
+            _ => (),
+        }
+    }
+    code.retain(&keep) || rewritten
+}
+
+/// Follows the chain of `Branch` instructions starting at `target` to where it ends.
+fn thread(code: &[Bytecode], mut target: CodeOffset) -> CodeOffset {
+    // Every step follows a different branch, unless the chain loops.
+    for _ in 0..code.len() {
+        match code.get(target as usize) {
+            Some(Bytecode::Branch(next)) if *next != target => target = *next,
+            _ => break,
+        }
+    }
+    target
+}
+
+fn set_branch_target(bytecode: &mut Bytecode, new_target: CodeOffset) {
+    match bytecode {
+        Bytecode::Branch(target) | Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+            *target = new_target
+        }
+        _ => (),
+    }
+}
+
+/// Folds operations on constants loaded just before them into a constant. Operations that would
+/// abort, such as an overflowing addition, are left for the runtime to abort on.
+fn fold_constants(code: &mut Code) -> bool {
+    let len = code.code.len();
+    let mut keep = vec![true; len];
+    let mut rewritten = false;
+    let targets = code.branch_targets();
+    let mut pc = 0;
+    while pc < len {
+        let in_block = |count: usize| {
+            pc + count < len && (pc + 1..=pc + count).all(|pc| !targets.contains(&pc))
+        };
+        let folded = if in_block(1) {
+            fold_unary(&code.code[pc], &code.code[pc + 1]).map(|folded| (1, folded))
+        } else {
+            None
+        };
+        let folded = folded.or_else(|| {
+            if in_block(2) {
+                fold_binary(&code.code[pc], &code.code[pc + 1], &code.code[pc + 2])
+                    .map(|folded| (2, folded))
+            } else {
+                None
+            }
+        });
+        match folded {
+            Some((operands, folded)) => {
+                for keep in &mut keep[pc..pc + operands] {
+                    *keep = false;
+                }
+                code.code[pc + operands] = folded;
+                rewritten = true;
+                pc += operands + 1;
+            }
+            None => pc += 1,
+        }
+    }
+    code.retain(&keep) || rewritten
+}
+
+fn fold_unary(operand: &Bytecode, op: &Bytecode) -> Option<Bytecode> {
+    match (operand, op) {
+        (Bytecode::LdTrue, Bytecode::Not) => Some(Bytecode::LdFalse),
+        (Bytecode::LdFalse, Bytecode::Not) => Some(Bytecode::LdTrue),
+        _ => None,
+    }
+}
+
+fn fold_binary(lhs: &Bytecode, rhs: &Bytecode, op: &Bytecode) -> Option<Bytecode> {
+    let load_bool = |value| {
+        if value {
+            Bytecode::LdTrue
+        } else {
+            Bytecode::LdFalse
+        }
+    };
+    match (lhs, rhs) {
+        (Bytecode::LdConst(lhs), Bytecode::LdConst(rhs)) => {
+            let (lhs, rhs) = (*lhs, *rhs);
+            Some(match op {
+                Bytecode::Add => Bytecode::LdConst(lhs.checked_add(rhs)?),
+                Bytecode::Sub => Bytecode::LdConst(lhs.checked_sub(rhs)?),
+                Bytecode::Mul => Bytecode::LdConst(lhs.checked_mul(rhs)?),
+                Bytecode::Div => Bytecode::LdConst(lhs.checked_div(rhs)?),
+                Bytecode::Mod => Bytecode::LdConst(lhs.checked_rem(rhs)?),
+                Bytecode::BitOr => Bytecode::LdConst(lhs | rhs),
+                Bytecode::BitAnd => Bytecode::LdConst(lhs & rhs),
+                Bytecode::Xor => Bytecode::LdConst(lhs ^ rhs),
+                Bytecode::Eq => load_bool(lhs == rhs),
+                Bytecode::Neq => load_bool(lhs != rhs),
+                Bytecode::Lt => load_bool(lhs < rhs),
+                Bytecode::Gt => load_bool(lhs > rhs),
+                Bytecode::Le => load_bool(lhs <= rhs),
+                Bytecode::Ge => load_bool(lhs >= rhs),
+                _ => return None,
+            })
+        }
+        (Bytecode::LdTrue, Bytecode::LdTrue)
+        | (Bytecode::LdTrue, Bytecode::LdFalse)
+        | (Bytecode::LdFalse, Bytecode::LdTrue)
+        | (Bytecode::LdFalse, Bytecode::LdFalse) => {
+            let (lhs, rhs) = (lhs == &Bytecode::LdTrue, rhs == &Bytecode::LdTrue);
+            Some(match op {
+                Bytecode::And => load_bool(lhs && rhs),
+                Bytecode::Or => load_bool(lhs || rhs),
+                Bytecode::Eq => load_bool(lhs == rhs),
+                Bytecode::Neq => load_bool(lhs != rhs),
+                _ => return None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Removes a `StLoc` immediately followed by a `MoveLoc` of the same local, which leaves the
+/// stack as it was. Without the pair the local keeps the value it held before, if any, rather than
+/// becoming unavailable. Verified code does not read it again before storing to it, but a
+/// reference kept in a local still borrows what it points to, so locals that hold references are
+/// left alone.
+fn remove_store_loads(code: &mut Code) -> bool {
+    let len = code.code.len();
+    let mut keep = vec![true; len];
+    let targets = code.branch_targets();
+    let mut pc = 0;
+    while pc + 1 < len {
+        match (&code.code[pc], &code.code[pc + 1]) {
+            (Bytecode::StLoc(stored), Bytecode::MoveLoc(moved))
+                if stored == moved
+                    && !code.references.contains(stored)
+                    && !targets.contains(&(pc + 1)) =>
+            {
+                keep[pc] = false;
+                keep[pc + 1] = false;
+                pc += 2;
+            }
+            _ => pc += 1,
+        }
+    }
+    code.retain(&keep)
+}
+
+/// Removes the instructions that no path from the start of the code reaches.
+fn remove_unreachable(code: &mut Code) -> bool {
+    let len = code.code.len();
+    let mut reachable = vec![false; len];
+    let mut pending = vec![0];
+    while let Some(pc) = pending.pop() {
+        if pc >= len || reachable[pc] {
+            continue;
+        }
+        reachable[pc] = true;
+        match &code.code[pc] {
+            Bytecode::Ret | Bytecode::Abort => (),
+            Bytecode::Branch(target) => pending.push(*target as usize),
+            Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+                pending.push(*target as usize);
+                pending.push(pc + 1);
+            }
+            _ => pending.push(pc + 1),
+        }
+    }
+    code.retain(&reachable)
+}
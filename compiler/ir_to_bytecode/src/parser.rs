@@ -1,115 +1,102 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use codespan::{ByteIndex, CodeMap, Span};
-use codespan_reporting::{emit, termcolor::Buffer, Diagnostic, Label, Severity};
+use crate::errors::{CompilationErrors, SourceError};
+use codespan::{ByteIndex, Span};
 use failure::*;
-use ir_to_bytecode_syntax::syntax;
-use lalrpop_util::ParseError;
-use regex::Regex;
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
+use ir_to_bytecode_syntax::{
+    lexer::{DocComments, LexError, Lexer, SpannedTok, Tok},
+    syntax,
 };
+use lalrpop_util::ParseError;
 use vm::types::AccountAddress;
 
-// Re-export this to make it convenient for other crates.
-pub use ir_to_bytecode_syntax::ast;
+// Re-export these to make it convenient for other crates.
+pub use ir_to_bytecode_syntax::{ast, lexer};
 
-// Since lalrpop can't handle comments without a custom lexer, we somewhat hackily remove all the
-// comments from the input string before passing it off to lalrpop. We only support single line
-// comments for now. Will later on add in other comment types.
-fn strip_comments(string: &str) -> String {
-    // Remove line comments
-    let line_comments = Regex::new(r"//.*(\r\n|\n|\r)").unwrap();
-    line_comments.replace_all(string, "$1").into_owned()
+// Tokenizes the whole input up front, so that the grammar can look up the doc comment of a
+// declaration by the offset of its first token.
+fn tokenize(input: &str) -> Result<(Vec<SpannedTok<'_>>, DocComments)> {
+    let mut lexer = Lexer::new(input);
+    match lexer.by_ref().collect() {
+        Ok(tokens) => Ok((tokens, lexer.into_doc_comments())),
+        Err(error) => handle_error(ParseError::User { error }),
+    }
 }
 
 /// Given the raw input of a file, creates a `Program` struct
 /// Fails with `Err(_)` if the text cannot be parsed
 pub fn parse_program(program_str: &str) -> Result<ast::Program> {
-    let stripped_string = &strip_comments(program_str);
+    let (tokens, doc_comments) = tokenize(program_str)?;
     let parser = syntax::ProgramParser::new();
-    match parser.parse(stripped_string) {
+    match parser.parse(&doc_comments, tokens) {
         Ok(program) => Ok(program),
-        Err(e) => handle_error(e, program_str),
+        Err(e) => handle_error(e),
     }
 }
 
 /// Given the raw input of a file, creates a `Script` struct
 /// Fails with `Err(_)` if the text cannot be parsed
 pub fn parse_script(script_str: &str) -> Result<ast::Script> {
-    let stripped_string = &strip_comments(script_str);
+    let (tokens, doc_comments) = tokenize(script_str)?;
     let parser = syntax::ScriptParser::new();
-    match parser.parse(stripped_string) {
+    match parser.parse(&doc_comments, tokens) {
         Ok(script) => Ok(script),
-        Err(e) => handle_error(e, script_str),
+        Err(e) => handle_error(e),
     }
 }
 
 /// Given the raw input of a file, creates a single `ModuleDefinition` struct
 /// Fails with `Err(_)` if the text cannot be parsed
 pub fn parse_module(modules_str: &str) -> Result<ast::ModuleDefinition> {
-    let stripped_string = &strip_comments(modules_str);
+    let (tokens, doc_comments) = tokenize(modules_str)?;
     let parser = syntax::ModuleParser::new();
-    match parser.parse(stripped_string) {
+    match parser.parse(&doc_comments, tokens) {
         Ok(module) => Ok(module),
-        Err(e) => handle_error(e, modules_str),
+        Err(e) => handle_error(e),
     }
 }
 
 /// Given the raw input of a file, creates a single `Cmd` struct
 /// Fails with `Err(_)` if the text cannot be parsed
 pub fn parse_cmd(cmd_str: &str, _sender_address: AccountAddress) -> Result<ast::Cmd> {
-    let stripped_string = &strip_comments(cmd_str);
+    let (tokens, doc_comments) = tokenize(cmd_str)?;
     let parser = syntax::CmdParser::new();
-    match parser.parse(stripped_string) {
+    match parser.parse(&doc_comments, tokens) {
         Ok(cmd) => Ok(cmd),
-        Err(e) => handle_error(e, cmd_str),
+        Err(e) => handle_error(e),
     }
 }
 
-fn handle_error<'input, T, Token>(
-    e: lalrpop_util::ParseError<usize, Token, &'static str>,
-    code_str: &'input str,
-) -> Result<T>
-where
-    Token: std::fmt::Display,
-{
-    let mut s = DefaultHasher::new();
-    code_str.hash(&mut s);
-    let mut code = CodeMap::new();
-    code.add_filemap(s.finish().to_string().into(), code_str.to_string());
-    let msg = match &e {
+fn handle_error<T>(e: ParseError<usize, Tok<'_>, LexError>) -> Result<T> {
+    let loc = |l: usize, r: usize| Some(Span::new(ByteIndex(l as u32), ByteIndex(r as u32)));
+    let expected_note = |expected: &[String]| {
+        format!(
+            "Expected: {}",
+            expected
+                .iter()
+                .fold(String::new(), |acc, token| format!("{} {},", acc, token))
+        )
+    };
+    let error = match &e {
         ParseError::InvalidToken { location } => {
-            let error =
-                Diagnostic::new(Severity::Error, "Invalid Token").with_label(Label::new_primary(
-                    Span::new(ByteIndex(*location as u32), ByteIndex(*location as u32)),
-                ));
-            let mut buffer = Buffer::no_color();
-            emit(&mut buffer, &code, &error).unwrap();
-            std::str::from_utf8(buffer.as_slice()).unwrap().to_string()
+            SourceError::new(loc(*location, *location), "Invalid Token")
         }
         ParseError::UnrecognizedToken {
             token: Some((l, tok, r)),
             expected,
-        } => {
-            let error = Diagnostic::new(Severity::Error, format!("Unrecognized Token: {}", tok))
-                .with_label(
-                    Label::new_primary(Span::new(ByteIndex(*l as u32), ByteIndex(*r as u32)))
-                        .with_message(format!(
-                            "Expected: {}",
-                            expected
-                                .iter()
-                                .fold(String::new(), |acc, token| format!("{} {},", acc, token))
-                        )),
-                );
-            let mut buffer = Buffer::no_color();
-            emit(&mut buffer, &code, &error).unwrap();
-            std::str::from_utf8(buffer.as_slice()).unwrap().to_string()
+        } => SourceError::new(loc(*l, *r), format!("Unrecognized Token: {}", tok))
+            .with_note(expected_note(expected)),
+        ParseError::UnrecognizedToken {
+            token: None,
+            expected,
+        } => SourceError::new(None, "Unexpected end of input").with_note(expected_note(expected)),
+        ParseError::ExtraToken { token: (l, tok, r) } => {
+            SourceError::new(loc(*l, *r), format!("Extra Token: {}", tok))
+        }
+        ParseError::User { error } => {
+            SourceError::new(loc(error.start, error.end), error.to_string())
         }
-        _ => format!("{}", e),
     };
-    println!("{}", msg);
-    bail!("ParserError: {}", e)
+    bail_err!(CompilationErrors(vec![error]))
 }
@@ -0,0 +1,218 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Source maps relating the bytecode of compiled modules and scripts to their Move IR source.
+//!
+//! The compiler records the byte span of the statement each instruction was generated from in a
+//! [`SpanMap`]. Given the source text, a span map is turned into a [`SourceMap`], which locates
+//! instructions by line and column and is serialized next to the bytecode.
+
+use crate::parser::ast::Loc;
+use codespan::{ByteIndex, Span};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+use vm::def::file_format::{CodeOffset, FunctionDefinitionIndex, TableIndex};
+
+/// The byte spans of the instructions of a function, by code offset.
+///
+/// Instructions without a span of their own, such as the branches of a loop, belong to the
+/// closest instruction before them that has one.
+pub type CodeSpans = BTreeMap<CodeOffset, Loc>;
+
+/// The name, local names and code spans of a function definition.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FunctionSpans {
+    pub name: String,
+    /// The names of the locals of the function, arguments first. Native functions have none.
+    pub locals: Vec<String>,
+    pub code: CodeSpans,
+}
+
+/// The byte spans of the code of a compiled module or script.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpanMap {
+    /// The spans of each function definition, in definition order. The only function of a
+    /// script is `main`.
+    pub functions: Vec<FunctionSpans>,
+}
+
+/// The byte spans of the code of a compiled program.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProgramSpanMap {
+    pub modules: Vec<SpanMap>,
+    pub script: SpanMap,
+}
+
+/// A line and column in a source file, both starting at 1.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct Position {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// The span of source an instruction was compiled from.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SourceSpan {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl SourceSpan {
+    /// Returns the byte span of this span in `source`.
+    pub fn to_loc(&self, source: &str) -> Loc {
+        let lines = LineIndex::new(source);
+        Span::new(
+            ByteIndex(lines.offset(self.start)),
+            ByteIndex(lines.offset(self.end)),
+        )
+    }
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}-{}:{}",
+            self.start.line, self.start.column, self.end.line, self.end.column
+        )
+    }
+}
+
+/// The source spans of the code of a function definition.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FunctionSourceMap {
+    pub name: String,
+    /// The names of the locals of the function, arguments first.
+    #[serde(default)]
+    pub locals: Vec<String>,
+    pub code: BTreeMap<CodeOffset, SourceSpan>,
+}
+
+/// Maps every function definition and code offset of a compiled module or script to the span of
+/// the source it was compiled from.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SourceMap {
+    /// The source file.
+    pub file: String,
+    /// The source map of each function definition, in definition order.
+    pub functions: Vec<FunctionSourceMap>,
+}
+
+impl SourceMap {
+    /// Resolves the byte spans of `spans` against `source`, the text of `file`.
+    pub fn new(file: &str, source: &str, spans: &SpanMap) -> Self {
+        let lines = LineIndex::new(source);
+        let functions = spans
+            .functions
+            .iter()
+            .map(|function| FunctionSourceMap {
+                name: function.name.clone(),
+                locals: function.locals.clone(),
+                code: function
+                    .code
+                    .iter()
+                    .map(|(offset, loc)| {
+                        let span = SourceSpan {
+                            start: lines.position(loc.start().0),
+                            end: lines.position(loc.end().0),
+                        };
+                        (*offset, span)
+                    })
+                    .collect(),
+            })
+            .collect();
+        SourceMap {
+            file: file.to_string(),
+            functions,
+        }
+    }
+
+    /// Returns the span of the source the instruction at `offset` in the function definition
+    /// `function` was compiled from.
+    pub fn span(
+        &self,
+        function: FunctionDefinitionIndex,
+        offset: CodeOffset,
+    ) -> Option<SourceSpan> {
+        self.functions
+            .get(function.0 as usize)?
+            .code
+            .range(..=offset)
+            .next_back()
+            .map(|(_, span)| *span)
+    }
+
+    /// Returns the index of the function definition named `name`.
+    pub fn function_index(&self, name: &str) -> Option<FunctionDefinitionIndex> {
+        self.functions
+            .iter()
+            .position(|function| function.name == name)
+            .map(|idx| FunctionDefinitionIndex::new(idx as TableIndex))
+    }
+}
+
+/// The source maps of the modules and the script of a compiled program.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ProgramSourceMap {
+    pub modules: Vec<SourceMap>,
+    pub script: SourceMap,
+}
+
+impl ProgramSourceMap {
+    /// Resolves the byte spans of `spans` against `source`, the text of `file`.
+    pub fn new(file: &str, source: &str, spans: &ProgramSpanMap) -> Self {
+        ProgramSourceMap {
+            modules: spans
+                .modules
+                .iter()
+                .map(|module| SourceMap::new(file, source, module))
+                .collect(),
+            script: SourceMap::new(file, source, &spans.script),
+        }
+    }
+}
+
+/// The byte offsets at which the lines of a text start.
+struct LineIndex<'a> {
+    starts: Vec<u32>,
+    source: &'a str,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(source: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx as u32 + 1))
+            .collect();
+        LineIndex { starts, source }
+    }
+
+    fn position(&self, offset: u32) -> Position {
+        let line = match self.starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let start = self.starts[line] as usize;
+        let end = (offset as usize).min(self.source.len());
+        let column = self
+            .source
+            .get(start..end)
+            .map_or(end - start, |text| text.chars().count());
+        Position {
+            line: line as u32 + 1,
+            column: column as u32 + 1,
+        }
+    }
+
+    fn offset(&self, position: Position) -> u32 {
+        let start = match self.starts.get(position.line as usize - 1) {
+            Some(start) => *start as usize,
+            None => return self.source.len() as u32,
+        };
+        let column = position.column as usize - 1;
+        let offset = self.source[start..]
+            .char_indices()
+            .nth(column)
+            .map_or(self.source.len(), |(idx, _)| start + idx);
+        offset as u32
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compiles the specifications of functions into runtime checks, so that contracts can be tested
+//! against their specifications.
+//!
+//! The checks are added to the AST before it is compiled. A function then aborts with
+//! [`SPEC_VIOLATION`] when:
+//!
+//! - the condition of one of its `requires<e>` annotations does not hold when it is called;
+//! - the condition of one of its `ensures<e>` annotations does not hold when it returns. The
+//!   condition can refer to the values returned as the locals `RET` if there is one, or `RET0`,
+//!   `RET1`, ... otherwise, and to the locals of the function that are still available. The
+//!   instrumentation fails if an argument or a local already has one of these names;
+//! - the condition of a `verify<e>` or `assume<e>` statement does not hold when it is reached.
+//!
+//! Without the checks, the compiler ignores specifications.
+
+use crate::parser::ast::{
+    Block, Cmd, Exp, Exp_, Function, FunctionAnnotation, FunctionBody, IfElse, Loc,
+    ModuleDefinition, Program, Spanned, Statement, UnaryOp, Var, Var_,
+};
+use failure::*;
+use std::{collections::VecDeque, mem};
+
+/// The abort code of a specification that does not hold.
+pub const SPEC_VIOLATION: u64 = 0x5bec_0000;
+
+/// Adds the checks of the specifications of the modules and script of `program`.
+pub fn instrument_program(program: &mut Program) -> Result<()> {
+    for module in &mut program.modules {
+        instrument_module(module)?;
+    }
+    instrument_function("main", &mut program.script.main)
+}
+
+/// Adds the checks of the specifications of the functions of `module`.
+pub fn instrument_module(module: &mut ModuleDefinition) -> Result<()> {
+    for (name, function) in &mut module.functions {
+        instrument_function(name.name_ref(), function)?;
+    }
+    Ok(())
+}
+
+fn instrument_function(name: &str, function: &mut Function) -> Result<()> {
+    let (locals, code) = match &mut function.body {
+        FunctionBody::Move { locals, code } => (locals, code),
+        FunctionBody::Native => return Ok(()),
+    };
+    let mut requires = vec![];
+    let mut ensures = vec![];
+    for annotation in &function.annotations {
+        match annotation {
+            FunctionAnnotation::Requires(cond) => requires.push(cond.clone()),
+            FunctionAnnotation::Ensures(cond) => ensures.push(cond.clone()),
+        }
+    }
+
+    let return_locals = if ensures.is_empty() {
+        vec![]
+    } else {
+        let return_type = &function.signature.return_type;
+        let names = if return_type.len() == 1 {
+            vec!["RET".to_string()]
+        } else {
+            (0..return_type.len())
+                .map(|idx| format!("RET{}", idx))
+                .collect()
+        };
+        let declared = function
+            .signature
+            .formals
+            .iter()
+            .map(|(var, _)| var)
+            .chain(locals.iter().map(|(var, _)| &var.value));
+        for var in declared {
+            if names.iter().any(|ret| ret == var.name()) {
+                bail!(
+                    "`{}` of function `{}` is reserved for a value returned in `ensures`",
+                    var.name(),
+                    name
+                );
+            }
+        }
+        names
+            .iter()
+            .map(|name| Var::new_(name))
+            .zip(return_type.iter().cloned())
+            .collect::<Vec<_>>()
+    };
+    let instrumenter = Instrumenter {
+        ensures,
+        return_vars: return_locals.iter().map(|(var, _)| var.clone()).collect(),
+    };
+    instrumenter.instrument_block(code);
+    for cond in requires.into_iter().rev() {
+        code.stmts.push_front(check(cond));
+    }
+    locals.extend(return_locals);
+    Ok(())
+}
+
+struct Instrumenter {
+    ensures: Vec<Exp_>,
+    // The locals the values returned are bound to while the `ensures` conditions are checked
+    return_vars: Vec<Var_>,
+}
+
+impl Instrumenter {
+    fn instrument_block(&self, block: &mut Block) {
+        let stmts = mem::replace(&mut block.stmts, VecDeque::new());
+        for stmt in stmts {
+            match stmt {
+                Statement::VerifyStatement(cond) | Statement::AssumeStatement(cond) => {
+                    block.stmts.push_back(check(cond))
+                }
+                Statement::CommandStatement(Spanned {
+                    value: Cmd::Return(exps),
+                    span,
+                }) => self.instrument_return(exps, span, block),
+                Statement::IfElseStatement(mut if_else) => {
+                    self.instrument_block(&mut if_else.if_block);
+                    if let Some(else_block) = &mut if_else.else_block {
+                        self.instrument_block(else_block);
+                    }
+                    block.stmts.push_back(Statement::IfElseStatement(if_else));
+                }
+                Statement::WhileStatement(mut while_) => {
+                    self.instrument_block(&mut while_.block);
+                    block.stmts.push_back(Statement::WhileStatement(while_));
+                }
+                Statement::LoopStatement(mut loop_) => {
+                    self.instrument_block(&mut loop_.block);
+                    block.stmts.push_back(Statement::LoopStatement(loop_));
+                }
+                stmt => block.stmts.push_back(stmt),
+            }
+        }
+    }
+
+    // Binds the values returned to the return locals, checks the `ensures` conditions and returns
+    // the values from the locals.
+    fn instrument_return(&self, exps: Exp_, span: Loc, block: &mut Block) {
+        let returns_values = !self.return_vars.is_empty();
+        let exps_span = exps.span;
+        if returns_values {
+            block.stmts.push_back(Statement::CommandStatement(Spanned {
+                value: Cmd::Assign(self.return_vars.clone(), exps),
+                span,
+            }));
+        }
+        for cond in &self.ensures {
+            block.stmts.push_back(check(cond.clone()));
+        }
+        let exps = if returns_values {
+            Spanned {
+                value: Exp::ExprList(self.return_vars.iter().cloned().map(Exp::move_).collect()),
+                span: exps_span,
+            }
+        } else {
+            exps
+        };
+        block.stmts.push_back(Statement::CommandStatement(Spanned {
+            value: Cmd::Return(exps),
+            span,
+        }));
+    }
+}
+
+// `assert(cond, SPEC_VIOLATION)`, located at the condition.
+fn check(cond: Exp_) -> Statement {
+    let span = cond.span;
+    let abort = Statement::CommandStatement(Spanned {
+        value: Cmd::Abort(Some(Spanned {
+            value: Exp::u64(SPEC_VIOLATION).value,
+            span,
+        })),
+        span,
+    });
+    let negated = Spanned {
+        value: Exp::UnaryExp(UnaryOp::Not, Box::new(cond)),
+        span,
+    };
+    Statement::IfElseStatement(IfElse::if_block(negated, Block::new(vec![abort])))
+}
@@ -122,8 +122,23 @@ pub struct StructDefinition {
     pub resource_kind: bool,
     /// Human-readable name for the struct that also serves as a nominal type
     pub name: StructName,
+    /// Kind constraints of the type parameters
+    pub type_formals: Vec<(TypeVar, Kind)>,
     /// the fields each instance has
-    pub fields: Fields<Type>,
+    pub fields: StructDefinitionFields,
+    /// The `///` doc comment before the struct, if any
+    pub doc: Option<String>,
+    /// The file location of the struct's name
+    pub name_span: Loc,
+}
+
+/// The fields of a Move struct definition
+#[derive(Clone, Debug, PartialEq)]
+pub enum StructDefinitionFields {
+    /// The fields are declared
+    Move { fields: Fields<Type> },
+    /// The struct is a type provided by the runtime
+    Native,
 }
 
 //**************************************************************************************************
@@ -137,6 +152,8 @@ pub struct FunctionName(String);
 /// The signature of a function
 #[derive(PartialEq, Debug, Clone)]
 pub struct FunctionSignature {
+    /// Possibly-empty list of (type formal name, kind constraint) pairs. Names are unique.
+    pub type_formals: Vec<(TypeVar, Kind)>,
     /// Possibly-empty list of (formal name, formal type) pairs. Names are unique.
     pub formals: Vec<(Var, Type)>,
     /// Optional return types
@@ -154,10 +171,13 @@ pub enum FunctionVisibility {
     Internal,
 }
 
+/// A specification of a function, checked by `ir_to_bytecode::specs` when asked to
 #[derive(PartialEq, Debug, Clone)]
 pub enum FunctionAnnotation {
-    Requires(String),
-    Ensures(String),
+    /// `requires<e>`, which must hold when the function is called
+    Requires(Exp_),
+    /// `ensures<e>`, which must hold when the function returns
+    Ensures(Exp_),
 }
 
 /// The body of a Move function
@@ -185,21 +205,29 @@ pub struct Function {
     pub annotations: Vec<FunctionAnnotation>,
     /// The code for the procedure
     pub body: FunctionBody,
+    /// The `///` doc comment before the function, if any
+    pub doc: Option<String>,
+    /// The file location of the function's name
+    pub name_span: Loc,
 }
 
 //**************************************************************************************************
 // Types
 //**************************************************************************************************
 
-/// Used to annotate struct types as a resource or value
+/// Used to annotate struct types as a resource or value, and to constrain type parameters
 #[derive(Debug, PartialEq, Clone)]
 pub enum Kind {
-    /// `R`
+    /// `R`, or `resource` on a type parameter
     Resource,
-    /// `V`
+    /// `V`, or `copyable` on a type parameter
     Value,
 }
 
+/// Newtype for the name of a type parameter
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct TypeVar(String);
+
 /// Identifier for a struct definition. Tells us where to look in the storage layer to find the
 /// code associated with the interface
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
@@ -216,17 +244,24 @@ pub struct StructType {
 pub enum Tag {
     /// `address`
     Address,
+    /// `u8`
+    U8,
     /// `u64`
     U64,
+    /// `u128`
+    U128,
     /// `bool`
     Bool,
     /// `bytearray`
     ByteArray,
     /// `string`
     String,
-    /// A module defined struct
-    /// `n`
-    Struct(StructType),
+    /// A module defined struct, instantiated with the given type actuals
+    /// `n` or `n<t_1, ..., t_j>`
+    Struct(StructType, Vec<Type>),
+    /// A type parameter of the enclosing struct or function
+    /// `T`
+    TypeParameter(TypeVar),
 }
 
 /// The type of a single value
@@ -265,10 +300,10 @@ pub enum Builtin {
     Release,
     /// Check if there is a struct object (`StructName` resolved by current module) associated with
     /// the given address
-    Exists(StructName),
+    Exists(StructName, Vec<Type>),
     /// Get the struct object (`StructName` resolved by current module) associated with the given
     /// address
-    BorrowGlobal(StructName),
+    BorrowGlobal(StructName, Vec<Type>),
     /// Returns the height of the current transaction.
     GetHeight,
     /// Returns the price per gas unit the current transaction is willing to pay
@@ -290,12 +325,19 @@ pub enum Builtin {
     /// Initialize a previously empty address by publishing a resource of type Account
     CreateAccount,
     /// Remove a resource of the given type from the account with the given address
-    MoveFrom(StructName),
+    MoveFrom(StructName, Vec<Type>),
     /// Publish an instantiated struct object into sender's account.
-    MoveToSender(StructName),
+    MoveToSender(StructName, Vec<Type>),
 
     /// Convert a mutable reference into an immutable one
     Freeze,
+
+    /// Convert an integer to a u8, aborting if it does not fit
+    ToU8,
+    /// Convert an integer to a u64, aborting if it does not fit
+    ToU64,
+    /// Convert an integer to a u128
+    ToU128,
 }
 
 /// Enum for different function calls
@@ -307,6 +349,7 @@ pub enum FunctionCall {
     ModuleFunctionCall {
         module: ModuleName,
         name: FunctionName,
+        type_actuals: Vec<Type>,
     },
 }
 /// The type for a function call and its location
@@ -317,8 +360,8 @@ pub type FunctionCall_ = Spanned<FunctionCall>;
 pub enum Cmd {
     /// `x = e`
     Assign(Vec<Var_>, Exp_),
-    /// `n { f_1: x_1, ... , f_j: x_j  } = e`
-    Unpack(StructName, Fields<Var_>, Exp_),
+    /// `n<t_1, ..., t_k> { f_1: x_1, ... , f_j: x_j  } = e`
+    Unpack(StructName, Vec<Type>, Fields<Var_>, Exp_),
     /// `*e_1 = e_2`
     Mutate(Exp_, Exp_),
     /// `abort e`
@@ -372,8 +415,10 @@ pub enum Statement {
     WhileStatement(While),
     /// `loop { s }`
     LoopStatement(Loop),
-    VerifyStatement(String),
-    AssumeStatement(String),
+    /// `verify<e>`
+    VerifyStatement(Exp_),
+    /// `assume<e>`
+    AssumeStatement(Exp_),
     /// no-op that eases parsing in some places
     EmptyStatement,
 }
@@ -395,8 +440,12 @@ pub struct Block {
 pub enum CopyableVal {
     /// An address in the global storage
     Address(AccountAddress),
-    /// An unsigned 64-bit integer
+    /// An unsigned 8-bit integer, `<n>u8`
+    U8(u8),
+    /// An unsigned 64-bit integer, `<n>` or `<n>u64`
     U64(u64),
+    /// An unsigned 128-bit integer, `<n>u128`
+    U128(u128),
     /// true or false
     Bool(bool),
     /// `b"<bytes>"`
@@ -419,7 +468,7 @@ pub enum UnaryOp {
 /// Enum for binary operators
 #[derive(Debug, Clone, PartialEq)]
 pub enum BinOp {
-    // u64 ops
+    // Integer ops
     /// `+`
     Add,
     /// `-`
@@ -436,6 +485,10 @@ pub enum BinOp {
     BitAnd,
     /// `^`
     Xor,
+    /// `<<`
+    Shl,
+    /// `>>`
+    Shr,
 
     // Bool ops
     /// `&&`
@@ -473,8 +526,8 @@ pub enum Exp {
     /// Takes the given field values and instantiates the struct
     /// Returns a fresh `StructInstance` whose type and kind (resource or otherwise)
     /// as the current struct class (i.e., the class of the method we're currently executing).
-    /// `n { f_1: e_1, ... , f_j: e_j }`
-    Pack(StructName, ExpFields),
+    /// `n<t_1, ..., t_k> { f_1: e_1, ... , f_j: e_j }`
+    Pack(StructName, Vec<Type>, ExpFields),
     /// `&e.f`, `&mut e.f`
     Borrow {
         /// mutable or not
@@ -645,11 +698,21 @@ impl Type {
         Type::Normal(Kind::Value, Tag::Address)
     }
 
+    /// Creates a new u8 type
+    pub fn u8() -> Type {
+        Type::Normal(Kind::Value, Tag::U8)
+    }
+
     /// Creates a new u64 type
     pub fn u64() -> Type {
         Type::Normal(Kind::Value, Tag::U64)
     }
 
+    /// Creates a new u128 type
+    pub fn u128() -> Type {
+        Type::Normal(Kind::Value, Tag::U128)
+    }
+
     /// Creates a new bool type
     pub fn bool() -> Type {
         Type::Normal(Kind::Value, Tag::Bool)
@@ -659,6 +722,11 @@ impl Type {
     pub fn bytearray() -> Type {
         Type::Normal(Kind::Value, Tag::ByteArray)
     }
+
+    /// Creates a new string type
+    pub fn string() -> Type {
+        Type::Normal(Kind::Value, Tag::String)
+    }
 }
 
 impl StructType {
@@ -714,18 +782,38 @@ impl StructName {
 
 impl StructDefinition {
     /// Creates a new StructDefinition from the resource kind (true if resource), the string
-    /// representation of the name, and the field names with their types
+    /// representation of the name, the type formals, and the field names with their types
     /// Does not verify the correctness of any internal properties, e.g. doesn't check that the
     /// fields do not have reference types
-    pub fn new(resource_kind: bool, name: String, fields: Fields<Type>) -> Self {
+    pub fn new(
+        resource_kind: bool,
+        name: String,
+        type_formals: Vec<(TypeVar, Kind)>,
+        fields: StructDefinitionFields,
+    ) -> Self {
         StructDefinition {
             resource_kind,
             name: StructName::new(name),
+            type_formals,
             fields,
+            doc: None,
+            name_span: Loc::default(),
         }
     }
 }
 
+impl TypeVar {
+    /// Create a new `TypeVar` identifier from a string
+    pub fn new(s: String) -> Self {
+        TypeVar(s)
+    }
+
+    /// Accessor for the name of the type variable
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
 impl FunctionName {
     /// Create a new `FunctionName` identifier from a string
     pub fn new(name: String) -> Self {
@@ -744,9 +832,15 @@ impl FunctionName {
 }
 
 impl FunctionSignature {
-    /// Creates a new function signature from the parameters and the return types
-    pub fn new(formals: Vec<(Var, Type)>, return_type: Vec<Type>) -> Self {
+    /// Creates a new function signature from the type formals, the parameters and the return
+    /// types
+    pub fn new(
+        type_formals: Vec<(TypeVar, Kind)>,
+        formals: Vec<(Var, Type)>,
+        return_type: Vec<Type>,
+    ) -> Self {
         FunctionSignature {
+            type_formals,
             formals,
             return_type,
         }
@@ -758,17 +852,20 @@ impl Function {
     /// See the declaration of the struct `Function` for more details
     pub fn new(
         visibility: FunctionVisibility,
+        type_formals: Vec<(TypeVar, Kind)>,
         formals: Vec<(Var, Type)>,
         return_type: Vec<Type>,
         annotations: Vec<FunctionAnnotation>,
         body: FunctionBody,
     ) -> Self {
-        let signature = FunctionSignature::new(formals, return_type);
+        let signature = FunctionSignature::new(type_formals, formals, return_type);
         Function {
             visibility,
             signature,
             annotations,
             body,
+            doc: None,
+            name_span: Loc::default(),
         }
     }
 }
@@ -792,8 +889,12 @@ impl Var {
 
 impl FunctionCall {
     /// Creates a `FunctionCall::ModuleFunctionCall` variant
-    pub fn module_call(module: ModuleName, name: FunctionName) -> Self {
-        FunctionCall::ModuleFunctionCall { module, name }
+    pub fn module_call(module: ModuleName, name: FunctionName, type_actuals: Vec<Type>) -> Self {
+        FunctionCall::ModuleFunctionCall {
+            module,
+            name,
+            type_actuals,
+        }
     }
 
     /// Creates a `FunctionCall::Builtin` variant with no location information
@@ -894,8 +995,8 @@ impl Exp {
     }
 
     /// Creates a new pack/struct-instantiation `Exp` with no location information
-    pub fn instantiate(n: StructName, s: ExpFields) -> Exp_ {
-        Spanned::no_loc(Exp::Pack(n, s))
+    pub fn instantiate(n: StructName, tys: Vec<Type>, s: ExpFields) -> Exp_ {
+        Spanned::no_loc(Exp::Pack(n, tys, s))
     }
 
     /// Creates a new binary operator `Exp` with no location information
@@ -1047,8 +1148,16 @@ impl fmt::Display for ModuleDefinition {
 
 impl fmt::Display for StructDefinition {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Struct({}, ", self.name)?;
-        writeln!(f, "{}", format_fields(&self.fields))?;
+        writeln!(
+            f,
+            "Struct({}{}, ",
+            self.name,
+            format_type_formals(&self.type_formals)
+        )?;
+        match &self.fields {
+            StructDefinitionFields::Move { fields } => writeln!(f, "{}", format_fields(fields))?,
+            StructDefinitionFields::Native => writeln!(f, "native")?,
+        }
         write!(f, ")")
     }
 }
@@ -1094,6 +1203,28 @@ fn intersperse<T: fmt::Display>(items: &[T], join: &str) -> String {
     })
 }
 
+fn format_type_formals(formals: &[(TypeVar, Kind)]) -> String {
+    if formals.is_empty() {
+        return String::new();
+    }
+    let formals = formals
+        .iter()
+        .map(|(tv, k)| match k {
+            Kind::Resource => format!("{}: resource", tv),
+            Kind::Value => format!("{}: copyable", tv),
+        })
+        .collect::<Vec<_>>();
+    format!("<{}>", formals.join(", "))
+}
+
+fn format_type_actuals(tys: &[Type]) -> String {
+    if tys.is_empty() {
+        return String::new();
+    }
+    let tys = tys.iter().map(|ty| ty.to_string()).collect::<Vec<_>>();
+    format!("<{}>", tys.join(", "))
+}
+
 fn format_fields<T: fmt::Display>(fields: &Fields<T>) -> String {
     fields.iter().fold(String::new(), |acc, (field, val)| {
         format!("{} {}: {},", acc, field, val)
@@ -1102,7 +1233,7 @@ fn format_fields<T: fmt::Display>(fields: &Fields<T>) -> String {
 
 impl fmt::Display for FunctionSignature {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "(")?;
+        write!(f, "{}(", format_type_formals(&self.type_formals))?;
         for (v, ty) in self.formals.iter() {
             write!(f, "{}: {}, ", v, ty)?;
         }
@@ -1129,19 +1260,22 @@ impl fmt::Display for StructType {
 impl fmt::Display for Tag {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            Tag::U8 => write!(f, "u8"),
             Tag::U64 => write!(f, "u64"),
+            Tag::U128 => write!(f, "u128"),
             Tag::Bool => write!(f, "bool"),
             Tag::Address => write!(f, "address"),
             Tag::ByteArray => write!(f, "bytearray"),
             Tag::String => write!(f, "string"),
-            Tag::Struct(ty) => write!(f, "{}", ty),
+            Tag::Struct(ty, tys) => write!(f, "{}{}", ty, format_type_actuals(tys)),
+            Tag::TypeParameter(tv) => write!(f, "{}", tv),
         }
     }
 }
 
 fn write_kind_tag(f: &mut fmt::Formatter<'_>, k: &Kind, t: &Tag) -> fmt::Result {
     match t {
-        Tag::Struct(_) => write!(f, "{}#{}", k, t),
+        Tag::Struct(..) => write!(f, "{}#{}", k, t),
         _ => write!(f, "{}", t),
     }
 }
@@ -1162,6 +1296,12 @@ impl fmt::Display for Type {
     }
 }
 
+impl fmt::Display for TypeVar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 impl fmt::Display for Var {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -1174,8 +1314,10 @@ impl fmt::Display for Builtin {
             Builtin::CreateAccount => write!(f, "create_account"),
             Builtin::Release => write!(f, "release"),
             Builtin::EmitEvent => write!(f, "log"),
-            Builtin::Exists(t) => write!(f, "exists<{}>", t),
-            Builtin::BorrowGlobal(t) => write!(f, "borrow_global<{}>", t),
+            Builtin::Exists(t, tys) => write!(f, "exists<{}{}>", t, format_type_actuals(tys)),
+            Builtin::BorrowGlobal(t, tys) => {
+                write!(f, "borrow_global<{}{}>", t, format_type_actuals(tys))
+            }
             Builtin::GetHeight => write!(f, "get_height"),
             Builtin::GetTxnMaxGasUnits => write!(f, "get_txn_max_gas_units"),
             Builtin::GetTxnGasUnitPrice => write!(f, "get_txn_gas_unit_price"),
@@ -1183,9 +1325,14 @@ impl fmt::Display for Builtin {
             Builtin::GetTxnSender => write!(f, "get_txn_sender"),
             Builtin::GetTxnSequenceNumber => write!(f, "get_txn_sequence_number"),
             Builtin::GetGasRemaining => write!(f, "get_gas_remaining"),
-            Builtin::MoveFrom(t) => write!(f, "move_from<{}>", t),
-            Builtin::MoveToSender(t) => write!(f, "move_to_sender<{}>", t),
+            Builtin::MoveFrom(t, tys) => write!(f, "move_from<{}{}>", t, format_type_actuals(tys)),
+            Builtin::MoveToSender(t, tys) => {
+                write!(f, "move_to_sender<{}{}>", t, format_type_actuals(tys))
+            }
             Builtin::Freeze => write!(f, "freeze"),
+            Builtin::ToU8 => write!(f, "to_u8"),
+            Builtin::ToU64 => write!(f, "to_u64"),
+            Builtin::ToU128 => write!(f, "to_u128"),
         }
     }
 }
@@ -1194,7 +1341,17 @@ impl fmt::Display for FunctionCall {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             FunctionCall::Builtin(fun) => write!(f, "{}", fun),
-            FunctionCall::ModuleFunctionCall { module, name } => write!(f, "{}.{}", module, name),
+            FunctionCall::ModuleFunctionCall {
+                module,
+                name,
+                type_actuals,
+            } => write!(
+                f,
+                "{}.{}{}",
+                module,
+                name,
+                format_type_actuals(type_actuals)
+            ),
         }
     }
 }
@@ -1209,10 +1366,11 @@ impl fmt::Display for Cmd {
                     write!(f, "{} = ({});", intersperse(var_list, ", "), e)
                 }
             }
-            Cmd::Unpack(n, bindings, e) => write!(
+            Cmd::Unpack(n, tys, bindings, e) => write!(
                 f,
-                "{} {{ {} }} = {}",
+                "{}{} {{ {} }} = {}",
                 n,
+                format_type_actuals(tys),
                 bindings
                     .iter()
                     .fold(String::new(), |acc, (field, var)| format!(
@@ -1275,7 +1433,7 @@ impl fmt::Display for Statement {
             Statement::IfElseStatement(if_else) => write!(f, "{}", if_else),
             Statement::WhileStatement(while_) => write!(f, "{}", while_),
             Statement::LoopStatement(loop_) => write!(f, "{}", loop_),
-            Statement::VerifyStatement(cond) => write!(f, "verify<{}>)", cond),
+            Statement::VerifyStatement(cond) => write!(f, "verify<{}>", cond),
             Statement::AssumeStatement(cond) => write!(f, "assume<{}>", cond),
             Statement::EmptyStatement => write!(f, "<empty statement>"),
         }
@@ -1294,7 +1452,9 @@ impl fmt::Display for Block {
 impl fmt::Display for CopyableVal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            CopyableVal::U8(v) => write!(f, "{}u8", v),
             CopyableVal::U64(v) => write!(f, "{}", v),
+            CopyableVal::U128(v) => write!(f, "{}u128", v),
             CopyableVal::Bool(v) => write!(f, "{}", v),
             CopyableVal::ByteArray(v) => write!(f, "{}", v),
             CopyableVal::Address(v) => write!(f, "0x{}", hex::encode(&v)),
@@ -1329,6 +1489,8 @@ impl fmt::Display for BinOp {
                 BinOp::BitOr => "|",
                 BinOp::BitAnd => "&",
                 BinOp::Xor => "^",
+                BinOp::Shl => "<<",
+                BinOp::Shr => ">>",
 
                 // Bool ops
                 BinOp::Or => "||",
@@ -1353,10 +1515,11 @@ impl fmt::Display for Exp {
             Exp::UnaryExp(o, e) => write!(f, "({}{})", o, e),
             Exp::BinopExp(e1, o, e2) => write!(f, "({} {} {})", o, e1, e2),
             Exp::Value(v) => write!(f, "{}", v),
-            Exp::Pack(n, s) => write!(
+            Exp::Pack(n, tys, s) => write!(
                 f,
-                "{}{{{}}}",
+                "{}{}{{{}}}",
                 n,
+                format_type_actuals(tys),
                 s.iter().fold(String::new(), |acc, (field, op)| format!(
                     "{} {} : {},",
                     acc, field, op,
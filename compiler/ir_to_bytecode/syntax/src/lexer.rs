@@ -0,0 +1,610 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The tokenizer of Move IR, which feeds the grammar in `syntax.lalrpop`.
+//!
+//! Whitespace and comments are skipped: `//` line comments and `/* */` block comments, which can
+//! be nested. Doc comments, lines starting with `///`, are kept and attached to the token that
+//! follows them, so that the grammar can hand them to the function or struct they document.
+
+use std::{collections::BTreeMap, fmt};
+
+/// The doc comments of a source, by the byte offset of the token they document.
+pub type DocComments = BTreeMap<usize, String>;
+
+/// A token and its start and end byte offsets, in the form lalrpop expects.
+pub type SpannedTok<'input> = (usize, Tok<'input>, usize);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tok<'input> {
+    /// `0x` followed by hex digits
+    AddressValue(&'input str),
+    /// `h"..."` with an even number of hex digits, quotes included
+    ByteArrayValue(&'input str),
+    /// `m.n`, a name qualified by a module name
+    DotNameValue(&'input str),
+    NameValue(&'input str),
+    /// Digits followed by `u8`, suffix included
+    U8Value(&'input str),
+    /// Digits, optionally followed by `u64`
+    U64Value(&'input str),
+    /// Digits followed by `u128`, suffix included
+    U128Value(&'input str),
+    /// The `<...>` condition of a `verify`, `assume`, `requires` or `ensures`, brackets included
+    VerifierCondition(&'input str),
+    Abort,
+    Address,
+    Amp,
+    AmpAmp,
+    AmpMut,
+    As,
+    AssertLParen,
+    Assume,
+    Bool,
+    BorrowGlobalLess,
+    Break,
+    Bytearray,
+    Caret,
+    Colon,
+    Comma,
+    Continue,
+    Copyable,
+    CopyLParen,
+    CreateAccount,
+    Else,
+    EmitEvent,
+    Ensures,
+    Equal,
+    EqualEqual,
+    Exclaim,
+    ExclaimEqual,
+    ExistsLess,
+    False,
+    Freeze,
+    GetGasRemaining,
+    GetHeight,
+    GetTxnGasUnitPrice,
+    GetTxnMaxGasUnits,
+    GetTxnPublicKey,
+    GetTxnSender,
+    GetTxnSequenceNumber,
+    Greater,
+    GreaterEqual,
+    GreaterGreater,
+    If,
+    Import,
+    LBrace,
+    LParen,
+    Less,
+    LessEqual,
+    LessLess,
+    Let,
+    Loop,
+    Main,
+    Minus,
+    Module,
+    ModulesColon,
+    MoveFromLess,
+    MoveLParen,
+    MoveToSenderLess,
+    Native,
+    NumSign,
+    Percent,
+    Period,
+    Pipe,
+    PipePipe,
+    Plus,
+    Public,
+    R,
+    RBrace,
+    RParen,
+    Release,
+    Requires,
+    Resource,
+    Return,
+    ScriptColon,
+    Semicolon,
+    Slash,
+    Star,
+    String,
+    Struct,
+    ToU128,
+    ToU64,
+    ToU8,
+    True,
+    U128,
+    U64,
+    U8,
+    V,
+    Verify,
+    While,
+}
+
+impl<'input> fmt::Display for Tok<'input> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Tok::AddressValue(s)
+            | Tok::ByteArrayValue(s)
+            | Tok::DotNameValue(s)
+            | Tok::NameValue(s)
+            | Tok::U8Value(s)
+            | Tok::U64Value(s)
+            | Tok::U128Value(s)
+            | Tok::VerifierCondition(s) => s,
+            Tok::Abort => "abort",
+            Tok::Address => "address",
+            Tok::Amp => "&",
+            Tok::AmpAmp => "&&",
+            Tok::AmpMut => "&mut ",
+            Tok::As => "as",
+            Tok::AssertLParen => "assert(",
+            Tok::Assume => "assume",
+            Tok::Bool => "bool",
+            Tok::BorrowGlobalLess => "borrow_global<",
+            Tok::Break => "break",
+            Tok::Bytearray => "bytearray",
+            Tok::Caret => "^",
+            Tok::Colon => ":",
+            Tok::Comma => ",",
+            Tok::Continue => "continue",
+            Tok::Copyable => "copyable",
+            Tok::CopyLParen => "copy(",
+            Tok::CreateAccount => "create_account",
+            Tok::Else => "else",
+            Tok::EmitEvent => "emit_event",
+            Tok::Ensures => "ensures",
+            Tok::Equal => "=",
+            Tok::EqualEqual => "==",
+            Tok::Exclaim => "!",
+            Tok::ExclaimEqual => "!=",
+            Tok::ExistsLess => "exists<",
+            Tok::False => "false",
+            Tok::Freeze => "freeze",
+            Tok::GetGasRemaining => "get_gas_remaining",
+            Tok::GetHeight => "get_height",
+            Tok::GetTxnGasUnitPrice => "get_txn_gas_unit_price",
+            Tok::GetTxnMaxGasUnits => "get_txn_max_gas_units",
+            Tok::GetTxnPublicKey => "get_txn_public_key",
+            Tok::GetTxnSender => "get_txn_sender",
+            Tok::GetTxnSequenceNumber => "get_txn_sequence_number",
+            Tok::Greater => ">",
+            Tok::GreaterEqual => ">=",
+            Tok::GreaterGreater => ">>",
+            Tok::If => "if",
+            Tok::Import => "import",
+            Tok::LBrace => "{",
+            Tok::LParen => "(",
+            Tok::Less => "<",
+            Tok::LessEqual => "<=",
+            Tok::LessLess => "<<",
+            Tok::Let => "let",
+            Tok::Loop => "loop",
+            Tok::Main => "main",
+            Tok::Minus => "-",
+            Tok::Module => "module",
+            Tok::ModulesColon => "modules:",
+            Tok::MoveFromLess => "move_from<",
+            Tok::MoveLParen => "move(",
+            Tok::MoveToSenderLess => "move_to_sender<",
+            Tok::Native => "native",
+            Tok::NumSign => "#",
+            Tok::Percent => "%",
+            Tok::Period => ".",
+            Tok::Pipe => "|",
+            Tok::PipePipe => "||",
+            Tok::Plus => "+",
+            Tok::Public => "public",
+            Tok::R => "R",
+            Tok::RBrace => "}",
+            Tok::RParen => ")",
+            Tok::Release => "release",
+            Tok::Requires => "requires",
+            Tok::Resource => "resource",
+            Tok::Return => "return",
+            Tok::ScriptColon => "script:",
+            Tok::Semicolon => ";",
+            Tok::Slash => "/",
+            Tok::Star => "*",
+            Tok::String => "string",
+            Tok::Struct => "struct",
+            Tok::ToU128 => "to_u128",
+            Tok::ToU64 => "to_u64",
+            Tok::ToU8 => "to_u8",
+            Tok::True => "true",
+            Tok::U128 => "u128",
+            Tok::U64 => "u64",
+            Tok::U8 => "u8",
+            Tok::V => "V",
+            Tok::Verify => "verify",
+            Tok::While => "while",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LexErrorKind {
+    InvalidCharacter,
+    InvalidByteArray,
+    UnterminatedBlockComment,
+    UnterminatedVerifierCondition,
+    IntegerOutOfRange,
+}
+
+/// Source the lexer cannot tokenize, at `start..end`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self.kind {
+            LexErrorKind::InvalidCharacter => "Invalid Token",
+            LexErrorKind::InvalidByteArray => {
+                "Invalid byte array, expected an even number of hex digits"
+            }
+            LexErrorKind::UnterminatedBlockComment => "Unterminated block comment",
+            LexErrorKind::UnterminatedVerifierCondition => {
+                "Unterminated condition, expected a closing '>' on the same line"
+            }
+            LexErrorKind::IntegerOutOfRange => "Integer literal does not fit in its type",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+/// An iterator over the tokens of a source. It stops after the first error.
+pub struct Lexer<'input> {
+    input: &'input str,
+    // the offset of `input` in the source, added to the offsets of tokens and errors
+    offset: usize,
+    pos: usize,
+    prev: Option<Tok<'input>>,
+    // the number of unclosed `<` of type parameters or actuals, inside which `>>` closes two of
+    // them instead of being a shift
+    type_depth: usize,
+    // the lines of the doc comment before the next token
+    doc: Vec<&'input str>,
+    doc_comments: DocComments,
+}
+
+impl<'input> Lexer<'input> {
+    pub fn new(input: &'input str) -> Self {
+        Self::with_offset(input, 0)
+    }
+
+    /// Lexes `input`, a part of a source starting at byte `offset`, such as the condition of a
+    /// `verify<...>` statement, giving tokens their offsets in the whole source.
+    pub fn with_offset(input: &'input str, offset: usize) -> Self {
+        Lexer {
+            input,
+            offset,
+            pos: 0,
+            prev: None,
+            type_depth: 0,
+            doc: vec![],
+            doc_comments: DocComments::new(),
+        }
+    }
+
+    /// Returns the doc comments of the tokens read so far.
+    pub fn into_doc_comments(self) -> DocComments {
+        self.doc_comments
+    }
+
+    fn rest(&self) -> &'input str {
+        &self.input[self.pos..]
+    }
+
+    // Skip whitespace and comments, keeping the lines of doc comments.
+    fn skip_trivia(&mut self) -> Result<(), LexError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                let line = &trimmed[..trimmed.find('\n').unwrap_or_else(|| trimmed.len())];
+                if line.starts_with("///") && !line.starts_with("////") {
+                    let text = line[3..].trim_end_matches('\r');
+                    self.doc.push(if text.starts_with(' ') {
+                        &text[1..]
+                    } else {
+                        text
+                    });
+                }
+                self.pos += line.len();
+            } else if trimmed.starts_with("/*") {
+                self.skip_block_comment()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_block_comment(&mut self) -> Result<(), LexError> {
+        let bytes = self.input.as_bytes();
+        let start = self.pos;
+        let mut depth = 0;
+        let mut pos = start;
+        while pos < bytes.len() {
+            if bytes[pos..].starts_with(b"/*") {
+                depth += 1;
+                pos += 2;
+            } else if bytes[pos..].starts_with(b"*/") {
+                depth -= 1;
+                pos += 2;
+                if depth == 0 {
+                    self.pos = pos;
+                    return Ok(());
+                }
+            } else {
+                pos += 1;
+            }
+        }
+        Err(LexError {
+            kind: LexErrorKind::UnterminatedBlockComment,
+            start: self.offset + start,
+            end: self.offset + start + 2,
+        })
+    }
+
+    // Lex the token at the start of `rest`, returning it with its length.
+    fn lex(
+        &self,
+        rest: &'input str,
+        c: char,
+    ) -> Result<(Tok<'input>, usize), (LexErrorKind, usize)> {
+        if is_ident_start(c) {
+            return lex_ident(rest);
+        }
+        if c.is_ascii_digit() {
+            return Ok(lex_number(rest));
+        }
+        match self.prev {
+            Some(Tok::Verify) | Some(Tok::Assume) | Some(Tok::Requires) | Some(Tok::Ensures)
+                if c == '<' =>
+            {
+                return lex_verifier_condition(rest);
+            }
+            _ => (),
+        }
+        let tok = match rest.get(..2).unwrap_or("") {
+            "==" => Some(Tok::EqualEqual),
+            "!=" => Some(Tok::ExclaimEqual),
+            "<=" => Some(Tok::LessEqual),
+            "<<" => Some(Tok::LessLess),
+            ">>" if self.type_depth == 0 => Some(Tok::GreaterGreater),
+            ">=" => Some(Tok::GreaterEqual),
+            "||" => Some(Tok::PipePipe),
+            "&&" => Some(Tok::AmpAmp),
+            _ => None,
+        };
+        if let Some(tok) = tok {
+            return Ok((tok, 2));
+        }
+        if rest.starts_with("&mut") {
+            if let Some(space) = rest[4..].chars().next().filter(|c| c.is_whitespace()) {
+                return Ok((Tok::AmpMut, 4 + space.len_utf8()));
+            }
+        }
+        let tok = match c {
+            '&' => Tok::Amp,
+            '^' => Tok::Caret,
+            ':' => Tok::Colon,
+            ',' => Tok::Comma,
+            '=' => Tok::Equal,
+            '!' => Tok::Exclaim,
+            '>' => Tok::Greater,
+            '{' => Tok::LBrace,
+            '<' => Tok::Less,
+            '(' => Tok::LParen,
+            '-' => Tok::Minus,
+            '#' => Tok::NumSign,
+            '%' => Tok::Percent,
+            '.' => Tok::Period,
+            '|' => Tok::Pipe,
+            '+' => Tok::Plus,
+            '}' => Tok::RBrace,
+            ')' => Tok::RParen,
+            ';' => Tok::Semicolon,
+            '/' => Tok::Slash,
+            '*' => Tok::Star,
+            _ => return Err((LexErrorKind::InvalidCharacter, c.len_utf8())),
+        };
+        Ok((tok, 1))
+    }
+
+    // A `<` right after a name, with no space in between, opens type parameters or actuals, as
+    // in `Vector.empty<u64>()` or `struct Cell<T>`, while a comparison is written `a < b`.
+    fn track_type_depth(&mut self, tok: Tok<'input>, adjacent: bool) {
+        match (self.prev, tok) {
+            (Some(Tok::NameValue(_)), Tok::Less) | (Some(Tok::DotNameValue(_)), Tok::Less)
+                if adjacent =>
+            {
+                self.type_depth += 1
+            }
+            (_, Tok::BorrowGlobalLess)
+            | (_, Tok::ExistsLess)
+            | (_, Tok::MoveFromLess)
+            | (_, Tok::MoveToSenderLess) => self.type_depth += 1,
+            (_, Tok::Greater) if self.type_depth > 0 => self.type_depth -= 1,
+            // Types never contain these, so a `<` they follow was a comparison after all.
+            (_, Tok::LParen)
+            | (_, Tok::RParen)
+            | (_, Tok::LBrace)
+            | (_, Tok::RBrace)
+            | (_, Tok::Semicolon) => self.type_depth = 0,
+            _ => (),
+        }
+    }
+}
+
+impl<'input> Iterator for Lexer<'input> {
+    type Item = Result<SpannedTok<'input>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let prev_end = self.pos;
+        if let Err(err) = self.skip_trivia() {
+            self.pos = self.input.len();
+            return Some(Err(err));
+        }
+        let rest = self.rest();
+        let c = rest.chars().next()?;
+        let start = self.offset + self.pos;
+        match self.lex(rest, c) {
+            Ok((tok, len)) => {
+                let adjacent = self.pos == prev_end;
+                self.pos += len;
+                self.track_type_depth(tok, adjacent);
+                self.prev = Some(tok);
+                if !self.doc.is_empty() {
+                    self.doc_comments.insert(start, self.doc.join("\n"));
+                    self.doc.clear();
+                }
+                Some(Ok((start, tok, self.offset + self.pos)))
+            }
+            Err((kind, len)) => {
+                self.pos = self.input.len();
+                Some(Err(LexError {
+                    kind,
+                    start,
+                    end: start + len,
+                }))
+            }
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '$' || c == '_'
+}
+
+fn ident_len(s: &str) -> usize {
+    s.find(|c: char| !(c.is_ascii_alphanumeric() || c == '$' || c == '_'))
+        .unwrap_or_else(|| s.len())
+}
+
+fn lex_ident(rest: &str) -> Result<(Tok<'_>, usize), (LexErrorKind, usize)> {
+    let len = ident_len(rest);
+    let name = &rest[..len];
+    let after = &rest[len..];
+    if name == "h" && after.starts_with('"') {
+        return lex_byte_array(rest);
+    }
+    if after.starts_with('.') && after[1..].starts_with(is_ident_start) {
+        let len = len + 1 + ident_len(&after[1..]);
+        return Ok((Tok::DotNameValue(&rest[..len]), len));
+    }
+    // A few tokens include the character after a name, like `move(` or `exists<`.
+    let suffixed = match (name, after.chars().next()) {
+        ("assert", Some('(')) => Some(Tok::AssertLParen),
+        ("copy", Some('(')) => Some(Tok::CopyLParen),
+        ("move", Some('(')) => Some(Tok::MoveLParen),
+        ("borrow_global", Some('<')) => Some(Tok::BorrowGlobalLess),
+        ("exists", Some('<')) => Some(Tok::ExistsLess),
+        ("move_from", Some('<')) => Some(Tok::MoveFromLess),
+        ("move_to_sender", Some('<')) => Some(Tok::MoveToSenderLess),
+        ("modules", Some(':')) => Some(Tok::ModulesColon),
+        ("script", Some(':')) => Some(Tok::ScriptColon),
+        _ => None,
+    };
+    if let Some(tok) = suffixed {
+        return Ok((tok, len + 1));
+    }
+    let tok = match name {
+        "abort" => Tok::Abort,
+        "address" => Tok::Address,
+        "as" => Tok::As,
+        "assume" => Tok::Assume,
+        "bool" => Tok::Bool,
+        "break" => Tok::Break,
+        "bytearray" => Tok::Bytearray,
+        "continue" => Tok::Continue,
+        "copyable" => Tok::Copyable,
+        "create_account" => Tok::CreateAccount,
+        "else" => Tok::Else,
+        "emit_event" => Tok::EmitEvent,
+        "ensures" => Tok::Ensures,
+        "false" => Tok::False,
+        "freeze" => Tok::Freeze,
+        "get_gas_remaining" => Tok::GetGasRemaining,
+        "get_height" => Tok::GetHeight,
+        "get_txn_gas_unit_price" => Tok::GetTxnGasUnitPrice,
+        "get_txn_max_gas_units" => Tok::GetTxnMaxGasUnits,
+        "get_txn_public_key" => Tok::GetTxnPublicKey,
+        "get_txn_sender" => Tok::GetTxnSender,
+        "get_txn_sequence_number" => Tok::GetTxnSequenceNumber,
+        "if" => Tok::If,
+        "import" => Tok::Import,
+        "let" => Tok::Let,
+        "loop" => Tok::Loop,
+        "main" => Tok::Main,
+        "module" => Tok::Module,
+        "native" => Tok::Native,
+        "public" => Tok::Public,
+        "R" => Tok::R,
+        "release" => Tok::Release,
+        "requires" => Tok::Requires,
+        "resource" => Tok::Resource,
+        "return" => Tok::Return,
+        "string" => Tok::String,
+        "struct" => Tok::Struct,
+        "to_u128" => Tok::ToU128,
+        "to_u64" => Tok::ToU64,
+        "to_u8" => Tok::ToU8,
+        "true" => Tok::True,
+        "u128" => Tok::U128,
+        "u64" => Tok::U64,
+        "u8" => Tok::U8,
+        "V" => Tok::V,
+        "verify" => Tok::Verify,
+        "while" => Tok::While,
+        _ => Tok::NameValue(name),
+    };
+    Ok((tok, len))
+}
+
+fn lex_number(rest: &str) -> (Tok<'_>, usize) {
+    let hex_len = |s: &str| {
+        s.find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or_else(|| s.len())
+    };
+    if (rest.starts_with("0x") || rest.starts_with("0X")) && hex_len(&rest[2..]) > 0 {
+        let len = 2 + hex_len(&rest[2..]);
+        return (Tok::AddressValue(&rest[..len]), len);
+    }
+    let len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or_else(|| rest.len());
+    // An integer literal may end with the name of its type, as in `255u8`.
+    let suffix = &rest[len..len + ident_len(&rest[len..])];
+    match suffix {
+        "u8" => (Tok::U8Value(&rest[..len + 2]), len + 2),
+        "u64" => (Tok::U64Value(&rest[..len + 3]), len + 3),
+        "u128" => (Tok::U128Value(&rest[..len + 4]), len + 4),
+        _ => (Tok::U64Value(&rest[..len]), len),
+    }
+}
+
+fn lex_byte_array(rest: &str) -> Result<(Tok<'_>, usize), (LexErrorKind, usize)> {
+    let digits = &rest[2..];
+    match digits.find('"') {
+        Some(end) if end % 2 == 0 && digits[..end].chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok((Tok::ByteArrayValue(&rest[..end + 3]), end + 3))
+        }
+        Some(end) => Err((LexErrorKind::InvalidByteArray, end + 3)),
+        None => Err((LexErrorKind::InvalidByteArray, 2)),
+    }
+}
+
+// A condition runs from `<` to the last `>` on its line.
+fn lex_verifier_condition(rest: &str) -> Result<(Tok<'_>, usize), (LexErrorKind, usize)> {
+    let line = &rest[..rest.find('\n').unwrap_or_else(|| rest.len())];
+    match line.rfind('>').filter(|end| *end >= 2) {
+        Some(end) => Ok((Tok::VerifierCondition(&rest[..=end]), end + 1)),
+        None => Err((LexErrorKind::UnterminatedVerifierCondition, line.len())),
+    }
+}
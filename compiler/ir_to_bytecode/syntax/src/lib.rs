@@ -2,6 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! # Grammar
+//! ## Comments
+//! ```text
+//! // line comment, up to the end of the line
+//! /* block comment, /* which can be nested */ */
+//! /// doc comment of the struct or procedure declared after it
+//! ```
+//!
 //! ## Identifiers
 //! ```text
 //! f ∈ FieldName     // [a-zA-Z$_][a-zA-Z0-9$_]*
@@ -19,9 +26,12 @@
 //!
 //! g ∈ GroundType ::=
 //!   | bool
+//!   | u8        // unsigned 8 bit integer
 //!   | u64       // unsigned 64 bit integer
+//!   | u128      // unsigned 128 bit integer
 //!   | address   // 32 byte account address
 //!   | bytearray // immutable, arbitrarily sized array of bytes
+//!   | string    // immutable UTF-8 string, handled by the natives of the StringUtil module
 //!
 //! d ∈ ModuleAlias ::=
 //!   | m         // module name that is an alias to a declared module, addr.m
@@ -45,13 +55,15 @@
 //!
 //! ## Values
 //! ```text
-//! u ∈ Unsigned64        // Unsigned, 64-bit Integer
+//! u ∈ Unsigned          // Unsigned Integer, written in decimal
 //! addr ∈ AccountAddress // addresses of blockchain accounts
 //! bytes ∈ ByteArray     // byte array of arbitrary length
 //! v ∈ Value ::=
 //!   | true
 //!   | false
-//!   | u        // u64 literal
+//!   | u        // u64 literal, also written 'uu64'
+//!   | uu8      // u8 literal
+//!   | uu128    // u128 literal
 //!   | 0xaddr   // 32 byte address literal
 //!   | b"bytes" // arbitrary length bytearray literal
 //! ```
@@ -82,7 +94,7 @@
 //!   | !e_1
 //!   | e_1 || e_2
 //!   | e_1 && e_2
-//!   // u64 operators
+//!   // integer operators, over two integers of the same type
 //!   | e_1 >= e_2
 //!   | e_1 <= e_2
 //!   | e_1 > e_2
@@ -95,6 +107,8 @@
 //!   | e_1 ^ e_2
 //!   | e_1 | e_2
 //!   | e_g & e_2
+//!   | e_1 << e_2 // 'e_2' is a u8 shift amount
+//!   | e_1 >> e_2 // 'e_2' is a u8 shift amount
 //!   // operators over any ground type
 //!   | e_1 == e_2
 //!   | e_1 != e_2
@@ -136,6 +150,12 @@
 //!                               // gives the sequence number for this transaction
 //!   | get_gas_remaining()       // type: 'unit -> u64'
 //!                               // gives the amount of gas gas units remaining before the transaction execution will be forced to halt execution
+//!   | to_u8(e)                  // type: 'u8 -> u8', 'u64 -> u8' or 'u128 -> u8'
+//!                               // converts an integer to a u8, failing if it does not fit
+//!   | to_u64(e)                 // type: 'u8 -> u64', 'u64 -> u64' or 'u128 -> u64'
+//!                               // converts an integer to a u64, failing if it does not fit
+//!   | to_u128(e)                // type: 'u8 -> u128', 'u64 -> u128' or 'u128 -> u128'
+//!                               // converts an integer to a u128
 //!
 //! call ∈ Call ::=
 //!   | mop
@@ -168,6 +188,20 @@
 //!   | s_1 s_2                     // sequencing
 //! ```
 //!
+//! ## Specifications
+//! ```text
+//! // checked at runtime, aborting with a dedicated code, when compiled with `--check-specs`
+//! a ∈ Annotation ::=
+//!   | requires<e> // 'e' holds when the procedure is called
+//!   | ensures<e>  // 'e' holds when the procedure returns, with the values returned bound to
+//!                 // 'RET', or to 'RET0' to 'RET_j' if there are several; no argument or
+//!                 // local of the procedure may have these names
+//!
+//! s ∈ Stmt ::=
+//!   | verify<e>   // 'e' holds at this point
+//!   | assume<e>   // 'e' is assumed to hold at this point
+//! ```
+//!
 //! ## Imports
 //!```text
 //! idecl ∈ Import ::=
@@ -185,8 +219,8 @@
 //!  | let x_1; ... let x_j; s // The locals declared in this procedure, and the code for that procedure
 //!
 //! pdecl ∈ ProcedureDecl ::=
-//!   | (public?) p(x_1: 𝛕_1, ..., x_j: 𝛕_j): 𝛕-list { body } // declaration of a defined procedure
-//!                                                          // the procedure may be public, or internal to the module
+//!   | (public?) p(x_1: 𝛕_1, ..., x_j: 𝛕_j): 𝛕-list a_1 ... a_i { body } // declaration of a defined procedure
+//!                                                                      // the procedure may be public, or internal to the module
 //!   | native (public?) p(x_1: 𝛕_1, ..., x_j: 𝛕_j): 𝛕-list; // declaration of a native procedure
 //!                                                         // the implementation is provided by the VM
 //!                                                         // the procedure may be public, or internal to the module
@@ -204,8 +238,11 @@
 //! ```
 
 pub mod ast;
+pub mod lexer;
 
 #[rustfmt::skip]
 #[allow(clippy::all)]
 #[allow(deprecated)]
-pub mod syntax;
+pub mod syntax {
+    include!(concat!(env!("OUT_DIR"), "/syntax.rs"));
+}
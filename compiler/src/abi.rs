@@ -0,0 +1,463 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! JSON ABIs of compiled modules and scripts, and the encoding and decoding of Move values
+//! against them.
+//!
+//! An ABI lists the layouts of the structs of a module, the signatures of its public functions
+//! and the events its functions emit, so that clients can build transaction arguments and decode
+//! published resources without hard-coding layouts.
+
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::convert::TryFrom;
+use vm::{
+    def::{
+        access::ModuleAccess,
+        file_format::{
+            Bytecode, CompiledModule, CompiledScript, FunctionDefinitionIndex, Kind,
+            SignatureToken, StructFieldInformation,
+        },
+        views::StructHandleView,
+    },
+    types::{parse_as_address, transaction::TransactionArgument, ByteArray},
+    vm_runtime::{
+        loaded_data::{struct_def::StructDef, types::Type},
+        vm_runtime_types::{native_structs::resolve_native_struct, value::Value},
+    },
+};
+
+/// The ABI of a module.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ModuleAbi {
+    /// The address the module is published under, as a `0x` prefixed hex string
+    pub address: String,
+    pub name: String,
+    pub structs: Vec<StructAbi>,
+    /// The public functions of the module
+    pub functions: Vec<FunctionAbi>,
+    pub events: Vec<EventAbi>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct StructAbi {
+    pub name: String,
+    pub kind: KindAbi,
+    /// The kind constraints of the type parameters
+    pub type_formals: Vec<KindAbi>,
+    /// The fields in declaration order, or `None` for native structs
+    pub fields: Option<Vec<FieldAbi>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FieldAbi {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: TypeAbi,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct FunctionAbi {
+    pub name: String,
+    /// The kind constraints of the type parameters
+    pub type_formals: Vec<KindAbi>,
+    pub args: Vec<TypeAbi>,
+    pub returns: Vec<TypeAbi>,
+}
+
+/// An event emitted by a function of a module.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EventAbi {
+    /// The name of the emitting function
+    pub function: String,
+    /// The hex encoded key of the event, if it is a constant
+    pub key: Option<String>,
+    #[serde(rename = "type")]
+    pub type_: TypeAbi,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KindAbi {
+    Resource,
+    Copyable,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeAbi {
+    Bool,
+    U8,
+    U64,
+    U128,
+    String,
+    ByteArray,
+    Address,
+    Struct(StructTypeAbi),
+    Reference(Box<TypeAbi>),
+    MutableReference(Box<TypeAbi>),
+    TypeParameter(u16),
+}
+
+/// A struct type, instantiated with `type_actuals`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StructTypeAbi {
+    /// The address of the declaring module, as a `0x` prefixed hex string
+    pub address: String,
+    pub module: String,
+    pub name: String,
+    pub type_actuals: Vec<TypeAbi>,
+}
+
+impl From<Kind> for KindAbi {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Resource => KindAbi::Resource,
+            Kind::Copyable => KindAbi::Copyable,
+        }
+    }
+}
+
+impl TypeAbi {
+    fn new(module: &CompiledModule, token: &SignatureToken) -> Self {
+        match token {
+            SignatureToken::Bool => TypeAbi::Bool,
+            SignatureToken::U8 => TypeAbi::U8,
+            SignatureToken::U64 => TypeAbi::U64,
+            SignatureToken::U128 => TypeAbi::U128,
+            SignatureToken::String => TypeAbi::String,
+            SignatureToken::ByteArray => TypeAbi::ByteArray,
+            SignatureToken::Address => TypeAbi::Address,
+            SignatureToken::Struct(idx, type_actuals) => {
+                let handle = StructHandleView::new(module, module.struct_handle_at(*idx));
+                let module_id = handle.module_id();
+                TypeAbi::Struct(StructTypeAbi {
+                    address: module_id.address().to_string(),
+                    module: module_id.name().clone(),
+                    name: handle.name().to_string(),
+                    type_actuals: type_actuals
+                        .iter()
+                        .map(|token| TypeAbi::new(module, token))
+                        .collect(),
+                })
+            }
+            SignatureToken::Reference(token) => {
+                TypeAbi::Reference(Box::new(TypeAbi::new(module, token)))
+            }
+            SignatureToken::MutableReference(token) => {
+                TypeAbi::MutableReference(Box::new(TypeAbi::new(module, token)))
+            }
+            SignatureToken::TypeParameter(idx) => TypeAbi::TypeParameter(*idx),
+        }
+    }
+
+    /// Replaces the type parameters in this type with `type_actuals`.
+    pub fn substitute(&self, type_actuals: &[TypeAbi]) -> TypeAbi {
+        match self {
+            TypeAbi::Struct(struct_type) => TypeAbi::Struct(StructTypeAbi {
+                type_actuals: struct_type
+                    .type_actuals
+                    .iter()
+                    .map(|ty| ty.substitute(type_actuals))
+                    .collect(),
+                ..struct_type.clone()
+            }),
+            TypeAbi::Reference(ty) => TypeAbi::Reference(Box::new(ty.substitute(type_actuals))),
+            TypeAbi::MutableReference(ty) => {
+                TypeAbi::MutableReference(Box::new(ty.substitute(type_actuals)))
+            }
+            TypeAbi::TypeParameter(idx) => type_actuals
+                .get(*idx as usize)
+                .cloned()
+                .unwrap_or_else(|| self.clone()),
+            _ => self.clone(),
+        }
+    }
+}
+
+/// Returns the ABI of `module`.
+pub fn module_abi(module: &CompiledModule) -> ModuleAbi {
+    let structs = module
+        .struct_defs()
+        .iter()
+        .map(|struct_def| {
+            let handle = module.struct_handle_at(struct_def.struct_handle);
+            let fields = match &struct_def.field_information {
+                StructFieldInformation::Native => None,
+                StructFieldInformation::Declared {
+                    field_count,
+                    fields,
+                } => Some(
+                    module
+                        .field_def_range(*field_count, *fields)
+                        .iter()
+                        .map(|field| FieldAbi {
+                            name: module.string_at(field.name).to_string(),
+                            type_: TypeAbi::new(
+                                module,
+                                &module.type_signature_at(field.signature).0,
+                            ),
+                        })
+                        .collect(),
+                ),
+            };
+            StructAbi {
+                name: module.string_at(handle.name).to_string(),
+                kind: handle.kind.into(),
+                type_formals: handle
+                    .kind_constraints
+                    .iter()
+                    .map(|k| (*k).into())
+                    .collect(),
+                fields,
+            }
+        })
+        .collect();
+
+    let mut functions = vec![];
+    let mut events = vec![];
+    for (idx, function_def) in module.function_defs().iter().enumerate() {
+        let idx = FunctionDefinitionIndex::new(idx as u16);
+        if function_def.is_public() {
+            functions.push(function_abi(module, idx));
+        }
+        events.extend(emitted_events(module, idx));
+    }
+
+    ModuleAbi {
+        address: module.address().to_string(),
+        name: module.name().to_string(),
+        structs,
+        functions,
+        events,
+    }
+}
+
+/// Returns the ABI of the `main` function of `script`.
+pub fn script_abi(script: &CompiledScript) -> FunctionAbi {
+    function_abi(&script.clone().into_module(), CompiledScript::MAIN_INDEX)
+}
+
+fn function_abi(module: &CompiledModule, idx: FunctionDefinitionIndex) -> FunctionAbi {
+    let handle = module.function_handle_at(module.function_def_at(idx).function);
+    let signature = module.function_signature_at(handle.signature);
+    FunctionAbi {
+        name: module.string_at(handle.name).to_string(),
+        type_formals: signature
+            .kind_constraints
+            .iter()
+            .map(|k| (*k).into())
+            .collect(),
+        args: signature
+            .arg_types
+            .iter()
+            .map(|token| TypeAbi::new(module, token))
+            .collect(),
+        returns: signature
+            .return_types
+            .iter()
+            .map(|token| TypeAbi::new(module, token))
+            .collect(),
+    }
+}
+
+/// Returns the events emitted by the function `idx`. The type of an event is only known when its
+/// data is a local or a struct packed right before `EmitEvent`, which is how the compiler emits
+/// `emit_event(ref, key, move(data))`.
+fn emitted_events(module: &CompiledModule, idx: FunctionDefinitionIndex) -> Vec<EventAbi> {
+    let function_def = module.function_def_at(idx);
+    let function = module
+        .string_at(module.function_handle_at(function_def.function).name)
+        .to_string();
+    let code = &function_def.code.code;
+    let locals = &module.locals_signature_at(function_def.code.locals).0;
+    let mut events = vec![];
+    for (offset, bytecode) in code.iter().enumerate() {
+        if *bytecode != Bytecode::EmitEvent || offset == 0 {
+            continue;
+        }
+        let type_ = match &code[offset - 1] {
+            Bytecode::MoveLoc(local) | Bytecode::CopyLoc(local) => {
+                TypeAbi::new(module, &locals[*local as usize])
+            }
+            Bytecode::Pack(struct_def_idx, type_actuals) => TypeAbi::new(
+                module,
+                &SignatureToken::Struct(
+                    module.struct_def_at(*struct_def_idx).struct_handle,
+                    module.locals_signature_at(*type_actuals).0.clone(),
+                ),
+            ),
+            _ => continue,
+        };
+        let key = match offset.checked_sub(2).map(|key_offset| &code[key_offset]) {
+            Some(Bytecode::LdByteArray(key)) => {
+                Some(hex::encode(module.byte_array_at(*key).as_bytes()))
+            }
+            _ => None,
+        };
+        events.push(EventAbi {
+            function: function.clone(),
+            key,
+            type_,
+        });
+    }
+    events
+}
+
+/// Encodes `args` as the arguments of `function`.
+///
+/// Integers are JSON numbers, except that u128 integers may also be decimal strings, addresses `0x` prefixed hex strings and byte arrays hex strings.
+pub fn encode_args(function: &FunctionAbi, args: &[JsonValue]) -> Result<Vec<TransactionArgument>> {
+    if function.args.len() != args.len() {
+        bail!(
+            "{} expects {} argument(s), {} given",
+            function.name,
+            function.args.len(),
+            args.len()
+        );
+    }
+    function
+        .args
+        .iter()
+        .zip(args)
+        .enumerate()
+        .map(|(idx, (ty, arg))| {
+            let encoded = match (ty, arg) {
+                (TypeAbi::U8, JsonValue::Number(n)) => n
+                    .as_u64()
+                    .and_then(|n| u8::try_from(n).ok())
+                    .map(TransactionArgument::U8),
+                (TypeAbi::U64, JsonValue::Number(n)) => n.as_u64().map(TransactionArgument::U64),
+                (TypeAbi::U128, JsonValue::Number(n)) => n
+                    .as_u64()
+                    .map(|n| TransactionArgument::U128(u128::from(n))),
+                (TypeAbi::U128, JsonValue::String(s)) => {
+                    s.parse::<u128>().ok().map(TransactionArgument::U128)
+                }
+                (TypeAbi::Address, JsonValue::String(s)) => parse_as_address(s).ok(),
+                (TypeAbi::ByteArray, JsonValue::String(s)) => hex::decode(s)
+                    .ok()
+                    .map(|bytes| TransactionArgument::ByteArray(ByteArray::new(bytes))),
+                (TypeAbi::String, JsonValue::String(s)) => {
+                    Some(TransactionArgument::String(s.clone()))
+                }
+                _ => None,
+            };
+            encoded.ok_or_else(|| {
+                format_err!(
+                    "argument {} of {}: cannot encode {} as {:?}",
+                    idx,
+                    function.name,
+                    arg,
+                    ty
+                )
+            })
+        })
+        .collect()
+}
+
+/// Decodes the canonically serialized resource `blob` of type `resource` to JSON, looking up the
+/// layouts of structs in `abis`. Structs are decoded to objects keyed by field name, vectors to
+/// arrays and u128 integers to decimal strings.
+pub fn decode_resource(
+    abis: &[ModuleAbi],
+    resource: &StructTypeAbi,
+    blob: &[u8],
+) -> Result<JsonValue> {
+    let struct_def = match layout(abis, &TypeAbi::Struct(resource.clone()))? {
+        Type::Struct(struct_def) => struct_def,
+        _ => bail!("{} is not a struct with declared fields", resource.name),
+    };
+    let value = Value::simple_deserialize(blob, struct_def)
+        .map_err(|err| format_err!("cannot deserialize {}: {:?}", resource.name, err))?;
+    to_json(abis, &value, &TypeAbi::Struct(resource.clone()))
+}
+
+fn find_struct<'a>(abis: &'a [ModuleAbi], struct_type: &StructTypeAbi) -> Result<&'a StructAbi> {
+    abis.iter()
+        .filter(|abi| abi.address == struct_type.address && abi.name == struct_type.module)
+        .flat_map(|abi| abi.structs.iter())
+        .find(|struct_abi| struct_abi.name == struct_type.name)
+        .ok_or_else(|| {
+            format_err!(
+                "no ABI for struct {}.{}.{}",
+                struct_type.address,
+                struct_type.module,
+                struct_type.name
+            )
+        })
+}
+
+/// Returns the runtime layout of the fully instantiated type `ty`.
+fn layout(abis: &[ModuleAbi], ty: &TypeAbi) -> Result<Type> {
+    Ok(match ty {
+        TypeAbi::Bool => Type::Bool,
+        TypeAbi::U8 => Type::U8,
+        TypeAbi::U64 => Type::U64,
+        TypeAbi::U128 => Type::U128,
+        TypeAbi::String => Type::String,
+        TypeAbi::ByteArray => Type::ByteArray,
+        TypeAbi::Address => Type::Address,
+        TypeAbi::Struct(struct_type) => {
+            let mut type_actuals = vec![];
+            for type_actual in &struct_type.type_actuals {
+                type_actuals.push(layout(abis, type_actual)?);
+            }
+            match &find_struct(abis, struct_type)?.fields {
+                Some(fields) => {
+                    let mut field_types = vec![];
+                    for field in fields {
+                        let field_type = field.type_.substitute(&struct_type.type_actuals);
+                        field_types.push(layout(abis, &field_type)?);
+                    }
+                    Type::Struct(StructDef::new(field_types))
+                }
+                None => {
+                    resolve_native_struct(&struct_type.module, &struct_type.name, &type_actuals)
+                        .ok_or_else(|| format_err!("unknown native struct {}", struct_type.name))?
+                }
+            }
+        }
+        TypeAbi::Reference(_) | TypeAbi::MutableReference(_) | TypeAbi::TypeParameter(_) => {
+            bail!("{:?} cannot be stored", ty)
+        }
+    })
+}
+
+fn to_json(abis: &[ModuleAbi], value: &Value, ty: &TypeAbi) -> Result<JsonValue> {
+    Ok(match (value, ty) {
+        (Value::Bool(b), _) => JsonValue::Bool(*b),
+        (Value::U8(n), _) => JsonValue::from(*n),
+        (Value::U64(n), _) => JsonValue::from(*n),
+        // JSON numbers cannot hold every u128
+        (Value::U128(n), _) => JsonValue::String(n.to_string()),
+        (Value::String(s), _) => JsonValue::String(s.clone()),
+        (Value::ByteArray(bytes), _) => JsonValue::String(hex::encode(bytes.as_bytes())),
+        (Value::Address(addr), _) => JsonValue::String(addr.to_string()),
+        (Value::Struct(vals), TypeAbi::Struct(struct_type)) => {
+            let fields = find_struct(abis, struct_type)?
+                .fields
+                .as_ref()
+                .ok_or_else(|| format_err!("{} has no fields", struct_type.name))?;
+            let mut object = serde_json::Map::new();
+            for (field, val) in fields.iter().zip(vals) {
+                let field_type = field.type_.substitute(&struct_type.type_actuals);
+                object.insert(field.name.clone(), to_json(abis, &val.peek(), &field_type)?);
+            }
+            JsonValue::Object(object)
+        }
+        (Value::Vector(vals), TypeAbi::Struct(struct_type)) => {
+            let elem_type = struct_type
+                .type_actuals
+                .first()
+                .ok_or_else(|| format_err!("vector {} has no element type", struct_type.name))?;
+            let mut elems = vec![];
+            for val in vals {
+                elems.push(to_json(abis, &val.peek(), elem_type)?);
+            }
+            JsonValue::Array(elems)
+        }
+        _ => bail!("value {:?} does not have type {:?}", value, ty),
+    })
+}
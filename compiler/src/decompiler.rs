@@ -0,0 +1,1011 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decompilation of compiled modules and scripts back to Move IR source.
+//!
+//! The imports, struct declarations and function signatures are rebuilt from the tables of the
+//! module. The code of a function is evaluated on a stack of expressions, and a statement is
+//! written whenever the stack is emptied, which is where the compiler starts a new one. `if`,
+//! `while` and `loop` statements are recovered from the basic blocks of the control flow graph,
+//! following the shapes the compiler emits them in, so that the output recompiles to the same
+//! bytecode. `BrTrue`, which the compiler does not emit, becomes an `if` on a negated condition.
+//!
+//! Locals are named after the source map of the module when one is given, and `argN` and `locN`
+//! otherwise.
+
+use failure::prelude::*;
+use ir_to_bytecode::source_map::SourceMap;
+use std::{collections::HashSet, rc::Rc};
+use vm::{
+    bytecode_verifier::control_flow_graph::{ControlFlowGraph, VMControlFlowGraph},
+    def::{
+        access::ModuleAccess,
+        file_format::{
+            Bytecode, CodeOffset, CompiledModule, CompiledScript, FunctionDefinitionIndex, Kind,
+            LocalsSignatureIndex, SignatureToken, StructDefinition, StructDefinitionIndex,
+            StructFieldInformation,
+        },
+    },
+    types::AccountAddress,
+};
+
+/// Decompiles `module` to Move IR source, naming the locals of its functions after
+/// `source_map` if given.
+pub fn decompile_module(module: &CompiledModule, source_map: Option<&SourceMap>) -> Result<String> {
+    let decompiler = Decompiler::new(module, source_map);
+    let mut out = format!("module {} {{\n", module.name());
+    if decompiler.imports(&mut out, 1) {
+        out.push('\n');
+    }
+    for struct_def in module.struct_defs() {
+        decompiler.struct_def(&mut out, struct_def)?;
+    }
+    for idx in 0..module.function_defs().len() {
+        if !module.struct_defs().is_empty() || idx > 0 {
+            out.push('\n');
+        }
+        decompiler.function_def(&mut out, FunctionDefinitionIndex::new(idx as u16), 1)?;
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+/// Decompiles `script` to Move IR source, naming the locals of `main` after `source_map` if
+/// given.
+pub fn decompile_script(script: &CompiledScript, source_map: Option<&SourceMap>) -> Result<String> {
+    let module = script.clone().into_module();
+    let decompiler = Decompiler::new(&module, source_map);
+    let mut out = String::new();
+    if decompiler.imports(&mut out, 0) {
+        out.push('\n');
+    }
+    decompiler.function_def(&mut out, CompiledScript::MAIN_INDEX, 0)?;
+    Ok(out)
+}
+
+const INDENT: &str = "    ";
+
+fn line(out: &mut String, indent: usize, text: &str) {
+    for _ in 0..indent {
+        out.push_str(INDENT);
+    }
+    out.push_str(text);
+    out.push('\n');
+}
+
+fn address_literal(address: &AccountAddress) -> String {
+    let hex = format!("{:x}", address);
+    let digits = hex.trim_start_matches('0');
+    format!("0x{}", if digits.is_empty() { "0" } else { digits })
+}
+
+fn type_formals(kinds: &[Kind]) -> String {
+    if kinds.is_empty() {
+        return String::new();
+    }
+    let formals = kinds
+        .iter()
+        .enumerate()
+        .map(|(idx, kind)| match kind {
+            Kind::Resource => format!("T{}", idx),
+            Kind::Copyable => format!("T{}: copyable", idx),
+        })
+        .collect::<Vec<_>>();
+    format!("<{}>", formals.join(", "))
+}
+
+struct Decompiler<'a> {
+    module: &'a CompiledModule,
+    source_map: Option<&'a SourceMap>,
+    // the name each module handle is referred to by: `Self` or the alias of its import
+    module_names: Vec<String>,
+}
+
+impl<'a> Decompiler<'a> {
+    fn new(module: &'a CompiledModule, source_map: Option<&'a SourceMap>) -> Self {
+        let mut taken = HashSet::new();
+        let module_names = module
+            .module_handles()
+            .iter()
+            .enumerate()
+            .map(|(idx, handle)| {
+                let name = if idx == 0 {
+                    "Self".to_string()
+                } else {
+                    let name = module.string_at(handle.name);
+                    if taken.contains(name) || name == "Self" {
+                        format!("{}{}", name, idx)
+                    } else {
+                        name.to_string()
+                    }
+                };
+                taken.insert(name.clone());
+                name
+            })
+            .collect();
+        Decompiler {
+            module,
+            source_map,
+            module_names,
+        }
+    }
+
+    // Writes an import for each module handle but the one of the module itself, and returns
+    // whether there were any.
+    fn imports(&self, out: &mut String, indent: usize) -> bool {
+        for (handle, alias) in self
+            .module
+            .module_handles()
+            .iter()
+            .zip(&self.module_names)
+            .skip(1)
+        {
+            let name = self.module.string_at(handle.name);
+            let address = address_literal(self.module.address_at(handle.address));
+            if name == alias {
+                line(out, indent, &format!("import {}.{};", address, name));
+            } else {
+                line(
+                    out,
+                    indent,
+                    &format!("import {}.{} as {};", address, name, alias),
+                );
+            }
+        }
+        self.module_names.len() > 1
+    }
+
+    fn struct_def(&self, out: &mut String, struct_def: &StructDefinition) -> Result<()> {
+        let handle = self.module.struct_handle_at(struct_def.struct_handle);
+        let kind = match handle.kind {
+            Kind::Resource => "resource",
+            Kind::Copyable => "struct",
+        };
+        let declaration = format!(
+            "{} {}{}",
+            kind,
+            self.module.string_at(handle.name),
+            type_formals(&handle.kind_constraints)
+        );
+        match &struct_def.field_information {
+            StructFieldInformation::Native => {
+                line(out, 1, &format!("native {};", declaration));
+            }
+            StructFieldInformation::Declared {
+                field_count,
+                fields,
+            } => {
+                line(out, 1, &format!("{} {{", declaration));
+                for field in self.module.field_def_range(*field_count, *fields) {
+                    let type_ = self.type_(&self.module.type_signature_at(field.signature).0)?;
+                    line(
+                        out,
+                        2,
+                        &format!("{}: {},", self.module.string_at(field.name), type_),
+                    );
+                }
+                line(out, 1, "}");
+            }
+        }
+        Ok(())
+    }
+
+    fn function_def(
+        &self,
+        out: &mut String,
+        idx: FunctionDefinitionIndex,
+        indent: usize,
+    ) -> Result<()> {
+        let function_def = self.module.function_def_at(idx);
+        let handle = self.module.function_handle_at(function_def.function);
+        let signature = self.module.function_signature_at(handle.signature);
+        let locals = &self.module.locals_signature_at(function_def.code.locals).0;
+        let names = self.local_names(idx, signature.arg_types.len(), locals.len());
+
+        let args = signature
+            .arg_types
+            .iter()
+            .zip(&names)
+            .map(|(token, name)| Ok(format!("{}: {}", name, self.type_(token)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let mut header = format!(
+            "{}{}{}{}({})",
+            if function_def.is_native() {
+                "native "
+            } else {
+                ""
+            },
+            // the `main` function of a script has no visibility
+            if function_def.is_public() && indent > 0 {
+                "public "
+            } else {
+                ""
+            },
+            self.module.string_at(handle.name),
+            type_formals(&signature.kind_constraints),
+            args.join(", ")
+        );
+        if !signature.return_types.is_empty() {
+            let returns = signature
+                .return_types
+                .iter()
+                .map(|token| self.type_(token))
+                .collect::<Result<Vec<_>>>()?;
+            header.push_str(&format!(": {}", returns.join(" * ")));
+        }
+        if function_def.is_native() {
+            line(out, indent, &format!("{};", header));
+            return Ok(());
+        }
+
+        line(out, indent, &format!("{} {{", header));
+        for (token, name) in locals.iter().zip(&names).skip(signature.arg_types.len()) {
+            line(
+                out,
+                indent + 1,
+                &format!("let {}: {};", name, self.type_(token)?),
+            );
+        }
+        let code = &function_def.code.code;
+        let mut function = FunctionDecompiler {
+            decompiler: self,
+            code,
+            cfg: VMControlFlowGraph::new(code),
+            locals,
+            names: &names,
+            loops: vec![],
+            next_id: 0,
+            out: String::new(),
+        };
+        function
+            .sequence(0, code.len() as CodeOffset, indent + 1)
+            .map_err(|err| {
+                format_err!(
+                    "in function `{}`: {}",
+                    self.module.string_at(handle.name),
+                    err
+                )
+            })?;
+        out.push_str(&function.out);
+        line(out, indent, "}");
+        Ok(())
+    }
+
+    // The names of the locals of the function `idx`, arguments first.
+    fn local_names(
+        &self,
+        idx: FunctionDefinitionIndex,
+        arg_count: usize,
+        local_count: usize,
+    ) -> Vec<String> {
+        let local_count = local_count.max(arg_count);
+        let names = self
+            .source_map
+            .and_then(|source_map| source_map.functions.get(idx.0 as usize))
+            .map(|function| &function.locals)
+            .filter(|names| names.len() == local_count);
+        match names {
+            Some(names) => names.clone(),
+            None => (0..local_count)
+                .map(|local| {
+                    if local < arg_count {
+                        format!("arg{}", local)
+                    } else {
+                        format!("loc{}", local - arg_count)
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    fn type_(&self, token: &SignatureToken) -> Result<String> {
+        Ok(match token {
+            SignatureToken::Bool => "bool".to_string(),
+            SignatureToken::U8 => "u8".to_string(),
+            SignatureToken::U64 => "u64".to_string(),
+            SignatureToken::U128 => "u128".to_string(),
+            SignatureToken::ByteArray => "bytearray".to_string(),
+            SignatureToken::Address => "address".to_string(),
+            SignatureToken::String => "string".to_string(),
+            SignatureToken::Struct(idx, type_actuals) => {
+                let handle = self.module.struct_handle_at(*idx);
+                format!(
+                    "{}#{}.{}{}",
+                    match handle.kind {
+                        Kind::Resource => "R",
+                        Kind::Copyable => "V",
+                    },
+                    self.module_names[handle.module.0 as usize],
+                    self.module.string_at(handle.name),
+                    self.type_actuals(type_actuals)?
+                )
+            }
+            SignatureToken::Reference(token) => format!("&{}", self.type_(token)?),
+            SignatureToken::MutableReference(token) => format!("&mut {}", self.type_(token)?),
+            SignatureToken::TypeParameter(idx) => format!("T{}", idx),
+        })
+    }
+
+    fn type_actuals(&self, tokens: &[SignatureToken]) -> Result<String> {
+        if tokens.is_empty() {
+            return Ok(String::new());
+        }
+        let types = tokens
+            .iter()
+            .map(|token| self.type_(token))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(format!("<{}>", types.join(", ")))
+    }
+
+    // The name of a struct defined in the module as used in `Pack`, `Unpack` and the global
+    // storage builtins, with its type actuals.
+    fn struct_name(
+        &self,
+        idx: StructDefinitionIndex,
+        type_actuals: LocalsSignatureIndex,
+    ) -> Result<String> {
+        let handle = self
+            .module
+            .struct_handle_at(self.module.struct_def_at(idx).struct_handle);
+        Ok(format!(
+            "{}{}",
+            self.module.string_at(handle.name),
+            self.type_actuals(&self.module.locals_signature_at(type_actuals).0)?
+        ))
+    }
+
+    fn field_names(&self, idx: StructDefinitionIndex) -> Result<Vec<String>> {
+        match &self.module.struct_def_at(idx).field_information {
+            StructFieldInformation::Native => bail!("native structs cannot be packed or unpacked"),
+            StructFieldInformation::Declared {
+                field_count,
+                fields,
+            } => Ok(self
+                .module
+                .field_def_range(*field_count, *fields)
+                .iter()
+                .map(|field| self.module.string_at(field.name).to_string())
+                .collect()),
+        }
+    }
+}
+
+// An expression computing one or more values.
+struct Exp {
+    text: String,
+    // whether the text can be an operand without parentheses
+    atomic: bool,
+    // the number of values the expression pushes on the stack
+    values: usize,
+    // the text of the expression when frozen, for mutable borrows
+    frozen: Option<String>,
+    // the struct and the names of the fields, for the values of an `Unpack`
+    unpack: Option<(String, Vec<String>)>,
+}
+
+impl Exp {
+    fn atom(text: String) -> Self {
+        Exp {
+            text,
+            atomic: true,
+            values: 1,
+            frozen: None,
+            unpack: None,
+        }
+    }
+
+    fn compound(text: String) -> Self {
+        Exp {
+            atomic: false,
+            ..Exp::atom(text)
+        }
+    }
+
+    fn operand(&self) -> String {
+        if self.atomic {
+            self.text.clone()
+        } else {
+            format!("({})", self.text)
+        }
+    }
+}
+
+// A value on the stack.
+#[derive(Clone)]
+struct Slot {
+    exp: Rc<Exp>,
+    // tells the values of different expressions apart
+    id: usize,
+    // whether the value is a mutable reference
+    mutable: bool,
+}
+
+struct FunctionDecompiler<'a> {
+    decompiler: &'a Decompiler<'a>,
+    code: &'a [Bytecode],
+    cfg: VMControlFlowGraph,
+    locals: &'a [SignatureToken],
+    names: &'a [String],
+    // the start and end offsets of the loops around the code being decompiled, innermost last
+    loops: Vec<(CodeOffset, CodeOffset)>,
+    next_id: usize,
+    out: String,
+}
+
+impl<'a> FunctionDecompiler<'a> {
+    // Decompiles the code in `start..end` as a sequence of statements.
+    fn sequence(&mut self, start: CodeOffset, end: CodeOffset, indent: usize) -> Result<()> {
+        let mut pc = start;
+        while pc < end {
+            pc = self.statement(pc, end, indent)?;
+        }
+        Ok(())
+    }
+
+    // Decompiles the statement starting at `pc` and returns the offset after it.
+    fn statement(&mut self, pc: CodeOffset, end: CodeOffset, indent: usize) -> Result<CodeOffset> {
+        // A loop ends with a branch back to its start. A branch back to the start of the
+        // innermost loop may also be a `continue`, so the candidate loops are tried from the
+        // outermost one on until one decompiles.
+        let code = self.code;
+        let latches = (pc..end)
+            .rev()
+            .filter(|offset| code[*offset as usize] == Bytecode::Branch(pc))
+            .collect::<Vec<_>>();
+        for latch in latches {
+            let (out_len, loop_depth) = (self.out.len(), self.loops.len());
+            if self.loop_(pc, latch, indent).is_ok() {
+                return Ok(latch + 1);
+            }
+            self.out.truncate(out_len);
+            self.loops.truncate(loop_depth);
+        }
+
+        let (exit, cond) = self.basic_block(pc, end, indent)?;
+        let next = exit + 1;
+        match (&code[exit as usize], cond) {
+            (Bytecode::BrFalse(target), Some(cond)) => {
+                self.if_(&cond.exp.text, *target, next, end, indent)
+            }
+            (Bytecode::BrTrue(target), Some(cond)) => self.if_(
+                &format!("!{}", cond.exp.operand()),
+                *target,
+                next,
+                end,
+                indent,
+            ),
+            (Bytecode::Branch(target), _) => {
+                match self.loops.last() {
+                    Some((start, _)) if start == target => line(&mut self.out, indent, "continue;"),
+                    Some((_, loop_end)) if loop_end == target => {
+                        line(&mut self.out, indent, "break;")
+                    }
+                    _ => bail!("cannot decompile branch at offset {}", exit),
+                }
+                Ok(next)
+            }
+            _ => Ok(next),
+        }
+    }
+
+    // Decompiles a loop from `start` to the branch back to it at `latch`.
+    fn loop_(&mut self, start: CodeOffset, latch: CodeOffset, indent: usize) -> Result<()> {
+        let loop_end = latch + 1;
+        self.loops.push((start, loop_end));
+
+        // A `while` is a loop whose first block is only its condition, leaving the loop when it
+        // is false.
+        let header_exit = self
+            .cfg
+            .block_of_id(start)
+            .ok_or_else(|| format_err!("no basic block at offset {}", start))?
+            .exit;
+        if header_exit < latch && self.code[header_exit as usize] == Bytecode::BrFalse(loop_end) {
+            let out_len = self.out.len();
+            let (_, cond) = self.basic_block(start, latch, indent)?;
+            match cond {
+                Some(cond) if self.out.len() == out_len => {
+                    line(
+                        &mut self.out,
+                        indent,
+                        &format!("while ({}) {{", cond.exp.text),
+                    );
+                    self.sequence(header_exit + 1, latch, indent + 1)?;
+                    line(&mut self.out, indent, "}");
+                    self.loops.pop();
+                    return Ok(());
+                }
+                _ => self.out.truncate(out_len),
+            }
+        }
+
+        line(&mut self.out, indent, "loop {");
+        self.sequence(start, latch, indent + 1)?;
+        line(&mut self.out, indent, "}");
+        self.loops.pop();
+        Ok(())
+    }
+
+    // Decompiles an `if` on `cond` whose block starts at `start`, and which branches to `target`
+    // when `cond` is false. Returns the offset after it.
+    fn if_(
+        &mut self,
+        cond: &str,
+        target: CodeOffset,
+        start: CodeOffset,
+        end: CodeOffset,
+        indent: usize,
+    ) -> Result<CodeOffset> {
+        if target < start || target > end {
+            bail!("cannot decompile branch at offset {}", start - 1);
+        }
+        // With an `else`, the `if` block ends with a branch over the `else` block.
+        let else_end = match &self.code[target as usize - 1] {
+            Bytecode::Branch(else_end)
+                if target > start && *else_end >= target && *else_end <= end =>
+            {
+                Some(*else_end)
+            }
+            _ => None,
+        };
+        line(&mut self.out, indent, &format!("if ({}) {{", cond));
+        match else_end {
+            Some(else_end) => {
+                self.sequence(start, target - 1, indent + 1)?;
+                line(&mut self.out, indent, "} else {");
+                self.sequence(target, else_end, indent + 1)?;
+                line(&mut self.out, indent, "}");
+                Ok(else_end)
+            }
+            None => {
+                self.sequence(start, target, indent + 1)?;
+                line(&mut self.out, indent, "}");
+                Ok(target)
+            }
+        }
+    }
+
+    // Decompiles the statements of the basic block starting at `entry`, stopping before `end`.
+    // Returns the offset of the last instruction decompiled and, if it is a conditional branch,
+    // its condition.
+    fn basic_block(
+        &mut self,
+        entry: CodeOffset,
+        end: CodeOffset,
+        indent: usize,
+    ) -> Result<(CodeOffset, Option<Slot>)> {
+        let exit = self
+            .cfg
+            .block_of_id(entry)
+            .ok_or_else(|| format_err!("no basic block at offset {}", entry))?
+            .exit
+            .min(end - 1);
+        let (module, code, names) = (self.decompiler.module, self.code, self.names);
+        let mut stack: Vec<Slot> = vec![];
+        let mut pc = entry;
+        while pc <= exit {
+            match &code[pc as usize] {
+                Bytecode::BrTrue(_) | Bytecode::BrFalse(_) => {
+                    let cond = self.pop(&mut stack, pc)?;
+                    self.check_empty(&stack, pc)?;
+                    return Ok((pc, Some(cond)));
+                }
+                Bytecode::Branch(_) => {
+                    self.check_empty(&stack, pc)?;
+                    return Ok((pc, None));
+                }
+                Bytecode::StLoc(_) => {
+                    // The stack holds the values of the right-hand side of an assignment, stored
+                    // into the locals on the left-hand side in reverse order.
+                    if stack.is_empty() {
+                        bail!("stack underflow at offset {}", pc);
+                    }
+                    let count = stack.len() as CodeOffset;
+                    let mut vars = vec![];
+                    for offset in pc..pc + count {
+                        match code.get(offset as usize) {
+                            Some(Bytecode::StLoc(idx)) if offset <= exit => {
+                                vars.push(names[*idx as usize].clone())
+                            }
+                            _ => bail!("cannot decompile assignment at offset {}", pc),
+                        }
+                    }
+                    vars.reverse();
+                    let unpack = stack[0].exp.unpack.clone();
+                    let text = match unpack {
+                        Some((name, fields)) => {
+                            if stack.iter().any(|slot| slot.id != stack[0].id) {
+                                bail!("cannot decompile assignment at offset {}", pc);
+                            }
+                            let bindings = fields
+                                .iter()
+                                .zip(&vars)
+                                .map(|(field, var)| format!("{}: {}", field, var))
+                                .collect::<Vec<_>>();
+                            format!(
+                                "{} {{ {} }} = {};",
+                                name,
+                                bindings.join(", "),
+                                stack[0].exp.text
+                            )
+                        }
+                        None => format!("{} = {};", vars.join(", "), self.join(&stack, pc)?),
+                    };
+                    line(&mut self.out, indent, &text);
+                    stack.clear();
+                    pc += count;
+                    continue;
+                }
+                Bytecode::Ret => {
+                    let values = self.join(&stack, pc)?;
+                    stack.clear();
+                    if values.is_empty() {
+                        line(&mut self.out, indent, "return;");
+                    } else {
+                        line(&mut self.out, indent, &format!("return {};", values));
+                    }
+                }
+                Bytecode::Abort => {
+                    let code = self.pop(&mut stack, pc)?;
+                    self.statement_exp(&stack, pc, indent, format!("abort {}", code.exp.text))?;
+                }
+                Bytecode::Pop => bail!("`Pop` at offset {} cannot be expressed in Move IR", pc),
+                Bytecode::LdStr(_) => {
+                    bail!(
+                        "string literals cannot be expressed in Move IR, at offset {}",
+                        pc
+                    )
+                }
+                Bytecode::LdConst(value) => self.push(&mut stack, Exp::atom(value.to_string())),
+                Bytecode::LdU8(value) => self.push(&mut stack, Exp::atom(format!("{}u8", value))),
+                Bytecode::LdU128(value) => {
+                    self.push(&mut stack, Exp::atom(format!("{}u128", value)))
+                }
+                Bytecode::LdByteArray(idx) => self.push(
+                    &mut stack,
+                    Exp::atom(format!(
+                        "h\"{}\"",
+                        hex::encode(module.byte_array_at(*idx).as_bytes())
+                    )),
+                ),
+                Bytecode::LdAddr(idx) => self.push(
+                    &mut stack,
+                    Exp::atom(address_literal(module.address_at(*idx))),
+                ),
+                Bytecode::LdTrue => self.push(&mut stack, Exp::atom("true".to_string())),
+                Bytecode::LdFalse => self.push(&mut stack, Exp::atom("false".to_string())),
+                Bytecode::CopyLoc(idx) | Bytecode::MoveLoc(idx) => {
+                    let instruction = match &code[pc as usize] {
+                        Bytecode::CopyLoc(_) => "copy",
+                        _ => "move",
+                    };
+                    let exp = Exp::atom(format!("{}({})", instruction, names[*idx as usize]));
+                    let mutable = match self.locals.get(*idx as usize) {
+                        Some(SignatureToken::MutableReference(_)) => true,
+                        _ => false,
+                    };
+                    self.push_values(&mut stack, exp, vec![mutable]);
+                }
+                Bytecode::Call(idx, type_actuals) => {
+                    let handle = module.function_handle_at(*idx);
+                    let signature = module.function_signature_at(handle.signature);
+                    let args = self.pop_n(&mut stack, signature.arg_types.len(), pc)?;
+                    let text = format!(
+                        "{}.{}{}({})",
+                        self.decompiler.module_names[handle.module.0 as usize],
+                        module.string_at(handle.name),
+                        self.decompiler
+                            .type_actuals(&module.locals_signature_at(*type_actuals).0)?,
+                        self.join(&args, pc)?
+                    );
+                    if signature.return_types.is_empty() {
+                        self.statement_exp(&stack, pc, indent, text)?;
+                    } else {
+                        let mutable = signature
+                            .return_types
+                            .iter()
+                            .map(|token| match token {
+                                SignatureToken::MutableReference(_) => true,
+                                _ => false,
+                            })
+                            .collect();
+                        self.push_values(&mut stack, Exp::atom(text), mutable);
+                    }
+                }
+                Bytecode::Pack(idx, type_actuals) => {
+                    let name = self.decompiler.struct_name(*idx, *type_actuals)?;
+                    let fields = self.decompiler.field_names(*idx)?;
+                    let values = self.pop_n(&mut stack, fields.len(), pc)?;
+                    if values.iter().any(|slot| slot.exp.values != 1) {
+                        bail!("cannot decompile `Pack` at offset {}", pc);
+                    }
+                    let text = if fields.is_empty() {
+                        format!("{} {{}}", name)
+                    } else {
+                        let fields = fields
+                            .iter()
+                            .zip(&values)
+                            .map(|(field, value)| format!("{}: {}", field, value.exp.text))
+                            .collect::<Vec<_>>();
+                        format!("{} {{ {} }}", name, fields.join(", "))
+                    };
+                    self.push(&mut stack, Exp::atom(text));
+                }
+                Bytecode::Unpack(idx, type_actuals) => {
+                    let name = self.decompiler.struct_name(*idx, *type_actuals)?;
+                    let fields = self.decompiler.field_names(*idx)?;
+                    let value = self.pop(&mut stack, pc)?;
+                    if fields.is_empty() {
+                        let text = format!("{} {{}} = {}", name, value.exp.text);
+                        self.statement_exp(&stack, pc, indent, text)?;
+                    } else {
+                        let exp = Exp {
+                            values: fields.len(),
+                            unpack: Some((name, fields)),
+                            ..Exp::atom(value.exp.text.clone())
+                        };
+                        let mutable = vec![false; exp.values];
+                        self.push_values(&mut stack, exp, mutable);
+                    }
+                }
+                Bytecode::ReadRef => {
+                    let reference = self.pop(&mut stack, pc)?;
+                    let text = format!("*{}", reference.exp.operand());
+                    self.push(&mut stack, Exp::compound(text));
+                }
+                Bytecode::WriteRef => {
+                    let reference = self.pop(&mut stack, pc)?;
+                    let value = self.pop(&mut stack, pc)?;
+                    let text = format!("*{} = {}", reference.exp.operand(), value.exp.text);
+                    self.statement_exp(&stack, pc, indent, text)?;
+                }
+                Bytecode::ReleaseRef => {
+                    let reference = self.pop(&mut stack, pc)?;
+                    let text = format!("release({})", reference.exp.text);
+                    self.statement_exp(&stack, pc, indent, text)?;
+                }
+                Bytecode::FreezeRef => {
+                    let reference = self.pop(&mut stack, pc)?;
+                    let exp = match &reference.exp.frozen {
+                        Some(frozen) => Exp::compound(frozen.clone()),
+                        None => Exp::atom(format!("freeze({})", reference.exp.text)),
+                    };
+                    self.push(&mut stack, exp);
+                }
+                Bytecode::BorrowLoc(idx) => {
+                    let name = &names[*idx as usize];
+                    let exp = Exp {
+                        frozen: Some(format!("&{}", name)),
+                        ..Exp::compound(format!("&mut {}", name))
+                    };
+                    self.push_values(&mut stack, exp, vec![true]);
+                }
+                Bytecode::BorrowField(idx) => {
+                    let reference = self.pop(&mut stack, pc)?;
+                    let field = module.string_at(module.field_def_at(*idx).name);
+                    let borrowed = format!("{}.{}", reference.exp.operand(), field);
+                    if reference.mutable {
+                        let exp = Exp {
+                            frozen: Some(format!("&{}", borrowed)),
+                            ..Exp::compound(format!("&mut {}", borrowed))
+                        };
+                        self.push_values(&mut stack, exp, vec![true]);
+                    } else {
+                        self.push(&mut stack, Exp::compound(format!("&{}", borrowed)));
+                    }
+                }
+                Bytecode::BorrowGlobal(idx, type_actuals) => {
+                    let address = self.pop(&mut stack, pc)?;
+                    let text = format!(
+                        "borrow_global<{}>({})",
+                        self.decompiler.struct_name(*idx, *type_actuals)?,
+                        address.exp.text
+                    );
+                    self.push_values(&mut stack, Exp::atom(text), vec![true]);
+                }
+                Bytecode::Exists(idx, type_actuals) | Bytecode::MoveFrom(idx, type_actuals) => {
+                    let builtin = match &code[pc as usize] {
+                        Bytecode::Exists(..) => "exists",
+                        _ => "move_from",
+                    };
+                    let address = self.pop(&mut stack, pc)?;
+                    let text = format!(
+                        "{}<{}>({})",
+                        builtin,
+                        self.decompiler.struct_name(*idx, *type_actuals)?,
+                        address.exp.text
+                    );
+                    self.push(&mut stack, Exp::atom(text));
+                }
+                Bytecode::MoveToSender(idx, type_actuals) => {
+                    let value = self.pop(&mut stack, pc)?;
+                    let text = format!(
+                        "move_to_sender<{}>({})",
+                        self.decompiler.struct_name(*idx, *type_actuals)?,
+                        value.exp.text
+                    );
+                    self.statement_exp(&stack, pc, indent, text)?;
+                }
+                Bytecode::CreateAccount => {
+                    let address = self.pop(&mut stack, pc)?;
+                    let text = format!("create_account({})", address.exp.text);
+                    self.statement_exp(&stack, pc, indent, text)?;
+                }
+                Bytecode::EmitEvent => {
+                    let args = self.pop_n(&mut stack, 3, pc)?;
+                    let text = format!("emit_event({})", self.join(&args, pc)?);
+                    self.statement_exp(&stack, pc, indent, text)?;
+                }
+                Bytecode::Not => {
+                    let value = self.pop(&mut stack, pc)?;
+                    let text = format!("!{}", value.exp.operand());
+                    self.push(&mut stack, Exp::compound(text));
+                }
+                Bytecode::CastU8 | Bytecode::CastU64 | Bytecode::CastU128 => {
+                    let value = self.pop(&mut stack, pc)?;
+                    let builtin = match &code[pc as usize] {
+                        Bytecode::CastU8 => "to_u8",
+                        Bytecode::CastU64 => "to_u64",
+                        _ => "to_u128",
+                    };
+                    let text = format!("{}({})", builtin, value.exp.text);
+                    self.push(&mut stack, Exp::atom(text));
+                }
+                Bytecode::Add
+                | Bytecode::Sub
+                | Bytecode::Mul
+                | Bytecode::Mod
+                | Bytecode::Div
+                | Bytecode::BitOr
+                | Bytecode::BitAnd
+                | Bytecode::Xor
+                | Bytecode::Shl
+                | Bytecode::Shr
+                | Bytecode::Or
+                | Bytecode::And
+                | Bytecode::Eq
+                | Bytecode::Neq
+                | Bytecode::Lt
+                | Bytecode::Gt
+                | Bytecode::Le
+                | Bytecode::Ge => {
+                    let rhs = self.pop(&mut stack, pc)?;
+                    let lhs = self.pop(&mut stack, pc)?;
+                    let text = format!(
+                        "{} {} {}",
+                        lhs.exp.operand(),
+                        binop(&code[pc as usize]),
+                        rhs.exp.operand()
+                    );
+                    self.push(&mut stack, Exp::compound(text));
+                }
+                Bytecode::GetTxnGasUnitPrice => self.push(
+                    &mut stack,
+                    Exp::atom("get_txn_gas_unit_price()".to_string()),
+                ),
+                Bytecode::GetTxnMaxGasUnits => {
+                    self.push(&mut stack, Exp::atom("get_txn_max_gas_units()".to_string()))
+                }
+                Bytecode::GetGasRemaining => {
+                    self.push(&mut stack, Exp::atom("get_gas_remaining()".to_string()))
+                }
+                Bytecode::GetTxnSenderAddress => {
+                    self.push(&mut stack, Exp::atom("get_txn_sender()".to_string()))
+                }
+                Bytecode::GetTxnSequenceNumber => self.push(
+                    &mut stack,
+                    Exp::atom("get_txn_sequence_number()".to_string()),
+                ),
+                Bytecode::GetTxnPublicKey => {
+                    self.push(&mut stack, Exp::atom("get_txn_public_key()".to_string()))
+                }
+            }
+            pc += 1;
+        }
+        self.check_empty(&stack, exit)?;
+        Ok((exit, None))
+    }
+
+    fn push(&mut self, stack: &mut Vec<Slot>, exp: Exp) {
+        self.push_values(stack, exp, vec![false]);
+    }
+
+    // Pushes the values of `exp`, which are mutable references as given by `mutable`.
+    fn push_values(&mut self, stack: &mut Vec<Slot>, exp: Exp, mutable: Vec<bool>) {
+        let exp = Rc::new(Exp {
+            values: mutable.len(),
+            ..exp
+        });
+        for mutable in mutable {
+            stack.push(Slot {
+                exp: exp.clone(),
+                id: self.next_id,
+                mutable,
+            });
+        }
+        self.next_id += 1;
+    }
+
+    // Pops a value computed by an expression of its own.
+    fn pop(&self, stack: &mut Vec<Slot>, pc: CodeOffset) -> Result<Slot> {
+        match stack.pop() {
+            Some(slot) if slot.exp.values == 1 && slot.exp.unpack.is_none() => Ok(slot),
+            Some(_) => bail!("cannot decompile the operand at offset {}", pc),
+            None => bail!("stack underflow at offset {}", pc),
+        }
+    }
+
+    fn pop_n(&self, stack: &mut Vec<Slot>, count: usize, pc: CodeOffset) -> Result<Vec<Slot>> {
+        if stack.len() < count {
+            bail!("stack underflow at offset {}", pc);
+        }
+        Ok(stack.split_off(stack.len() - count))
+    }
+
+    // Joins the expressions computing `slots` with commas. An expression computing several
+    // values must compute them all.
+    fn join(&self, slots: &[Slot], pc: CodeOffset) -> Result<String> {
+        let mut exps = vec![];
+        let mut idx = 0;
+        while idx < slots.len() {
+            let slot = &slots[idx];
+            let values = slot.exp.values;
+            if slot.exp.unpack.is_some()
+                || idx + values > slots.len()
+                || slots[idx..idx + values].iter().any(|s| s.id != slot.id)
+            {
+                bail!("cannot decompile the operands at offset {}", pc);
+            }
+            exps.push(slot.exp.text.clone());
+            idx += values;
+        }
+        Ok(exps.join(", "))
+    }
+
+    // Writes `text` as a statement, which requires that no values be left on the stack.
+    fn statement_exp(
+        &mut self,
+        stack: &[Slot],
+        pc: CodeOffset,
+        indent: usize,
+        text: String,
+    ) -> Result<()> {
+        self.check_empty(stack, pc)?;
+        line(&mut self.out, indent, &format!("{};", text));
+        Ok(())
+    }
+
+    fn check_empty(&self, stack: &[Slot], pc: CodeOffset) -> Result<()> {
+        if !stack.is_empty() {
+            bail!(
+                "cannot decompile the values left on the stack at offset {}",
+                pc
+            );
+        }
+        Ok(())
+    }
+}
+
+fn binop(bytecode: &Bytecode) -> &'static str {
+    match bytecode {
+        Bytecode::Add => "+",
+        Bytecode::Sub => "-",
+        Bytecode::Mul => "*",
+        Bytecode::Mod => "%",
+        Bytecode::Div => "/",
+        Bytecode::BitOr => "|",
+        Bytecode::BitAnd => "&",
+        Bytecode::Xor => "^",
+        Bytecode::Shl => "<<",
+        Bytecode::Shr => ">>",
+        Bytecode::Or => "||",
+        Bytecode::And => "&&",
+        Bytecode::Eq => "==",
+        Bytecode::Neq => "!=",
+        Bytecode::Lt => "<",
+        Bytecode::Gt => ">",
+        Bytecode::Le => "<=",
+        Bytecode::Ge => ">=",
+        _ => unreachable!("not a binary operator: {:?}", bytecode),
+    }
+}
@@ -1,10 +1,21 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod abi;
+pub mod decompiler;
+pub mod project;
 pub mod util;
 
+#[cfg(test)]
+mod unit_tests;
+
 use failure::prelude::*;
-use ir_to_bytecode::{compiler::compile_program, parser::parse_program};
+use ir_to_bytecode::{
+    compiler::{compile_program, compile_program_with_spans},
+    parser::{ast, parse_program},
+    source_map::ProgramSourceMap,
+    specs,
+};
 use std::mem;
 use stdlib::stdlib_modules;
 use vm::{
@@ -29,6 +40,9 @@ pub struct Compiler<'a> {
     pub stdlib_address: AccountAddress,
     /// Extra dependencies to compile with.
     pub extra_deps: Vec<VerifiedModule>,
+    /// Compile the specifications of functions into runtime checks, see
+    /// `ir_to_bytecode::specs`.
+    pub check_specs: bool,
 
     // The typical way this should be used is with functional record update syntax:
     //
@@ -55,6 +69,20 @@ impl<'a> Compiler<'a> {
         self.compile_impl()
     }
 
+    /// Compiles into a `CompiledProgram` and also returns the dependencies and the source map of
+    /// the program, which refers to the code as being in `file`.
+    pub fn into_compiled_program_deps_and_source_map(
+        mut self,
+        file: &str,
+    ) -> Result<(CompiledProgram, Vec<VerifiedModule>, ProgramSourceMap)> {
+        let parsed_program = self.parse()?;
+        let deps = self.deps();
+        let (compiled_program, spans) =
+            compile_program_with_spans(&self.address, &parsed_program, &deps)?;
+        let source_map = ProgramSourceMap::new(file, self.code, &spans);
+        Ok((compiled_program, deps, source_map))
+    }
+
     /// Compiles the script into a serialized form.
     pub fn into_script_blob(mut self) -> Result<Vec<u8>> {
         let compiled_program = self.compile_impl()?.0;
@@ -80,12 +108,20 @@ impl<'a> Compiler<'a> {
     }
 
     fn compile_impl(&mut self) -> Result<(CompiledProgram, Vec<VerifiedModule>)> {
-        let parsed_program = parse_program(self.code)?;
+        let parsed_program = self.parse()?;
         let deps = self.deps();
         let compiled_program = compile_program(&self.address, &parsed_program, &deps)?;
         Ok((compiled_program, deps))
     }
 
+    fn parse(&self) -> Result<ast::Program> {
+        let mut parsed_program = parse_program(self.code)?;
+        if self.check_specs {
+            specs::instrument_program(&mut parsed_program)?;
+        }
+        Ok(parsed_program)
+    }
+
     fn deps(&mut self) -> Vec<VerifiedModule> {
         let extra_deps = mem::replace(&mut self.extra_deps, vec![]);
         if self.skip_stdlib_deps {
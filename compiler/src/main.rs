@@ -1,10 +1,26 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use compiler::{util, Compiler};
-use ir_to_bytecode::parser::{parse_module, parse_script};
+use compiler::{
+    abi, decompiler,
+    project::{self, SourceFile},
+    util, Compiler,
+};
+use failure::prelude::*;
+use ir_to_bytecode::{
+    diagnostics,
+    errors::SourceError,
+    optimizer,
+    parser::{parse_module, parse_script},
+    source_map::{ProgramSourceMap, SourceMap},
+};
 use serde_json;
-use std::{convert::TryFrom, fs, io::Write, path::PathBuf};
+use std::{
+    convert::TryFrom,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 use stdlib::stdlib_modules;
 use structopt::StructOpt;
 use vm::{
@@ -12,7 +28,10 @@ use vm::{
         verifier::{verify_module_dependencies, VerifiedProgram},
         VerifiedModule,
     },
-    def::{errors::VerificationError, file_format::CompiledModule},
+    def::{
+        file_format::{CompiledModule, CompiledProgram, CompiledScript},
+        printers::{ModuleListing, ScriptListing},
+    },
     types::{transaction::Program, AccessPath, AccountAddress},
 };
 
@@ -20,10 +39,12 @@ use vm::{
 #[structopt(
     name = "IR Compiler",
     author = "Libra",
-    about = "Move IR to bytecode compiler."
+    about = "Move IR to bytecode compiler.",
+    raw(setting = "structopt::clap::AppSettings::SubcommandsNegateReqs")
 )]
 struct Args {
-    /// Serialize and write the compiled output to this file
+    /// Serialize and write the compiled output to this file, and its source map to the same path
+    /// with a `.map` suffix
     #[structopt(short = "o", long = "output")]
     pub output_path: Option<String>,
     /// Treat input file as a module (default is to treat file as a program)
@@ -39,34 +60,116 @@ struct Args {
     #[structopt(long = "no-verify")]
     pub no_verify: bool,
     /// Path to the Move IR source to compile
-    #[structopt(parse(from_os_str))]
-    pub source_path: PathBuf,
+    #[structopt(parse(from_os_str), raw(required = "true"))]
+    pub source_path: Option<PathBuf>,
     /// Instead of compiling the source, emit a dependency list of the compiled source
     #[structopt(short = "-l", long = "list_dependencies")]
     pub list_dependencies: bool,
     /// Path to the list of modules that we want to link with
     #[structopt(long = "deps")]
     pub deps_path: Option<String>,
+    /// Print a disassembly listing instead of the raw tables when no output file is given
+    #[structopt(short = "d", long = "disassemble")]
+    pub disassemble: bool,
+    /// Emit the JSON ABI of the module, or of the `main` function of the script: to the output
+    /// path with a `.abi` suffix, or instead of the raw tables when no output file is given
+    #[structopt(long = "abi")]
+    pub abi: bool,
+    /// Decompile the input file to Move IR instead of compiling it: a serialized module with
+    /// `-m`, or a program as written by `-o` otherwise. Locals are named after the source map
+    /// next to the input file, if any
+    #[structopt(long = "decompile")]
+    pub decompile: bool,
+    /// Optimize the compiled code to spend less gas, before it is verified
+    #[structopt(short = "O", long = "optimize")]
+    pub optimize: bool,
+    /// Compile the `requires`, `ensures`, `verify` and `assume` specifications into runtime checks
+    /// that abort with a dedicated code when they do not hold
+    #[structopt(long = "check-specs")]
+    pub check_specs: bool,
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Builds every Move IR file of a directory, compiling modules after the modules they import
+    #[structopt(name = "build")]
+    Build(BuildArgs),
+}
+
+#[derive(Debug, StructOpt)]
+struct BuildArgs {
+    /// Write the compiled modules and scripts to this directory, rather than to a `build`
+    /// directory inside the project: each module to `<module name>.mv` and each script to
+    /// `<file name>.program`, each with its source map alongside
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    pub output_dir: Option<PathBuf>,
+    /// Account address the modules are published under
+    #[structopt(short = "a", long = "address")]
+    pub address: Option<String>,
+    /// Do not automatically compile stdlib dependencies
+    #[structopt(long = "no-stdlib")]
+    pub no_stdlib: bool,
+    /// Path to the list of modules, besides those of the project, that we want to link with
+    #[structopt(long = "deps")]
+    pub deps_path: Option<String>,
+    /// Path to the directory with the Move IR files of the project
+    #[structopt(parse(from_os_str))]
+    pub project_dir: PathBuf,
+}
+
+/// The text of a source file and the name to refer to it by in diagnostics.
+struct Source {
+    file: String,
+    text: String,
 }
 
-fn print_errors_and_exit(verification_errors: &[VerificationError]) -> ! {
-    println!("Verification failed. Errors below:");
-    for e in verification_errors {
-        println!("{:?}", e);
+impl Source {
+    fn read(path: &Path) -> Self {
+        Source {
+            file: path.to_string_lossy().into_owned(),
+            text: fs::read_to_string(path).expect("Unable to read file"),
+        }
+    }
+
+    fn print_errors_and_exit(&self, errors: &[SourceError]) -> ! {
+        print!("{}", diagnostics::render(&self.file, &self.text, errors));
+        std::process::exit(1);
+    }
+
+    fn unwrap_or_exit<T>(&self, result: Result<T>) -> T {
+        result.unwrap_or_else(|err| {
+            print!(
+                "{}",
+                diagnostics::render_error(&self.file, &self.text, &err)
+            );
+            std::process::exit(1);
+        })
     }
-    std::process::exit(1);
 }
 
-fn do_verify_module(module: CompiledModule, dependencies: &[VerifiedModule]) -> VerifiedModule {
-    let verified_module = match VerifiedModule::new(module) {
-        Ok(module) => module,
-        Err((_, errors)) => print_errors_and_exit(&errors),
+fn do_verify_module(
+    module: CompiledModule,
+    dependencies: &[VerifiedModule],
+    source: &Source,
+    source_map: &SourceMap,
+) -> VerifiedModule {
+    let errors = match VerifiedModule::new(module) {
+        Ok(module) => {
+            let (verified_module, errors) = verify_module_dependencies(module, dependencies);
+            if errors.is_empty() {
+                return verified_module;
+            }
+            errors
+        }
+        Err((_, errors)) => errors,
     };
-    let (verified_module, errors) = verify_module_dependencies(verified_module, dependencies);
-    if !errors.is_empty() {
-        print_errors_and_exit(&errors);
-    }
-    verified_module
+    source.print_errors_and_exit(&diagnostics::verification_errors(
+        &source.text,
+        source_map,
+        &errors,
+    ))
 }
 
 fn write_output(path: &str, buf: &[u8]) {
@@ -76,8 +179,170 @@ fn write_output(path: &str, buf: &[u8]) {
         .unwrap_or_else(|err| panic!("Unable to write to output file {}: {}", path, err));
 }
 
+fn write_source_map<T: serde::Serialize>(path: &str, source_map: &T) {
+    let source_map = serde_json::to_vec(source_map).expect("Unable to serialize source map");
+    write_output(&format!("{}.map", path), &source_map);
+}
+
+fn write_abi<T: serde::Serialize>(path: &str, abi: &T) {
+    let abi = serde_json::to_vec_pretty(abi).expect("Unable to serialize ABI");
+    write_output(&format!("{}.abi", path), &abi);
+}
+
+fn print_abi<T: serde::Serialize>(abi: &T) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(abi).expect("Unable to serialize ABI")
+    );
+}
+
+fn read_source_map<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    let source_map = fs::read(format!("{}.map", path.display())).ok()?;
+    serde_json::from_slice(&source_map).ok()
+}
+
+fn decompile(path: &Path, module_input: bool) {
+    let blob = fs::read(path).expect("Unable to read file");
+    let source = if module_input {
+        let module = CompiledModule::deserialize(&blob).expect("Unable to deserialize module");
+        let source_map: Option<SourceMap> = read_source_map(path);
+        decompiler::decompile_module(&module, source_map.as_ref())
+    } else {
+        let program: Program =
+            serde_json::from_slice(&blob).expect("Unable to deserialize program");
+        let source_map: Option<ProgramSourceMap> = read_source_map(path);
+        let modules = program
+            .modules()
+            .iter()
+            .enumerate()
+            .map(|(idx, module)| {
+                let module =
+                    CompiledModule::deserialize(module).expect("Unable to deserialize module");
+                let source_map = source_map.as_ref().and_then(|map| map.modules.get(idx));
+                decompiler::decompile_module(&module, source_map)
+            })
+            .collect::<Result<Vec<_>>>();
+        let script =
+            CompiledScript::deserialize(program.code()).expect("Unable to deserialize script");
+        let script_source_map = source_map.as_ref().map(|map| &map.script);
+        modules.and_then(|modules| {
+            let script = decompiler::decompile_script(&script, script_source_map)?;
+            Ok(if modules.is_empty() {
+                script
+            } else {
+                format!("modules:\n{}script:\n{}", modules.concat(), script)
+            })
+        })
+    };
+    match source {
+        Ok(source) => print!("{}", source),
+        Err(err) => {
+            eprintln!("Unable to decompile {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn read_deps(deps_path: Option<String>, no_stdlib: bool) -> Vec<VerifiedModule> {
+    if let Some(path) = deps_path {
+        let deps = fs::read_to_string(path).expect("Unable to read dependency file");
+        let deps_list: Vec<Vec<u8>> =
+            serde_json::from_str(deps.as_str()).expect("Unable to parse dependency file");
+        deps_list
+            .into_iter()
+            .map(|module_bytes| {
+                VerifiedModule::new(
+                    CompiledModule::deserialize(module_bytes.as_slice())
+                        .expect("Downloaded module blob can't be deserialized"),
+                )
+                .expect("Downloaded module blob failed verifier")
+            })
+            .collect()
+    } else if no_stdlib {
+        vec![]
+    } else {
+        stdlib_modules().to_vec()
+    }
+}
+
+fn serialize_program(compiled_program: &CompiledProgram) -> Vec<u8> {
+    let mut script = vec![];
+    compiled_program
+        .script
+        .serialize(&mut script)
+        .expect("Unable to serialize script");
+    let mut modules = vec![];
+    for m in compiled_program.modules.iter() {
+        let mut buf = vec![];
+        m.serialize(&mut buf).expect("Unable to serialize module");
+        modules.push(buf);
+    }
+    let program = Program::new(script, modules, vec![]);
+    serde_json::to_vec(&program).expect("Unable to serialize program")
+}
+
+fn build(args: BuildArgs) {
+    let address = args
+        .address
+        .map(|a| AccountAddress::try_from(a).unwrap())
+        .unwrap_or_else(AccountAddress::default);
+    let deps = read_deps(args.deps_path, args.no_stdlib);
+    let sources = SourceFile::read_dir(&args.project_dir).unwrap_or_else(|err| {
+        panic!(
+            "Unable to read project directory {}: {}",
+            args.project_dir.display(),
+            err
+        )
+    });
+    let project = project::build(&sources, &address, &deps).unwrap_or_else(|err| {
+        print!("{}", err);
+        std::process::exit(1);
+    });
+
+    let output_dir = args
+        .output_dir
+        .unwrap_or_else(|| args.project_dir.join("build"));
+    fs::create_dir_all(&output_dir).unwrap_or_else(|err| {
+        panic!(
+            "Unable to create output directory {}: {}",
+            output_dir.display(),
+            err
+        )
+    });
+    for built in &project.modules {
+        let module = built.module.as_inner();
+        let path = output_dir.join(format!("{}.mv", module.self_id().name()));
+        let path = path.to_string_lossy();
+        let mut out = vec![];
+        module
+            .serialize(&mut out)
+            .expect("Unable to serialize module");
+        write_output(&path, &out);
+        write_source_map(&path, &built.source_map);
+        println!("{} -> {}", built.file, path);
+    }
+    for built in &project.scripts {
+        let path = output_dir.join(format!("{}.program", project::script_name(&built.file)));
+        let path = path.to_string_lossy();
+        write_output(&path, &serialize_program(&built.program));
+        write_source_map(&path, &built.source_map);
+        println!("{} -> {}", built.file, path);
+    }
+}
+
 fn main() {
     let args = Args::from_args();
+    let source_path = match args.command {
+        Some(Command::Build(build_args)) => return build(build_args),
+        None => args
+            .source_path
+            .expect("The source path is required without a subcommand"),
+    };
+
+    if args.decompile {
+        decompile(&source_path, args.module_input);
+        return;
+    }
 
     let address = args
         .address
@@ -85,12 +350,12 @@ fn main() {
         .unwrap_or_else(AccountAddress::default);
 
     if args.list_dependencies {
-        let source = fs::read_to_string(args.source_path).expect("Unable to read file");
+        let source = Source::read(&source_path);
         let dependency_list: Vec<AccessPath> = if args.module_input {
-            let module = parse_module(&source).expect("Unable to parse module");
+            let module = source.unwrap_or_exit(parse_module(&source.text));
             module.get_external_deps()
         } else {
-            let script = parse_script(&source).expect("Unable to parse module");
+            let script = source.unwrap_or_exit(parse_script(&source.text));
             script.get_external_deps()
         }
         .into_iter()
@@ -110,69 +375,59 @@ fn main() {
         return;
     }
 
-    let deps = {
-        if let Some(path) = args.deps_path {
-            let deps = fs::read_to_string(path).expect("Unable to read dependency file");
-            let deps_list: Vec<Vec<u8>> =
-                serde_json::from_str(deps.as_str()).expect("Unable to parse dependency file");
-            deps_list
-                .into_iter()
-                .map(|module_bytes| {
-                    VerifiedModule::new(
-                        CompiledModule::deserialize(module_bytes.as_slice())
-                            .expect("Downloaded module blob can't be deserialized"),
-                    )
-                    .expect("Downloaded module blob failed verifier")
-                })
-                .collect()
-        } else if args.no_stdlib {
-            vec![]
-        } else {
-            stdlib_modules().to_vec()
-        }
-    };
+    let deps = read_deps(args.deps_path, args.no_stdlib);
 
     println!("verify {}", args.no_verify);
 
     if !args.module_input {
         println!("compile script##");
-        let source = fs::read_to_string(args.source_path).expect("Unable to read file");
+        let source = Source::read(&source_path);
         let compiler = Compiler {
             address,
-            code: &source,
+            code: &source.text,
             skip_stdlib_deps: args.no_stdlib,
             extra_deps: deps,
+            check_specs: args.check_specs,
             ..Compiler::default()
         };
-        let (compiled_program, dependencies) = compiler
-            .into_compiled_program_and_deps()
-            .expect("Failed to compile program");
-
-        let compiled_program = if !args.no_verify {
-            let verified_program = VerifiedProgram::new(compiled_program, &dependencies)
-                .expect("Failed to verify program");
-            verified_program.into_inner()
+        let (compiled_program, dependencies, mut source_map) =
+            source.unwrap_or_exit(compiler.into_compiled_program_deps_and_source_map(&source.file));
+        let compiled_program = if args.optimize {
+            source.unwrap_or_exit(optimizer::optimize_program(
+                compiled_program,
+                &mut source_map.modules,
+                &mut source_map.script,
+            ))
         } else {
             compiled_program
         };
 
+        let compiled_program =
+            if !args.no_verify {
+                match VerifiedProgram::new(compiled_program, &dependencies) {
+                    Ok(verified_program) => verified_program.into_inner(),
+                    Err(statuses) => source.print_errors_and_exit(
+                        &diagnostics::verification_statuses(&source.text, &source_map, &statuses),
+                    ),
+                }
+            } else {
+                compiled_program
+            };
+
         match args.output_path {
             Some(path) => {
-                let mut script = vec![];
-                compiled_program
-                    .script
-                    .serialize(&mut script)
-                    .expect("Unable to serialize script");
-                let mut modules = vec![];
-                for m in compiled_program.modules.iter() {
-                    let mut buf = vec![];
-                    m.serialize(&mut buf).expect("Unable to serialize module");
-                    modules.push(buf);
+                write_output(&path, &serialize_program(&compiled_program));
+                write_source_map(&path, &source_map);
+                if args.abi {
+                    write_abi(&path, &abi::script_abi(&compiled_program.script));
+                }
+            }
+            None if args.abi => print_abi(&abi::script_abi(&compiled_program.script)),
+            None if args.disassemble => {
+                for module in &compiled_program.modules {
+                    println!("{}", ModuleListing(module));
                 }
-                let program = Program::new(script, modules, vec![]);
-                let program_bytes =
-                    serde_json::to_vec(&program).expect("Unable to serialize program");
-                write_output(&path, &program_bytes);
+                println!("{}", ScriptListing(&compiled_program.script));
             }
             None => {
                 println!("{}", compiled_program);
@@ -180,9 +435,21 @@ fn main() {
         }
     } else {
         println!("compile module##");
-        let compiled_module = util::do_compile_module(&args.source_path, &address, &deps);
+        let source = Source::read(&source_path);
+        let compile = if args.check_specs {
+            util::compile_module_with_spec_checks
+        } else {
+            util::compile_module_with_source_map
+        };
+        let (compiled_module, mut source_map) =
+            source.unwrap_or_exit(compile(&source.file, &source.text, &address, &deps));
+        let compiled_module = if args.optimize {
+            source.unwrap_or_exit(optimizer::optimize_module(compiled_module, &mut source_map))
+        } else {
+            compiled_module
+        };
         let compiled_module = if !args.no_verify {
-            let verified_module = do_verify_module(compiled_module, &deps);
+            let verified_module = do_verify_module(compiled_module, &deps, &source, &source_map);
             verified_module.into_inner()
         } else {
             compiled_module
@@ -194,6 +461,14 @@ fn main() {
                     .serialize(&mut out)
                     .expect("Unable to serialize module");
                 write_output(&path, &out);
+                write_source_map(&path, &source_map);
+                if args.abi {
+                    write_abi(&path, &abi::module_abi(&compiled_module));
+                }
+            }
+            None if args.abi => print_abi(&abi::module_abi(&compiled_module)),
+            None if args.disassemble => {
+                println!("{}", ModuleListing(&compiled_module));
             }
             None => {
                 println!("{}", compiled_module);
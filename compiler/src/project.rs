@@ -0,0 +1,295 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Builds a project: every Move IR file of a directory, with the modules compiled in the order of
+//! their imports so that each one is linked and verified against the modules it uses.
+
+use failure::prelude::*;
+use ir_to_bytecode::{
+    compiler::{compile_module_with_spans, compile_program_with_spans},
+    diagnostics,
+    parser::{
+        ast::{ImportDefinition, ModuleDefinition, ModuleIdent},
+        lexer::{Lexer, Tok},
+        parse_module, parse_program,
+    },
+    source_map::{ProgramSourceMap, SourceMap},
+};
+use std::{collections::HashMap, fs, path::Path};
+use vm::{
+    bytecode_verifier::{
+        verifier::{verify_module_dependencies, VerifiedProgram},
+        VerifiedModule,
+    },
+    def::file_format::CompiledProgram,
+    types::{AccountAddress, ModuleId},
+};
+
+/// The extension of the Move IR files of a project.
+pub const SOURCE_EXTENSION: &str = "mvir";
+
+/// A Move IR file of a project.
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    /// The name to refer to the file by in diagnostics.
+    pub file: String,
+    /// The text of the file.
+    pub text: String,
+}
+
+impl SourceFile {
+    /// Reads the Move IR files directly in `dir`, sorted by path.
+    pub fn read_dir(dir: &Path) -> Result<Vec<SourceFile>> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file()
+                && path
+                    .extension()
+                    .map_or(false, |ext| ext == SOURCE_EXTENSION)
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| {
+                Ok(SourceFile {
+                    file: path.to_string_lossy().into_owned(),
+                    text: fs::read_to_string(&path)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the file is a module, rather than a script or a program with modules of its own.
+    fn is_module(&self) -> bool {
+        match Lexer::new(&self.text).next() {
+            Some(Ok((_, Tok::Module, _))) => true,
+            _ => false,
+        }
+    }
+
+    fn render_error(&self, error: &Error) -> Error {
+        format_err!(
+            "{}",
+            diagnostics::render_error(&self.file, &self.text, error)
+        )
+    }
+}
+
+/// A module of a project, compiled and verified.
+#[derive(Debug)]
+pub struct BuiltModule {
+    /// The file the module is defined in.
+    pub file: String,
+    /// The compiled module.
+    pub module: VerifiedModule,
+    /// The source map of the module.
+    pub source_map: SourceMap,
+}
+
+/// A script of a project, compiled and verified along with the modules it defines, if any.
+#[derive(Debug)]
+pub struct BuiltScript {
+    /// The file the script is defined in.
+    pub file: String,
+    /// The compiled program.
+    pub program: CompiledProgram,
+    /// The source map of the program.
+    pub source_map: ProgramSourceMap,
+}
+
+/// The result of building a project.
+#[derive(Debug)]
+pub struct BuiltProject {
+    /// The modules of the project, each after the modules it imports.
+    pub modules: Vec<BuiltModule>,
+    /// The scripts of the project, in the order of their files.
+    pub scripts: Vec<BuiltScript>,
+}
+
+/// Compiles and verifies the modules and scripts in `sources`, publishing the modules at
+/// `address`. Modules are compiled after the modules of the project they import, and may also
+/// import `deps`. Scripts may import any of them. Fails on the first file with errors, with the
+/// errors rendered against its source, or if modules import each other in a cycle.
+pub fn build(
+    sources: &[SourceFile],
+    address: &AccountAddress,
+    deps: &[VerifiedModule],
+) -> Result<BuiltProject> {
+    let mut modules = vec![];
+    let mut scripts = vec![];
+    for source in sources {
+        if source.is_module() {
+            let module = parse_module(&source.text).map_err(|err| source.render_error(&err))?;
+            modules.push((source, module));
+        } else {
+            let program = parse_program(&source.text).map_err(|err| source.render_error(&err))?;
+            scripts.push((source, program));
+        }
+    }
+
+    let mut built_modules: Vec<BuiltModule> = vec![];
+    for idx in build_order(address, &modules)? {
+        let (source, module) = &modules[idx];
+        let (compiled_module, spans) = {
+            let deps = deps.iter().chain(built_modules.iter().map(|m| &m.module));
+            compile_module_with_spans(address, module, deps)
+                .map_err(|err| source.render_error(&err))?
+        };
+        let source_map = SourceMap::new(&source.file, &source.text, &spans);
+        let errors = match VerifiedModule::new(compiled_module) {
+            Ok(module) => {
+                let deps = deps.iter().chain(built_modules.iter().map(|m| &m.module));
+                let (module, errors) = verify_module_dependencies(module, deps);
+                if errors.is_empty() {
+                    built_modules.push(BuiltModule {
+                        file: source.file.clone(),
+                        module,
+                        source_map,
+                    });
+                    continue;
+                }
+                errors
+            }
+            Err((_, errors)) => errors,
+        };
+        let errors = diagnostics::verification_errors(&source.text, &source_map, &errors);
+        bail!(
+            "{}",
+            diagnostics::render(&source.file, &source.text, &errors)
+        );
+    }
+
+    let mut built_scripts = vec![];
+    for (source, program) in &scripts {
+        let all_deps = || deps.iter().chain(built_modules.iter().map(|m| &m.module));
+        let (program, spans) = compile_program_with_spans(address, program, all_deps())
+            .map_err(|err| source.render_error(&err))?;
+        let source_map = ProgramSourceMap::new(&source.file, &source.text, &spans);
+        let program = match VerifiedProgram::new(program, all_deps()) {
+            Ok(program) => program.into_inner(),
+            Err(statuses) => {
+                let errors =
+                    diagnostics::verification_statuses(&source.text, &source_map, &statuses);
+                bail!(
+                    "{}",
+                    diagnostics::render(&source.file, &source.text, &errors)
+                );
+            }
+        };
+        built_scripts.push(BuiltScript {
+            file: source.file.clone(),
+            program,
+            source_map,
+        });
+    }
+
+    Ok(BuiltProject {
+        modules: built_modules,
+        scripts: built_scripts,
+    })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    Pending,
+    InProgress,
+    Done,
+}
+
+/// Orders `modules`, published at `address`, so that each comes after the modules of the project
+/// it imports. Modules are otherwise kept in the order they are given in.
+fn build_order(
+    address: &AccountAddress,
+    modules: &[(&SourceFile, ModuleDefinition)],
+) -> Result<Vec<usize>> {
+    let mut indices = HashMap::new();
+    for (idx, (source, module)) in modules.iter().enumerate() {
+        let id = ModuleId::new(*address, module.name.name());
+        if let Some(other) = indices.insert(id, idx) {
+            bail!(
+                "module `{}` is defined in both {} and {}",
+                module.name,
+                modules[other].0.file,
+                source.file
+            );
+        }
+    }
+    let imports = modules
+        .iter()
+        .map(|(_, module)| {
+            imported_modules(address, &module.imports)
+                .filter_map(|id| indices.get(&id).copied())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut order = vec![];
+    let mut visits = vec![Visit::Pending; modules.len()];
+    let mut path = vec![];
+    for idx in 0..modules.len() {
+        visit(idx, &imports, &mut visits, &mut path, &mut order).map_err(|cycle| {
+            let names = cycle
+                .iter()
+                .map(|idx| modules[*idx].1.name.name_ref())
+                .collect::<Vec<_>>();
+            format_err!(
+                "modules import each other in a cycle: {}",
+                names.join(" -> ")
+            )
+        })?;
+    }
+    Ok(order)
+}
+
+/// Visits the module at `idx` in depth-first order, appending it to `order` after the modules it
+/// imports. On finding a cycle, returns the modules in it, starting and ending with the same one.
+fn visit(
+    idx: usize,
+    imports: &[Vec<usize>],
+    visits: &mut [Visit],
+    path: &mut Vec<usize>,
+    order: &mut Vec<usize>,
+) -> std::result::Result<(), Vec<usize>> {
+    match visits[idx] {
+        Visit::Done => return Ok(()),
+        Visit::InProgress => {
+            let start = path.iter().position(|other| *other == idx).unwrap();
+            let mut cycle = path[start..].to_vec();
+            cycle.push(idx);
+            return Err(cycle);
+        }
+        Visit::Pending => (),
+    }
+    visits[idx] = Visit::InProgress;
+    path.push(idx);
+    for import in &imports[idx] {
+        visit(*import, imports, visits, path, order)?;
+    }
+    path.pop();
+    visits[idx] = Visit::Done;
+    order.push(idx);
+    Ok(())
+}
+
+/// The modules imported by `imports`, in order, where `Transaction` refers to `address`.
+fn imported_modules<'a>(
+    address: &'a AccountAddress,
+    imports: &'a [ImportDefinition],
+) -> impl Iterator<Item = ModuleId> + 'a {
+    imports.iter().map(move |import| match &import.ident {
+        ModuleIdent::Transaction(name) => ModuleId::new(*address, name.name()),
+        ModuleIdent::Qualified(id) => ModuleId::new(id.address, id.name.name()),
+    })
+}
+
+/// The name of the program a script is built into.
+pub fn script_name(file: &str) -> String {
+    Path::new(file).file_stem().map_or_else(
+        || file.to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    )
+}
@@ -0,0 +1,135 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    abi::{
+        decode_resource, encode_args, module_abi, FieldAbi, FunctionAbi, KindAbi, ModuleAbi,
+        StructTypeAbi, TypeAbi,
+    },
+    util::compile_module_with_source_map,
+};
+use serde_json::json;
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    types::{transaction::TransactionArgument, AccountAddress, ByteArray},
+    vm_runtime::vm_runtime_types::value::{MutVal, Value},
+};
+
+const NO_DEPS: &[VerifiedModule] = &[];
+
+const MODULE: &str = "module M {
+    struct Pair<T: copyable> { first: T, second: T }
+    resource Vault { owner: address, pair: V#Self.Pair<u64> }
+    resource Events { count: u64 }
+
+    public open(owner: address, key: bytearray): bool {
+        return true;
+    }
+
+    signal(events: &mut R#Self.Events, pair: V#Self.Pair<u64>) {
+        emit_event(&mut move(events).count, h\"cafe\", move(pair));
+        return;
+    }
+}";
+
+fn abi() -> ModuleAbi {
+    let module =
+        compile_module_with_source_map("m.mvir", MODULE, &AccountAddress::default(), NO_DEPS)
+            .unwrap()
+            .0;
+    module_abi(&module)
+}
+
+fn pair_type(type_actual: TypeAbi) -> TypeAbi {
+    TypeAbi::Struct(StructTypeAbi {
+        address: AccountAddress::default().to_string(),
+        module: "M".to_string(),
+        name: "Pair".to_string(),
+        type_actuals: vec![type_actual],
+    })
+}
+
+#[test]
+fn module_abi_lists_structs_functions_and_events() {
+    let abi = abi();
+    assert_eq!(abi.name, "M");
+
+    let vault = &abi.structs[1];
+    assert_eq!(vault.name, "Vault");
+    assert_eq!(vault.kind, KindAbi::Resource);
+    assert_eq!(
+        vault.fields,
+        Some(vec![
+            FieldAbi {
+                name: "owner".to_string(),
+                type_: TypeAbi::Address,
+            },
+            FieldAbi {
+                name: "pair".to_string(),
+                type_: pair_type(TypeAbi::U64),
+            },
+        ])
+    );
+    assert_eq!(abi.structs[0].type_formals, vec![KindAbi::Copyable]);
+
+    // `signal` is not public
+    assert_eq!(
+        abi.functions,
+        vec![FunctionAbi {
+            name: "open".to_string(),
+            type_formals: vec![],
+            args: vec![TypeAbi::Address, TypeAbi::ByteArray],
+            returns: vec![TypeAbi::Bool],
+        }]
+    );
+
+    assert_eq!(abi.events.len(), 1);
+    assert_eq!(abi.events[0].function, "signal");
+    assert_eq!(abi.events[0].key, Some("cafe".to_string()));
+    assert_eq!(abi.events[0].type_, pair_type(TypeAbi::U64));
+}
+
+#[test]
+fn encode_args_against_abi() {
+    let abi = abi();
+    let open = &abi.functions[0];
+    assert_eq!(
+        encode_args(open, &[json!("0x1"), json!("00ff")]).unwrap(),
+        vec![
+            TransactionArgument::Address(AccountAddress::new({
+                let mut addr = [0; 32];
+                addr[31] = 1;
+                addr
+            })),
+            TransactionArgument::ByteArray(ByteArray::new(vec![0, 255])),
+        ]
+    );
+    encode_args(open, &[json!(1), json!("00ff")]).unwrap_err();
+    encode_args(open, &[json!("0x1")]).unwrap_err();
+}
+
+#[test]
+fn decode_resource_against_abi() {
+    let owner = AccountAddress::random();
+    let vault = Value::Struct(vec![
+        MutVal::new(Value::Address(owner)),
+        MutVal::new(Value::Struct(vec![
+            MutVal::new(Value::U64(1)),
+            MutVal::new(Value::U64(2)),
+        ])),
+    ]);
+    let blob = vault.simple_serialize().unwrap();
+    let resource = StructTypeAbi {
+        address: AccountAddress::default().to_string(),
+        module: "M".to_string(),
+        name: "Vault".to_string(),
+        type_actuals: vec![],
+    };
+    assert_eq!(
+        decode_resource(&[abi()], &resource, &blob).unwrap(),
+        json!({
+            "owner": owner.to_string(),
+            "pair": { "first": 1, "second": 2 },
+        })
+    );
+}
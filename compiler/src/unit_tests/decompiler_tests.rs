@@ -0,0 +1,189 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    decompiler::{decompile_module, decompile_script},
+    util::compile_module_with_source_map,
+    Compiler,
+};
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    def::file_format::{CompiledModule, CompiledScript},
+    types::AccountAddress,
+};
+
+const NO_DEPS: &[VerifiedModule] = &[];
+
+const MODULE: &str = "module M {
+    struct Pair<T: copyable> { first: T, second: T }
+    resource Counter { count: u64 }
+    native struct Handle<T>;
+
+    native public hash(data: bytearray): bytearray;
+
+    public new_pair<T: copyable>(first: T, second: T): V#Self.Pair<T> {
+        return Pair<T> { first: move(first), second: move(second) };
+    }
+
+    public split(pair: V#Self.Pair<u64>): u64 * u64 {
+        let first: u64;
+        let second: u64;
+        Pair<u64> { first, second } = move(pair);
+        return move(first), move(second);
+    }
+
+    public swap(pair: V#Self.Pair<u64>): V#Self.Pair<u64> {
+        let first: u64;
+        let second: u64;
+        first, second = Self.split(move(pair));
+        return Self.new_pair<u64>(move(second), move(first));
+    }
+
+    public first_of(pair: &V#Self.Pair<u64>): u64 {
+        return *(&move(pair).first);
+    }
+
+    public first_of_local(pair: V#Self.Pair<u64>): u64 {
+        let r: &V#Self.Pair<u64>;
+        r = &pair;
+        return *(&move(r).first);
+    }
+
+    public sum_to(n: u64): u64 {
+        let i: u64;
+        let sum: u64;
+        i = 0;
+        sum = 0;
+        while (copy(i) < copy(n)) {
+            i = copy(i) + 1;
+            if (copy(i) % 2 == 0) {
+                continue;
+            }
+            sum = copy(sum) + copy(i);
+        }
+        return move(sum);
+    }
+
+    public first_over(limit: u64): u64 {
+        let i: u64;
+        i = 1;
+        loop {
+            if (copy(i) * copy(i) > copy(limit)) {
+                break;
+            } else {
+                i = copy(i) + 1;
+            }
+        }
+        return move(i);
+    }
+
+    public classify(x: u64): u64 {
+        if (copy(x) == 0) {
+            return 0;
+        }
+        if (copy(x) < 10) {
+            x = 1;
+        } else {
+            x = 2;
+        }
+        return move(x);
+    }
+
+    public publish(start: u64) {
+        move_to_sender<Counter>(Counter { count: move(start) });
+        return;
+    }
+
+    public increment(addr: address): u64 {
+        let counter: &mut R#Self.Counter;
+        let count: &mut u64;
+        assert(exists<Counter>(copy(addr)), 42);
+        counter = borrow_global<Counter>(move(addr));
+        count = &mut move(counter).count;
+        *copy(count) = *copy(count) + 1;
+        return *move(count);
+    }
+
+    public constants(): bytearray * address {
+        return h\"cafe\", 0x1d8;
+    }
+}";
+
+fn compile(source: &str) -> CompiledModule {
+    compile_module_with_source_map("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+        .unwrap()
+        .0
+}
+
+#[test]
+fn decompiled_module_recompiles_to_the_same_bytecode() {
+    let module = compile(MODULE);
+    let source = decompile_module(&module, None).unwrap();
+    assert_eq!(compile(&source), module, "decompiled source:\n{}", source);
+}
+
+#[test]
+fn decompiled_statements_are_structured() {
+    let (module, source_map) =
+        compile_module_with_source_map("m.mvir", MODULE, &AccountAddress::default(), NO_DEPS)
+            .unwrap();
+    let source = decompile_module(&module, Some(&source_map)).unwrap();
+    for expected in &[
+        "    native struct Handle<T0>;\n",
+        "    public new_pair<T0: copyable>(first: T0, second: T0): V#Self.Pair<T0> {\n",
+        "        Pair<u64> { first: first, second: second } = move(pair);\n",
+        "        first, second = Self.split(move(pair));\n",
+        "        r = &pair;\n",
+        "        while (copy(i) < copy(n)) {\n",
+        "                continue;\n",
+        "        loop {\n",
+        "                break;\n",
+        "            } else {\n",
+        "        if (!exists<Counter>(copy(addr))) {\n",
+        "        count = &mut move(counter).count;\n",
+        "        return h\"cafe\", 0x1d8;\n",
+    ] {
+        assert!(
+            source.contains(expected),
+            "missing {:?} in decompiled source:\n{}",
+            expected,
+            source
+        );
+    }
+}
+
+#[test]
+fn locals_are_named_without_a_source_map() {
+    let source = decompile_module(&compile(MODULE), None).unwrap();
+    assert!(source.contains("    public sum_to(arg0: u64): u64 {\n"));
+    assert!(source.contains("        let loc0: u64;\n"));
+    assert!(source.contains("        while (copy(loc0) < copy(arg0)) {\n"));
+}
+
+#[test]
+fn decompiled_script_recompiles_to_the_same_bytecode() {
+    let compile_script = |code: &str| -> CompiledScript {
+        Compiler {
+            code,
+            ..Compiler::default()
+        }
+        .into_compiled_program()
+        .unwrap()
+        .script
+    };
+    let script = compile_script(
+        "import 0x0.LibraAccount;
+main(payee: address, amount: u64) {
+    LibraAccount.pay_from_sender(move(payee), move(amount));
+    return;
+}",
+    );
+    let source = decompile_script(&script, None).unwrap();
+    assert!(source.starts_with("import 0x0.LibraAccount;\n"));
+    assert_eq!(
+        compile_script(&source),
+        script,
+        "decompiled source:\n{}",
+        source
+    );
+}
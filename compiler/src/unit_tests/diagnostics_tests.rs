@@ -0,0 +1,100 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::util::compile_module_with_source_map;
+use ir_to_bytecode::{diagnostics, errors::CompilationErrors};
+use vm::{bytecode_verifier::VerifiedModule, types::AccountAddress};
+
+const NO_DEPS: &[VerifiedModule] = &[];
+
+fn compile_errors(source: &str) -> CompilationErrors {
+    let err = compile_module_with_source_map("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+        .unwrap_err();
+    err.downcast::<CompilationErrors>()
+        .expect("errors in the source are reported as compilation errors")
+}
+
+#[test]
+fn reports_every_error_in_one_pass() {
+    let source = "module M {
+    f(): u64 {
+        let x: u64;
+        x = move(y);
+        return move(x);
+    }
+    g() {
+        Foo.bar();
+        return;
+    }
+}";
+    let errors = compile_errors(source).0;
+    assert_eq!(errors.len(), 2);
+
+    let text = |idx: usize| {
+        let loc = errors[idx].loc.expect("statement errors have a location");
+        &source[loc.start().0 as usize..loc.end().0 as usize]
+    };
+    assert!(text(0).starts_with("x = move(y)"));
+    assert_eq!(errors[0].message, "variable y undefined");
+    assert_eq!(errors[0].notes, vec!["in function `f`".to_string()]);
+    assert!(text(1).starts_with("Foo.bar()"));
+    assert_eq!(errors[1].notes, vec!["in function `g`".to_string()]);
+
+    let rendered = diagnostics::render("m.mvir", source, &errors);
+    assert!(rendered.contains("error: variable y undefined"));
+    assert!(rendered.contains("m.mvir:4:"));
+    assert!(rendered.contains("x = move(y);"));
+    assert!(rendered.contains("= in function `g`"));
+}
+
+#[test]
+fn reports_parse_errors_with_a_location() {
+    let source = "module M {\n    f() { return }\n}";
+    let errors = compile_errors(source).0;
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.starts_with("Unrecognized Token"));
+    let rendered = diagnostics::render("m.mvir", source, &errors);
+    assert!(rendered.contains("m.mvir:2:"));
+}
+
+#[test]
+fn locates_verifier_errors() {
+    let source = "module M {
+    f(): u64 {
+        return true;
+    }
+}";
+    let (module, source_map) =
+        compile_module_with_source_map("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+            .unwrap();
+    let (_, errors) = VerifiedModule::new(module).unwrap_err();
+    let errors = diagnostics::verification_errors(source, &source_map, &errors);
+    assert!(!errors.is_empty());
+    let loc = errors[0].loc.expect("code errors have a location");
+    assert!(source[loc.start().0 as usize..].starts_with("return true"));
+    assert_eq!(errors[0].notes, vec!["in function `f`".to_string()]);
+}
+
+#[test]
+fn locates_definition_errors_at_their_name() {
+    let source = "module M {
+    struct T { f: Self.Missing }
+    g(x: Self.Missing) {
+        return;
+    }
+}";
+    let errors = compile_errors(source).0;
+    assert_eq!(errors.len(), 2);
+
+    let text = |idx: usize| {
+        let loc = errors[idx].loc.expect("definition errors have a location");
+        &source[loc.start().0 as usize..loc.end().0 as usize]
+    };
+    assert_eq!(text(0), "T");
+    assert_eq!(errors[0].notes, vec!["in struct `T`".to_string()]);
+    assert_eq!(text(1), "g");
+    assert_eq!(
+        errors[1].notes,
+        vec!["in the signature of function `g`".to_string()]
+    );
+}
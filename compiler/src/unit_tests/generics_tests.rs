@@ -0,0 +1,149 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::util::compile_module_with_source_map;
+use ir_to_bytecode::errors::CompilationErrors;
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    def::{
+        access::ModuleAccess,
+        file_format::{
+            Bytecode, CompiledModule, FunctionDefinitionIndex, Kind, SignatureToken,
+            StructFieldInformation,
+        },
+    },
+    types::AccountAddress,
+};
+
+const NO_DEPS: &[VerifiedModule] = &[];
+
+const MODULE: &str = "module M {
+    struct Box<T: copyable> { item: T }
+    resource Vault<T> { content: T }
+
+    public new<T: copyable>(item: T): V#Self.Box<T> {
+        return Box<T> { item: move(item) };
+    }
+
+    public item(b: &V#Self.Box<u64>): u64 {
+        return *(&move(b).item);
+    }
+
+    public boxed_one(): V#Self.Box<u64> {
+        return Self.new<u64>(1);
+    }
+
+    public lock<T>(content: T) {
+        move_to_sender<Vault<T>>(Vault<T> { content: move(content) });
+        return;
+    }
+}";
+
+fn compile(source: &str) -> CompiledModule {
+    compile_module_with_source_map("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+        .unwrap()
+        .0
+}
+
+fn code(module: &CompiledModule, idx: u16) -> &[Bytecode] {
+    &module
+        .function_def_at(FunctionDefinitionIndex::new(idx))
+        .code
+        .code
+}
+
+#[test]
+fn type_formals_are_compiled_to_kind_constraints() {
+    let module = compile(MODULE);
+    let constraints = module
+        .struct_handles()
+        .iter()
+        .map(|handle| handle.kind_constraints.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        constraints,
+        vec![vec![Kind::Copyable], vec![Kind::Resource]]
+    );
+
+    let new = module.function_handle_at(
+        module
+            .function_def_at(FunctionDefinitionIndex::new(0))
+            .function,
+    );
+    let signature = module.function_signature_at(new.signature);
+    assert_eq!(signature.kind_constraints, vec![Kind::Copyable]);
+    assert_eq!(signature.arg_types, vec![SignatureToken::TypeParameter(0)]);
+
+    VerifiedModule::new(module).expect("generic module verifies");
+}
+
+#[test]
+fn instructions_carry_type_actuals() {
+    let module = compile(MODULE);
+    let actuals = |bytecode: &Bytecode| match bytecode {
+        Bytecode::Call(_, idx) | Bytecode::Pack(_, idx) | Bytecode::MoveToSender(_, idx) => {
+            module.locals_signature_at(*idx).0.clone()
+        }
+        _ => panic!("unexpected instruction {:?}", bytecode),
+    };
+
+    let pack = code(&module, 0)
+        .iter()
+        .find(|bytecode| match bytecode {
+            Bytecode::Pack(..) => true,
+            _ => false,
+        })
+        .unwrap();
+    assert_eq!(actuals(pack), vec![SignatureToken::TypeParameter(0)]);
+
+    let call = code(&module, 2)
+        .iter()
+        .find(|bytecode| match bytecode {
+            Bytecode::Call(..) => true,
+            _ => false,
+        })
+        .unwrap();
+    assert_eq!(actuals(call), vec![SignatureToken::U64]);
+
+    let move_to_sender = code(&module, 3)
+        .iter()
+        .find(|bytecode| match bytecode {
+            Bytecode::MoveToSender(..) => true,
+            _ => false,
+        })
+        .unwrap();
+    assert_eq!(
+        actuals(move_to_sender),
+        vec![SignatureToken::TypeParameter(0)]
+    );
+}
+
+#[test]
+fn unbound_type_parameter() {
+    let source = "module M {
+    struct Box { item: T }
+}";
+    let err = compile_module_with_source_map("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+        .unwrap_err();
+    let errors = err.downcast::<CompilationErrors>().unwrap().0;
+    assert_eq!(errors[0].message, "unbound type parameter T");
+}
+
+#[test]
+fn native_struct_has_native_field_information() {
+    let source = "module M {
+    native struct T<E>;
+    native public empty<E>(): V#Self.T<E>;
+}";
+    let module = compile(source);
+    let struct_def = &module.struct_defs()[0];
+    assert_eq!(struct_def.field_information, StructFieldInformation::Native);
+    assert_eq!(
+        module
+            .struct_handle_at(struct_def.struct_handle)
+            .kind_constraints,
+        vec![Kind::Resource]
+    );
+
+    VerifiedModule::new(module).expect("native struct verifies");
+}
@@ -0,0 +1,12 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+mod abi_tests;
+mod decompiler_tests;
+mod diagnostics_tests;
+mod generics_tests;
+mod optimizer_tests;
+mod parser_tests;
+mod project_tests;
+mod source_map_tests;
+mod specs_tests;
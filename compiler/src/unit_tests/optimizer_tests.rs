@@ -0,0 +1,238 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::util::compile_module_with_source_map;
+use ir_to_bytecode::optimizer::{optimize_code, optimize_module};
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    def::{
+        access::ModuleAccess,
+        file_format::{Bytecode, FunctionDefinitionIndex, SignatureToken},
+    },
+    types::AccountAddress,
+};
+
+const NO_DEPS: &[VerifiedModule] = &[];
+const LOCALS: &[SignatureToken] = &[SignatureToken::U64, SignatureToken::U64];
+
+#[test]
+fn unreachable_code_is_removed() {
+    let (code, origins) = optimize_code(
+        &[
+            Bytecode::LdConst(7),
+            Bytecode::Abort,
+            Bytecode::LdConst(0),
+            Bytecode::Ret,
+        ],
+        LOCALS,
+    );
+    assert_eq!(code, vec![Bytecode::LdConst(7), Bytecode::Abort]);
+    assert_eq!(origins, vec![0, 1]);
+}
+
+#[test]
+fn store_load_pairs_are_removed() {
+    let (code, origins) = optimize_code(
+        &[
+            Bytecode::CopyLoc(0),
+            Bytecode::StLoc(1),
+            Bytecode::MoveLoc(1),
+            Bytecode::Ret,
+        ],
+        LOCALS,
+    );
+    assert_eq!(code, vec![Bytecode::CopyLoc(0), Bytecode::Ret]);
+    assert_eq!(origins, vec![0, 3]);
+
+    // The load is a branch target, so the stored value may not be the loaded one
+    let code = vec![
+        Bytecode::CopyLoc(0),
+        Bytecode::BrFalse(4),
+        Bytecode::LdConst(1),
+        Bytecode::StLoc(1),
+        Bytecode::MoveLoc(1),
+        Bytecode::Ret,
+    ];
+    assert_eq!(optimize_code(&code, LOCALS).0, code);
+
+    // The stored reference would keep what it borrows borrowed
+    let code = vec![
+        Bytecode::BorrowLoc(0),
+        Bytecode::StLoc(1),
+        Bytecode::MoveLoc(1),
+        Bytecode::ReadRef,
+        Bytecode::Ret,
+    ];
+    let locals = &[
+        SignatureToken::U64,
+        SignatureToken::MutableReference(Box::new(SignatureToken::U64)),
+    ];
+    assert_eq!(optimize_code(&code, locals).0, code);
+}
+
+#[test]
+fn constants_are_folded() {
+    let (code, _) = optimize_code(
+        &[
+            Bytecode::LdConst(1),
+            Bytecode::LdConst(2),
+            Bytecode::Add,
+            Bytecode::LdConst(4),
+            Bytecode::Mul,
+            Bytecode::LdConst(12),
+            Bytecode::Eq,
+            Bytecode::Not,
+            Bytecode::Ret,
+        ],
+        LOCALS,
+    );
+    assert_eq!(code, vec![Bytecode::LdFalse, Bytecode::Ret]);
+
+    // Aborting operations are left to abort at runtime
+    let code = vec![
+        Bytecode::LdConst(1),
+        Bytecode::LdConst(0),
+        Bytecode::Div,
+        Bytecode::LdConst(0),
+        Bytecode::LdConst(1),
+        Bytecode::Sub,
+        Bytecode::Ret,
+    ];
+    assert_eq!(optimize_code(&code, LOCALS).0, code);
+}
+
+#[test]
+fn branches_are_threaded() {
+    let (code, _) = optimize_code(
+        &[
+            Bytecode::CopyLoc(0),
+            Bytecode::BrFalse(4),
+            Bytecode::LdConst(1),
+            Bytecode::Ret,
+            Bytecode::Branch(5),
+            Bytecode::Branch(6),
+            Bytecode::LdConst(2),
+            Bytecode::Ret,
+        ],
+        LOCALS,
+    );
+    assert_eq!(
+        code,
+        vec![
+            Bytecode::CopyLoc(0),
+            Bytecode::BrFalse(4),
+            Bytecode::LdConst(1),
+            Bytecode::Ret,
+            Bytecode::LdConst(2),
+            Bytecode::Ret,
+        ]
+    );
+
+    // A constant condition makes one of the branches unreachable
+    let (code, _) = optimize_code(
+        &[
+            Bytecode::LdTrue,
+            Bytecode::BrFalse(4),
+            Bytecode::LdConst(1),
+            Bytecode::Ret,
+            Bytecode::LdConst(2),
+            Bytecode::Ret,
+        ],
+        LOCALS,
+    );
+    assert_eq!(code, vec![Bytecode::LdConst(1), Bytecode::Ret]);
+}
+
+#[test]
+fn optimized_module_verifies_and_keeps_its_source_map() {
+    let source = "module M {
+    resource Counter { count: u64 }
+
+    public sum_to(n: u64): u64 {
+        let i: u64;
+        let sum: u64;
+        i = 0;
+        sum = 0;
+        while (copy(i) < copy(n)) {
+            i = copy(i) + 1;
+            sum = copy(sum) + copy(i);
+        }
+        return move(sum);
+    }
+
+    public seconds_in(days: u64): u64 {
+        let seconds: u64;
+        seconds = copy(days) * (24 * 60 * 60);
+        if (true) {
+            return move(seconds);
+        }
+        return 0;
+    }
+
+    public publish() {
+        move_to_sender<Counter>(Counter { count: 0 });
+        return;
+    }
+}";
+    let (module, source_map) =
+        compile_module_with_source_map("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+            .unwrap();
+    let mut optimized_source_map = source_map.clone();
+    let optimized = optimize_module(module.clone(), &mut optimized_source_map).unwrap();
+    let optimized = VerifiedModule::new(optimized).expect("optimized module verifies");
+
+    let code = |module: &VerifiedModule, idx: u16| {
+        module
+            .function_def_at(FunctionDefinitionIndex::new(idx))
+            .code
+            .code
+            .clone()
+    };
+    assert_eq!(
+        code(&optimized, 1),
+        vec![
+            Bytecode::CopyLoc(0),
+            Bytecode::LdConst(86400),
+            Bytecode::Mul,
+            Bytecode::Ret,
+        ]
+    );
+    let original = VerifiedModule::new(module).unwrap();
+    for idx in 0..3 {
+        assert!(code(&optimized, idx).len() <= code(&original, idx).len());
+    }
+
+    // The return of `seconds_in` was moved next to the multiplication, and keeps its span
+    let function = FunctionDefinitionIndex::new(1);
+    let return_offset = code(&original, 1)
+        .iter()
+        .position(|bytecode| bytecode == &Bytecode::Ret)
+        .unwrap();
+    assert_eq!(
+        optimized_source_map.span(function, 3),
+        source_map.span(function, return_offset as u16)
+    );
+}
+
+#[test]
+fn optimized_module_with_reborrowed_references_verifies() {
+    let source = "module M {
+    public reborrow(): u64 {
+        let a: u64;
+        let b: u64;
+        let r: &mut u64;
+        let c: u64;
+        a = 1;
+        b = 2;
+        r = &mut a;
+        r = &mut b;
+        c = *move(r);
+        return move(a) + move(c);
+    }
+}";
+    let (module, mut source_map) =
+        compile_module_with_source_map("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+            .unwrap();
+    let optimized = optimize_module(module, &mut source_map).unwrap();
+    VerifiedModule::new(optimized).expect("optimized module verifies");
+}
@@ -0,0 +1,160 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use ir_to_bytecode::{
+    errors::CompilationErrors,
+    parser::{
+        ast::{FunctionAnnotation, FunctionBody, FunctionName, Statement},
+        parse_module, parse_program,
+    },
+};
+
+#[test]
+fn comment_on_the_last_line() {
+    parse_program("main() { return; } // no newline after this").unwrap();
+}
+
+#[test]
+fn nested_block_comments() {
+    let source = "module M {
+    /* a block comment /* with a nested one */ still commented */
+    f(): u64 {
+        /* inline */ return 1;
+    }
+}";
+    let module = parse_module(source).unwrap();
+    let function = &module.functions[0].1;
+    let body = match &function.body {
+        FunctionBody::Move { code, .. } => code,
+        _ => panic!("f is not native"),
+    };
+    match &body.stmts[0] {
+        Statement::CommandStatement(cmd) => {
+            let start = cmd.span.start().0 as usize;
+            assert!(source[start..].starts_with("return 1"));
+        }
+        stmt => panic!("unexpected statement {}", stmt),
+    }
+}
+
+#[test]
+fn unterminated_block_comment() {
+    let err = parse_module("module M { /* f() { return; } }").unwrap_err();
+    let errors = err.downcast::<CompilationErrors>().unwrap().0;
+    assert_eq!(errors[0].message, "Unterminated block comment");
+    let loc = errors[0].loc.unwrap();
+    assert_eq!(loc.start().0, 11);
+}
+
+#[test]
+fn doc_comments() {
+    let module = parse_module(
+        "module M {
+    /// A coin.
+    ///
+    /// Holds value.
+    resource T { value: u64 }
+
+    //// not a doc comment
+    f() { return; }
+
+    /// Returns nothing.
+    // a regular comment in between
+    public g() { return; }
+
+    /// Provided by the VM.
+    native h();
+}",
+    )
+    .unwrap();
+    assert_eq!(
+        module.structs[0].doc.as_ref().map(String::as_str),
+        Some("A coin.\n\nHolds value.")
+    );
+    let doc = |name: &str| {
+        module
+            .functions
+            .iter()
+            .find(|(function_name, _)| function_name == &FunctionName::new(name.to_string()))
+            .and_then(|(_, function)| function.doc.clone())
+    };
+    assert_eq!(doc("f"), None);
+    assert_eq!(doc("g"), Some("Returns nothing.".to_string()));
+    assert_eq!(doc("h"), Some("Provided by the VM.".to_string()));
+}
+
+#[test]
+fn spec_conditions_are_parsed_in_place() {
+    let source = "module M {
+    f(x: u64): u64
+    requires<copy(x) > 0>
+    {
+        verify<copy(x) >= 1>
+        return move(x);
+    }
+}";
+    let module = parse_module(source).unwrap();
+    let function = &module.functions[0].1;
+    let located = |start: u32| &source[start as usize..];
+    match &function.annotations[..] {
+        [FunctionAnnotation::Requires(cond)] => {
+            assert!(located(cond.span.start().0).starts_with("copy(x) > 0>"))
+        }
+        annotations => panic!("unexpected annotations {:?}", annotations),
+    }
+    let body = match &function.body {
+        FunctionBody::Move { code, .. } => code,
+        _ => panic!("f is not native"),
+    };
+    match &body.stmts[0] {
+        Statement::VerifyStatement(cond) => {
+            assert!(located(cond.span.start().0).starts_with("copy(x) >= 1>"))
+        }
+        stmt => panic!("unexpected statement {}", stmt),
+    }
+
+    let err = parse_module("module M { f() { verify<copy(1) > 0> return; } }").unwrap_err();
+    let errors = err.downcast::<CompilationErrors>().unwrap().0;
+    assert_eq!(errors[0].loc.unwrap().start().0, 29);
+}
+
+#[test]
+fn shifts_and_nested_type_actuals() {
+    let module = parse_module(
+        "module M {
+    struct Box<T> { value: T }
+    f(b: V#Self.Box<V#Self.Box<u128>>, x: u128): V#Self.Box<V#Self.Box<u128>> {
+        assert(copy(x) >> 2u8 < 1u128 << 100u8, 1);
+        return move(b);
+    }
+}",
+    )
+    .unwrap();
+    let function = &module.functions[0].1;
+    let body = match &function.body {
+        FunctionBody::Move { code, .. } => code,
+        _ => panic!("f is not native"),
+    };
+    // shifts bind tighter than comparisons
+    let assertion = body.stmts[0].to_string();
+    assert!(
+        assertion.contains("(< (>> copy(x) 2u8) (<< 1u128 100u8))"),
+        assertion
+    );
+}
+
+#[test]
+fn integer_literals_out_of_range() {
+    let source = "module M { f(): u8 { return 300u8; } }";
+    let err = parse_module(source).unwrap_err();
+    let errors = err.downcast::<CompilationErrors>().unwrap().0;
+    assert_eq!(
+        errors[0].message,
+        "Integer literal does not fit in its type"
+    );
+    let loc = errors[0].loc.unwrap();
+    assert_eq!(
+        &source[loc.start().0 as usize..loc.end().0 as usize],
+        "300u8"
+    );
+}
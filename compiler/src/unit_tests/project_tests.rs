@@ -0,0 +1,113 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::project::{build, SourceFile};
+use vm::{bytecode_verifier::VerifiedModule, def::access::ModuleAccess, types::AccountAddress};
+
+const NO_DEPS: &[VerifiedModule] = &[];
+
+fn source(file: &str, text: &str) -> SourceFile {
+    SourceFile {
+        file: file.to_string(),
+        text: text.to_string(),
+    }
+}
+
+fn sources() -> Vec<SourceFile> {
+    vec![
+        source(
+            "a.mvir",
+            "// Uses Counter
+/* A /* nested */ comment */
+module A {
+    import Transaction.Counter;
+
+    public next(): u64 {
+        return Counter.start() + 1;
+    }
+}",
+        ),
+        source(
+            "counter.mvir",
+            "module Counter {
+    public start(): u64 {
+        return 0;
+    }
+}",
+        ),
+        source(
+            "run.mvir",
+            "import Transaction.A;
+main() {
+    assert(A.next() == 1, 42);
+    return;
+}",
+        ),
+    ]
+}
+
+#[test]
+fn modules_are_built_after_their_imports() {
+    let project = build(&sources(), &AccountAddress::default(), NO_DEPS).unwrap();
+    let names = project
+        .modules
+        .iter()
+        .map(|built| built.module.self_id().name().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Counter", "A"]);
+    assert_eq!(project.modules[1].file, "a.mvir");
+
+    assert_eq!(project.scripts.len(), 1);
+    assert_eq!(project.scripts[0].file, "run.mvir");
+    assert!(project.scripts[0].program.modules.is_empty());
+}
+
+#[test]
+fn import_cycles_are_reported() {
+    let mut sources = sources();
+    sources[1] = source(
+        "counter.mvir",
+        "module Counter {
+    import Transaction.A;
+
+    public start(): u64 {
+        return 0;
+    }
+}",
+    );
+    let err = build(&sources, &AccountAddress::default(), NO_DEPS).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "modules import each other in a cycle: A -> Counter -> A"
+    );
+}
+
+#[test]
+fn modules_are_defined_once() {
+    let mut sources = sources();
+    sources.push(source(
+        "z.mvir",
+        "module A {
+}",
+    ));
+    let err = build(&sources, &AccountAddress::default(), NO_DEPS).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "module `A` is defined in both a.mvir and z.mvir"
+    );
+}
+
+#[test]
+fn errors_are_rendered_against_their_file() {
+    let mut sources = sources();
+    sources[1] = source(
+        "counter.mvir",
+        "module Counter {
+    public start(): u64 {
+        return false;
+    }
+}",
+    );
+    let err = build(&sources, &AccountAddress::default(), NO_DEPS).unwrap_err();
+    assert!(err.to_string().contains("in function `start`"), "{}", err);
+}
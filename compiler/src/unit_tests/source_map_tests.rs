@@ -0,0 +1,106 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Compiler;
+use ir_to_bytecode::source_map::{Position, ProgramSourceMap};
+use vm::def::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+};
+
+const PROGRAM: &str = "modules:
+module M {
+    public f(x: u64): u64 {
+        // comments are blanked out, spans still refer to this text
+        if (copy(x) > 0) {
+            return move(x) - 1;
+        }
+        return 0;
+    }
+}
+script:
+import Transaction.M;
+main() {
+    let y: u64;
+    y = M.f(1);
+    return;
+}
+";
+
+fn start(source_map: &ProgramSourceMap, module: Option<usize>, offset: CodeOffset) -> Position {
+    let map = match module {
+        Some(idx) => &source_map.modules[idx],
+        None => &source_map.script,
+    };
+    map.span(FunctionDefinitionIndex::new(0), offset)
+        .expect("every instruction has a span")
+        .start
+}
+
+#[test]
+fn spans_of_module_and_script() {
+    let compiler = Compiler {
+        code: PROGRAM,
+        skip_stdlib_deps: true,
+        ..Compiler::default()
+    };
+    let (program, _, source_map) = compiler
+        .into_compiled_program_deps_and_source_map("test.mvir")
+        .unwrap();
+    assert_eq!(source_map.script.file, "test.mvir");
+    assert_eq!(
+        source_map.modules[0].function_index("f"),
+        Some(FunctionDefinitionIndex::new(0))
+    );
+
+    let code = &program.modules[0]
+        .function_def_at(FunctionDefinitionIndex::new(0))
+        .code
+        .code;
+    let brfalse = code
+        .iter()
+        .position(|instr| match instr {
+            Bytecode::BrFalse(_) => true,
+            _ => false,
+        })
+        .unwrap() as CodeOffset;
+    // The condition and its branch map to the condition.
+    let position = start(&source_map, Some(0), 0);
+    assert_eq!(
+        position,
+        Position {
+            line: 5,
+            column: 13
+        }
+    );
+    assert_eq!(start(&source_map, Some(0), brfalse), position);
+    assert_eq!(start(&source_map, Some(0), brfalse + 1).line, 6);
+    assert_eq!(
+        start(&source_map, Some(0), code.len() as CodeOffset - 1).line,
+        8
+    );
+
+    assert_eq!(
+        start(&source_map, None, 0),
+        Position {
+            line: 15,
+            column: 5
+        }
+    );
+    assert_eq!(start(&source_map, None, 3).line, 16);
+}
+
+#[test]
+fn source_map_serialization() {
+    let compiler = Compiler {
+        code: PROGRAM,
+        skip_stdlib_deps: true,
+        ..Compiler::default()
+    };
+    let (_, _, source_map) = compiler
+        .into_compiled_program_deps_and_source_map("test.mvir")
+        .unwrap();
+    let serialized = serde_json::to_string(&source_map).unwrap();
+    let deserialized: ProgramSourceMap = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, source_map);
+}
@@ -0,0 +1,92 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::util::{compile_module_with_source_map, compile_module_with_spec_checks};
+use ir_to_bytecode::specs::SPEC_VIOLATION;
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    def::{
+        access::ModuleAccess,
+        file_format::{Bytecode, CompiledModule, FunctionDefinitionIndex},
+    },
+    types::AccountAddress,
+};
+
+const NO_DEPS: &[VerifiedModule] = &[];
+
+const MODULE: &str = "module M {
+    public split(x: u64): u64 * u64
+    requires<copy(x) % 2 == 0>
+    ensures<copy(RET0) == copy(RET1)>
+    {
+        if (copy(x) == 0) {
+            return 0, 0;
+        }
+        verify<copy(x) >= 2>
+        return copy(x) / 2, move(x) / 2;
+    }
+}";
+
+const MODULE_WITHOUT_SPECS: &str = "module M {
+    public split(x: u64): u64 * u64 {
+        if (copy(x) == 0) {
+            return 0, 0;
+        }
+        return copy(x) / 2, move(x) / 2;
+    }
+}";
+
+fn code(module: &CompiledModule) -> &[Bytecode] {
+    &module
+        .function_def_at(FunctionDefinitionIndex::new(0))
+        .code
+        .code
+}
+
+#[test]
+fn specs_are_ignored_by_default() {
+    let compile = |source| {
+        compile_module_with_source_map("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+            .unwrap()
+            .0
+    };
+    assert_eq!(compile(MODULE), compile(MODULE_WITHOUT_SPECS));
+}
+
+#[test]
+fn specs_are_checked_at_runtime() {
+    let (module, source_map) =
+        compile_module_with_spec_checks("m.mvir", MODULE, &AccountAddress::default(), NO_DEPS)
+            .unwrap();
+    let checks = code(&module)
+        .iter()
+        .filter(|bytecode| *bytecode == &Bytecode::LdConst(SPEC_VIOLATION))
+        .count();
+    // The requirement, the verification and the guarantee at each of the two returns
+    assert_eq!(checks, 4);
+    assert_eq!(
+        source_map.functions[0].locals,
+        vec!["x".to_string(), "RET0".to_string(), "RET1".to_string()]
+    );
+    VerifiedModule::new(module).expect("module with spec checks verifies");
+}
+
+#[test]
+fn return_locals_must_not_clash_with_locals() {
+    let module = "module M {
+    public double(x: u64): u64
+    ensures<copy(RET) == copy(x) * 2>
+    {
+        let RET: u64;
+        RET = copy(x) * 2;
+        return move(RET);
+    }
+}";
+    let compile = |source: &str| {
+        compile_module_with_spec_checks("m.mvir", source, &AccountAddress::default(), NO_DEPS)
+    };
+    let err = compile(module).unwrap_err().to_string();
+    assert!(err.contains("`RET` of function `double` is reserved"));
+    // Without `ensures`, there is no return local
+    assert!(compile(&module.replace("ensures<copy(RET) == copy(x) * 2>", "")).is_ok());
+}
@@ -1,7 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use ir_to_bytecode::{compiler::compile_module, parser::parse_module};
+use failure::prelude::*;
+use ir_to_bytecode::{
+    compiler::compile_module_with_spans, parser::parse_module, source_map::SourceMap, specs,
+};
 use std::{fs, path::Path};
 use vm::{
     def::{access::ModuleAccess, file_format::CompiledModule},
@@ -15,6 +18,46 @@ pub fn do_compile_module<T: ModuleAccess>(
 ) -> CompiledModule {
     let source = fs::read_to_string(source_path)
         .unwrap_or_else(|_| panic!("Unable to read file: {:?}", source_path));
-    let parsed_module = parse_module(&source).unwrap();
-    compile_module(address, &parsed_module, dependencies).unwrap()
+    let file = source_path.to_string_lossy();
+    compile_module_with_source_map(&file, &source, address, dependencies)
+        .unwrap()
+        .0
+}
+
+/// Compiles the module in `source`, the text of `file`, and returns it with its source map.
+pub fn compile_module_with_source_map<T: ModuleAccess>(
+    file: &str,
+    source: &str,
+    address: &AccountAddress,
+    dependencies: &[T],
+) -> Result<(CompiledModule, SourceMap)> {
+    compile_module_impl(file, source, address, dependencies, false)
+}
+
+/// Like `compile_module_with_source_map`, with the specifications of the functions of the module
+/// compiled into runtime checks.
+pub fn compile_module_with_spec_checks<T: ModuleAccess>(
+    file: &str,
+    source: &str,
+    address: &AccountAddress,
+    dependencies: &[T],
+) -> Result<(CompiledModule, SourceMap)> {
+    compile_module_impl(file, source, address, dependencies, true)
+}
+
+fn compile_module_impl<T: ModuleAccess>(
+    file: &str,
+    source: &str,
+    address: &AccountAddress,
+    dependencies: &[T],
+    check_specs: bool,
+) -> Result<(CompiledModule, SourceMap)> {
+    let mut parsed_module = parse_module(source)?;
+    if check_specs {
+        specs::instrument_module(&mut parsed_module)?;
+    }
+    let (compiled_module, spans) =
+        compile_module_with_spans(address, &parsed_module, dependencies)?;
+    let source_map = SourceMap::new(file, source, &spans);
+    Ok((compiled_module, source_map))
 }
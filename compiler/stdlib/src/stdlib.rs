@@ -28,8 +28,9 @@ lazy_static! {
         make_module_definition!("../modules/u64_util.mvir");
     static ref BYTEARRAY_UTIL_MODULE: ModuleDefinition =
         make_module_definition!("../modules/bytearray_util.mvir");
-    // static ref VECTOR_MODULE: ModuleDefinition =
-    // 	make_module_definition!("../modules/vector.mvir");
+    static ref VECTOR_MODULE: ModuleDefinition = make_module_definition!("../modules/vector.mvir");
+    static ref STRING_UTIL_MODULE: ModuleDefinition =
+        make_module_definition!("../modules/string_util.mvir");
     static ref MODULE_DEFS: Vec<&'static ModuleDefinition> = {
         vec![
             &*COIN_MODULE,
@@ -40,7 +41,8 @@ lazy_static! {
             &*ADDRESS_UTIL_MODULE,
             &*U64_UTIL_MODULE,
             &*BYTEARRAY_UTIL_MODULE,
-            // &*VECTOR_MODULE,
+            &*VECTOR_MODULE,
+            &*STRING_UTIL_MODULE,
         ]
     };
 }
@@ -77,9 +79,13 @@ pub fn bytearray_util_module() -> ModuleDefinition {
     BYTEARRAY_UTIL_MODULE.clone()
 }
 
-// pub fn vector_module() -> ModuleDefinition {
-// 	VECTOR_MODULE.clone()
-// }
+pub fn vector_module() -> ModuleDefinition {
+    VECTOR_MODULE.clone()
+}
+
+pub fn string_util_module() -> ModuleDefinition {
+    STRING_UTIL_MODULE.clone()
+}
 
 pub fn module_defs() -> &'static [&'static ModuleDefinition] {
     &*MODULE_DEFS
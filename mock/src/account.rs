@@ -436,6 +436,17 @@ impl AccountResource {
         }
     }
 
+    /// Returns the account `account` with its authentication key replaced by `auth_key`.
+    pub fn with_auth_key(account: Value, auth_key: AccountAddress) -> Value {
+        match account {
+            Value::Struct(mut fields) => {
+                fields[0] = MutVal::new(Value::ByteArray(ByteArray::new(auth_key.to_vec())));
+                Value::Struct(fields)
+            }
+            _ => panic!("Account must be a Value::Struct"),
+        }
+    }
+
     /// Returns the balance read from a [`Value`] representing the account.
     pub fn read_balance(account: &Value) -> u64 {
         match account {
@@ -458,6 +469,23 @@ impl AccountResource {
         }
     }
 
+    /// Returns whether the withdrawal capability has been delegated, read from a [`Value`]
+    /// representing the account.
+    pub fn read_delegated_withdrawal_capability(account: &Value) -> bool {
+        match account {
+            Value::Struct(fields) => {
+                let delegated = fields
+                    .get(2)
+                    .expect("delegated_withdrawal_capability must be field 2 in Account");
+                match &*delegated.peek() {
+                    Value::Bool(val) => *val,
+                    _ => panic!("delegated_withdrawal_capability field must be a bool"),
+                }
+            }
+            _ => panic!("Account must be a Value::Struct"),
+        }
+    }
+
     /// Returns the received events count read from a [`Value`] representing the account.
     pub fn read_received_events_count(account: &Value) -> u64 {
         match account {
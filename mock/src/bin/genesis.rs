@@ -0,0 +1,54 @@
+use clap::{App, Arg};
+use mock::{
+    account::GENESIS_KEYPAIR,
+    genesis::{GenesisBuilder, GenesisSpec},
+};
+use std::{fs, path::Path};
+
+fn main() {
+    let args = App::new("move-genesis")
+        .about("Builds a genesis write set and writes it in canonical bytes.")
+        .arg(
+            Arg::with_name("spec")
+                .help("a TOML or JSON genesis spec, the default genesis if not given")
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .required(true)
+                .help("the file to write the write set to"),
+        )
+        .get_matches();
+
+    let builder = match args.value_of("spec") {
+        Some(path) => {
+            let path = Path::new(path);
+            let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+            GenesisSpec::load(path)
+                .and_then(|spec| spec.into_builder(GENESIS_KEYPAIR.1, base_dir))
+                .unwrap_or_else(|err| exit_with_error(&err.to_string()))
+        }
+        None => GenesisBuilder::new(GENESIS_KEYPAIR.1),
+    };
+    let genesis = builder
+        .build()
+        .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+
+    let output = args.value_of("output").expect("output is required");
+    fs::write(output, genesis.write_set_bytes())
+        .unwrap_or_else(|err| exit_with_error(&format!("{}: {}", output, err)));
+    println!(
+        "wrote {} entries to {}",
+        genesis.write_set.iter().count(),
+        output
+    );
+    println!("publishing option: {:?}", genesis.publishing_option);
+}
+
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
+}
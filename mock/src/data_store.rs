@@ -7,7 +7,12 @@ use crate::account::{AccountData, GENESIS_KEYPAIR};
 use crate::genesis::create_genesis_write_set;
 use failure::prelude::*;
 use lazy_static::lazy_static;
-use std::{collections::HashMap, fs::File, io::prelude::*, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::prelude::*,
+    path::{Path, PathBuf},
+};
 use vm::def::{errors::*, file_format::CompiledModule};
 use vm::state_view::StateView;
 use vm::types::{
@@ -77,6 +82,27 @@ impl FakeDataStore {
         }
     }
 
+    /// Returns an iterator over the (key, value) pairs of this data store.
+    pub fn iter(&self) -> impl Iterator<Item = (&AccessPath, &Vec<u8>)> {
+        self.data.iter()
+    }
+
+    /// Loads a data store previously written by [`FakeDataStore::save`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut data = vec![];
+        File::open(path)?.read_to_end(&mut data)?;
+        let data: BTreeMap<AccessPath, Vec<u8>> = bincode::deserialize(&data)?;
+        Ok(FakeDataStore::new(data.into_iter().collect()))
+    }
+
+    /// Writes this data store to `path`, sorted by access path so that equal stores produce equal
+    /// files.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data: BTreeMap<&AccessPath, &Vec<u8>> = self.data.iter().collect();
+        File::create(path)?.write_all(&bincode::serialize(&data)?)?;
+        Ok(())
+    }
+
     /// Adds a [`CompiledModule`] to this data store.
     ///
     /// Does not do any sort of verification on the module.
@@ -6,7 +6,10 @@
 use crate::{
     account::{Account, AccountData},
     data_store::{FakeDataStore, GENESIS_WRITE_SET},
+    snapshot::Snapshot,
 };
+use failure::prelude::*;
+use std::path::Path;
 use vm::{
     def::file_format::CompiledModule,
     state_view::StateView,
@@ -14,7 +17,7 @@ use vm::{
         transaction::{SignedTransaction, TransactionOutput},
         vm_error::VMStatus,
         write_set::WriteSet,
-        AccessPath, ModuleId,
+        AccessPath, AccountAddress, ModuleId,
     },
     vm_runtime::{
         config::{VMConfig, VMPublishingOption},
@@ -44,6 +47,9 @@ impl FakeExecutor {
             config: VMConfig::default(),
             data_store: FakeDataStore::default(),
         };
+        if let Some(publishing_options) = publishing_options {
+            executor.config.publishing_options = publishing_options;
+        }
         executor.apply_write_set(write_set);
         executor
     }
@@ -71,6 +77,27 @@ impl FakeExecutor {
         }
     }
 
+    /// Creates an executor on top of an existing data store.
+    pub fn from_data_store(data_store: FakeDataStore) -> Self {
+        FakeExecutor {
+            config: VMConfig::default(),
+            data_store,
+        }
+    }
+
+    /// Creates an executor holding the state of a [`Snapshot`] file, so that transactions can be
+    /// replayed against the state of a chain.
+    pub fn from_snapshot(path: &Path) -> Result<Self> {
+        Ok(Self::from_data_store(
+            Snapshot::load(path)?.into_data_store(),
+        ))
+    }
+
+    /// Returns this executor's data store.
+    pub fn data_store(&self) -> &FakeDataStore {
+        &self.data_store
+    }
+
     /// Creates a number of [`Account`] instances all with the same balance and sequence number,
     /// and publishes them to this executor's data store.
     pub fn create_accounts(&mut self, size: usize, balance: u64, seq_num: u64) -> Vec<Account> {
@@ -110,6 +137,12 @@ impl FakeExecutor {
         Account::read_account_resource(&data_blob, account_type)
     }
 
+    /// Reads the account resource [`Value`] at `address`, or `None` if there is no account.
+    pub fn read_account_resource_at(&self, address: &AccountAddress) -> Option<Value> {
+        let data_blob = self.read_from_access_path(&AccessPath::new_for_account(*address))?;
+        Account::read_account_resource(&data_blob, Self::get_account_struct_def())
+    }
+
     /// Executes the given block of transactions.
     ///
     /// Typical tests will call this method and check that the output matches what was expected.
@@ -0,0 +1,291 @@
+//! Data-driven functional tests for Move IR.
+//!
+//! A test file is a sequence of transactions separated by `//! new-transaction` lines, run in
+//! order against the genesis state. Directives configure the test:
+//!
+//! - `//! account: <name>, <balance>` creates an account before the first transaction. The
+//!   accounts `default` and `association` always exist.
+//! - `//! sender: <name>` sets the sender of the current transaction, `default` otherwise.
+//! - `//! args: <arg>, ...` sets the arguments of the current script.
+//! - `//! check-specs` compiles every transaction with its specifications turned into runtime
+//!   checks.
+//!
+//! `{{<name>}}` anywhere in the code, arguments and checks is replaced by the address of the account
+//! `<name>`.
+//!
+//! `// check: <text>` lines list texts that must appear, in order, in the output of the current
+//! transaction: its compilation error, or its status, gas used, write set and events. A
+//! transaction without checks must execute successfully. Modules published by a successful
+//! transaction can be imported by the following ones.
+
+use crate::{
+    account::{Account, AccountData, AccountResource},
+    executor::FakeExecutor,
+    sandbox::describe_output,
+};
+use compiler::Compiler;
+use failure::prelude::*;
+use std::{collections::BTreeMap, fs, path::Path};
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    def::gas_schedule::{self, GasAlgebra},
+    types::{
+        parse_as_transaction_argument,
+        transaction::{Program, TransactionArgument, TransactionStatus},
+        vm_error::{ExecutionStatus, VMStatus},
+    },
+};
+
+/// The balance of the `default` account.
+pub const DEFAULT_BALANCE: u64 = 1_000_000_000;
+
+const DIRECTIVE: &str = "//!";
+const CHECK: &str = "// check:";
+
+/// A transaction of a test file, before account names are substituted.
+#[derive(Debug, Default)]
+pub struct TestTransaction {
+    pub sender: Option<String>,
+    pub args: Vec<String>,
+    pub code: String,
+    pub checks: Vec<String>,
+}
+
+/// A parsed test file.
+#[derive(Debug, Default)]
+pub struct TestFile {
+    pub accounts: Vec<(String, u64)>,
+    pub check_specs: bool,
+    pub transactions: Vec<TestTransaction>,
+}
+
+impl TestFile {
+    /// Parses the directives, code and checks of a test file.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut test = TestFile::default();
+        let mut transaction = TestTransaction::default();
+        for (idx, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with(DIRECTIVE) {
+                let directive = trimmed[DIRECTIVE.len()..].trim();
+                let (name, value) = match directive.find(':') {
+                    Some(colon) => (directive[..colon].trim(), directive[colon + 1..].trim()),
+                    None => (directive, ""),
+                };
+                match name {
+                    "new-transaction" => test.transactions.push(std::mem::replace(
+                        &mut transaction,
+                        TestTransaction::default(),
+                    )),
+                    "account" => test.accounts.push(
+                        parse_account(value).map_err(|e| format_err!("line {}: {}", idx + 1, e))?,
+                    ),
+                    "sender" => transaction.sender = Some(value.to_string()),
+                    "check-specs" => test.check_specs = true,
+                    "args" => {
+                        transaction.args = value
+                            .split(',')
+                            .map(|arg| arg.trim().to_string())
+                            .filter(|arg| !arg.is_empty())
+                            .collect()
+                    }
+                    _ => bail!("line {}: unknown directive {}", idx + 1, name),
+                }
+                continue;
+            }
+            if trimmed.starts_with(CHECK) {
+                transaction
+                    .checks
+                    .push(trimmed[CHECK.len()..].trim().to_string());
+            }
+            transaction.code.push_str(line);
+            transaction.code.push('\n');
+        }
+        test.transactions.push(transaction);
+        Ok(test)
+    }
+}
+
+fn parse_account(value: &str) -> Result<(String, u64)> {
+    let mut parts = value.split(',').map(str::trim);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(name), Some(balance), None) if !name.is_empty() => Ok((
+            name.to_string(),
+            balance
+                .parse()
+                .map_err(|_| format_err!("invalid balance {}", balance))?,
+        )),
+        _ => bail!("expect `account: <name>, <balance>`, got {}", value),
+    }
+}
+
+/// Runs the test file at `path`.
+pub fn run_file(path: &Path) -> Result<()> {
+    let text = fs::read_to_string(path).map_err(|e| format_err!("{}: {}", path.display(), e))?;
+    run_test(&text)
+}
+
+/// Runs the transactions of a test file and checks their outputs.
+pub fn run_test(text: &str) -> Result<()> {
+    let test = TestFile::parse(text)?;
+    let mut executor = FakeExecutor::from_genesis_file();
+
+    let mut accounts = BTreeMap::new();
+    accounts.insert("association".to_string(), Account::new_association());
+    let mut declared = test.accounts.clone();
+    if !declared.iter().any(|(name, _)| name == "default") {
+        declared.push(("default".to_string(), DEFAULT_BALANCE));
+    }
+    for (name, balance) in declared {
+        let account_data = AccountData::new(balance, 0);
+        executor.add_account_data(&account_data);
+        if accounts
+            .insert(name.clone(), account_data.into_account())
+            .is_some()
+        {
+            bail!("account {} is declared twice", name);
+        }
+    }
+
+    let mut modules: Vec<VerifiedModule> = vec![];
+    for (idx, transaction) in test.transactions.iter().enumerate() {
+        let sender_name = transaction
+            .sender
+            .as_ref()
+            .map_or("default", String::as_str);
+        let sender = accounts
+            .get(sender_name)
+            .ok_or_else(|| format_err!("transaction {}: unknown sender {}", idx, sender_name))?;
+        let code = substitute(&transaction.code, &accounts);
+        let args = transaction
+            .args
+            .iter()
+            .map(|arg| parse_as_transaction_argument(&substitute(arg, &accounts)))
+            .collect::<Result<Vec<TransactionArgument>>>()
+            .map_err(|e| format_err!("transaction {}: {}", idx, e))?;
+
+        let compiler = Compiler {
+            address: *sender.address(),
+            code: &code,
+            extra_deps: modules.clone(),
+            check_specs: test.check_specs,
+            ..Compiler::default()
+        };
+        let (log, executed) = match compiler.into_compiled_program() {
+            Err(err) => (format!("compilation error: {}", err), false),
+            Ok(compiled) => {
+                let mut script = vec![];
+                compiled.script.serialize(&mut script)?;
+                let mut blobs = vec![];
+                for module in &compiled.modules {
+                    let mut blob = vec![];
+                    module.serialize(&mut blob)?;
+                    blobs.push(blob);
+                }
+                let sequence_number = executor
+                    .read_account_resource_at(sender.address())
+                    .map_or(0, |resource| {
+                        AccountResource::read_sequence_number(&resource)
+                    });
+                let txn = sender.create_signed_txn_impl(
+                    *sender.address(),
+                    Program::new(script, blobs, args),
+                    sequence_number,
+                    gas_schedule::MAXIMUM_NUMBER_OF_GAS_UNITS.get(),
+                    0,
+                );
+                let output = executor.execute_transaction(txn);
+                if let TransactionStatus::Keep(_) = output.status() {
+                    executor.apply_write_set(output.write_set());
+                }
+                let executed = output.status()
+                    == &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed));
+                if executed {
+                    for module in compiled.modules {
+                        modules.push(
+                            VerifiedModule::new(module)
+                                .map_err(|(_, errors)| format_err!("{:?}", errors))?,
+                        );
+                    }
+                }
+                (describe_output(&output), executed)
+            }
+        };
+        let checks: Vec<String> = transaction
+            .checks
+            .iter()
+            .map(|check| substitute(check, &accounts))
+            .collect();
+        check(&checks, &log, executed)
+            .map_err(|e| format_err!("transaction {}: {}\n{}", idx, e, log))?;
+    }
+    Ok(())
+}
+
+/// Replaces `{{<name>}}` by the address of the account `<name>`.
+fn substitute(code: &str, accounts: &BTreeMap<String, Account>) -> String {
+    accounts
+        .iter()
+        .fold(code.to_string(), |code, (name, account)| {
+            code.replace(&format!("{{{{{}}}}}", name), &account.address().to_string())
+        })
+}
+
+/// Checks that `checks` appear in order in `log`, or that the transaction executed if there are
+/// none.
+fn check(checks: &[String], log: &str, executed: bool) -> Result<()> {
+    if checks.is_empty() {
+        if !executed {
+            bail!("expected the transaction to execute");
+        }
+        return Ok(());
+    }
+    let mut rest = log;
+    for expected in checks {
+        match rest.find(expected.as_str()) {
+            Some(pos) => rest = &rest[pos + expected.len()..],
+            None => bail!("check `{}` does not match", expected),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_directives() {
+        let test = TestFile::parse(
+            "//! account: alice, 1000\n\
+             //! sender: alice\n\
+             //! args: 1, {{alice}}\n\
+             main(a: u64, b: address) { return; }\n\
+             //! new-transaction\n\
+             main() { return; }\n\
+             // check: Aborted(42)\n",
+        )
+        .unwrap();
+        assert_eq!(test.accounts, vec![("alice".to_string(), 1000)]);
+        assert_eq!(test.transactions.len(), 2);
+        assert_eq!(test.transactions[0].sender, Some("alice".to_string()));
+        assert_eq!(test.transactions[0].args, vec!["1", "{{alice}}"]);
+        assert!(!test.transactions[0].code.contains("//!"));
+        assert_eq!(test.transactions[1].checks, vec!["Aborted(42)"]);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_directives() {
+        TestFile::parse("//! acount: alice, 10\n").unwrap_err();
+        TestFile::parse("//! account: alice\n").unwrap_err();
+    }
+
+    #[test]
+    fn checks_match_in_order() {
+        let checks = vec!["a".to_string(), "c".to_string()];
+        check(&checks, "abc", false).unwrap();
+        check(&checks, "cba", false).unwrap_err();
+        check(&[], "", true).unwrap();
+        check(&[], "", false).unwrap_err();
+    }
+}
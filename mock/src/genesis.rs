@@ -1,95 +1,557 @@
-use crate::account::{Account, AccountData, AccountResource, ALICE, BOB, GENESIS_KEYPAIR};
+//! Support for building genesis write sets.
+//!
+//! [`create_genesis_write_set`] builds the default genesis: the stdlib and the association
+//! account. [`GenesisBuilder`] adds accounts, modules and an initial validator set on top of it,
+//! and [`GenesisSpec`] describes such a genesis in a TOML or JSON file.
+
 use crate::data_store::FakeDataStore;
-use crypto::{signing, signing::KeyPair, PrivateKey, PublicKey};
-use stdlib::{
-    stdlib_modules,
-    transaction_scripts::{
-        CREATE_ACCOUNT_TXN_BODY, MINT_TXN_BODY, PEER_TO_PEER_TRANSFER_TXN_BODY,
-        ROTATE_AUTHENTICATION_KEY_TXN_BODY,
-    },
-};
+use canonical_serialization::{SimpleDeserializer, SimpleSerializer};
+use compiler::Compiler;
+use crypto::{PrivateKey, PublicKey};
+use failure::prelude::*;
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::{collections::HashSet, fs, path::Path};
+use stdlib::stdlib_modules;
 use vm::{
-    def::{access::ModuleAccess, transaction_metadata::TransactionMetadata},
+    bytecode_verifier::VerifiedModule,
+    def::{
+        access::ModuleAccess, errors::VMResult, file_format::CompiledModule,
+        transaction_metadata::TransactionMetadata,
+    },
     types::{
-        account_config,
-        transaction::{Program, RawTransaction, SignatureCheckedTransaction, TransactionArgument},
-        write_set::{WriteOp, WriteSet},
-        AccessPath, AccountAddress, ByteArray, SCRIPT_HASH_LENGTH,
+        account_config, write_set::WriteSet, AccountAddress, ByteArray, ModuleId,
+        SCRIPT_HASH_LENGTH,
     },
-    vm_runtime::vm_runtime_types::value::Local,
     vm_runtime::{
         code_cache::{
             module_adapter::FakeFetcher,
             module_cache::{BlockModuleCache, VMModuleCache},
         },
+        config::VMPublishingOption,
         data_cache::BlockDataCache,
         txn_executor::{TransactionExecutor, ACCOUNT_MODULE, COIN_MODULE},
+        vm_runtime_types::value::{Local, MutVal, Value},
     },
 };
 use vm_cache_map::Arena;
 
-pub fn create_genesis_write_set(private_key: &PrivateKey, public_key: PublicKey) -> WriteSet {
-    // TODO: Currently validator set is unused because MoveVM doesn't support collections for now.
-    //       Fix it later when we have collections.
-
-    const INIT_BALANCE: u64 = 1_000_000_000;
-    // Compile the needed stdlib modules.
-    let modules = stdlib_modules();
-    let arena = Arena::new();
-    let state_view = FakeDataStore::default();
-    let vm_cache = VMModuleCache::new(&arena);
-    let genesis_addr = account_config::association_address();
-    let genesis_auth_key = ByteArray::new(AccountAddress::from(public_key).to_vec());
-
-    let genesis_write_set = {
-        let fake_fetcher = FakeFetcher::new(modules.iter().map(|m| m.as_inner().clone()).collect());
+lazy_static! {
+    /// The ModuleId of the ValidatorSet module.
+    static ref VALIDATOR_SET_MODULE: ModuleId =
+        { ModuleId::new(account_config::core_code_address(), "ValidatorSet".to_string()) };
+}
+
+/// The balance of the association in the default genesis.
+pub const ASSOCIATION_BALANCE: u64 = 1_000_000_000;
+
+/// The number of validator keys the ValidatorSet module can hold.
+pub const MAX_VALIDATORS: usize = 10;
+
+pub fn create_genesis_write_set(_private_key: &PrivateKey, public_key: PublicKey) -> WriteSet {
+    GenesisBuilder::new(public_key)
+        .build()
+        .expect("default genesis should build")
+        .write_set
+}
+
+/// The keys of a validator in the initial validator set.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ValidatorInfo {
+    pub account_address: AccountAddress,
+    pub consensus_public_key: Vec<u8>,
+    pub network_signing_public_key: Vec<u8>,
+    pub network_identity_public_key: Vec<u8>,
+}
+
+impl ValidatorInfo {
+    /// Returns the `ValidatorSet.ValidatorPublicKeys` value for these keys.
+    fn to_local(&self) -> Local {
+        // Fields are laid out in declaration order.
+        Local::struct_(vec![
+            MutVal::new(Value::Address(self.account_address)),
+            MutVal::new(Value::ByteArray(ByteArray::new(
+                self.consensus_public_key.clone(),
+            ))),
+            MutVal::new(Value::ByteArray(ByteArray::new(
+                self.network_identity_public_key.clone(),
+            ))),
+            MutVal::new(Value::ByteArray(ByteArray::new(
+                self.network_signing_public_key.clone(),
+            ))),
+        ])
+    }
+}
+
+/// A genesis write set together with the publishing option the VM should run with.
+#[derive(Clone, Debug)]
+pub struct Genesis {
+    pub write_set: WriteSet,
+    pub publishing_option: VMPublishingOption,
+}
+
+impl Genesis {
+    /// Serializes the write set canonically.
+    pub fn write_set_bytes(&self) -> Vec<u8> {
+        SimpleSerializer::<Vec<u8>>::serialize(&self.write_set).expect("serialization failed")
+    }
+}
+
+/// Decodes a canonical serialized genesis write set.
+pub fn decode_genesis_write_set(data: &[u8]) -> Result<WriteSet> {
+    SimpleDeserializer::deserialize(data)
+}
+
+/// Builds a genesis write set.
+///
+/// The genesis always publishes the stdlib and creates the association account, which holds the
+/// mint capability and is authenticated by the given key.
+#[derive(Clone, Debug)]
+pub struct GenesisBuilder {
+    association_key: PublicKey,
+    association_balance: u64,
+    accounts: Vec<(PublicKey, u64)>,
+    modules: Vec<VerifiedModule>,
+    validators: Vec<ValidatorInfo>,
+    publishing_option: VMPublishingOption,
+}
+
+impl GenesisBuilder {
+    /// Creates a builder for the default genesis with `association_key` as the association key.
+    pub fn new(association_key: PublicKey) -> Self {
+        GenesisBuilder {
+            association_key,
+            association_balance: ASSOCIATION_BALANCE,
+            accounts: vec![],
+            modules: vec![],
+            validators: vec![],
+            publishing_option: VMPublishingOption::Open,
+        }
+    }
+
+    /// Sets the balance minted to the association.
+    pub fn association_balance(mut self, balance: u64) -> Self {
+        self.association_balance = balance;
+        self
+    }
+
+    /// Creates the account authenticated by `public_key` with `balance`.
+    pub fn account(mut self, public_key: PublicKey, balance: u64) -> Self {
+        self.accounts.push((public_key, balance));
+        self
+    }
+
+    /// Publishes `module` next to the stdlib.
+    pub fn module(mut self, module: VerifiedModule) -> Self {
+        self.modules.push(module);
+        self
+    }
+
+    /// Adds a validator to the initial validator set.
+    pub fn validator(mut self, validator: ValidatorInfo) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    /// Sets the publishing option of the genesis.
+    pub fn publishing_option(mut self, publishing_option: VMPublishingOption) -> Self {
+        self.publishing_option = publishing_option;
+        self
+    }
+
+    /// Runs the genesis and returns its write set.
+    pub fn build(self) -> Result<Genesis> {
+        if self.validators.len() > MAX_VALIDATORS {
+            bail!(
+                "at most {} validators are supported, got {}",
+                MAX_VALIDATORS,
+                self.validators.len()
+            );
+        }
+        let stdlib = stdlib_modules();
+        let arena = Arena::new();
+        let state_view = FakeDataStore::default();
+        let vm_cache = VMModuleCache::new(&arena);
+        let genesis_addr = account_config::association_address();
+        let genesis_auth_key = ByteArray::new(AccountAddress::from(self.association_key).to_vec());
+
+        let fake_fetcher = FakeFetcher::new(stdlib.iter().map(|m| m.as_inner().clone()).collect());
         let data_cache = BlockDataCache::new(&state_view);
         let block_cache = BlockModuleCache::new(&vm_cache, fake_fetcher);
-        {
-            let mut txn_data = TransactionMetadata::default();
-            txn_data.sender = genesis_addr;
-
-            let mut txn_executor = TransactionExecutor::new(&block_cache, &data_cache, txn_data);
-            txn_executor.create_account(genesis_addr).unwrap().unwrap();
-            txn_executor
-                .execute_function(&COIN_MODULE, "initialize", vec![])
-                .unwrap()
-                .unwrap();
-
-            txn_executor
-                .execute_function(
+
+        let mut txn_data = TransactionMetadata::default();
+        txn_data.sender = genesis_addr;
+        let mut txn_executor = TransactionExecutor::new(&block_cache, &data_cache, txn_data);
+        check(
+            txn_executor.create_account(genesis_addr),
+            "creating the association",
+        )?;
+        check(
+            txn_executor.execute_function(&COIN_MODULE, "initialize", vec![]),
+            "initializing LibraCoin",
+        )?;
+        check(
+            txn_executor.execute_function(
+                &ACCOUNT_MODULE,
+                "mint_to_address",
+                vec![
+                    Local::address(genesis_addr),
+                    Local::u64(self.association_balance),
+                ],
+            ),
+            "minting to the association",
+        )?;
+        check(
+            txn_executor.execute_function(
+                &ACCOUNT_MODULE,
+                "rotate_authentication_key",
+                vec![Local::bytearray(genesis_auth_key)],
+            ),
+            "setting the association key",
+        )?;
+
+        // The address of an account is derived from its key, so it starts authenticated by it.
+        for (public_key, balance) in &self.accounts {
+            let address = AccountAddress::from(*public_key);
+            check(
+                txn_executor.execute_function(
                     &ACCOUNT_MODULE,
                     "mint_to_address",
-                    vec![Local::address(genesis_addr), Local::u64(INIT_BALANCE)],
-                )
-                .unwrap()
-                .unwrap();
+                    vec![Local::address(address), Local::u64(*balance)],
+                ),
+                &format!("creating account {}", address),
+            )?;
+        }
 
-            txn_executor
-                .execute_function(
-                    &ACCOUNT_MODULE,
-                    "rotate_authentication_key",
-                    vec![Local::bytearray(genesis_auth_key)],
-                )
-                .unwrap()
-                .unwrap();
-
-            let stdlib_modules = modules
-                .iter()
-                .map(|m| {
-                    let mut module_vec = vec![];
-                    m.serialize(&mut module_vec).unwrap();
-                    (m.self_id(), module_vec)
-                })
-                .collect();
-
-            txn_executor
-                .make_write_set(stdlib_modules, Ok(Ok(())))
-                .unwrap()
-                .write_set()
-                .clone()
-                .into_mut()
+        if !self.validators.is_empty() {
+            let mut args = vec![Local::u64(self.validators.len() as u64)];
+            for idx in 0..MAX_VALIDATORS {
+                let validator = self.validators.get(idx).cloned().unwrap_or_default();
+                args.push(validator.to_local());
+            }
+            check(
+                txn_executor.execute_function(&VALIDATOR_SET_MODULE, "publish_validator_set", args),
+                "publishing the validator set",
+            )?;
+        }
+
+        let mut published = HashSet::new();
+        let mut modules = vec![];
+        for module in stdlib.iter().chain(self.modules.iter()) {
+            if !published.insert(module.self_id()) {
+                bail!("module {} is published twice", module.self_id());
+            }
+            let mut blob = vec![];
+            module.serialize(&mut blob)?;
+            modules.push((module.self_id(), blob));
+        }
+
+        let write_set = txn_executor
+            .make_write_set(modules, Ok(Ok(())))
+            .map_err(|err| format_err!("making the write set failed: {:?}", err))?
+            .write_set()
+            .clone();
+        Ok(Genesis {
+            write_set,
+            publishing_option: self.publishing_option,
+        })
+    }
+}
+
+fn check(result: VMResult<()>, step: &str) -> Result<()> {
+    match result {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(err)) => bail!("{} failed: {:?}", step, err),
+        Err(err) => bail!("{} failed: {:?}", step, err),
+    }
+}
+
+/// A genesis described in a TOML or JSON file:
+///
+/// ```toml
+/// association_balance = 1000000000
+/// publishing_option = "open" # or "custom_scripts", or { locked = ["<script hash>"] }
+///
+/// [[accounts]]
+/// public_key = "0x..."
+/// balance = 1000
+///
+/// [[modules]]
+/// source = "token.mvir" # compiled under `address`, or `bytecode = "token.mv"`
+/// address = "0x..."
+///
+/// [[validators]]
+/// account_address = "0x..."
+/// consensus_public_key = "0x..."
+/// network_signing_public_key = "0x..."
+/// network_identity_public_key = "0x..."
+/// ```
+///
+/// Paths are relative to the spec file.
+#[derive(Debug, Default, Deserialize)]
+pub struct GenesisSpec {
+    /// The association key, the default genesis key if not given.
+    pub association_public_key: Option<String>,
+    pub association_balance: Option<u64>,
+    pub publishing_option: Option<PublishingOptionSpec>,
+    #[serde(default)]
+    pub accounts: Vec<AccountSpec>,
+    #[serde(default)]
+    pub modules: Vec<ModuleSpec>,
+    #[serde(default)]
+    pub validators: Vec<ValidatorSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublishingOptionSpec {
+    Open,
+    CustomScripts,
+    Locked(Vec<String>),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccountSpec {
+    pub public_key: String,
+    pub balance: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModuleSpec {
+    pub source: Option<String>,
+    pub bytecode: Option<String>,
+    /// The address a source module is compiled under, the association by default.
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidatorSpec {
+    pub account_address: String,
+    pub consensus_public_key: String,
+    pub network_signing_public_key: String,
+    pub network_identity_public_key: String,
+}
+
+impl GenesisSpec {
+    /// Loads a spec, as JSON if the file ends in `.json` and as TOML otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data =
+            fs::read_to_string(path).map_err(|e| format_err!("{}: {}", path.display(), e))?;
+        let spec = if path.extension().map_or(false, |ext| ext == "json") {
+            serde_json::from_str(&data).map_err(|e| format_err!("{}: {}", path.display(), e))?
+        } else {
+            toml::from_str(&data).map_err(|e| format_err!("{}: {}", path.display(), e))?
+        };
+        Ok(spec)
+    }
+
+    /// Returns a builder for this spec, resolving paths against `base_dir`.
+    ///
+    /// Source modules are compiled in order, so each can import the ones before it.
+    pub fn into_builder(self, default_key: PublicKey, base_dir: &Path) -> Result<GenesisBuilder> {
+        let association_key = match &self.association_public_key {
+            Some(key) => parse_public_key(key)?,
+            None => default_key,
+        };
+        let mut builder = GenesisBuilder::new(association_key);
+        if let Some(balance) = self.association_balance {
+            builder = builder.association_balance(balance);
         }
+        if let Some(option) = self.publishing_option {
+            builder = builder.publishing_option(option.into_publishing_option()?);
+        }
+        for account in &self.accounts {
+            builder = builder.account(parse_public_key(&account.public_key)?, account.balance);
+        }
+        for module in &self.modules {
+            let module = module.load(base_dir, &builder.modules)?;
+            builder = builder.module(module);
+        }
+        for validator in &self.validators {
+            builder = builder.validator(validator.to_validator_info()?);
+        }
+        Ok(builder)
+    }
+}
+
+impl PublishingOptionSpec {
+    fn into_publishing_option(self) -> Result<VMPublishingOption> {
+        Ok(match self {
+            PublishingOptionSpec::Open => VMPublishingOption::Open,
+            PublishingOptionSpec::CustomScripts => VMPublishingOption::CustomScripts,
+            PublishingOptionSpec::Locked(hashes) => {
+                let mut whitelist = HashSet::new();
+                for hash in hashes {
+                    let data = parse_hex(&hash)?;
+                    if data.len() != SCRIPT_HASH_LENGTH {
+                        bail!("invalid script hash {}", hash);
+                    }
+                    let mut script_hash = [0u8; SCRIPT_HASH_LENGTH];
+                    script_hash.copy_from_slice(&data);
+                    whitelist.insert(script_hash);
+                }
+                VMPublishingOption::Locked(whitelist)
+            }
+        })
+    }
+}
+
+impl ModuleSpec {
+    fn load(&self, base_dir: &Path, deps: &[VerifiedModule]) -> Result<VerifiedModule> {
+        let module = match (&self.source, &self.bytecode) {
+            (Some(source), None) => {
+                let path = base_dir.join(source);
+                let code = fs::read_to_string(&path)
+                    .map_err(|e| format_err!("{}: {}", path.display(), e))?;
+                let address = match &self.address {
+                    Some(address) => AccountAddress::from_hex_literal(address)?,
+                    None => account_config::association_address(),
+                };
+                let compiler = Compiler {
+                    address,
+                    code: &code,
+                    extra_deps: deps.to_vec(),
+                    ..Compiler::default()
+                };
+                let mut program = compiler
+                    .into_compiled_program()
+                    .map_err(|e| format_err!("{}: {}", path.display(), e))?;
+                if program.modules.len() != 1 {
+                    bail!("{} must define exactly one module", path.display());
+                }
+                program.modules.remove(0)
+            }
+            (None, Some(bytecode)) => {
+                let path = base_dir.join(bytecode);
+                let data = fs::read(&path).map_err(|e| format_err!("{}: {}", path.display(), e))?;
+                CompiledModule::deserialize(&data)
+                    .map_err(|e| format_err!("{}: {:?}", path.display(), e))?
+            }
+            _ => bail!("a module needs either a source or a bytecode file"),
+        };
+        VerifiedModule::new(module).map_err(|(module, errors)| {
+            format_err!("module {} does not verify: {:?}", module.self_id(), errors)
+        })
+    }
+}
+
+impl ValidatorSpec {
+    fn to_validator_info(&self) -> Result<ValidatorInfo> {
+        Ok(ValidatorInfo {
+            account_address: AccountAddress::from_hex_literal(&self.account_address)?,
+            consensus_public_key: parse_hex(&self.consensus_public_key)?,
+            network_signing_public_key: parse_hex(&self.network_signing_public_key)?,
+            network_identity_public_key: parse_hex(&self.network_identity_public_key)?,
+        })
+    }
+}
+
+fn parse_hex(data: &str) -> Result<Vec<u8>> {
+    hex::decode(data.trim_start_matches("0x"))
+        .map_err(|e| format_err!("invalid hex {}: {}", data, e))
+}
+
+fn parse_public_key(data: &str) -> Result<PublicKey> {
+    Ok(PublicKey::from_slice(&parse_hex(data)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        account::{Account, AccountResource, GENESIS_KEYPAIR},
+        executor::FakeExecutor,
     };
-    genesis_write_set.freeze().unwrap()
+    use canonical_serialization::CanonicalDeserializer;
+    use vm::types::{AccessPath, Accesses, StructTag};
+
+    #[test]
+    fn default_genesis_is_unchanged_by_builder() {
+        let genesis = GenesisBuilder::new(GENESIS_KEYPAIR.1).build().unwrap();
+        let executor = FakeExecutor::from_genesis(&genesis.write_set, None);
+        let association = executor
+            .read_account_resource_at(&account_config::association_address())
+            .unwrap();
+        assert_eq!(
+            AccountResource::read_balance(&association),
+            ASSOCIATION_BALANCE
+        );
+    }
+
+    #[test]
+    fn build_with_accounts_and_validators() {
+        let account = Account::new();
+        let genesis = GenesisBuilder::new(GENESIS_KEYPAIR.1)
+            .association_balance(5)
+            .account(account.pubkey, 1_000)
+            .validator(ValidatorInfo {
+                account_address: *account.address(),
+                consensus_public_key: vec![1; 32],
+                ..ValidatorInfo::default()
+            })
+            .build()
+            .unwrap();
+
+        let data = genesis.write_set_bytes();
+        let write_set = decode_genesis_write_set(&data).unwrap();
+        assert_eq!(write_set, genesis.write_set);
+
+        let executor = FakeExecutor::from_genesis(&write_set, None);
+        let resource = executor
+            .read_account_resource_at(account.address())
+            .unwrap();
+        assert_eq!(AccountResource::read_balance(&resource), 1_000);
+        assert_eq!(
+            AccountResource::read_auth_key(&resource),
+            *account.address()
+        );
+
+        // The association publishes the validator set, whose first field is its size.
+        let validator_set_path = AccessPath::new(
+            account_config::association_address(),
+            AccessPath::resource_access_vec(
+                &StructTag {
+                    address: account_config::core_code_address(),
+                    module: VALIDATOR_SET_MODULE.name().to_string(),
+                    name: "T".to_string(),
+                    type_params: vec![],
+                },
+                &Accesses::empty(),
+            ),
+        );
+        let validator_set = executor
+            .read_from_access_path(&validator_set_path)
+            .expect("the validator set should be published");
+        let array_size = SimpleDeserializer::new(&validator_set)
+            .decode_u64()
+            .unwrap();
+        assert_eq!(array_size, 1);
+    }
+
+    #[test]
+    fn reject_too_many_validators() {
+        let mut builder = GenesisBuilder::new(GENESIS_KEYPAIR.1);
+        for _ in 0..=MAX_VALIDATORS {
+            builder = builder.validator(ValidatorInfo::default());
+        }
+        builder.build().unwrap_err();
+    }
+
+    #[test]
+    fn parse_spec() {
+        let spec: GenesisSpec = toml::from_str(
+            "association_balance = 10\n\
+             publishing_option = \"custom_scripts\"\n\
+             [[accounts]]\n\
+             public_key = \"0x01add5624932fc6e5e82ea4b8b4217c2ea4372a1e4fbc9d910a38b2514931166\"\n\
+             balance = 7\n",
+        )
+        .unwrap();
+        let builder = spec
+            .into_builder(GENESIS_KEYPAIR.1, Path::new("."))
+            .unwrap();
+        assert_eq!(builder.association_balance, 10);
+        assert_eq!(builder.accounts.len(), 1);
+        assert!(builder.publishing_option.custom_scripts_only());
+    }
 }
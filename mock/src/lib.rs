@@ -3,8 +3,11 @@ pub mod common;
 pub mod compile;
 pub mod data_store;
 pub mod executor;
+pub mod functional;
 pub mod gas_costs;
 pub mod genesis;
+pub mod sandbox;
+pub mod snapshot;
 
 use compiler::Compiler;
 use data_store::FakeDataStore;
@@ -1,238 +1,133 @@
-use mock::account::{Account, AccountData, AccountResource, ALICE, BOB, GENESIS_KEYPAIR};
-use mock::common::*;
-use mock::compile::*;
-use mock::executor::FakeExecutor;
-use mock::*;
-use vm::{bytecode_verifier::VerifiedModule, types::transaction::SignedTransaction};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use mock::sandbox::{describe_output, Sandbox};
+use std::path::Path;
+use vm::types::{
+    account_config::association_address, parse_as_address, parse_as_transaction_argument,
+    transaction::TransactionArgument, AccountAddress,
+};
 
-use lazy_static::lazy_static;
-use stdlib::stdlib_modules;
-
-lazy_static! {
-    pub static ref GENESIS_ACCOUNT: Account =
-        { Account::with_keypair(GENESIS_KEYPAIR.0.clone(), GENESIS_KEYPAIR.1.clone()) };
-    pub static ref ALICE_ACCOUNT: Account =
-        { Account::with_keypair(ALICE.0.clone(), ALICE.1.clone()) };
-    pub static ref BOB_ACCOUNT: Account = { Account::with_keypair(BOB.0.clone(), BOB.1.clone()) };
-}
-
-fn create_account() {
-    let mut executor = FakeExecutor::from_genesis_file();
-    let sender = AccountData::new_with_account(GENESIS_ACCOUNT.clone(), 2_000_000, 1);
-
-    // add module
-    //	for module in stdlib_modules() {
-    //		let compiledModule = module.as_inner();
-    //		println!("module {:?}", compiledModule.self_id());
-    //		executor.add_module(&compiledModule.self_id(), compiledModule);
-    //	}
-
-    executor.add_account_data(&sender);
-    let new_account = Account::new();
-
-    let initial_amount = 1000;
-    let txn = create_account_txn(sender.account(), &new_account, 1, initial_amount);
-
-    println!("txn : {:?}", txn);
-    let output = executor.execute_block(vec![txn]);
-    let txn_output = output.get(0).expect("must have a transaction output");
-
-    println!("output : {:?}", txn_output);
-    println!("write set {:?}", txn_output.write_set());
-}
-
-fn simple_arithmetic() {
-    let program = String::from(
-        "
-        modules:
-        module M {
-            public max(a: u64, b: u64): u64 {
-                if (copy(a) > copy(b)) {
-                    return copy(a);
-                } else {
-                    return copy(b);
-                }
-                return 0;
-            }
-
-            public sum(a: u64, b: u64): u64 {
-                let c: u64;
-                c = copy(a) + copy(b);
-                return copy(c);
+fn main() {
+    let args = App::new("move-sandbox")
+        .about("Publishes modules and runs scripts against a local state directory.")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("state")
+                .long("state")
+                .takes_value(true)
+                .default_value("sandbox")
+                .help("the directory holding the sandbox state"),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .about("create the state from genesis")
+                .arg(
+                    Arg::with_name("snapshot")
+                        .long("snapshot")
+                        .takes_value(true)
+                        .help("start from a snapshot of a chain instead of genesis"),
+                ),
+        )
+        .subcommand(SubCommand::with_name("reset").about("reset the state to genesis"))
+        .subcommand(
+            SubCommand::with_name("publish")
+                .about("publish the modules in a source or compiled module file")
+                .arg(Arg::with_name("file").required(true))
+                .arg(sender_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("run a source or compiled script")
+                .arg(Arg::with_name("script").required(true))
+                .arg(
+                    Arg::with_name("args")
+                        .long("args")
+                        .takes_value(true)
+                        .multiple(true)
+                        .help("script arguments: addresses, integers, b\"<hex>\" or \"strings\""),
+                )
+                .arg(sender_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("view")
+                .about("show what is stored under an address")
+                .arg(Arg::with_name("address").required(true)),
+        )
+        .get_matches();
+
+    let state = Path::new(args.value_of("state").expect("state has a default"));
+    match args.subcommand() {
+        ("init", Some(matches)) => {
+            match matches.value_of("snapshot") {
+                Some(snapshot) => Sandbox::init_from_snapshot(state, Path::new(snapshot)),
+                None => Sandbox::init(state),
             }
+            .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+            println!("initialized {}", state.display());
         }
-        script:
-        import 0x0000000000000000000000000000000000000000000000000000000000000000.M;
-
-        main() {
-            let a: u64;
-            let b: u64;
-            let c: u64;
-            let d: u64;
-
-            a = 10;
-            b = 2;
-            c = M.max(copy(a), copy(b));
-            d = M.sum(copy(a), copy(b));
-            assert(copy(c) == 10, 42);
-            assert(copy(d) == 12, 42);
-            return;
+        ("reset", _) => {
+            Sandbox::reset(state).unwrap_or_else(|err| exit_with_error(&err.to_string()));
+            println!("reset {} to genesis", state.display());
         }
-        ",
-    );
-    let ret = compile_and_execute(&program, vec![]);
-    println!("ret : {:?}", ret);
-}
-
-fn upload_script_and_execute() {
-    let mut executor = FakeExecutor::from_genesis_file();
-
-    let sender = AccountData::new_with_account(ALICE_ACCOUNT.clone(), 2_000_000, 1);
-    executor.add_account_data(&sender);
-
-    let program = String::from(
-        "
-        module M {
-            public max(a: u64, b: u64): u64 {
-                if (copy(a) > copy(b)) {
-                    return copy(a);
-                } else {
-                    return copy(b);
-                }
-                return 0;
-            }
-
-            public sum(a: u64, b: u64): u64 {
-                let c: u64;
-                c = copy(a) + copy(b);
-                return copy(c);
-            }
+        ("publish", Some(matches)) => {
+            let path = Path::new(matches.value_of("file").expect("file is required"));
+            let output = open(state)
+                .publish(parse_sender(matches), path)
+                .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+            print!("{}", describe_output(&output));
         }
-		",
-    );
-
-    // compile must with address. why?
-    let compiled_program = compile_inner_program_with_address(sender.address(), &program);
-    let program = compile_program_with_address(sender.address(), &program, vec![]);
-
-    // make transaction
-    let txn = sender.account().create_signed_txn_with_program(
-        program,
-        1,
-        gas_costs::TXN_RESERVED, // this is a default for gas
-        0,                       // this is a default for gas
-    );
-
-    // execute transaction
-    let txns: Vec<SignedTransaction> = vec![txn];
-    let output = executor.execute_block(txns);
-    let txn_output = output.get(0).expect("must have a transaction output");
-    println!("output {:?}", txn_output);
-
-    executor.apply_write_set(txn_output.write_set());
-
-    // call module
-    let program = String::from(
-        "
-        import 0x826b443135b47a8bfdf090f0088a427aeae1de70c6cf833823715c07539bcc3a.M;
-
-        main() {
-            let a: u64;
-            let b: u64;
-            let c: u64;
-            let d: u64;
-
-            a = 10;
-            b = 2;
-            c = M.max(copy(a), copy(b));
-            d = M.sum(copy(a), copy(b));
-            assert(copy(c) == 10, 42);
-            assert(copy(d) == 12, 42);
-            return;
+        ("run", Some(matches)) => {
+            let path = Path::new(matches.value_of("script").expect("script is required"));
+            let output = open(state)
+                .run(parse_sender(matches), path, parse_args(matches))
+                .unwrap_or_else(|err| exit_with_error(&err.to_string()));
+            print!("{}", describe_output(&output));
         }
-		",
-    );
-
-    let deps: Vec<VerifiedModule> = compiled_program
-        .modules
-        .into_iter()
-        .map(|m| VerifiedModule::new(m).unwrap())
-        .collect();
-    let program = compile_program_with_deps(sender.address(), &program, vec![], deps);
-
-    // make transaction
-    let txn = sender.account().create_signed_txn_with_program(
-        program,
-        2,
-        gas_costs::TXN_RESERVED, // this is a default for gas
-        0,                       // this is a default for gas
-    );
-
-    // execute transaction
-    let txns: Vec<SignedTransaction> = vec![txn];
-    let output = executor.execute_block(txns);
-    let txn_output = output.get(0).expect("must have a transaction output");
-    println!("output {:?}", txn_output);
+        ("view", Some(matches)) => {
+            let address = parse_address(matches.value_of("address").expect("address is required"));
+            print!("{}", open(state).view(&address));
+        }
+        _ => unreachable!("a subcommand is required"),
+    }
 }
 
-fn transfer() {
-    let mut executor = FakeExecutor::from_genesis_file();
-
-    let sender = AccountData::new_with_account(ALICE_ACCOUNT.clone(), 2_000_000, 1);
-    let receiver = AccountData::new_with_account(BOB_ACCOUNT.clone(), 50_000, 1);
-
-    executor.add_account_data(&sender);
+fn sender_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("sender")
+        .long("sender")
+        .takes_value(true)
+        .help("the sender address, the association by default")
+}
 
-    executor.add_account_data(&receiver);
+fn open(state: &Path) -> Sandbox {
+    Sandbox::open(state).unwrap_or_else(|err| exit_with_error(&err.to_string()))
+}
 
-    let value = executor.read_account_resource(sender.account());
-    if let Some(value) = value {
-        println!("value: {:?}", AccountResource::read_balance(&value));
-    }
+fn parse_sender(args: &ArgMatches) -> AccountAddress {
+    args.value_of("sender")
+        .map(parse_address)
+        .unwrap_or_else(association_address)
+}
 
-    let value = executor.read_account_resource(receiver.account());
-    if let Some(value) = value {
-        println!("value: {:?}", AccountResource::read_balance(&value));
+fn parse_address(address: &str) -> AccountAddress {
+    match parse_as_address(address) {
+        Ok(TransactionArgument::Address(address)) => address,
+        _ => exit_with_error(&format!("invalid address: {}", address)),
     }
+}
 
-    // transfer
-    let transfer_amount = 1_000;
-    let txn = peer_to_peer_txn(sender.account(), receiver.account(), 1, transfer_amount);
-
-    println!("{:?}", txn);
-
-    // execute transaction
-    let txns: Vec<SignedTransaction> = vec![txn];
-    let output = executor.execute_block(txns);
-    let txn_output = output.get(0).expect("must have a transaction output");
-
-    println!("{:?}", output);
-
-    executor.apply_write_set(txn_output.write_set());
-
-    let gas = txn_output.gas_used();
-    let sender_balance = 2_000_000 - transfer_amount - gas;
-    let receiver_balance = 50_000 + transfer_amount;
-
-    let updated_sender = executor
-        .read_account_resource(sender.account())
-        .expect("sender must exist");
-    let updated_receiver = executor
-        .read_account_resource(receiver.account())
-        .expect("receiver must exist");
-
-    assert_eq!(
-        receiver_balance,
-        AccountResource::read_balance(&updated_receiver)
-    );
-    assert_eq!(
-        sender_balance,
-        AccountResource::read_balance(&updated_sender)
-    );
+fn parse_args(args: &ArgMatches) -> Vec<TransactionArgument> {
+    args.values_of("args")
+        .map(|values| {
+            values
+                .map(|value| {
+                    parse_as_transaction_argument(value)
+                        .unwrap_or_else(|err| exit_with_error(&err.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
 }
-fn main() {
-    //	create_account();
-    // simple_arithmetic();
-    //	transfer();
-    upload_script_and_execute();
+
+fn exit_with_error(err: &str) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
 }
@@ -0,0 +1,394 @@
+//! A sandbox keeping the state of a [`FakeExecutor`] in a directory between invocations, so that
+//! modules can be published and scripts run one step at a time against genesis.
+//!
+//! The sandbox does not care about keys: each transaction is signed with a throwaway key the
+//! sender's account only holds while it runs, and accounts that do not exist yet are created with
+//! [`SENDER_BALANCE`].
+
+use crate::{
+    account::{Account, AccountData, AccountResource},
+    compile::compile_script,
+    data_store::{FakeDataStore, GENESIS_WRITE_SET},
+    executor::FakeExecutor,
+};
+use compiler::Compiler;
+use failure::prelude::*;
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+use vm::{
+    bytecode_verifier::VerifiedModule,
+    def::{
+        access::ModuleAccess,
+        file_format::{CompiledModule, CompiledScript},
+        gas_schedule::{self, GasAlgebra},
+    },
+    types::{
+        transaction::{Program, TransactionArgument, TransactionOutput, TransactionStatus},
+        write_set::{WriteOp, WriteSetMut},
+        AccessPath, AccountAddress,
+    },
+};
+
+/// The file in the state directory holding the serialized [`FakeDataStore`].
+pub const STATE_FILE: &str = "state.bin";
+
+/// The balance of accounts the sandbox creates for unknown senders.
+pub const SENDER_BALANCE: u64 = 1_000_000_000;
+
+/// A [`FakeExecutor`] backed by a state directory.
+pub struct Sandbox {
+    state: PathBuf,
+    executor: FakeExecutor,
+}
+
+impl Sandbox {
+    /// Creates a sandbox holding the genesis state in `dir`. Fails if `dir` already has a state.
+    pub fn init(dir: &Path) -> Result<Self> {
+        Self::check_uninitialized(dir)?;
+        Self::create(dir, FakeExecutor::from_genesis(&GENESIS_WRITE_SET, None))
+    }
+
+    /// Creates a sandbox holding the state of the snapshot file `snapshot` in `dir`, to replay
+    /// transactions against the state of a chain. Fails if `dir` already has a state.
+    pub fn init_from_snapshot(dir: &Path, snapshot: &Path) -> Result<Self> {
+        Self::check_uninitialized(dir)?;
+        Self::create(dir, FakeExecutor::from_snapshot(snapshot)?)
+    }
+
+    /// Replaces the state in `dir` by the genesis state.
+    pub fn reset(dir: &Path) -> Result<Self> {
+        Self::create(dir, FakeExecutor::from_genesis(&GENESIS_WRITE_SET, None))
+    }
+
+    /// Opens the sandbox in `dir`.
+    pub fn open(dir: &Path) -> Result<Self> {
+        let state = dir.join(STATE_FILE);
+        if !state.exists() {
+            bail!("{} is not initialized, run init first", dir.display());
+        }
+        let executor = FakeExecutor::from_data_store(FakeDataStore::load(&state)?);
+        Ok(Sandbox { state, executor })
+    }
+
+    fn check_uninitialized(dir: &Path) -> Result<()> {
+        if dir.join(STATE_FILE).exists() {
+            bail!("{} is already initialized, use reset", dir.display());
+        }
+        Ok(())
+    }
+
+    fn create(dir: &Path, executor: FakeExecutor) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let sandbox = Sandbox {
+            state: dir.join(STATE_FILE),
+            executor,
+        };
+        sandbox.save()?;
+        Ok(sandbox)
+    }
+
+    fn save(&self) -> Result<()> {
+        self.executor.data_store().save(&self.state)
+    }
+
+    /// Returns every module published in the sandbox, genesis modules included.
+    pub fn modules(&self) -> Vec<VerifiedModule> {
+        self.executor
+            .data_store()
+            .iter()
+            .filter_map(|(access_path, blob)| {
+                let module = CompiledModule::deserialize(blob).ok()?;
+                if AccessPath::from(&module.self_id()) != *access_path {
+                    return None;
+                }
+                VerifiedModule::new(module).ok()
+            })
+            .collect()
+    }
+
+    /// Publishes the modules in `path`, a module source or a compiled module, as `sender`.
+    pub fn publish(&mut self, sender: AccountAddress, path: &Path) -> Result<TransactionOutput> {
+        let program = self.load_program(sender, path, vec![])?;
+        if program.modules().is_empty() {
+            bail!("{} does not define any module", path.display());
+        }
+        self.execute(sender, program)
+    }
+
+    /// Runs the script in `path`, a source or a compiled script, as `sender` with `args`.
+    pub fn run(
+        &mut self,
+        sender: AccountAddress,
+        path: &Path,
+        args: Vec<TransactionArgument>,
+    ) -> Result<TransactionOutput> {
+        let program = self.load_program(sender, path, args)?;
+        self.execute(sender, program)
+    }
+
+    /// Compiles the source in `path` against the modules in the sandbox, or wraps it in a
+    /// program if it is already compiled.
+    fn load_program(
+        &self,
+        sender: AccountAddress,
+        path: &Path,
+        args: Vec<TransactionArgument>,
+    ) -> Result<Program> {
+        let data = fs::read(path).map_err(|e| format_err!("{}: {}", path.display(), e))?;
+        if CompiledModule::deserialize(&data).is_ok() {
+            let script = compile_script("main() { return; }");
+            return Ok(Program::new(script, vec![data], args));
+        }
+        if CompiledScript::deserialize(&data).is_ok() {
+            return Ok(Program::new(data, vec![], args));
+        }
+        let code = String::from_utf8(data)
+            .map_err(|_| format_err!("{} is neither bytecode nor source", path.display()))?;
+        let compiler = Compiler {
+            address: sender,
+            code: &code,
+            skip_stdlib_deps: true,
+            extra_deps: self.modules(),
+            ..Compiler::default()
+        };
+        compiler
+            .into_program(args)
+            .map_err(|e| format_err!("{}: {}", path.display(), e))
+    }
+
+    /// Executes `program` as `sender` and keeps its write set if the VM does.
+    pub fn execute(
+        &mut self,
+        sender: AccountAddress,
+        program: Program,
+    ) -> Result<TransactionOutput> {
+        let account_path = AccessPath::new_for_account(sender);
+        let stored = self.executor.read_from_access_path(&account_path);
+        let resource = self.executor.read_account_resource_at(&sender);
+        // New accounts get their address as authentication key, like accounts created on chain.
+        let auth_key = resource
+            .as_ref()
+            .map_or(sender, AccountResource::read_auth_key);
+
+        let account = Account::mock_from_address(sender);
+        let sender_data = match resource {
+            Some(resource) => AccountData::with_account_and_event_counts(
+                account,
+                AccountResource::read_balance(&resource),
+                AccountResource::read_sequence_number(&resource),
+                AccountResource::read_sent_events_count(&resource),
+                AccountResource::read_received_events_count(&resource),
+                AccountResource::read_delegated_withdrawal_capability(&resource),
+            ),
+            None => AccountData::with_account(account, SENDER_BALANCE, 0),
+        };
+        self.executor.add_account_data(&sender_data);
+
+        let txn = sender_data.account().create_signed_txn_impl(
+            sender,
+            program,
+            sender_data.sequence_number(),
+            gas_schedule::MAXIMUM_NUMBER_OF_GAS_UNITS.get(),
+            0,
+        );
+        let output = self.executor.execute_transaction(txn);
+
+        // Put the stored authentication key back in place of the throwaway one.
+        let keep = match output.status() {
+            TransactionStatus::Keep(_) => true,
+            TransactionStatus::Discard(_) => false,
+        };
+        let account_op = if keep {
+            self.executor.apply_write_set(output.write_set());
+            let resource = self
+                .executor
+                .read_account_resource_at(&sender)
+                .ok_or_else(|| format_err!("{} has no account after the transaction", sender))?;
+            let blob = AccountResource::with_auth_key(resource, auth_key)
+                .simple_serialize()
+                .ok_or_else(|| format_err!("can't serialize the account of {}", sender))?;
+            WriteOp::Value(blob)
+        } else {
+            stored.map_or(WriteOp::Deletion, WriteOp::Value)
+        };
+        self.executor
+            .apply_write_set(&WriteSetMut::new(vec![(account_path, account_op)]).freeze()?);
+        if keep {
+            self.save()?;
+        }
+        Ok(output)
+    }
+
+    /// Describes the account, modules and resources stored under `address`.
+    pub fn view(&self, address: &AccountAddress) -> String {
+        let mut view = String::new();
+        if let Some(resource) = self.executor.read_account_resource_at(address) {
+            writeln!(view, "account {}", address).unwrap();
+            writeln!(
+                view,
+                "    authentication key: {}",
+                AccountResource::read_auth_key(&resource)
+            )
+            .unwrap();
+            writeln!(
+                view,
+                "    balance: {}",
+                AccountResource::read_balance(&resource)
+            )
+            .unwrap();
+            writeln!(
+                view,
+                "    sequence number: {}",
+                AccountResource::read_sequence_number(&resource)
+            )
+            .unwrap();
+            writeln!(
+                view,
+                "    sent events: {}",
+                AccountResource::read_sent_events_count(&resource)
+            )
+            .unwrap();
+            writeln!(
+                view,
+                "    received events: {}",
+                AccountResource::read_received_events_count(&resource)
+            )
+            .unwrap();
+        }
+        let mut modules: Vec<String> = self
+            .modules()
+            .iter()
+            .map(|module| module.self_id())
+            .filter(|id| id.address() == address)
+            .map(|id| id.to_string())
+            .collect();
+        modules.sort();
+        for module in modules {
+            writeln!(view, "module {}", module).unwrap();
+        }
+        let mut resources: Vec<(&AccessPath, &Vec<u8>)> = self
+            .executor
+            .data_store()
+            .iter()
+            .filter(|(access_path, blob)| {
+                access_path.address == *address
+                    && **access_path != AccessPath::new_for_account(*address)
+                    && CompiledModule::deserialize(blob).is_err()
+            })
+            .collect();
+        resources.sort();
+        for (access_path, blob) in resources {
+            writeln!(view, "{}: 0x{}", access_path, hex::encode(blob)).unwrap();
+        }
+        if view.is_empty() {
+            writeln!(view, "nothing is stored under {}", address).unwrap();
+        }
+        view
+    }
+}
+
+/// Describes the status, gas used, write set and events of a transaction output.
+pub fn describe_output(output: &TransactionOutput) -> String {
+    let mut description = String::new();
+    writeln!(description, "status: {:?}", output.status()).unwrap();
+    writeln!(description, "gas used: {}", output.gas_used()).unwrap();
+    writeln!(description, "write set:").unwrap();
+    for (access_path, write_op) in output.write_set() {
+        match write_op {
+            WriteOp::Value(blob) => writeln!(
+                description,
+                "    write {}: {} bytes",
+                access_path,
+                blob.len()
+            )
+            .unwrap(),
+            WriteOp::Deletion => writeln!(description, "    delete {}", access_path).unwrap(),
+        }
+    }
+    writeln!(description, "events:").unwrap();
+    for event in output.events() {
+        writeln!(description, "    {}", event).unwrap();
+    }
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vm::types::{
+        account_config::association_address,
+        vm_error::{ExecutionStatus, VMStatus},
+    };
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sandbox-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn assert_executed(output: &TransactionOutput) {
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed))
+        );
+    }
+
+    #[test]
+    fn publish_and_run_across_invocations() {
+        let dir = temp_dir("publish");
+        let sender = AccountAddress::random();
+        Sandbox::init(&dir).unwrap();
+        Sandbox::init(&dir).unwrap_err();
+
+        let module = dir.join("m.mvir");
+        fs::write(
+            &module,
+            "module M { public sum(a: u64, b: u64): u64 { return move(a) + move(b); } }",
+        )
+        .unwrap();
+        let output = Sandbox::open(&dir)
+            .unwrap()
+            .publish(sender, &module)
+            .unwrap();
+        assert_executed(&output);
+        assert!(describe_output(&output).contains("write set:"));
+
+        let script = dir.join("s.mvir");
+        fs::write(
+            &script,
+            format!(
+                "import {}.M;\nmain(x: u64) {{ assert(M.sum(copy(x), 1) == 3, 42); return; }}",
+                sender
+            ),
+        )
+        .unwrap();
+        let mut sandbox = Sandbox::open(&dir).unwrap();
+        let output = sandbox
+            .run(sender, &script, vec![TransactionArgument::U64(2)])
+            .unwrap();
+        assert_executed(&output);
+
+        let view = sandbox.view(&sender);
+        assert!(view.contains("sequence number: 2"));
+        assert!(view.contains(&format!("module {}.M", sender)));
+
+        Sandbox::reset(&dir).unwrap();
+        assert!(Sandbox::open(&dir)
+            .unwrap()
+            .view(&sender)
+            .starts_with("nothing"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn view_association() {
+        let dir = temp_dir("view");
+        let view = Sandbox::init(&dir).unwrap().view(&association_address());
+        assert!(view.contains("balance: "));
+        assert!(view.contains("module 0x0"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+//! Snapshots of the move state of a chain, for replaying transactions locally against real state.
+
+use crate::data_store::FakeDataStore;
+use failure::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+use vm::types::AccessPath;
+
+/// Every `AccessStorage` entry of a chain at a block.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Snapshot {
+    /// The hash of the block the state was read at, as hex.
+    pub block_hash: String,
+    pub entries: BTreeMap<AccessPath, Vec<u8>>,
+}
+
+impl Snapshot {
+    /// Loads a snapshot written by [`Snapshot::save`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read(path).map_err(|e| format_err!("{}: {}", path.display(), e))?;
+        bincode::deserialize(&data)
+            .map_err(|e| format_err!("{}: invalid snapshot: {}", path.display(), e))
+    }
+
+    /// Writes this snapshot to `path`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, bincode::serialize(self)?)
+            .map_err(|e| format_err!("{}: {}", path.display(), e))
+    }
+
+    /// Returns a data store holding the state of this snapshot.
+    pub fn into_data_store(self) -> FakeDataStore {
+        FakeDataStore::new(self.entries.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{data_store::GENESIS_WRITE_SET, executor::FakeExecutor};
+    use vm::types::{account_config::association_address, write_set::WriteOp};
+
+    #[test]
+    fn replay_from_snapshot() {
+        let mut snapshot = Snapshot::default();
+        for (access_path, write_op) in GENESIS_WRITE_SET.iter() {
+            if let WriteOp::Value(blob) = write_op {
+                snapshot.entries.insert(access_path.clone(), blob.clone());
+            }
+        }
+        let path = std::env::temp_dir().join(format!("snapshot-{}", std::process::id()));
+        snapshot.save(&path).unwrap();
+
+        let executor = FakeExecutor::from_snapshot(&path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert!(executor
+            .read_account_resource_at(&association_address())
+            .is_some());
+    }
+}
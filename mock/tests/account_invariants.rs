@@ -0,0 +1,375 @@
+//! Runs random sequences of create-account, mint, peer-to-peer and rotate-key transactions
+//! through a [`FakeExecutor`] and checks the invariants of the account module after each of them:
+//!
+//! - the total supply only changes by the minted amount and the gas charged,
+//! - sequence numbers never decrease and only the sender's is bumped,
+//! - a failed transaction only charges gas to its sender,
+//! - event counters move by the number of events emitted to their paths.
+
+use crypto::{signing, PrivateKey, PublicKey};
+use mock::{
+    account::{Account, AccountData, AccountResource},
+    common::{CREATE_ACCOUNT, MINT, PEER_TO_PEER, ROTATE_KEY},
+    executor::FakeExecutor,
+    gas_costs::TXN_RESERVED,
+};
+use proptest::{collection::vec, prelude::*, sample::Index};
+use rand::{rngs::StdRng, SeedableRng};
+use vm::types::{
+    account_config::association_address,
+    transaction::{SignedTransaction, TransactionArgument, TransactionOutput, TransactionStatus},
+    vm_error::{ExecutionStatus, VMStatus},
+    AccessPath, AccountAddress, ByteArray,
+};
+
+/// The number of accounts that exist before the first transaction.
+const FUNDED_ACCOUNTS: usize = 4;
+/// The number of accounts that only exist once a transaction creates them.
+const NEW_ACCOUNTS: usize = 2;
+const INITIAL_BALANCE: u64 = 1_000_000;
+const MAX_MINT: u64 = 1_000_000;
+/// Gas is charged so that the supply invariant covers it.
+const GAS_PRICE: u64 = 1;
+
+#[derive(Clone, Debug)]
+enum Op {
+    CreateAccount {
+        sender: Index,
+        new_account: Index,
+        amount: u64,
+    },
+    Mint {
+        receiver: Index,
+        amount: u64,
+    },
+    Transfer {
+        sender: Index,
+        receiver: Index,
+        amount: u64,
+    },
+    RotateKey {
+        sender: Index,
+        seed: u8,
+    },
+}
+
+fn arb_op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (any::<Index>(), any::<Index>(), 0..INITIAL_BALANCE).prop_map(
+            |(sender, new_account, amount)| Op::CreateAccount {
+                sender,
+                new_account,
+                amount,
+            }
+        ),
+        (any::<Index>(), 0..MAX_MINT).prop_map(|(receiver, amount)| Op::Mint { receiver, amount }),
+        (any::<Index>(), any::<Index>(), 0..INITIAL_BALANCE).prop_map(
+            |(sender, receiver, amount)| Op::Transfer {
+                sender,
+                receiver,
+                amount,
+            }
+        ),
+        (any::<Index>(), any::<u8>()).prop_map(|(sender, seed)| Op::RotateKey { sender, seed }),
+    ]
+}
+
+fn keypair(seed: u8) -> (PrivateKey, PublicKey) {
+    let mut rng: StdRng = SeedableRng::from_seed([seed; 32]);
+    signing::generate_keypair_for_testing(&mut rng)
+}
+
+/// The part of an account resource the invariants are about.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct AccountState {
+    balance: u64,
+    sequence_number: u64,
+    sent_events: u64,
+    received_events: u64,
+    auth_key: AccountAddress,
+}
+
+struct Harness {
+    executor: FakeExecutor,
+    association: Account,
+    /// The funded accounts followed by the new ones.
+    accounts: Vec<Account>,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let mut executor = FakeExecutor::from_genesis_file();
+        let accounts = (0..FUNDED_ACCOUNTS + NEW_ACCOUNTS)
+            .map(|idx| {
+                let (privkey, pubkey) = keypair(idx as u8);
+                Account::with_keypair(privkey, pubkey)
+            })
+            .collect::<Vec<_>>();
+        for account in &accounts[..FUNDED_ACCOUNTS] {
+            executor.add_account_data(&AccountData::with_account(
+                account.clone(),
+                INITIAL_BALANCE,
+                0,
+            ));
+        }
+        Harness {
+            executor,
+            association: Account::new_association(),
+            accounts,
+        }
+    }
+
+    fn addresses(&self) -> Vec<AccountAddress> {
+        std::iter::once(association_address())
+            .chain(self.accounts.iter().map(|account| *account.address()))
+            .collect()
+    }
+
+    fn state(&self, address: &AccountAddress) -> Option<AccountState> {
+        let resource = self.executor.read_account_resource_at(address)?;
+        Some(AccountState {
+            balance: AccountResource::read_balance(&resource),
+            sequence_number: AccountResource::read_sequence_number(&resource),
+            sent_events: AccountResource::read_sent_events_count(&resource),
+            received_events: AccountResource::read_received_events_count(&resource),
+            auth_key: AccountResource::read_auth_key(&resource),
+        })
+    }
+
+    fn states(&self) -> Vec<Option<AccountState>> {
+        self.addresses()
+            .iter()
+            .map(|address| self.state(address))
+            .collect()
+    }
+
+    fn sequence_number(&self, address: &AccountAddress) -> u64 {
+        self.state(address).map_or(0, |state| state.sequence_number)
+    }
+
+    fn txn(
+        &self,
+        sender: &Account,
+        script: &[u8],
+        args: Vec<TransactionArgument>,
+    ) -> SignedTransaction {
+        sender.create_signed_txn_with_args(
+            script.to_vec(),
+            args,
+            self.sequence_number(sender.address()),
+            TXN_RESERVED,
+            GAS_PRICE,
+        )
+    }
+
+    /// Runs `op` and checks the invariants against the states before and after it.
+    fn run(&mut self, op: &Op) {
+        let (sender, txn, minted) = match op {
+            Op::CreateAccount {
+                sender,
+                new_account,
+                amount,
+            } => {
+                let sender = sender.index(self.accounts.len());
+                let new_account = *self.accounts[new_account.index(self.accounts.len())].address();
+                let args = vec![
+                    TransactionArgument::Address(new_account),
+                    TransactionArgument::U64(*amount),
+                ];
+                let txn = self.txn(&self.accounts[sender], &CREATE_ACCOUNT, args);
+                (Some(sender), txn, 0)
+            }
+            Op::Mint { receiver, amount } => {
+                let receiver = *self.accounts[receiver.index(self.accounts.len())].address();
+                let args = vec![
+                    TransactionArgument::Address(receiver),
+                    TransactionArgument::U64(*amount),
+                ];
+                (None, self.txn(&self.association, &MINT, args), *amount)
+            }
+            Op::Transfer {
+                sender,
+                receiver,
+                amount,
+            } => {
+                let sender = sender.index(self.accounts.len());
+                let receiver = *self.accounts[receiver.index(self.accounts.len())].address();
+                let args = vec![
+                    TransactionArgument::Address(receiver),
+                    TransactionArgument::U64(*amount),
+                ];
+                let txn = self.txn(&self.accounts[sender], &PEER_TO_PEER, args);
+                (Some(sender), txn, 0)
+            }
+            Op::RotateKey { sender, seed } => {
+                let sender = sender.index(self.accounts.len());
+                let (_, pubkey) = keypair(*seed);
+                let args = vec![TransactionArgument::ByteArray(ByteArray::new(
+                    AccountAddress::from(pubkey).to_vec(),
+                ))];
+                let txn = self.txn(&self.accounts[sender], &ROTATE_KEY, args);
+                (Some(sender), txn, 0)
+            }
+        };
+        // The association is first in `addresses` and `states`.
+        let sender_idx = sender.map_or(0, |idx| idx + 1);
+
+        let before = self.states();
+        let output = self.executor.execute_transaction(txn);
+        if let TransactionStatus::Keep(_) = output.status() {
+            self.executor.apply_write_set(output.write_set());
+        }
+        let after = self.states();
+
+        let executed = output.status()
+            == &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Executed));
+        if executed {
+            if let (Op::RotateKey { seed, .. }, Some(sender)) = (op, sender) {
+                let (privkey, pubkey) = keypair(*seed);
+                self.accounts[sender].rotate_key(privkey, pubkey);
+            }
+        }
+
+        let minted = if executed { minted } else { 0 };
+        check_supply(&before, &after, minted, &output);
+        check_sequence_numbers(&before, &after, sender_idx, &output);
+        if !executed {
+            check_failure(&before, &after, sender_idx, &output);
+        }
+        check_events(&self.addresses(), &before, &after, &output);
+    }
+}
+
+fn supply(states: &[Option<AccountState>]) -> u64 {
+    states.iter().flatten().map(|state| state.balance).sum()
+}
+
+fn check_supply(
+    before: &[Option<AccountState>],
+    after: &[Option<AccountState>],
+    minted: u64,
+    output: &TransactionOutput,
+) {
+    match output.status() {
+        TransactionStatus::Keep(_) => assert_eq!(
+            supply(after),
+            supply(before) + minted - output.gas_used(),
+            "the supply changed by more than the minted amount and the gas charged"
+        ),
+        TransactionStatus::Discard(_) => assert_eq!(supply(after), supply(before)),
+    }
+}
+
+fn check_sequence_numbers(
+    before: &[Option<AccountState>],
+    after: &[Option<AccountState>],
+    sender: usize,
+    output: &TransactionOutput,
+) {
+    let kept = match output.status() {
+        TransactionStatus::Keep(_) => true,
+        TransactionStatus::Discard(_) => false,
+    };
+    for (idx, (before, after)) in before.iter().zip(after).enumerate() {
+        let before = before.as_ref().map_or(0, |state| state.sequence_number);
+        let after = after.as_ref().map_or(0, |state| state.sequence_number);
+        if kept && idx == sender {
+            assert_eq!(
+                after,
+                before + 1,
+                "the sender's sequence number was not bumped"
+            );
+        } else {
+            assert_eq!(
+                after, before,
+                "the sequence number of account {} changed",
+                idx
+            );
+        }
+    }
+}
+
+fn check_failure(
+    before: &[Option<AccountState>],
+    after: &[Option<AccountState>],
+    sender: usize,
+    output: &TransactionOutput,
+) {
+    assert!(
+        output.events().is_empty(),
+        "a failed transaction emitted events"
+    );
+    for (idx, (before, after)) in before.iter().zip(after).enumerate() {
+        if idx != sender {
+            assert_eq!(
+                after, before,
+                "a failed transaction changed account {}",
+                idx
+            );
+            continue;
+        }
+        match output.status() {
+            TransactionStatus::Keep(_) => {
+                let mut charged = before.clone().expect("a kept transaction has a sender");
+                charged.balance -= output.gas_used();
+                charged.sequence_number += 1;
+                assert_eq!(after.as_ref(), Some(&charged));
+            }
+            TransactionStatus::Discard(_) => assert_eq!(after, before),
+        }
+    }
+}
+
+fn check_events(
+    addresses: &[AccountAddress],
+    before: &[Option<AccountState>],
+    after: &[Option<AccountState>],
+    output: &TransactionOutput,
+) {
+    for (address, (before, after)) in addresses.iter().zip(before.iter().zip(after)) {
+        let counts = |state: &Option<AccountState>| {
+            state
+                .as_ref()
+                .map_or((0, 0), |state| (state.sent_events, state.received_events))
+        };
+        let (sent_before, received_before) = counts(before);
+        let (sent_after, received_after) = counts(after);
+        for (path, count_before, count_after) in &[
+            (
+                AccessPath::new_for_sent_event(*address),
+                sent_before,
+                sent_after,
+            ),
+            (
+                AccessPath::new_for_received_event(*address),
+                received_before,
+                received_after,
+            ),
+        ] {
+            let sequence_numbers: Vec<u64> = output
+                .events()
+                .iter()
+                .filter(|event| event.access_path() == path)
+                .map(|event| event.sequence_number())
+                .collect();
+            let expected: Vec<u64> = (*count_before..*count_after).collect();
+            assert_eq!(
+                sequence_numbers, expected,
+                "the events emitted to {} do not match its counter",
+                path
+            );
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn account_invariants(ops in vec(arb_op(), 1..20)) {
+        let mut harness = Harness::new();
+        for op in &ops {
+            harness.run(op);
+        }
+    }
+}
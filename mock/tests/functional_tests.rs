@@ -0,0 +1,25 @@
+use mock::functional::run_file;
+use std::{fs, path::Path};
+
+/// Runs every `.mvir` file under `tests/functional`.
+#[test]
+fn functional_tests() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/functional");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "mvir"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no tests in {}", dir.display());
+
+    let failures: Vec<String> = paths
+        .iter()
+        .filter_map(|path| {
+            run_file(path)
+                .err()
+                .map(|err| format!("{}: {}", path.display(), err))
+        })
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
@@ -32,7 +32,8 @@ decl_storage! {
         /// code storage
         HasGenesis get(has_genesis): bool;
         CodeStorage: map Vec<u8> => MoveModule;
-        pub AccessStorage get(access_storage): map Vec<u8> => Option<Vec<u8>>;
+        /// A linked map, as map keys are hashed and could not be enumerated for snapshots.
+        pub AccessStorage get(access_storage): linked_map Vec<u8> => Option<Vec<u8>>;
         AccessBalance get(balance): map Vec<u8> => u64;
         AccessSequence get(sequence): map Vec<u8> => u64;
     }
@@ -48,6 +49,9 @@ decl_event!(
 
         /// An event from contract of account.
         Contract(AccountId, Vec<u8>),
+
+        /// A move transaction was executed, with its status and the gas used.
+        Executed(AccountId, Vec<u8>, Gas),
     }
 );
 
@@ -76,7 +80,7 @@ decl_module! {
 
 
         pub fn execute(origin, transaction: Vec<u8>) -> Result {
-            let _sender = ensure_signed(origin)?;
+            let sender = ensure_signed(origin)?;
             let mut executor = Self::get_executor();
             let txn = SimpleDeserializer::deserialize(&transaction);
             ensure!(txn.is_ok(), "unknown transaction");
@@ -84,6 +88,13 @@ decl_module! {
             println!("output {:?}", output);
 
             executor.apply_write_set(output.write_set());
+            for event in output.events() {
+                let event = SimpleSerializer::<Vec<u8>>::serialize(event)
+                    .expect("event should serialize");
+                Self::deposit_event(RawEvent::Contract(sender.clone(), event));
+            }
+            let status = format!("{:?}", output.status()).into_bytes();
+            Self::deposit_event(RawEvent::Executed(sender, status, output.gas_used()));
             Ok(())
         }
 
@@ -17,7 +17,7 @@ use primitives::{
 };
 use runtime_io::with_externalities;
 use substrate_primitives::{Blake2Hasher, H256};
-use support::{assert_ok, impl_outer_origin};
+use support::{assert_ok, impl_outer_origin, EnumerableStorageMap};
 
 impl_outer_origin! {
     pub enum Origin for Test {}
@@ -84,10 +84,12 @@ fn test_transfer() {
         // get resource
         let ap = receiver.account().make_access_path();
         let bytes = bincode::serialize(&ap).expect("serialization failed");
-        let blob = ExecutorModule::access_storage(bytes).unwrap();
+        let blob = ExecutorModule::access_storage(bytes.clone()).unwrap();
         let account_type = executor.struct_def();
         let value = Account::read_account_resource(&blob, account_type).unwrap();
         assert_eq!(AccountResource::read_balance(&value), transfer_amount);
+        // the new account can be found by enumerating the stored access paths
+        assert!(<AccessStorage<Test>>::enumerate().any(|(key, _)| key == bytes));
 
         let value = executor.read_account_resource(sender.account()).unwrap();
         assert_eq!(AccountResource::read_balance(&value), 1_999_000);
@@ -171,6 +171,20 @@ fn test_serialization_roundtrip() {
     );
 }
 
+#[test]
+fn test_u128_encode() {
+    let value = 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10u128;
+    let serialized_bytes = SimpleSerializer::<Vec<u8>>::serialize(&value).unwrap();
+    assert_eq!(
+        serialized_bytes,
+        vec![16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1]
+    );
+    assert_eq!(
+        SimpleDeserializer::deserialize::<u128>(&serialized_bytes).unwrap(),
+        value
+    );
+}
+
 #[test]
 fn test_encode_vec() {
     let bar1 = Bar {
@@ -18,7 +18,8 @@
 //! (All unsigned integers are encoded in little-endian representation unless specified otherwise)
 //!
 //! 1. The encoding of an unsigned 64-bit integer is defined as its little endian representation
-//!    in 8 bytes
+//!    in 8 bytes, and that of an unsigned 128-bit integer as its little endian representation in
+//!    16 bytes
 //!
 //! 2. The encoding of an item (byte array) is defined as:
 //!    [length in bytes, represented as 4-byte integer] || [item in bytes]
@@ -76,6 +77,8 @@ pub trait CanonicalSerializer {
         Ok(self)
     }
 
+    fn encode_u128(&mut self, v: u128) -> Result<&mut Self>;
+
     fn encode_u64(&mut self, v: u64) -> Result<&mut Self>;
 
     fn encode_u32(&mut self, v: u32) -> Result<&mut Self>;
@@ -149,6 +152,11 @@ impl<W> CanonicalSerializer for SimpleSerializer<W>
 where
     W: std::io::Write,
 {
+    fn encode_u128(&mut self, v: u128) -> Result<&mut Self> {
+        self.output.write_u128::<Endianness>(v)?;
+        Ok(self)
+    }
+
     fn encode_u64(&mut self, v: u64) -> Result<&mut Self> {
         self.output.write_u64::<Endianness>(v)?;
         Ok(self)
@@ -252,6 +260,8 @@ pub trait CanonicalDeserializer {
         T::deserialize(self)
     }
 
+    fn decode_u128(&mut self) -> Result<u128>;
+
     fn decode_u64(&mut self) -> Result<u64>;
 
     fn decode_u32(&mut self) -> Result<u32>;
@@ -305,6 +315,11 @@ impl<'a> SimpleDeserializer<'a> {
 }
 
 impl<'a> CanonicalDeserializer for SimpleDeserializer<'a> {
+    fn decode_u128(&mut self) -> Result<u128> {
+        let num = self.raw_bytes.read_u128::<Endianness>()?;
+        Ok(num)
+    }
+
     fn decode_u64(&mut self) -> Result<u64> {
         let num = self.raw_bytes.read_u64::<Endianness>()?;
         Ok(num)
@@ -492,6 +507,23 @@ impl CanonicalDeserialize for u64 {
     }
 }
 
+impl CanonicalSerialize for u128 {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        serializer.encode_u128(*self)?;
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for u128 {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let num = deserializer.decode_u128()?;
+        Ok(num)
+    }
+}
+
 impl CanonicalSerialize for i64 {
     fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
         serializer.encode_u64(*self as u64)?;
@@ -314,6 +314,11 @@ impl PrivateKey {
             Err(err) => bail!("Private key decode error: {}", err),
         }
     }
+
+    /// Convert the private key into a slice.
+    pub fn to_slice(&self) -> [u8; ed25519_dalek::SECRET_KEY_LENGTH] {
+        self.value.to_bytes()
+    }
 }
 
 impl PublicKey {
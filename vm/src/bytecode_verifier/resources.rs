@@ -3,9 +3,12 @@
 
 //! This module implements a checker for verifying that a non-resource struct does not
 //! have resource fields inside it.
+//!
+//! The type parameters of a non-resource struct are treated as copyable: instantiating one with a
+//! resource makes the instantiated struct a resource.
 use crate::def::{
     errors::{VMStaticViolation, VerificationError},
-    file_format::CompiledModule,
+    file_format::{CompiledModule, Kind},
     views::ModuleView,
     IndexKind,
 };
@@ -29,8 +32,10 @@ impl<'a> ResourceTransitiveChecker<'a> {
                 match struct_def.fields() {
                     None => (),
                     Some(mut fields) => {
+                        let type_formals =
+                            vec![Kind::Copyable; struct_def.kind_constraints().len()];
                         let any_resource_field =
-                            fields.any(|field| field.type_signature().is_resource());
+                            fields.any(|field| field.type_signature().is_resource(&type_formals));
                         if any_resource_field {
                             errors.push(VerificationError {
                                 kind: IndexKind::StructDefinition,
@@ -4,24 +4,30 @@
 //! This module implements a checker for verifying signature tokens used in types of function
 //! parameters, locals, and fields of structs are well-formed. References can only occur at the
 //! top-level in all tokens.  Additionally, references cannot occur at all in field types.
+//!
+//! Generic types and functions must be instantiated with as many type actuals as they have type
+//! formals, each satisfying the kind constraint of its formal, and type parameters can only be
+//! used within the struct or function that declares them.
 use crate::def::{
     access::ModuleAccess,
     errors::{VMStaticViolation, VerificationError},
-    file_format::{CompiledModule, SignatureToken},
+    file_format::{Bytecode, CompiledModule, Kind, SignatureToken},
     views::{
         FieldDefinitionView, FunctionSignatureView, LocalsSignatureView, ModuleView,
-        TypeSignatureView, ViewInternals,
+        SignatureTokenView, TypeSignatureView, ViewInternals,
     },
     IndexKind, SignatureTokenKind,
 };
 
 pub struct SignatureChecker<'a> {
+    module: &'a CompiledModule,
     module_view: ModuleView<'a, CompiledModule>,
 }
 
 impl<'a> SignatureChecker<'a> {
     pub fn new(module: &'a CompiledModule) -> Self {
         Self {
+            module,
             module_view: ModuleView::new(module),
         }
     }
@@ -56,9 +62,91 @@ impl<'a> SignatureChecker<'a> {
             .collect();
         errors.push(signature_ref_errors);
 
+        errors.push(self.verify_function_signature_instantiations());
+        errors.push(self.verify_field_instantiations());
+        errors.push(self.verify_function_definition_instantiations());
+
         errors.into_iter().flatten().collect()
     }
 
+    /// The type parameters of a function signature are those of the function.
+    fn verify_function_signature_instantiations(&self) -> Vec<VerificationError> {
+        let module = self.module;
+        module
+            .function_signatures()
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, signature)| {
+                signature
+                    .return_types
+                    .iter()
+                    .chain(&signature.arg_types)
+                    .flat_map(move |token| {
+                        check_instantiations(module, token, &signature.kind_constraints)
+                    })
+                    .map(move |err| VerificationError {
+                        kind: IndexKind::FunctionSignature,
+                        idx,
+                        err,
+                    })
+            })
+            .collect()
+    }
+
+    /// The type parameters of a field are those of the struct it is defined in.
+    fn verify_field_instantiations(&self) -> Vec<VerificationError> {
+        let module = self.module;
+        module
+            .field_defs()
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, field)| {
+                let type_formals = &module.struct_handle_at(field.struct_).kind_constraints;
+                let token = &module.type_signature_at(field.signature).0;
+                check_instantiations(module, token, type_formals)
+                    .into_iter()
+                    .map(move |err| VerificationError {
+                        kind: IndexKind::FieldDefinition,
+                        idx,
+                        err,
+                    })
+            })
+            .collect()
+    }
+
+    /// The locals of a function definition and the type actuals of its instructions can use the
+    /// type parameters of the function.
+    fn verify_function_definition_instantiations(&self) -> Vec<VerificationError> {
+        let module = self.module;
+        module
+            .function_defs()
+            .iter()
+            .enumerate()
+            .filter(|(_, function_def)| !function_def.is_native())
+            .flat_map(|(idx, function_def)| {
+                let function_handle = module.function_handle_at(function_def.function);
+                let type_formals = &module
+                    .function_signature_at(function_handle.signature)
+                    .kind_constraints;
+                let locals_errors = module
+                    .locals_signature_at(function_def.code.locals)
+                    .0
+                    .iter()
+                    .flat_map(move |token| check_instantiations(module, token, type_formals));
+                let code_errors = function_def.code.code.iter().flat_map(move |bytecode| {
+                    check_bytecode_instantiation(module, bytecode, type_formals)
+                });
+                locals_errors
+                    .chain(code_errors)
+                    .map(move |err| VerificationError {
+                        kind: IndexKind::FunctionDefinition,
+                        idx,
+                        err,
+                    })
+            })
+            .collect()
+    }
+
     #[inline]
     fn verify_impl(
         kind: IndexKind,
@@ -145,3 +233,103 @@ pub(crate) fn check_structure(token: &SignatureToken) -> Option<VMStaticViolatio
     }
     None
 }
+
+/// Checks that the type actuals of an instruction instantiate the function or struct it refers to,
+/// where the kinds of the type parameters in scope are `type_formals`.
+fn check_bytecode_instantiation(
+    module: &CompiledModule,
+    bytecode: &Bytecode,
+    type_formals: &[Kind],
+) -> Vec<VMStaticViolation> {
+    use Bytecode::*;
+
+    let (kind_constraints, type_actuals) = match bytecode {
+        Call(idx, type_actuals) => {
+            let function_handle = module.function_handle_at(*idx);
+            let signature = module.function_signature_at(function_handle.signature);
+            (&signature.kind_constraints, *type_actuals)
+        }
+        Pack(idx, type_actuals)
+        | Unpack(idx, type_actuals)
+        | Exists(idx, type_actuals)
+        | BorrowGlobal(idx, type_actuals)
+        | MoveFrom(idx, type_actuals)
+        | MoveToSender(idx, type_actuals) => {
+            let struct_def = module.struct_def_at(*idx);
+            let struct_handle = module.struct_handle_at(struct_def.struct_handle);
+            (&struct_handle.kind_constraints, *type_actuals)
+        }
+        _ => return vec![],
+    };
+    let type_actuals = &module.locals_signature_at(type_actuals).0;
+    check_type_actuals(module, kind_constraints, type_actuals, type_formals)
+}
+
+/// Checks that the struct types in `token` are instantiated correctly and that its type
+/// parameters are in scope, where the kinds of the type parameters in scope are `type_formals`.
+fn check_instantiations(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    type_formals: &[Kind],
+) -> Vec<VMStaticViolation> {
+    use SignatureToken::*;
+
+    match token {
+        Struct(sh_idx, type_actuals) => {
+            let kind_constraints = &module.struct_handle_at(*sh_idx).kind_constraints;
+            check_type_actuals(module, kind_constraints, type_actuals, type_formals)
+        }
+        Reference(token) | MutableReference(token) => {
+            check_instantiations(module, token, type_formals)
+        }
+        TypeParameter(idx) => {
+            let idx = *idx as usize;
+            if idx >= type_formals.len() {
+                vec![VMStaticViolation::IndexOutOfBounds(
+                    IndexKind::TypeParameter,
+                    type_formals.len(),
+                    idx,
+                )]
+            } else {
+                vec![]
+            }
+        }
+        Bool | U64 | String | ByteArray | Address => vec![],
+    }
+}
+
+/// Checks that `type_actuals` instantiate type formals with the kinds `kind_constraints`.
+/// References cannot be type actuals, and `copyable` type formals can only be instantiated with
+/// copyable types.
+fn check_type_actuals(
+    module: &CompiledModule,
+    kind_constraints: &[Kind],
+    type_actuals: &[SignatureToken],
+    type_formals: &[Kind],
+) -> Vec<VMStaticViolation> {
+    if kind_constraints.len() != type_actuals.len() {
+        return vec![VMStaticViolation::NumberOfTypeActualsMismatch(
+            kind_constraints.len(),
+            type_actuals.len(),
+        )];
+    }
+
+    let mut errors = vec![];
+    for (constraint, actual) in kind_constraints.iter().zip(type_actuals) {
+        if actual.is_reference() {
+            errors.push(VMStaticViolation::InvalidTypeActualReference(
+                actual.clone(),
+            ));
+            continue;
+        }
+        errors.extend(check_instantiations(module, actual, type_formals));
+        let actual_kind = SignatureTokenView::new(module, actual).type_kind(type_formals);
+        if *constraint == Kind::Copyable && actual_kind == Kind::Resource {
+            errors.push(VMStaticViolation::ConstraintKindMismatch(
+                actual.clone(),
+                *constraint,
+            ));
+        }
+    }
+    errors
+}
@@ -76,6 +76,8 @@ impl<'a> StackUsageVerifier<'a> {
             Bytecode::Branch(_) | Bytecode::BorrowField(_) => 0,
 
             Bytecode::LdConst(_)
+            | Bytecode::LdU8(_)
+            | Bytecode::LdU128(_)
             | Bytecode::LdAddr(_)
             | Bytecode::LdStr(_)
             | Bytecode::LdTrue
@@ -126,6 +128,8 @@ impl<'a> StackUsageVerifier<'a> {
             | Bytecode::BitOr
             | Bytecode::BitAnd
             | Bytecode::Xor
+            | Bytecode::Shl
+            | Bytecode::Shr
             | Bytecode::Or
             | Bytecode::And
             | Bytecode::Eq
@@ -136,7 +140,7 @@ impl<'a> StackUsageVerifier<'a> {
             | Bytecode::Ge
             | Bytecode::Abort => -1,
 
-            Bytecode::Not => 0,
+            Bytecode::Not | Bytecode::CastU8 | Bytecode::CastU64 | Bytecode::CastU128 => 0,
 
             Bytecode::FreezeRef => 0,
             Bytecode::Exists(_, _) => 0,
@@ -12,7 +12,10 @@ use super::{
 use crate::def::{
     access::ModuleAccess,
     errors::VMStaticViolation,
-    file_format::{Bytecode, CompiledModule, FunctionDefinition, LocalIndex, SignatureToken},
+    file_format::{
+        Bytecode, CompiledModule, FunctionDefinition, Kind, LocalIndex, LocalsSignatureIndex,
+        SignatureToken,
+    },
     views::{
         FunctionDefinitionView, FunctionSignatureView, LocalsSignatureView, SignatureTokenView,
         StructDefinitionView, ViewInternals,
@@ -30,6 +33,8 @@ pub struct TypeAndMemorySafetyAnalysis<'a> {
     module: &'a CompiledModule,
     function_definition_view: FunctionDefinitionView<'a, CompiledModule>,
     locals_signature_view: LocalsSignatureView<'a, CompiledModule>,
+    /// The kind constraints of the type parameters of the function.
+    type_formals: &'a [Kind],
     stack: Vec<StackAbstractValue>,
     next_nonce: usize,
     errors: Vec<VMStaticViolation>,
@@ -44,6 +49,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
         let function_definition_view = FunctionDefinitionView::new(module, function_definition);
         let locals_signature_view = function_definition_view.locals_signature();
         let function_signature_view = function_definition_view.signature();
+        let type_formals = function_signature_view.kind_constraints();
         let mut locals = BTreeMap::new();
         for (arg_idx, arg_type_view) in function_signature_view.arg_tokens().enumerate() {
             if arg_type_view.is_reference() {
@@ -54,7 +60,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
             } else {
                 locals.insert(
                     arg_idx as LocalIndex,
-                    AbstractValue::full_value(arg_type_view.is_resource()),
+                    AbstractValue::full_value(arg_type_view.is_resource(type_formals)),
                 );
             }
         }
@@ -65,6 +71,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
             module,
             function_definition_view: FunctionDefinitionView::new(module, function_definition),
             locals_signature_view,
+            type_formals,
             stack: vec![],
             next_nonce,
             errors: vec![],
@@ -83,6 +90,14 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
         verifier.errors
     }
 
+    fn type_actuals(&self, idx: LocalsSignatureIndex) -> &'a [SignatureToken] {
+        &self.module.locals_signature_at(idx).0
+    }
+
+    fn is_resource(&self, signature: &SignatureToken) -> bool {
+        SignatureTokenView::new(self.module, signature).is_resource(self.type_formals)
+    }
+
     fn get_nonce(&mut self, state: &mut AbstractState) -> Nonce {
         let nonce = Nonce::new(self.next_nonce);
         state.add_nonce(nonce.clone());
@@ -127,7 +142,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
         match bytecode {
             Bytecode::Pop => {
                 let operand = self.stack.pop().unwrap();
-                if SignatureTokenView::new(self.module, &operand.signature).is_resource() {
+                if self.is_resource(&operand.signature) {
                     Err(VMStaticViolation::PopResourceError(offset))
                 } else if operand.value.is_reference() {
                     Err(VMStaticViolation::PopReferenceError(offset))
@@ -232,11 +247,19 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                         .module
                         .is_field_in_struct(*field_definition_index, struct_handle_index)
                     {
+                        let type_actuals = match &operand.signature {
+                            SignatureToken::Reference(token)
+                            | SignatureToken::MutableReference(token) => match &**token {
+                                SignatureToken::Struct(_, type_actuals) => type_actuals.clone(),
+                                _ => vec![],
+                            },
+                            _ => vec![],
+                        };
                         let field_signature = self
                             .module
                             .get_field_signature(*field_definition_index)
                             .0
-                            .clone();
+                            .substitute(&type_actuals);
                         let operand_nonce = operand.value.extract_nonce().unwrap().clone();
                         let nonce = self.get_nonce(&mut state);
                         if operand.signature.is_mutable_reference() {
@@ -291,6 +314,22 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 Ok(())
             }
 
+            Bytecode::LdU8(_) => {
+                self.stack.push(StackAbstractValue {
+                    signature: SignatureToken::U8,
+                    value: AbstractValue::full_value(false),
+                });
+                Ok(())
+            }
+
+            Bytecode::LdU128(_) => {
+                self.stack.push(StackAbstractValue {
+                    signature: SignatureToken::U128,
+                    value: AbstractValue::full_value(false),
+                });
+                Ok(())
+            }
+
             Bytecode::LdAddr(_) => {
                 self.stack.push(StackAbstractValue {
                     signature: SignatureToken::Address,
@@ -335,7 +374,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                         value: AbstractValue::Reference(nonce),
                     });
                     Ok(())
-                } else if signature_view.is_resource() {
+                } else if signature_view.is_resource(self.type_formals) {
                     Err(VMStaticViolation::CopyLocResourceError(offset))
                 } else if state.is_full(state.local(*idx)) {
                     self.stack.push(StackAbstractValue {
@@ -380,18 +419,18 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 }
             }
 
-            // TODO: Handle type actuals for generics
-            Bytecode::Call(idx, _) => {
+            Bytecode::Call(idx, type_actuals_idx) => {
                 let function_handle = self.module.function_handle_at(*idx);
                 let function_signature =
                     self.module.function_signature_at(function_handle.signature);
                 let function_signature_view =
                     FunctionSignatureView::new(self.module, function_signature);
+                let type_actuals = self.type_actuals(*type_actuals_idx);
                 let mut all_references_to_borrow_from = BTreeSet::new();
                 let mut mutable_references_to_borrow_from = BTreeSet::new();
                 for arg_type in function_signature.arg_types.iter().rev() {
                     let arg = self.stack.pop().unwrap();
-                    if arg.signature != *arg_type {
+                    if arg.signature != arg_type.substitute(type_actuals) {
                         return Err(VMStaticViolation::CallTypeMismatchError(offset));
                     }
                     if arg_type.is_mutable_reference() && !state.is_full(&arg.value) {
@@ -405,6 +444,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                     }
                 }
                 for return_type_view in function_signature_view.return_tokens() {
+                    let return_type = return_type_view.as_inner().substitute(type_actuals);
                     if return_type_view.is_reference() {
                         let nonce = self.get_nonce(&mut state);
                         if return_type_view.is_mutable_reference() {
@@ -416,13 +456,14 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                             state.borrow_from_nonces(&all_references_to_borrow_from, nonce.clone());
                         }
                         self.stack.push(StackAbstractValue {
-                            signature: return_type_view.as_inner().clone(),
+                            signature: return_type,
                             value: AbstractValue::Reference(nonce),
                         });
                     } else {
+                        let is_resource = self.is_resource(&return_type);
                         self.stack.push(StackAbstractValue {
-                            signature: return_type_view.as_inner().clone(),
-                            value: AbstractValue::full_value(return_type_view.is_resource()),
+                            signature: return_type,
+                            value: AbstractValue::full_value(is_resource),
                         });
                     }
                 }
@@ -432,41 +473,50 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 Ok(())
             }
 
-            // TODO: Handle type actuals for generics
-            Bytecode::Pack(idx, _) => {
+            Bytecode::Pack(idx, type_actuals_idx) => {
                 let struct_definition = self.module.struct_def_at(*idx);
+                let type_actuals = self.type_actuals(*type_actuals_idx);
                 let struct_definition_view =
                     StructDefinitionView::new(self.module, struct_definition);
                 match struct_definition_view.fields() {
                     None => {
-                        // TODO pack on native error
                         self.errors
-                            .push(VMStaticViolation::PackTypeMismatchError(offset));
+                            .push(VMStaticViolation::PackNativeStructError(offset));
                     }
                     Some(fields) => {
                         for field_definition_view in fields.rev() {
                             let field_signature_view = field_definition_view.type_signature();
                             let arg = self.stack.pop().unwrap();
-                            if arg.signature != *field_signature_view.token().as_inner() {
+                            let field_signature = field_signature_view
+                                .token()
+                                .as_inner()
+                                .substitute(type_actuals);
+                            if arg.signature != field_signature {
                                 self.errors
                                     .push(VMStaticViolation::PackTypeMismatchError(offset));
                             }
                         }
                     }
                 }
+                let signature =
+                    SignatureToken::Struct(struct_definition.struct_handle, type_actuals.to_vec());
+                let is_resource = self.is_resource(&signature);
                 self.stack.push(StackAbstractValue {
-                    signature: SignatureToken::Struct(struct_definition.struct_handle, vec![]),
-                    value: AbstractValue::full_value(struct_definition_view.is_resource()),
+                    signature,
+                    value: AbstractValue::full_value(is_resource),
                 });
                 Ok(())
             }
 
-            // TODO: Handle type actuals for generics
-            Bytecode::Unpack(idx, _) => {
+            Bytecode::Unpack(idx, type_actuals_idx) => {
                 let struct_definition = self.module.struct_def_at(*idx);
+                let type_actuals = self.type_actuals(*type_actuals_idx);
                 let struct_arg = self.stack.pop().unwrap();
                 if struct_arg.signature
-                    != SignatureToken::Struct(struct_definition.struct_handle, vec![])
+                    != SignatureToken::Struct(
+                        struct_definition.struct_handle,
+                        type_actuals.to_vec(),
+                    )
                 {
                     return Err(VMStaticViolation::UnpackTypeMismatchError(offset));
                 }
@@ -474,18 +524,20 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                     StructDefinitionView::new(self.module, struct_definition);
                 match struct_definition_view.fields() {
                     None => {
-                        // TODO unpack on native error
                         self.errors
-                            .push(VMStaticViolation::UnpackTypeMismatchError(offset));
+                            .push(VMStaticViolation::UnpackNativeStructError(offset));
                     }
                     Some(fields) => {
                         for field_definition_view in fields {
                             let field_signature_view = field_definition_view.type_signature();
+                            let signature = field_signature_view
+                                .token()
+                                .as_inner()
+                                .substitute(type_actuals);
+                            let is_resource = self.is_resource(&signature);
                             self.stack.push(StackAbstractValue {
-                                signature: field_signature_view.token().as_inner().clone(),
-                                value: AbstractValue::full_value(
-                                    field_signature_view.is_resource(),
-                                ),
+                                signature,
+                                value: AbstractValue::full_value(is_resource),
                             })
                         }
                     }
@@ -498,7 +550,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 match operand.signature {
                     SignatureToken::Reference(signature) => {
                         let operand_nonce = operand.value.extract_nonce().unwrap().clone();
-                        if SignatureTokenView::new(self.module, &signature).is_resource() {
+                        if self.is_resource(&signature) {
                             Err(VMStaticViolation::ReadRefResourceError(offset))
                         } else {
                             self.stack.push(StackAbstractValue {
@@ -511,7 +563,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                     }
                     SignatureToken::MutableReference(signature) => {
                         let operand_nonce = operand.value.extract_nonce().unwrap().clone();
-                        if SignatureTokenView::new(self.module, &signature).is_resource() {
+                        if self.is_resource(&signature) {
                             Err(VMStaticViolation::ReadRefResourceError(offset))
                         } else {
                             let borrowed_nonces = state.borrowed_nonces(operand_nonce.clone());
@@ -535,7 +587,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 let ref_operand = self.stack.pop().unwrap();
                 let val_operand = self.stack.pop().unwrap();
                 if let SignatureToken::MutableReference(signature) = ref_operand.signature {
-                    if SignatureTokenView::new(self.module, &signature).is_resource() {
+                    if self.is_resource(&signature) {
                         Err(VMStaticViolation::WriteRefResourceError(offset))
                     } else if val_operand.signature != *signature {
                         Err(VMStaticViolation::WriteRefTypeMismatchError(offset))
@@ -561,11 +613,41 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
             | Bytecode::Xor => {
                 let operand1 = self.stack.pop().unwrap();
                 let operand2 = self.stack.pop().unwrap();
-                if operand1.signature == SignatureToken::U64
-                    && operand2.signature == SignatureToken::U64
-                {
+                if operand1.signature.is_integer() && operand1.signature == operand2.signature {
+                    self.stack.push(StackAbstractValue {
+                        signature: operand1.signature,
+                        value: AbstractValue::full_value(false),
+                    });
+                    Ok(())
+                } else {
+                    Err(VMStaticViolation::IntegerOpTypeMismatchError(offset))
+                }
+            }
+
+            Bytecode::Shl | Bytecode::Shr => {
+                let operand1 = self.stack.pop().unwrap();
+                let operand2 = self.stack.pop().unwrap();
+                if operand1.signature == SignatureToken::U8 && operand2.signature.is_integer() {
+                    self.stack.push(StackAbstractValue {
+                        signature: operand2.signature,
+                        value: AbstractValue::full_value(false),
+                    });
+                    Ok(())
+                } else {
+                    Err(VMStaticViolation::IntegerOpTypeMismatchError(offset))
+                }
+            }
+
+            Bytecode::CastU8 | Bytecode::CastU64 | Bytecode::CastU128 => {
+                let operand = self.stack.pop().unwrap();
+                if operand.signature.is_integer() {
+                    let signature = match bytecode {
+                        Bytecode::CastU8 => SignatureToken::U8,
+                        Bytecode::CastU64 => SignatureToken::U64,
+                        _ => SignatureToken::U128,
+                    };
                     self.stack.push(StackAbstractValue {
-                        signature: SignatureToken::U64,
+                        signature,
                         value: AbstractValue::full_value(false),
                     });
                     Ok(())
@@ -606,8 +688,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
             Bytecode::Eq | Bytecode::Neq => {
                 let operand1 = self.stack.pop().unwrap();
                 let operand2 = self.stack.pop().unwrap();
-                let is_resource =
-                    SignatureTokenView::new(self.module, &operand1.signature).is_resource();
+                let is_resource = self.is_resource(&operand1.signature);
                 if !is_resource && operand1.signature == operand2.signature {
                     if let AbstractValue::Reference(nonce) = operand1.value {
                         state.destroy_nonce(nonce);
@@ -628,9 +709,7 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
             Bytecode::Lt | Bytecode::Gt | Bytecode::Le | Bytecode::Ge => {
                 let operand1 = self.stack.pop().unwrap();
                 let operand2 = self.stack.pop().unwrap();
-                if operand1.signature == SignatureToken::U64
-                    && operand2.signature == SignatureToken::U64
-                {
+                if operand1.signature.is_integer() && operand1.signature == operand2.signature {
                     self.stack.push(StackAbstractValue {
                         signature: SignatureToken::Bool,
                         value: AbstractValue::full_value(false),
@@ -641,7 +720,6 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 }
             }
 
-            // TODO: Handle type actuals for generics
             Bytecode::Exists(_, _) => {
                 let operand = self.stack.pop().unwrap();
                 if operand.signature == SignatureToken::Address {
@@ -655,9 +733,9 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 }
             }
 
-            // TODO: Handle type actuals for generics
-            Bytecode::BorrowGlobal(idx, _) => {
+            Bytecode::BorrowGlobal(idx, type_actuals_idx) => {
                 let struct_definition = self.module.struct_def_at(*idx);
+                let type_actuals = self.type_actuals(*type_actuals_idx);
                 if !StructDefinitionView::new(self.module, struct_definition).is_resource() {
                     return Err(VMStaticViolation::BorrowGlobalNoResourceError(offset));
                 }
@@ -667,7 +745,10 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                     let nonce = self.get_nonce(&mut state);
                     self.stack.push(StackAbstractValue {
                         signature: SignatureToken::MutableReference(Box::new(
-                            SignatureToken::Struct(struct_definition.struct_handle, vec![]),
+                            SignatureToken::Struct(
+                                struct_definition.struct_handle,
+                                type_actuals.to_vec(),
+                            ),
                         )),
                         value: AbstractValue::Reference(nonce),
                     });
@@ -677,9 +758,9 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 }
             }
 
-            // TODO: Handle type actuals for generics
-            Bytecode::MoveFrom(idx, _) => {
+            Bytecode::MoveFrom(idx, type_actuals_idx) => {
                 let struct_definition = self.module.struct_def_at(*idx);
+                let type_actuals = self.type_actuals(*type_actuals_idx);
                 if !StructDefinitionView::new(self.module, struct_definition).is_resource() {
                     return Err(VMStaticViolation::MoveFromNoResourceError(offset));
                 }
@@ -687,7 +768,10 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 let operand = self.stack.pop().unwrap();
                 if operand.signature == SignatureToken::Address {
                     self.stack.push(StackAbstractValue {
-                        signature: SignatureToken::Struct(struct_definition.struct_handle, vec![]),
+                        signature: SignatureToken::Struct(
+                            struct_definition.struct_handle,
+                            type_actuals.to_vec(),
+                        ),
                         value: AbstractValue::full_value(true),
                     });
                     Ok(())
@@ -696,16 +780,19 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
                 }
             }
 
-            // TODO: Handle type actuals for generics
-            Bytecode::MoveToSender(idx, _) => {
+            Bytecode::MoveToSender(idx, type_actuals_idx) => {
                 let struct_definition = self.module.struct_def_at(*idx);
+                let type_actuals = self.type_actuals(*type_actuals_idx);
                 if !StructDefinitionView::new(self.module, struct_definition).is_resource() {
                     return Err(VMStaticViolation::MoveToSenderNoResourceError(offset));
                 }
 
                 let value_operand = self.stack.pop().unwrap();
                 if value_operand.signature
-                    == SignatureToken::Struct(struct_definition.struct_handle, vec![])
+                    == SignatureToken::Struct(
+                        struct_definition.struct_handle,
+                        type_actuals.to_vec(),
+                    )
                 {
                     Ok(())
                 } else {
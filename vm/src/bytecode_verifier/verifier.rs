@@ -408,7 +408,10 @@ fn verify_struct_kind(
         let owner_module = &dependency_map[&owner_module_id];
         let owner_module_view = ModuleView::new(*owner_module);
         if let Some(struct_definition_view) = owner_module_view.struct_definition(struct_name) {
-            if struct_handle_view.is_resource() != struct_definition_view.is_resource() {
+            if struct_handle_view.is_resource() != struct_definition_view.is_resource()
+                || struct_handle_view.kind_constraints()
+                    != struct_definition_view.kind_constraints()
+            {
                 errors.push(VerificationError {
                     kind: IndexKind::StructHandle,
                     idx,
@@ -264,9 +264,19 @@ impl BoundsCheck for &LocalsSignature {
 impl SignatureToken {
     #[inline]
     fn check_bounds(&self, module: &CompiledModuleMut) -> Option<VMStaticViolation> {
-        match self.struct_index() {
-            Some(sh_idx) => check_bounds_impl(&module.struct_handles, sh_idx),
-            None => None,
+        match self {
+            SignatureToken::Struct(sh_idx, type_actuals) => {
+                check_bounds_impl(&module.struct_handles, *sh_idx).or_else(|| {
+                    type_actuals
+                        .iter()
+                        .filter_map(|token| token.check_bounds(module))
+                        .next()
+                })
+            }
+            SignatureToken::Reference(token) | SignatureToken::MutableReference(token) => {
+                token.check_bounds(module)
+            }
+            _ => None,
         }
     }
 }
@@ -296,13 +306,17 @@ impl FunctionDefinition {
                     LdByteArray(idx) => check_bounds_impl(&module.byte_array_pool, *idx),
                     LdStr(idx) => check_bounds_impl(&module.string_pool, *idx),
                     BorrowField(idx) => check_bounds_impl(&module.field_defs, *idx),
-                    Call(idx, _) => check_bounds_impl(&module.function_handles, *idx), // FIXME: check bounds for type actuals?
-                    Pack(idx, _)
-                    | Unpack(idx, _)
-                    | Exists(idx, _)
-                    | BorrowGlobal(idx, _)
-                    | MoveFrom(idx, _)
-                    | MoveToSender(idx, _) => check_bounds_impl(&module.struct_defs, *idx),
+                    Call(idx, type_actuals) => check_bounds_impl(&module.function_handles, *idx)
+                        .or_else(|| check_bounds_impl(&module.locals_signatures, *type_actuals)),
+                    Pack(idx, type_actuals)
+                    | Unpack(idx, type_actuals)
+                    | Exists(idx, type_actuals)
+                    | BorrowGlobal(idx, type_actuals)
+                    | MoveFrom(idx, type_actuals)
+                    | MoveToSender(idx, type_actuals) => {
+                        check_bounds_impl(&module.struct_defs, *idx)
+                            .or_else(|| check_bounds_impl(&module.locals_signatures, *type_actuals))
+                    }
                     // Instructions that refer to this code block.
                     BrTrue(offset) | BrFalse(offset) | Branch(offset) => {
                         // XXX IndexOutOfBounds seems correct, but IndexKind::CodeDefinition
@@ -334,9 +348,9 @@ impl FunctionDefinition {
 
                     // List out the other options explicitly so there's a compile error if a new
                     // bytecode gets added.
-                    FreezeRef | ReleaseRef | Pop | Ret | LdConst(_) | LdTrue | LdFalse
-                    | ReadRef | WriteRef | Add | Sub | Mul | Mod | Div | BitOr | BitAnd | Xor
-                    | Or | And | Not | Eq | Neq | Lt | Gt | Le | Ge | Abort
+                    FreezeRef | ReleaseRef | Pop | Ret | LdConst(_) | LdU8(_) | LdU128(_) | LdTrue
+                    | LdFalse | ReadRef | WriteRef | Add | Sub | Mul | Mod | Div | BitOr | BitAnd
+                    | Xor | Shl | Shr | CastU8 | CastU64 | CastU128 | Or | And | Not | Eq | Neq | Lt | Gt | Le | Ge | Abort
                     | GetTxnGasUnitPrice | GetTxnMaxGasUnits | GetGasRemaining
                     | GetTxnSenderAddress | CreateAccount | EmitEvent | GetTxnSequenceNumber
                     | GetTxnPublicKey => None,
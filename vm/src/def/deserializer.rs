@@ -653,7 +653,9 @@ fn load_signature_token(cursor: &mut Cursor<&[u8]>) -> BinaryLoaderResult<Signat
     if let Ok(byte) = cursor.read_u8() {
         match SerializedType::from_u8(byte)? {
             SerializedType::BOOL => Ok(SignatureToken::Bool),
+            SerializedType::U8 => Ok(SignatureToken::U8),
             SerializedType::INTEGER => Ok(SignatureToken::U64),
+            SerializedType::U128 => Ok(SignatureToken::U128),
             SerializedType::STRING => Ok(SignatureToken::String),
             SerializedType::BYTEARRAY => Ok(SignatureToken::ByteArray),
             SerializedType::ADDRESS => Ok(SignatureToken::Address),
@@ -844,6 +846,14 @@ fn load_code(cursor: &mut Cursor<&[u8]>, code: &mut Vec<Bytecode>) -> BinaryLoad
                 let value = read_u64_internal(cursor)?;
                 Bytecode::LdConst(value)
             }
+            Opcodes::LD_U8 => {
+                let value = cursor.read_u8().map_err(|_| BinaryError::Malformed)?;
+                Bytecode::LdU8(value)
+            }
+            Opcodes::LD_U128 => {
+                let value = read_u128_internal(cursor)?;
+                Bytecode::LdU128(value)
+            }
             Opcodes::LD_ADDR => {
                 let idx = read_uleb_u16_internal(cursor)?;
                 Bytecode::LdAddr(AddressPoolIndex(idx))
@@ -903,6 +913,11 @@ fn load_code(cursor: &mut Cursor<&[u8]>, code: &mut Vec<Bytecode>) -> BinaryLoad
             Opcodes::BIT_OR => Bytecode::BitOr,
             Opcodes::BIT_AND => Bytecode::BitAnd,
             Opcodes::XOR => Bytecode::Xor,
+            Opcodes::SHL => Bytecode::Shl,
+            Opcodes::SHR => Bytecode::Shr,
+            Opcodes::CAST_U8 => Bytecode::CastU8,
+            Opcodes::CAST_U64 => Bytecode::CastU64,
+            Opcodes::CAST_U128 => Bytecode::CastU128,
             Opcodes::OR => Bytecode::Or,
             Opcodes::AND => Bytecode::And,
             Opcodes::NOT => Bytecode::Not,
@@ -979,6 +994,12 @@ fn read_u64_internal(cursor: &mut Cursor<&[u8]>) -> BinaryLoaderResult<u64> {
         .map_err(|_| BinaryError::Malformed)
 }
 
+fn read_u128_internal(cursor: &mut Cursor<&[u8]>) -> BinaryLoaderResult<u128> {
+    cursor
+        .read_u128::<LittleEndian>()
+        .map_err(|_| BinaryError::Malformed)
+}
+
 impl TableType {
     fn from_u8(value: u8) -> BinaryLoaderResult<TableType> {
         match value {
@@ -1024,6 +1045,8 @@ impl SerializedType {
             0x7 => Ok(SerializedType::STRUCT),
             0x8 => Ok(SerializedType::BYTEARRAY),
             0x9 => Ok(SerializedType::TYPE_PARAMETER),
+            0xA => Ok(SerializedType::U8),
+            0xB => Ok(SerializedType::U128),
             _ => Err(BinaryError::UnknownSerializedType),
         }
     }
@@ -1105,6 +1128,13 @@ impl Opcodes {
             0x33 => Ok(Opcodes::GET_TXN_SEQUENCE_NUMBER),
             0x34 => Ok(Opcodes::GET_TXN_PUBLIC_KEY),
             0x35 => Ok(Opcodes::FREEZE_REF),
+            0x36 => Ok(Opcodes::LD_U8),
+            0x37 => Ok(Opcodes::LD_U128),
+            0x38 => Ok(Opcodes::SHL),
+            0x39 => Ok(Opcodes::SHR),
+            0x3A => Ok(Opcodes::CAST_U8),
+            0x3B => Ok(Opcodes::CAST_U64),
+            0x3C => Ok(Opcodes::CAST_U128),
             _ => Err(BinaryError::UnknownOpcode),
         }
     }
@@ -1,7 +1,10 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{file_format::SignatureToken, IndexKind, SignatureTokenKind};
+use super::{
+    file_format::{CodeOffset, Kind, SignatureToken, SELF_MODULE_NAME},
+    IndexKind, SignatureTokenKind,
+};
 use crate::types::vm_error::{
     ArithmeticErrorType, BinaryError as VMBinaryError, DynamicReferenceErrorType, ExecutionStatus,
     VMInvariantViolationError, VMStatus, VMValidationStatus, VMVerificationError,
@@ -28,10 +31,32 @@ pub struct VMRuntimeError {
     pub err: VMErrorKind,
 }
 
-// TODO: Fill in the details for Locations. Ideally it should be a unique handle into a function and
-// a pc.
-#[derive(Debug, Default)]
-pub struct Location {}
+/// An instruction of a function: the module defining it, its name and the offset of the
+/// instruction in its code. Scripts are in the `<SELF>` module of their sender.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CodeLocation {
+    pub module: ModuleId,
+    pub function: String,
+    pub offset: CodeOffset,
+}
+
+impl fmt::Display for CodeLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.module.name() == SELF_MODULE_NAME {
+            write!(f, "script {}+{}", self.function, self.offset)
+        } else {
+            write!(f, "{}::{}+{}", self.module, self.function, self.offset)
+        }
+    }
+}
+
+/// Where a runtime error happened: the call stack at the failing instruction, innermost call
+/// first. The call stack is empty when the location is unknown, e.g. for errors raised outside of
+/// the execution of code.
+#[derive(Clone, Default, Eq, Hash, PartialEq)]
+pub struct Location {
+    pub call_stack: Vec<CodeLocation>,
+}
 
 #[derive(Debug, PartialEq)]
 pub enum VMErrorKind {
@@ -126,6 +151,24 @@ pub enum VMStaticViolation {
     #[fail(display = "Field definition has invalid type: {} ({:?})", _1, _0)]
     InvalidFieldDefReference(SignatureToken, SignatureTokenKind),
 
+    #[fail(
+        display = "Wrong number of type actuals (expected {}, found {})",
+        _0, _1
+    )]
+    NumberOfTypeActualsMismatch(usize, usize),
+
+    #[fail(
+        display = "Type actual {:?} does not satisfy the kind constraint {:?}",
+        _0, _1
+    )]
+    ConstraintKindMismatch(SignatureToken, Kind),
+
+    #[fail(
+        display = "Invalid type actual {:?}: references cannot be type actuals",
+        _0
+    )]
+    InvalidTypeActualReference(SignatureToken),
+
     #[fail(display = "Recursive struct definition")]
     RecursiveStructDef,
 
@@ -240,6 +283,12 @@ pub enum VMStaticViolation {
     #[fail(display = "Unable to verify Unpack at offset {}", _0)]
     UnpackTypeMismatchError(usize),
 
+    #[fail(display = "Pack of a native struct at offset {}", _0)]
+    PackNativeStructError(usize),
+
+    #[fail(display = "Unpack of a native struct at offset {}", _0)]
+    UnpackNativeStructError(usize),
+
     #[fail(display = "Unable to verify ReadRef at offset {}", _0)]
     ReadRefTypeMismatchError(usize),
 
@@ -295,6 +344,66 @@ pub enum VMStaticViolation {
     CreateAccountTypeMismatchError(usize),
 }
 
+impl VMStaticViolation {
+    /// The code offset of the instruction this violation is about, for violations found while
+    /// checking the code of a function definition.
+    pub fn code_offset(&self) -> Option<usize> {
+        use VMStaticViolation::*;
+
+        match self {
+            JoinFailure(offset) | PositiveStackSizeAtBlockEnd(offset) => Some(*offset),
+            NegativeStackSizeInsideBlock(_, offset) => Some(*offset),
+            PopReferenceError(offset)
+            | PopResourceError(offset)
+            | ReleaseRefTypeMismatchError(offset)
+            | BrTypeMismatchError(offset)
+            | AbortTypeMismatchError(offset)
+            | StLocTypeMismatchError(offset)
+            | StLocUnsafeToDestroyError(offset)
+            | RetUnsafeToDestroyError(offset)
+            | RetTypeMismatchError(offset)
+            | FreezeRefTypeMismatchError(offset)
+            | FreezeRefExistsMutableBorrowError(offset)
+            | BorrowFieldTypeMismatchError(offset)
+            | BorrowFieldBadFieldError(offset)
+            | BorrowFieldExistsMutableBorrowError(offset)
+            | CopyLocUnavailableError(offset)
+            | CopyLocResourceError(offset)
+            | CopyLocExistsBorrowError(offset)
+            | MoveLocUnavailableError(offset)
+            | MoveLocExistsBorrowError(offset)
+            | BorrowLocReferenceError(offset)
+            | BorrowLocUnavailableError(offset)
+            | BorrowLocExistsBorrowError(offset)
+            | CallTypeMismatchError(offset)
+            | CallBorrowedMutableReferenceError(offset)
+            | PackTypeMismatchError(offset)
+            | UnpackTypeMismatchError(offset)
+            | PackNativeStructError(offset)
+            | UnpackNativeStructError(offset)
+            | ReadRefTypeMismatchError(offset)
+            | ReadRefResourceError(offset)
+            | ReadRefExistsMutableBorrowError(offset)
+            | WriteRefTypeMismatchError(offset)
+            | WriteRefResourceError(offset)
+            | WriteRefExistsBorrowError(offset)
+            | WriteRefNoMutableReferenceError(offset)
+            | IntegerOpTypeMismatchError(offset)
+            | BooleanOpTypeMismatchError(offset)
+            | EqualityOpTypeMismatchError(offset)
+            | ExistsResourceTypeMismatchError(offset)
+            | BorrowGlobalTypeMismatchError(offset)
+            | BorrowGlobalNoResourceError(offset)
+            | MoveFromTypeMismatchError(offset)
+            | MoveFromNoResourceError(offset)
+            | MoveToSenderTypeMismatchError(offset)
+            | MoveToSenderNoResourceError(offset)
+            | CreateAccountTypeMismatchError(offset) => Some(*offset),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, Fail, Ord, PartialEq, PartialOrd)]
 pub enum VMInvariantViolation {
     #[fail(
@@ -343,7 +452,50 @@ pub type VMResult<T> = ::std::result::Result<VMRuntimeResult<T>, VMInvariantViol
 
 impl Location {
     pub fn new() -> Self {
-        Location {}
+        Location::default()
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        self.call_stack.is_empty()
+    }
+
+    /// The failing instruction.
+    pub fn code_location(&self) -> Option<&CodeLocation> {
+        self.call_stack.first()
+    }
+
+    pub fn module(&self) -> Option<&ModuleId> {
+        self.code_location().map(|loc| &loc.module)
+    }
+
+    pub fn function(&self) -> Option<&str> {
+        self.code_location().map(|loc| loc.function.as_str())
+    }
+
+    pub fn offset(&self) -> Option<CodeOffset> {
+        self.code_location().map(|loc| loc.offset)
+    }
+}
+
+/// Prints the call stack as `0x1.M::withdraw+17 <- script main+4`.
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_unknown() {
+            return write!(f, "unknown location");
+        }
+        for (idx, loc) in self.call_stack.iter().enumerate() {
+            if idx > 0 {
+                write!(f, " <- ")?;
+            }
+            write!(f, "{}", loc)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Location({})", self)
     }
 }
 
@@ -535,6 +687,15 @@ impl From<&VerificationError> for VMVerificationError {
             VMStaticViolation::InvalidFieldDefReference(_, _) => {
                 VMVerificationError::InvalidFieldDefReference(message)
             }
+            VMStaticViolation::NumberOfTypeActualsMismatch(_, _) => {
+                VMVerificationError::NumberOfTypeActualsMismatch(message)
+            }
+            VMStaticViolation::ConstraintKindMismatch(_, _) => {
+                VMVerificationError::ConstraintKindMismatch(message)
+            }
+            VMStaticViolation::InvalidTypeActualReference(_) => {
+                VMVerificationError::InvalidTypeActualReference(message)
+            }
             VMStaticViolation::RecursiveStructDef => {
                 VMVerificationError::RecursiveStructDefinition(message)
             }
@@ -641,6 +802,12 @@ impl From<&VerificationError> for VMVerificationError {
             VMStaticViolation::UnpackTypeMismatchError(_) => {
                 VMVerificationError::UnpackTypeMismatchError(message)
             }
+            VMStaticViolation::PackNativeStructError(_) => {
+                VMVerificationError::PackNativeStructError(message)
+            }
+            VMStaticViolation::UnpackNativeStructError(_) => {
+                VMVerificationError::UnpackNativeStructError(message)
+            }
             VMStaticViolation::ReadRefTypeMismatchError(_) => {
                 VMVerificationError::ReadRefTypeMismatchError(message)
             }
@@ -763,6 +930,12 @@ impl From<&VMErrorKind> for VMStatus {
 
 impl From<&VMRuntimeError> for VMStatus {
     fn from(error: &VMRuntimeError) -> Self {
-        VMStatus::from(&error.err)
+        match VMStatus::from(&error.err) {
+            VMStatus::Execution(status) if !error.loc.is_unknown() => VMStatus::ExecutionFailure {
+                status,
+                location: error.loc.clone(),
+            },
+            vm_status => vm_status,
+        }
     }
 }
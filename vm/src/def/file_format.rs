@@ -449,8 +449,12 @@ impl Kind {
 pub enum SignatureToken {
     /// Boolean, `true` or `false`.
     Bool,
+    /// Unsigned integers, 8 bits length.
+    U8,
     /// Unsigned integers, 64 bits length.
     U64,
+    /// Unsigned integers, 128 bits length.
+    U128,
     /// Strings, immutable, utf8 representation.
     String,
     /// ByteArray, variable size, immutable byte array.
@@ -478,7 +482,9 @@ impl Arbitrary for SignatureToken {
 
         let leaf = prop_oneof![
             Just(Bool),
+            Just(U8),
             Just(U64),
+            Just(U128),
             Just(String),
             Just(ByteArray),
             Just(Address),
@@ -507,7 +513,9 @@ impl ::std::fmt::Debug for SignatureToken {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
         match self {
             SignatureToken::Bool => write!(f, "Bool"),
+            SignatureToken::U8 => write!(f, "U8"),
             SignatureToken::U64 => write!(f, "U64"),
+            SignatureToken::U128 => write!(f, "U128"),
             SignatureToken::String => write!(f, "String"),
             SignatureToken::ByteArray => write!(f, "ByteArray"),
             SignatureToken::Address => write!(f, "Address"),
@@ -549,8 +557,8 @@ impl SignatureToken {
         match self {
             Reference(_) => SignatureTokenKind::Reference,
             MutableReference(_) => SignatureTokenKind::MutableReference,
-            Bool | U64 | ByteArray | String | Address | Struct(_, _) => SignatureTokenKind::Value,
-            TypeParameter(_) => unimplemented!(),
+            Bool | U8 | U64 | U128 | ByteArray | String | Address | Struct(_, _)
+            | TypeParameter(_) => SignatureTokenKind::Value,
         }
     }
 
@@ -563,7 +571,7 @@ impl SignatureToken {
         match self {
             Struct(sh_idx, _) => Some(*sh_idx),
             Reference(token) | MutableReference(token) => token.struct_index(),
-            Bool | U64 | ByteArray | String | Address | TypeParameter(_) => None,
+            Bool | U8 | U64 | U128 | ByteArray | String | Address | TypeParameter(_) => None,
         }
     }
 
@@ -571,16 +579,25 @@ impl SignatureToken {
     pub fn is_primitive(&self) -> bool {
         use SignatureToken::*;
         match self {
-            Bool | U64 | String | ByteArray | Address => true,
+            Bool | U8 | U64 | U128 | String | ByteArray | Address => true,
             Struct(_, _) | Reference(_) | MutableReference(_) | TypeParameter(_) => false,
         }
     }
 
+    /// Returns `true` if the `SignatureToken` is an unsigned integer type.
+    pub fn is_integer(&self) -> bool {
+        use SignatureToken::*;
+        match self {
+            U8 | U64 | U128 => true,
+            _ => false,
+        }
+    }
+
     /// Checks if the signature token is usable for Eq and Neq.
     ///
     /// Currently equality operations are only allowed on:
     /// - Bool
-    /// - U64
+    /// - U8, U64 and U128
     /// - String
     /// - ByteArray
     /// - Address
@@ -614,6 +631,34 @@ impl SignatureToken {
         }
     }
 
+    /// Returns the type obtained by replacing the type parameters of this type with
+    /// `type_actuals`, indexed by type parameter.
+    ///
+    /// Panics if a type parameter has no type actual. The verifier rejects such instantiations.
+    pub fn substitute(&self, type_actuals: &[SignatureToken]) -> SignatureToken {
+        use SignatureToken::*;
+
+        match self {
+            Bool => Bool,
+            U8 => U8,
+            U64 => U64,
+            U128 => U128,
+            String => String,
+            ByteArray => ByteArray,
+            Address => Address,
+            Struct(sh_idx, actuals) => Struct(
+                *sh_idx,
+                actuals
+                    .iter()
+                    .map(|token| token.substitute(type_actuals))
+                    .collect(),
+            ),
+            Reference(token) => Reference(Box::new(token.substitute(type_actuals))),
+            MutableReference(token) => MutableReference(Box::new(token.substitute(type_actuals))),
+            TypeParameter(idx) => type_actuals[*idx as usize].clone(),
+        }
+    }
+
     /// Set the index to this one. Useful for random testing.
     ///
     /// Panics if this token doesn't contain a struct handle.
@@ -705,6 +750,18 @@ pub enum Bytecode {
     ///
     /// ```... -> ..., u64_value```
     LdConst(u64),
+    /// Push a u8 constant onto the stack.
+    ///
+    /// Stack transition:
+    ///
+    /// ```... -> ..., u8_value```
+    LdU8(u8),
+    /// Push a u128 constant onto the stack.
+    ///
+    /// Stack transition:
+    ///
+    /// ```... -> ..., u128_value```
+    LdU128(u128),
     /// Push a `string` literal onto the stack. The string is loaded from the `StringPool` via
     /// `StringPoolIndex`.
     ///
@@ -849,59 +906,105 @@ pub enum Bytecode {
     ///
     /// ```..., address_value -> ..., reference_value```
     BorrowGlobal(StructDefinitionIndex, LocalsSignatureIndex),
-    /// Add the 2 u64 at the top of the stack and pushes the result on the stack.
+    /// Add the 2 integers of the same type at the top of the stack and pushes the result on the
+    /// stack.
     /// The operation aborts the transaction in case of overflow.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., u64_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., integer_value```
     Add,
-    /// Subtract the 2 u64 at the top of the stack and pushes the result on the stack.
+    /// Subtract the 2 integers of the same type at the top of the stack and pushes the result on
+    /// the stack.
     /// The operation aborts the transaction in case of underflow.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., u64_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., integer_value```
     Sub,
-    /// Multiply the 2 u64 at the top of the stack and pushes the result on the stack.
+    /// Multiply the 2 integers of the same type at the top of the stack and pushes the result on
+    /// the stack.
     /// The operation aborts the transaction in case of overflow.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., u64_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., integer_value```
     Mul,
-    /// Perform a modulo operation on the 2 u64 at the top of the stack and pushes the
-    /// result on the stack.
+    /// Perform a modulo operation on the 2 integers of the same type at the top of the stack and
+    /// pushes the result on the stack.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., u64_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., integer_value```
     Mod,
-    /// Divide the 2 u64 at the top of the stack and pushes the result on the stack.
+    /// Divide the 2 integers of the same type at the top of the stack and pushes the result on the
+    /// stack.
     /// The operation aborts the transaction in case of "divide by 0".
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., u64_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., integer_value```
     Div,
-    /// Bitwise OR the 2 u64 at the top of the stack and pushes the result on the stack.
+    /// Bitwise OR the 2 integers of the same type at the top of the stack and pushes the result on
+    /// the stack.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., u64_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., integer_value```
     BitOr,
-    /// Bitwise AND the 2 u64 at the top of the stack and pushes the result on the stack.
+    /// Bitwise AND the 2 integers of the same type at the top of the stack and pushes the result on
+    /// the stack.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., u64_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., integer_value```
     BitAnd,
-    /// Bitwise XOR the 2 u64 at the top of the stack and pushes the result on the stack.
+    /// Bitwise XOR the 2 integers of the same type at the top of the stack and pushes the result on
+    /// the stack.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., u64_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., integer_value```
     Xor,
+    /// Shift the integer below the top of the stack left by the number of bits at the top of
+    /// the stack and pushes the result on the stack. Bits shifted out are discarded.
+    /// The operation aborts the transaction if the number of bits is not less than the width
+    /// of the integer.
+    ///
+    /// Stack transition:
+    ///
+    /// ```..., integer_value, u8_value -> ..., integer_value```
+    Shl,
+    /// Shift the integer below the top of the stack right by the number of bits at the top of
+    /// the stack and pushes the result on the stack.
+    /// The operation aborts the transaction if the number of bits is not less than the width
+    /// of the integer.
+    ///
+    /// Stack transition:
+    ///
+    /// ```..., integer_value, u8_value -> ..., integer_value```
+    Shr,
+    /// Convert the integer at the top of the stack to a u8 and pushes the result on the stack.
+    /// The operation aborts the transaction if the value does not fit in a u8.
+    ///
+    /// Stack transition:
+    ///
+    /// ```..., integer_value -> ..., u8_value```
+    CastU8,
+    /// Convert the integer at the top of the stack to a u64 and pushes the result on the stack.
+    /// The operation aborts the transaction if the value does not fit in a u64.
+    ///
+    /// Stack transition:
+    ///
+    /// ```..., integer_value -> ..., u64_value```
+    CastU64,
+    /// Convert the integer at the top of the stack to a u128 and pushes the result on the
+    /// stack.
+    ///
+    /// Stack transition:
+    ///
+    /// ```..., integer_value -> ..., u128_value```
+    CastU128,
     /// Logical OR the 2 bool at the top of the stack and pushes the result on the stack.
     ///
     /// Stack transition:
@@ -936,33 +1039,33 @@ pub enum Bytecode {
     ///
     /// ```..., value(1), value(2) -> ..., bool_value```
     Neq,
-    /// Perform a "less than" operation of the 2 u64 at the top of the stack and pushes the
-    /// result on the stack.
+    /// Perform a "less than" operation of the 2 integers of the same type at the top of the stack
+    /// and pushes the result on the stack.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., bool_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., bool_value```
     Lt,
-    /// Perform a "greater than" operation of the 2 u64 at the top of the stack and pushes the
-    /// result on the stack.
+    /// Perform a "greater than" operation of the 2 integers of the same type at the top of the
+    /// stack and pushes the result on the stack.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., bool_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., bool_value```
     Gt,
-    /// Perform a "less than or equal" operation of the 2 u64 at the top of the stack and pushes
-    /// the result on the stack.
+    /// Perform a "less than or equal" operation of the 2 integers of the same type at the top of
+    /// the stack and pushes the result on the stack.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., bool_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., bool_value```
     Le,
-    /// Perform a "greater than or equal" than operation of the 2 u64 at the top of the stack
-    /// and pushes the result on the stack.
+    /// Perform a "greater than or equal" than operation of the 2 integers of the same type at the
+    /// top of the stack and pushes the result on the stack.
     ///
     /// Stack transition:
     ///
-    /// ```..., u64_value(1), u64_value(2) -> ..., bool_value```
+    /// ```..., integer_value(1), integer_value(2) -> ..., bool_value```
     Ge,
     /// Abort execution with errorcode
     ///
@@ -1047,7 +1150,7 @@ pub enum Bytecode {
 /// The number of bytecode instructions.
 /// This is necessary for checking that all instructions are covered since Rust
 /// does not provide a way of determining the number of variants of an enum.
-pub const NUMBER_OF_BYTECODE_INSTRUCTIONS: usize = 53;
+pub const NUMBER_OF_BYTECODE_INSTRUCTIONS: usize = 60;
 
 impl ::std::fmt::Debug for Bytecode {
     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
@@ -1058,6 +1161,8 @@ impl ::std::fmt::Debug for Bytecode {
             Bytecode::BrFalse(a) => write!(f, "BrFalse({})", a),
             Bytecode::Branch(a) => write!(f, "Branch({})", a),
             Bytecode::LdConst(a) => write!(f, "LdConst({})", a),
+            Bytecode::LdU8(a) => write!(f, "LdU8({})", a),
+            Bytecode::LdU128(a) => write!(f, "LdU128({})", a),
             Bytecode::LdStr(a) => write!(f, "LdStr({})", a),
             Bytecode::LdByteArray(a) => write!(f, "LdByteArray({})", a),
             Bytecode::LdAddr(a) => write!(f, "LdAddr({})", a),
@@ -1084,6 +1189,11 @@ impl ::std::fmt::Debug for Bytecode {
             Bytecode::BitOr => write!(f, "BitOr"),
             Bytecode::BitAnd => write!(f, "BitAnd"),
             Bytecode::Xor => write!(f, "Xor"),
+            Bytecode::Shl => write!(f, "Shl"),
+            Bytecode::Shr => write!(f, "Shr"),
+            Bytecode::CastU8 => write!(f, "CastU8"),
+            Bytecode::CastU64 => write!(f, "CastU64"),
+            Bytecode::CastU128 => write!(f, "CastU128"),
             Bytecode::Or => write!(f, "Or"),
             Bytecode::And => write!(f, "And"),
             Bytecode::Not => write!(f, "Not"),
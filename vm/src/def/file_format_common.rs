@@ -81,6 +81,8 @@ pub enum SerializedType {
     STRUCT                  = 0x7,
     BYTEARRAY               = 0x8,
     TYPE_PARAMETER          = 0x9,
+    U8                      = 0xA,
+    U128                    = 0xB,
 }
 
 #[rustfmt::skip]
@@ -160,6 +162,13 @@ pub enum Opcodes {
     GET_TXN_SEQUENCE_NUMBER = 0x33,
     GET_TXN_PUBLIC_KEY      = 0x34,
     FREEZE_REF              = 0x35,
+    LD_U8                   = 0x36,
+    LD_U128                 = 0x37,
+    SHL                     = 0x38,
+    SHR                     = 0x39,
+    CAST_U8                 = 0x3A,
+    CAST_U64                = 0x3B,
+    CAST_U128               = 0x3C,
 }
 
 /// Upper limit on the binary size
@@ -275,6 +284,11 @@ pub fn write_u64(binary: &mut BinaryData, value: u64) -> Result<()> {
     binary.extend(&value.to_le_bytes())
 }
 
+/// Write a `u128` in Little Endian format.
+pub fn write_u128(binary: &mut BinaryData, value: u128) -> Result<()> {
+    binary.extend(&value.to_le_bytes())
+}
+
 /// Reads a `u16` in ULEB128 format from a `binary`.
 ///
 /// Takes a `&mut Cursor<&[u8]>` and returns a pair:
@@ -290,12 +290,19 @@ lazy_static! {
             (Ret, 28, 1),
             (Lt, 49, 1),
             (LdConst(0), 29, 1),
+            (LdU8(0), 29, 1),
+            (LdU128(0), 29, 1),
             (Abort, 39, 1),
             (BorrowLoc(0), 45, 1),
             (LdStr(StringPoolIndex::new(0)), 52, 1),
             (LdAddr(AddressPoolIndex::new(0)), 36, 1),
             (Ge, 46, 1),
             (Xor, 46, 1),
+            (Shl, 46, 1),
+            (Shr, 46, 1),
+            (CastU8, 29, 1),
+            (CastU64, 29, 1),
+            (CastU128, 29, 1),
             (Neq, 51, 1),
             (Not, 35,1),
             (Call(FunctionHandleIndex::new(0), NO_TYPE_ACTUALS), 197, 1),
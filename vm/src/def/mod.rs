@@ -34,6 +34,7 @@ pub enum IndexKind {
     AddressPool,
     LocalPool,
     CodeDefinition,
+    TypeParameter,
 }
 
 impl IndexKind {
@@ -55,6 +56,7 @@ impl IndexKind {
             AddressPool,
             LocalPool,
             CodeDefinition,
+            TypeParameter,
         ]
     }
 }
@@ -78,6 +80,7 @@ impl fmt::Display for IndexKind {
             AddressPool => "address pool",
             LocalPool => "local pool",
             CodeDefinition => "code definition pool",
+            TypeParameter => "type parameter",
         };
 
         f.write_str(desc)
@@ -70,6 +70,7 @@ use std::{collections::VecDeque, fmt};
 // `access.rs`.
 pub trait TableAccess {
     fn get_field_def_at(&self, idx: FieldDefinitionIndex) -> Result<&FieldDefinition>;
+    fn get_struct_def_at(&self, idx: StructDefinitionIndex) -> Result<&StructDefinition>;
 
     fn get_module_at(&self, idx: ModuleHandleIndex) -> Result<&ModuleHandle>;
     fn get_struct_at(&self, idx: StructHandleIndex) -> Result<&StructHandle>;
@@ -77,6 +78,7 @@ pub trait TableAccess {
 
     fn get_string_at(&self, idx: StringPoolIndex) -> Result<&String>;
     fn get_address_at(&self, idx: AddressPoolIndex) -> Result<&AccountAddress>;
+    fn get_byte_array_at(&self, idx: ByteArrayPoolIndex) -> Result<&ByteArray>;
     fn get_type_signature_at(&self, idx: TypeSignatureIndex) -> Result<&TypeSignature>;
     fn get_function_signature_at(&self, idx: FunctionSignatureIndex) -> Result<&FunctionSignature>;
     fn get_locals_signature_at(&self, idx: LocalsSignatureIndex) -> Result<&LocalsSignature>;
@@ -87,6 +89,10 @@ impl TableAccess for CompiledScriptMut {
         bail!("no field definitions in scripts");
     }
 
+    fn get_struct_def_at(&self, _idx: StructDefinitionIndex) -> Result<&StructDefinition> {
+        bail!("no struct definitions in scripts");
+    }
+
     fn get_module_at(&self, idx: ModuleHandleIndex) -> Result<&ModuleHandle> {
         match self.module_handles.get(idx.0 as usize) {
             None => bail!("bad module handle index {}", idx),
@@ -122,6 +128,13 @@ impl TableAccess for CompiledScriptMut {
         }
     }
 
+    fn get_byte_array_at(&self, idx: ByteArrayPoolIndex) -> Result<&ByteArray> {
+        match self.byte_array_pool.get(idx.0 as usize) {
+            None => bail!("bad byte array index {}", idx),
+            Some(byte_array) => Ok(byte_array),
+        }
+    }
+
     fn get_type_signature_at(&self, idx: TypeSignatureIndex) -> Result<&TypeSignature> {
         match self.type_signatures.get(idx.0 as usize) {
             None => bail!("bad signature index {}", idx),
@@ -152,6 +165,13 @@ impl TableAccess for CompiledModuleMut {
         }
     }
 
+    fn get_struct_def_at(&self, idx: StructDefinitionIndex) -> Result<&StructDefinition> {
+        match self.struct_defs.get(idx.0 as usize) {
+            None => bail!("bad struct definition index {}", idx),
+            Some(s) => Ok(s),
+        }
+    }
+
     fn get_module_at(&self, idx: ModuleHandleIndex) -> Result<&ModuleHandle> {
         match self.module_handles.get(idx.0 as usize) {
             None => bail!("bad module handle index {}", idx),
@@ -187,6 +207,13 @@ impl TableAccess for CompiledModuleMut {
         }
     }
 
+    fn get_byte_array_at(&self, idx: ByteArrayPoolIndex) -> Result<&ByteArray> {
+        match self.byte_array_pool.get(idx.0 as usize) {
+            None => bail!("bad byte array index {}", idx),
+            Some(byte_array) => Ok(byte_array),
+        }
+    }
+
     fn get_type_signature_at(&self, idx: TypeSignatureIndex) -> Result<&TypeSignature> {
         match self.type_signatures.get(idx.0 as usize) {
             None => bail!("bad signature index {}", idx),
@@ -593,7 +620,9 @@ fn display_signature_token<T: TableAccess>(
 ) -> fmt::Result {
     match token {
         SignatureToken::Bool => write!(f, "Bool"),
+        SignatureToken::U8 => write!(f, "U8"),
         SignatureToken::U64 => write!(f, "Integer"),
+        SignatureToken::U128 => write!(f, "U128"),
         SignatureToken::String => write!(f, "String"),
         SignatureToken::ByteArray => write!(f, "ByteArray"),
         SignatureToken::Address => write!(f, "Address"),
@@ -635,6 +664,11 @@ fn display_bytecode<T: TableAccess>(
             write!(f, ")")
         }
         Bytecode::LdStr(idx) => write!(f, "LdStr({})", tables.get_string_at(*idx).unwrap()),
+        Bytecode::LdByteArray(idx) => {
+            write!(f, "LdByteArray(")?;
+            display_byte_array(tables.get_byte_array_at(*idx).unwrap(), f)?;
+            write!(f, ")")
+        }
         Bytecode::BorrowField(idx) => {
             write!(f, "BorrowField(")?;
             display_field_definition(tables.get_field_def_at(*idx).unwrap(), tables, f)?;
@@ -651,6 +685,180 @@ fn display_bytecode<T: TableAccess>(
             display_function_handle(tables.get_function_at(*idx).unwrap(), tables, f)?;
             write!(f, ")")
         }
+        Bytecode::Pack(idx, types_idx) => display_struct_op("Pack", *idx, *types_idx, tables, f),
+        Bytecode::Unpack(idx, types_idx) => {
+            display_struct_op("Unpack", *idx, *types_idx, tables, f)
+        }
+        Bytecode::BorrowGlobal(idx, types_idx) => {
+            display_struct_op("BorrowGlobal", *idx, *types_idx, tables, f)
+        }
+        Bytecode::Exists(idx, types_idx) => {
+            display_struct_op("Exists", *idx, *types_idx, tables, f)
+        }
+        Bytecode::MoveFrom(idx, types_idx) => {
+            display_struct_op("MoveFrom", *idx, *types_idx, tables, f)
+        }
+        Bytecode::MoveToSender(idx, types_idx) => {
+            display_struct_op("MoveToSender", *idx, *types_idx, tables, f)
+        }
         _ => write!(f, "{:?}", bytecode),
     }
 }
+
+fn display_struct_op<T: TableAccess>(
+    name: &str,
+    idx: StructDefinitionIndex,
+    types_idx: LocalsSignatureIndex,
+    tables: &T,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    write!(f, "{}", name)?;
+    display_type_parameters(
+        &tables.get_locals_signature_at(types_idx).unwrap().0,
+        tables,
+        f,
+    )?;
+    write!(f, "(")?;
+    display_struct_definition(tables.get_struct_def_at(idx).unwrap(), tables, f)?;
+    write!(f, ")")
+}
+
+//
+// Disassembly listings
+// Unlike the Display impls above, which dump every table, a listing reads like source: the
+// imports, the struct layouts and the functions with the offset of every instruction, which is
+// what branch targets refer to, e.g.
+// ```text
+// module 0x1.M
+// import 0x0.LibraAccount
+//
+// struct Pair {
+//     a: Integer
+//     b: Integer
+// }
+//
+// public max(Integer, Integer): (Integer)
+//     locals(0): Integer, Integer
+//     0: CopyLoc(0)
+//     1: CopyLoc(1)
+//     2: Gt
+//     3: BrFalse(6)
+//     ...
+// ```
+
+/// A disassembly listing of a module.
+pub struct ModuleListing<'a>(pub &'a CompiledModule);
+
+/// A disassembly listing of a script.
+pub struct ScriptListing<'a>(pub &'a CompiledScript);
+
+impl<'a> fmt::Display for ModuleListing<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inner = self.0.as_inner();
+        write!(f, "module ")?;
+        display_module_handle(&inner.module_handles[0], inner, f)?;
+        writeln!(f)?;
+        display_imports(&inner.module_handles[1..], inner, f)?;
+
+        for struct_def in &inner.struct_defs {
+            writeln!(f)?;
+            let handle = inner.get_struct_at(struct_def.struct_handle).unwrap();
+            let kind = match handle.kind {
+                Kind::Resource => "resource",
+                Kind::Copyable => "struct",
+            };
+            let name = inner.get_string_at(handle.name).unwrap();
+            match &struct_def.field_information {
+                StructFieldInformation::Native => writeln!(f, "native {} {}", kind, name)?,
+                StructFieldInformation::Declared {
+                    field_count,
+                    fields,
+                } => {
+                    writeln!(f, "{} {} {{", kind, name)?;
+                    for idx in fields.0..fields.0 + *field_count {
+                        let field = inner.get_field_def_at(FieldDefinitionIndex(idx)).unwrap();
+                        write!(f, "    {}: ", inner.get_string_at(field.name).unwrap())?;
+                        display_type_signature(
+                            inner.get_type_signature_at(field.signature).unwrap(),
+                            inner,
+                            f,
+                        )?;
+                        writeln!(f)?;
+                    }
+                    writeln!(f, "}}")?;
+                }
+            }
+        }
+
+        for function_def in &inner.function_defs {
+            writeln!(f)?;
+            display_function_listing(function_def, inner, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for ScriptListing<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let inner = self.0.as_inner();
+        let self_module = inner.get_function_at(inner.main.function).unwrap().module;
+        writeln!(f, "script")?;
+        let imports: Vec<_> = inner
+            .module_handles
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != self_module.0 as usize)
+            .map(|(_, handle)| handle.clone())
+            .collect();
+        display_imports(&imports, inner, f)?;
+        writeln!(f)?;
+        display_function_listing(&inner.main, inner, f)
+    }
+}
+
+fn display_imports<T: TableAccess>(
+    modules: &[ModuleHandle],
+    tables: &T,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    for module in modules {
+        write!(f, "import ")?;
+        display_module_handle(module, tables, f)?;
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+fn display_function_listing<T: TableAccess>(
+    function: &FunctionDefinition,
+    tables: &T,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    let handle = tables.get_function_at(function.function).unwrap();
+    display_function_flags(function.flags, f)?;
+    write!(f, "{}", tables.get_string_at(handle.name).unwrap())?;
+    display_function_signature(
+        tables.get_function_signature_at(handle.signature).unwrap(),
+        tables,
+        f,
+    )?;
+    writeln!(f)?;
+    if function.flags & CodeUnit::NATIVE != 0 {
+        return Ok(());
+    }
+    write!(f, "    locals({}): ", function.code.locals)?;
+    display_locals_signature(
+        tables
+            .get_locals_signature_at(function.code.locals)
+            .unwrap(),
+        tables,
+        f,
+    )?;
+    writeln!(f)?;
+    for (offset, bytecode) in function.code.code.iter().enumerate() {
+        write!(f, "    {}: ", offset)?;
+        display_bytecode(bytecode, tables, f)?;
+        writeln!(f)?;
+    }
+    Ok(())
+}
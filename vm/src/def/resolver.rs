@@ -58,7 +58,9 @@ impl Resolver {
     ) -> Result<SignatureToken, VMStaticViolation> {
         match sig_token {
             SignatureToken::Bool
+            | SignatureToken::U8
             | SignatureToken::U64
+            | SignatureToken::U128
             | SignatureToken::String
             | SignatureToken::ByteArray
             | SignatureToken::Address
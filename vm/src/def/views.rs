@@ -188,6 +188,11 @@ impl<'a, T: ModuleAccess> StructHandleView<'a, T> {
         }
     }
 
+    /// The kind constraints of the type parameters of the struct.
+    pub fn kind_constraints(&self) -> &'a [Kind] {
+        &self.struct_handle.kind_constraints
+    }
+
     pub fn definition(&self) -> StructDefinitionView<'a, T> {
         unimplemented!("this requires linking")
     }
@@ -259,6 +264,10 @@ impl<'a, T: ModuleAccess> StructDefinitionView<'a, T> {
         self.struct_handle_view.is_resource()
     }
 
+    pub fn kind_constraints(&self) -> &'a [Kind] {
+        self.struct_handle_view.kind_constraints()
+    }
+
     pub fn fields(
         &self,
     ) -> Option<impl DoubleEndedIterator<Item = FieldDefinitionView<'a, T>> + Send> {
@@ -379,8 +388,8 @@ impl<'a, T: ModuleAccess> TypeSignatureView<'a, T> {
     }
 
     #[inline]
-    pub fn is_resource(&self) -> bool {
-        self.token().is_resource()
+    pub fn is_resource(&self, type_formals: &[Kind]) -> bool {
+        self.token().is_resource(type_formals)
     }
 }
 
@@ -423,6 +432,11 @@ impl<'a, T: ModuleAccess> FunctionSignatureView<'a, T> {
     pub fn arg_count(&self) -> usize {
         self.function_signature.arg_types.len()
     }
+
+    /// The kind constraints of the type parameters of the function.
+    pub fn kind_constraints(&self) -> &'a [Kind] {
+        &self.function_signature.kind_constraints
+    }
 }
 
 pub struct LocalsSignatureView<'a, T> {
@@ -485,27 +499,47 @@ impl<'a, T: ModuleAccess> SignatureTokenView<'a, T> {
         self.token.kind()
     }
 
-    #[inline]
-    pub fn is_resource(&self) -> bool {
+    /// Returns the kind of this type, where the kinds of the type parameters in scope are
+    /// `type_formals`.
+    ///
+    /// An instantiated struct is a resource if it is declared as one or if any of its type
+    /// actuals is a resource. A type parameter constrained to `resource` may be instantiated with
+    /// any type, so it is treated as a resource.
+    pub fn type_kind(&self, type_formals: &[Kind]) -> Kind {
         match self.token {
-            // TODO: Type actuals are ignored, fix it (generics).
-            SignatureToken::Struct(sh_idx, _) => {
-                StructHandleView::new(self.module, self.module.struct_handle_at(*sh_idx))
-                    .is_resource()
+            SignatureToken::Struct(sh_idx, type_actuals) => {
+                let struct_handle = self.module.struct_handle_at(*sh_idx);
+                let resource_actual = type_actuals.iter().any(|actual| {
+                    SignatureTokenView::new(self.module, actual).is_resource(type_formals)
+                });
+                if struct_handle.kind.is_resource() || resource_actual {
+                    Kind::Resource
+                } else {
+                    Kind::Copyable
+                }
             }
             SignatureToken::Reference(_)
             | SignatureToken::MutableReference(_)
             | SignatureToken::Bool
+            | SignatureToken::U8
             | SignatureToken::U64
+            | SignatureToken::U128
             | SignatureToken::String
             | SignatureToken::ByteArray
-            | SignatureToken::Address => false,
-            // TODO: To get the kind of a type parameter we need to look at the struct/function
-            // that contains it. Change the API or remodel accesses/views with a tiered system.
-            SignatureToken::TypeParameter(_) => panic!("cannot tell if a type parameter is a resource or not (feature not yet implemented)"),
+            | SignatureToken::Address => Kind::Copyable,
+            // Out of scope type parameters are reported by the signature checker.
+            SignatureToken::TypeParameter(idx) => type_formals
+                .get(*idx as usize)
+                .cloned()
+                .unwrap_or(Kind::Resource),
         }
     }
 
+    #[inline]
+    pub fn is_resource(&self, type_formals: &[Kind]) -> bool {
+        self.type_kind(type_formals).is_resource()
+    }
+
     #[inline]
     pub fn is_reference(&self) -> bool {
         self.token.is_reference()
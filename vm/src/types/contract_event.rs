@@ -8,7 +8,8 @@ use super::{
     AccessPath, AccountEvent, Version,
 };
 use canonical_serialization::{
-    CanonicalSerialize, CanonicalSerializer, SimpleDeserializer, SimpleSerializer,
+    CanonicalDeserialize, CanonicalDeserializer, CanonicalSerialize, CanonicalSerializer,
+    SimpleDeserializer, SimpleSerializer,
 };
 use crypto::{
     hash::{ContractEventHasher, CryptoHash, CryptoHasher},
@@ -87,6 +88,20 @@ impl CanonicalSerialize for ContractEvent {
     }
 }
 
+impl CanonicalDeserialize for ContractEvent {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
+        let access_path = deserializer.decode_struct::<AccessPath>()?;
+        let sequence_number = deserializer.decode_u64()?;
+        let event_data = deserializer.decode_variable_length_bytes()?;
+
+        Ok(Self {
+            access_path,
+            sequence_number,
+            event_data,
+        })
+    }
+}
+
 impl CryptoHash for ContractEvent {
     type Hasher = ContractEventHasher;
 
@@ -288,7 +288,7 @@ impl From<VMStatus> for TransactionStatus {
             // that user's account for the gas fees related to decoding, running the prologue etc.
             VMStatus::Deserialization(_) => false,
             // Any error encountered during the execution of the transaction will charge gas.
-            VMStatus::Execution(_) => false,
+            VMStatus::Execution(_) | VMStatus::ExecutionFailure { .. } => false,
         };
 
         if should_discard {
@@ -652,12 +652,89 @@ impl From<Vec<u8>> for Accesses {
     }
 }
 
+/// A fully instantiated Move type, as used in the type parameters of a `StructTag`.
+#[derive(Debug, PartialEq, Hash, Eq, Clone, PartialOrd, Ord)]
+pub enum TypeTag {
+    Bool,
+    U8,
+    U64,
+    U128,
+    String,
+    ByteArray,
+    Address,
+    Struct(StructTag),
+}
+
+impl CanonicalSerialize for TypeTag {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        match self {
+            TypeTag::Bool => serializer.encode_u32(TypeTagType::Bool as u32)?,
+            TypeTag::U8 => serializer.encode_u32(TypeTagType::U8 as u32)?,
+            TypeTag::U64 => serializer.encode_u32(TypeTagType::U64 as u32)?,
+            TypeTag::U128 => serializer.encode_u32(TypeTagType::U128 as u32)?,
+            TypeTag::String => serializer.encode_u32(TypeTagType::String as u32)?,
+            TypeTag::ByteArray => serializer.encode_u32(TypeTagType::ByteArray as u32)?,
+            TypeTag::Address => serializer.encode_u32(TypeTagType::Address as u32)?,
+            TypeTag::Struct(struct_tag) => serializer
+                .encode_u32(TypeTagType::Struct as u32)?
+                .encode_struct(struct_tag)?,
+        };
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for TypeTag {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
+        let decoded_type = deserializer.decode_u32()?;
+        match TypeTagType::from_u32(decoded_type) {
+            Some(TypeTagType::Bool) => Ok(TypeTag::Bool),
+            Some(TypeTagType::U8) => Ok(TypeTag::U8),
+            Some(TypeTagType::U64) => Ok(TypeTag::U64),
+            Some(TypeTagType::U128) => Ok(TypeTag::U128),
+            Some(TypeTagType::String) => Ok(TypeTag::String),
+            Some(TypeTagType::ByteArray) => Ok(TypeTag::ByteArray),
+            Some(TypeTagType::Address) => Ok(TypeTag::Address),
+            Some(TypeTagType::Struct) => Ok(TypeTag::Struct(deserializer.decode_struct()?)),
+            None => bail!("Invalid TypeTag type {}", decoded_type),
+        }
+    }
+}
+
+enum TypeTagType {
+    Bool = 0,
+    U64 = 1,
+    String = 2,
+    ByteArray = 3,
+    Address = 4,
+    Struct = 5,
+    U8 = 6,
+    U128 = 7,
+}
+
+impl TypeTagType {
+    fn from_u32(value: u32) -> Option<TypeTagType> {
+        match value {
+            0 => Some(TypeTagType::Bool),
+            1 => Some(TypeTagType::U64),
+            2 => Some(TypeTagType::String),
+            3 => Some(TypeTagType::ByteArray),
+            4 => Some(TypeTagType::Address),
+            5 => Some(TypeTagType::Struct),
+            6 => Some(TypeTagType::U8),
+            7 => Some(TypeTagType::U128),
+            _ => None,
+        }
+    }
+}
+
+/// The type of a resource, which together with an address identifies it in global storage.
+/// Instances of generic resources are told apart by their type parameters.
 #[derive(Debug, PartialEq, Hash, Eq, Clone, PartialOrd, Ord)]
 pub struct StructTag {
     pub address: AccountAddress,
     pub module: String,
     pub name: String,
-    pub type_params: Vec<StructTag>,
+    pub type_params: Vec<TypeTag>,
 }
 
 impl CanonicalSerialize for StructTag {
@@ -676,7 +753,7 @@ impl CanonicalDeserialize for StructTag {
         let address = deserializer.decode_struct::<AccountAddress>()?;
         let module = String::from_utf8(deserializer.decode_variable_length_bytes()?)?;
         let name = String::from_utf8(deserializer.decode_variable_length_bytes()?)?;
-        let type_params = deserializer.decode_vec::<StructTag>()?;
+        let type_params = deserializer.decode_vec::<TypeTag>()?;
         Ok(Self {
             address,
             name,
@@ -858,9 +935,44 @@ pub fn parse_as_byte_array(s: &str) -> Result<TransactionArgument> {
     }
 }
 
-/// Parses the given string as u64.
+/// Returns `s` without `suffix`, if it ends with it.
+fn without_suffix<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+    if s.ends_with(suffix) {
+        Some(&s[..s.len() - suffix.len()])
+    } else {
+        None
+    }
+}
+
+/// Parses the given string as u8, which must be suffixed with `u8`.
+pub fn parse_as_u8(s: &str) -> Result<TransactionArgument> {
+    match without_suffix(s, "u8") {
+        Some(digits) => Ok(TransactionArgument::U8(digits.parse::<u8>()?)),
+        None => Err(ErrorKind::ParseError(format!("\"{}\" is not a u8", s)).into()),
+    }
+}
+
+/// Parses the given string as u64, optionally suffixed with `u64`.
 pub fn parse_as_u64(s: &str) -> Result<TransactionArgument> {
-    Ok(TransactionArgument::U64(s.parse::<u64>()?))
+    let digits = without_suffix(s, "u64").unwrap_or(s);
+    Ok(TransactionArgument::U64(digits.parse::<u64>()?))
+}
+
+/// Parses the given string as u128, which must be suffixed with `u128`.
+pub fn parse_as_u128(s: &str) -> Result<TransactionArgument> {
+    match without_suffix(s, "u128") {
+        Some(digits) => Ok(TransactionArgument::U128(digits.parse::<u128>()?)),
+        None => Err(ErrorKind::ParseError(format!("\"{}\" is not a u128", s)).into()),
+    }
+}
+
+/// Parses the given string as a double-quoted string literal.
+pub fn parse_as_string(s: &str) -> Result<TransactionArgument> {
+    if s.starts_with('"') && s.ends_with('"') && s.len() >= 2 {
+        Ok(TransactionArgument::String(s[1..s.len() - 1].to_string()))
+    } else {
+        Err(ErrorKind::ParseError(format!("\"{}\" is not a string", s)).into())
+    }
 }
 
 macro_rules! return_if_ok {
@@ -874,8 +986,11 @@ macro_rules! return_if_ok {
 /// Parses the given string as any transaction argument type.
 pub fn parse_as_transaction_argument(s: &str) -> Result<TransactionArgument> {
     return_if_ok!(parse_as_address(s));
+    return_if_ok!(parse_as_u8(s));
     return_if_ok!(parse_as_u64(s));
+    return_if_ok!(parse_as_u128(s));
     return_if_ok!(parse_as_byte_array(s));
+    return_if_ok!(parse_as_string(s));
     Err(ErrorKind::ParseError(format!("cannot parse \"{}\" as transaction argument", s)).into())
 }
 
@@ -893,6 +1008,36 @@ mod test_transaction_argument {
         }
     }
 
+    #[test]
+    fn parse_sized_integers() {
+        assert_eq!(parse_as_u8("255u8").unwrap(), TransactionArgument::U8(255));
+        assert_eq!(parse_as_u64("7u64").unwrap(), TransactionArgument::U64(7));
+        assert_eq!(
+            parse_as_u128("340282366920938463463374607431768211455u128").unwrap(),
+            TransactionArgument::U128(u128::max_value())
+        );
+        for s in &["256u8", "1", "u8", "1u128"] {
+            parse_as_u8(s).unwrap_err();
+        }
+        for s in &["1", "1u8", "-1u128"] {
+            parse_as_u128(s).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn bincode_variant_indices() {
+        let arg = TransactionArgument::U64(7);
+        let bytes = bincode::serialize(&arg).unwrap();
+        assert_eq!(bytes[..4], 0u32.to_le_bytes());
+        assert_eq!(
+            bincode::deserialize::<TransactionArgument>(&bytes).unwrap(),
+            arg
+        );
+        // The sized integers come after the variants that were already serialized
+        let bytes = bincode::serialize(&TransactionArgument::U8(7)).unwrap();
+        assert_eq!(bytes[..4], 4u32.to_le_bytes());
+    }
+
     #[test]
     fn parse_address() {
         for s in &[
@@ -928,9 +1073,20 @@ mod test_transaction_argument {
         }
     }
 
+    #[test]
+    fn parse_string() {
+        for s in &["\"\"", "\"hello\"", "\"hello world\""] {
+            parse_as_string(s).unwrap();
+        }
+
+        for s in &["", "\"", "hello", "b\"aa\""] {
+            parse_as_string(s).unwrap_err();
+        }
+    }
+
     #[test]
     fn parse_args() {
-        for s in &["123", "0xf", "b\"aaa\""] {
+        for s in &["123", "1u8", "1u128", "0xf", "b\"aaa\"", "\"aaa\""] {
             parse_as_transaction_argument(s).unwrap();
         }
 
@@ -63,6 +63,32 @@ impl Program {
     }
 }
 
+impl CanonicalSerialize for Program {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        serializer
+            .encode_variable_length_bytes(&self.code)?
+            .encode_vec(&self.args)?
+            .encode_u32(self.modules.len() as u32)?;
+        for module in &self.modules {
+            serializer.encode_variable_length_bytes(module)?;
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for Program {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
+        let code = deserializer.decode_variable_length_bytes()?;
+        let args = deserializer.decode_vec()?;
+        let len = deserializer.decode_u32()?;
+        let mut modules = vec![];
+        for _ in 0..len {
+            modules.push(deserializer.decode_variable_length_bytes()?);
+        }
+        Ok(Program::new(code, modules, args))
+    }
+}
+
 impl fmt::Debug for Program {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // XXX note that "code" will eventually be encoded bytecode and will no longer be a
@@ -80,12 +106,16 @@ pub enum TransactionArgument {
     Address(AccountAddress),
     ByteArray(ByteArray),
     String(String),
+    U8(u8),
+    U128(u128),
 }
 
 impl fmt::Debug for TransactionArgument {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            TransactionArgument::U8(value) => write!(f, "{{U8: {}}}", value),
             TransactionArgument::U64(value) => write!(f, "{{U64: {}}}", value),
+            TransactionArgument::U128(value) => write!(f, "{{U128: {}}}", value),
             TransactionArgument::Address(address) => write!(f, "{{ADDRESS: {:?}}}", address),
             TransactionArgument::String(string) => write!(f, "{{STRING: {}}}", string),
             TransactionArgument::ByteArray(byte_array) => {
@@ -95,6 +125,82 @@ impl fmt::Debug for TransactionArgument {
     }
 }
 
+impl CanonicalSerialize for TransactionArgument {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        match self {
+            TransactionArgument::U8(value) => serializer
+                .encode_u32(TransactionArgumentType::U8 as u32)?
+                .encode_u8(*value)?,
+            TransactionArgument::U64(value) => serializer
+                .encode_u32(TransactionArgumentType::U64 as u32)?
+                .encode_u64(*value)?,
+            TransactionArgument::U128(value) => serializer
+                .encode_u32(TransactionArgumentType::U128 as u32)?
+                .encode_u128(*value)?,
+            TransactionArgument::Address(address) => serializer
+                .encode_u32(TransactionArgumentType::Address as u32)?
+                .encode_struct(address)?,
+            TransactionArgument::String(string) => serializer
+                .encode_u32(TransactionArgumentType::String as u32)?
+                .encode_variable_length_bytes(string.as_bytes())?,
+            TransactionArgument::ByteArray(byte_array) => serializer
+                .encode_u32(TransactionArgumentType::ByteArray as u32)?
+                .encode_struct(byte_array)?,
+        };
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for TransactionArgument {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
+        let decoded_argument_type = deserializer.decode_u32()?;
+        match TransactionArgumentType::from_u32(decoded_argument_type) {
+            Some(TransactionArgumentType::U8) => {
+                Ok(TransactionArgument::U8(deserializer.decode_u8()?))
+            }
+            Some(TransactionArgumentType::U64) => {
+                Ok(TransactionArgument::U64(deserializer.decode_u64()?))
+            }
+            Some(TransactionArgumentType::U128) => {
+                Ok(TransactionArgument::U128(deserializer.decode_u128()?))
+            }
+            Some(TransactionArgumentType::Address) => {
+                Ok(TransactionArgument::Address(deserializer.decode_struct()?))
+            }
+            Some(TransactionArgumentType::String) => Ok(TransactionArgument::String(
+                String::from_utf8(deserializer.decode_variable_length_bytes()?)?,
+            )),
+            Some(TransactionArgumentType::ByteArray) => Ok(TransactionArgument::ByteArray(
+                deserializer.decode_struct()?,
+            )),
+            None => bail!("Invalid TransactionArgument type {}", decoded_argument_type),
+        }
+    }
+}
+
+enum TransactionArgumentType {
+    U64 = 0,
+    Address = 1,
+    String = 2,
+    ByteArray = 3,
+    U8 = 4,
+    U128 = 5,
+}
+
+impl TransactionArgumentType {
+    fn from_u32(value: u32) -> Option<TransactionArgumentType> {
+        match value {
+            0 => Some(TransactionArgumentType::U64),
+            1 => Some(TransactionArgumentType::Address),
+            2 => Some(TransactionArgumentType::String),
+            3 => Some(TransactionArgumentType::ByteArray),
+            4 => Some(TransactionArgumentType::U8),
+            5 => Some(TransactionArgumentType::U128),
+            _ => None,
+        }
+    }
+}
+
 pub const MAX_TRANSACTION_SIZE_IN_BYTES: usize = 4096;
 
 /// RawTransaction is the portion of a transaction that a client signs
@@ -224,6 +330,38 @@ impl RawTransaction {
     }
 }
 
+impl CanonicalSerialize for RawTransaction {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        serializer
+            .encode_struct(&self.sender)?
+            .encode_u64(self.sequence_number)?
+            .encode_struct(&self.payload)?
+            .encode_u64(self.max_gas_amount)?
+            .encode_u64(self.gas_unit_price)?
+            .encode_u64(self.expiration_time.as_secs())?;
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for RawTransaction {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
+        let sender = deserializer.decode_struct()?;
+        let sequence_number = deserializer.decode_u64()?;
+        let payload = deserializer.decode_struct()?;
+        let max_gas_amount = deserializer.decode_u64()?;
+        let gas_unit_price = deserializer.decode_u64()?;
+        let expiration_time = Duration::from_secs(deserializer.decode_u64()?);
+        Ok(RawTransaction {
+            sender,
+            sequence_number,
+            payload,
+            max_gas_amount,
+            gas_unit_price,
+            expiration_time,
+        })
+    }
+}
+
 pub struct RawTransactionBytes<'a>(pub &'a [u8]);
 
 impl<'a> CryptoHash for RawTransactionBytes<'a> {
@@ -243,6 +381,50 @@ pub enum TransactionPayload {
     WriteSet(WriteSet),
 }
 
+impl CanonicalSerialize for TransactionPayload {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        match self {
+            TransactionPayload::Program(program) => serializer
+                .encode_u32(TransactionPayloadType::Program as u32)?
+                .encode_struct(program)?,
+            TransactionPayload::WriteSet(write_set) => serializer
+                .encode_u32(TransactionPayloadType::WriteSet as u32)?
+                .encode_struct(write_set)?,
+        };
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for TransactionPayload {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
+        let decoded_payload_type = deserializer.decode_u32()?;
+        match TransactionPayloadType::from_u32(decoded_payload_type) {
+            Some(TransactionPayloadType::Program) => {
+                Ok(TransactionPayload::Program(deserializer.decode_struct()?))
+            }
+            Some(TransactionPayloadType::WriteSet) => {
+                Ok(TransactionPayload::WriteSet(deserializer.decode_struct()?))
+            }
+            None => bail!("Invalid TransactionPayload type {}", decoded_payload_type),
+        }
+    }
+}
+
+enum TransactionPayloadType {
+    Program = 0,
+    WriteSet = 1,
+}
+
+impl TransactionPayloadType {
+    fn from_u32(value: u32) -> Option<TransactionPayloadType> {
+        match value {
+            0 => Some(TransactionPayloadType::Program),
+            1 => Some(TransactionPayloadType::WriteSet),
+            _ => None,
+        }
+    }
+}
+
 /// A transaction that has been signed.
 ///
 /// A `SignedTransaction` is a single transaction that can be atomically executed. Clients submit
@@ -523,7 +705,7 @@ impl From<VMStatus> for TransactionStatus {
             // that user's account for the gas fees related to decoding, running the prologue etc.
             VMStatus::Deserialization(_) => false,
             // Any error encountered during the execution of the transaction will charge gas.
-            VMStatus::Execution(_) => false,
+            VMStatus::Execution(_) | VMStatus::ExecutionFailure { .. } => false,
         };
 
         if should_discard {
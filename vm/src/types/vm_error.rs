@@ -1,4 +1,4 @@
-use crate::types::ModuleId;
+use crate::{def::errors::Location, types::ModuleId};
 use failure::Fail;
 
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
@@ -29,6 +29,9 @@ pub enum VMVerificationError {
     ModuleAddressDoesNotMatchSender(String),
     InvalidSignatureToken(String),
     InvalidFieldDefReference(String),
+    NumberOfTypeActualsMismatch(String),
+    ConstraintKindMismatch(String),
+    InvalidTypeActualReference(String),
     RecursiveStructDefinition(String),
     InvalidResourceField(String),
     InvalidFallThrough(String),
@@ -72,6 +75,8 @@ pub enum VMVerificationError {
     CallBorrowedMutableReferenceError(String),
     PackTypeMismatchError(String),
     UnpackTypeMismatchError(String),
+    PackNativeStructError(String),
+    UnpackNativeStructError(String),
     ReadRefTypeMismatchError(String),
     ReadRefResourceError(String),
     ReadRefExistsMutableBorrowError(String),
@@ -173,9 +178,35 @@ pub enum VMStatus {
     InvariantViolation(VMInvariantViolationError),
     Deserialization(BinaryError),
     Execution(ExecutionStatus),
+    /// A runtime error raised while executing code, with the call stack at the failing
+    /// instruction.
+    ExecutionFailure {
+        status: ExecutionStatus,
+        location: Location,
+    },
     Verification(Vec<VMVerificationStatus>),
 }
 
+impl VMStatus {
+    /// The status of the execution, if the transaction got to run.
+    pub fn execution_status(&self) -> Option<ExecutionStatus> {
+        match self {
+            VMStatus::Execution(status) | VMStatus::ExecutionFailure { status, .. } => {
+                Some(*status)
+            }
+            _ => None,
+        }
+    }
+
+    /// Where execution failed, if it did while running code.
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            VMStatus::ExecutionFailure { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Fail, Eq, PartialEq)]
 pub enum DecodingError {
     #[fail(display = "Module index {} greater than max possible value 65535", _0)]
@@ -5,6 +5,9 @@
 //! path it updates. For each access path, the VM can either give its new value or delete it.
 
 use super::AccessPath;
+use canonical_serialization::{
+    CanonicalDeserialize, CanonicalDeserializer, CanonicalSerialize, CanonicalSerializer,
+};
 use failure::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -41,6 +44,46 @@ impl std::fmt::Debug for WriteOp {
     }
 }
 
+impl CanonicalSerialize for WriteOp {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        match self {
+            WriteOp::Deletion => serializer.encode_u32(WriteOpType::Deletion as u32)?,
+            WriteOp::Value(value) => serializer
+                .encode_u32(WriteOpType::Value as u32)?
+                .encode_variable_length_bytes(value)?,
+        };
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for WriteOp {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
+        let decoded_write_op_type = deserializer.decode_u32()?;
+        match WriteOpType::from_u32(decoded_write_op_type) {
+            Some(WriteOpType::Deletion) => Ok(WriteOp::Deletion),
+            Some(WriteOpType::Value) => {
+                Ok(WriteOp::Value(deserializer.decode_variable_length_bytes()?))
+            }
+            None => bail!("Invalid WriteOp type {}", decoded_write_op_type),
+        }
+    }
+}
+
+enum WriteOpType {
+    Deletion = 0,
+    Value = 1,
+}
+
+impl WriteOpType {
+    fn from_u32(value: u32) -> Option<WriteOpType> {
+        match value {
+            0 => Some(WriteOpType::Deletion),
+            1 => Some(WriteOpType::Value),
+            _ => None,
+        }
+    }
+}
+
 /// `WriteSet` contains all access paths that one transaction modifies. Each of them is a `WriteOp`
 /// where `Value(val)` means that serialized representation should be updated to `val`, and
 /// `Deletion` means that we are going to delete this access path.
@@ -69,6 +112,31 @@ impl WriteSet {
     }
 }
 
+impl CanonicalSerialize for WriteSet {
+    fn serialize(&self, serializer: &mut impl CanonicalSerializer) -> Result<()> {
+        serializer.encode_u32(self.len() as u32)?;
+        for (access_path, write_op) in self {
+            serializer
+                .encode_struct(access_path)?
+                .encode_struct(write_op)?;
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for WriteSet {
+    fn deserialize(deserializer: &mut impl CanonicalDeserializer) -> Result<Self> {
+        let len = deserializer.decode_u32()?;
+        let mut write_set = WriteSetMut::default();
+        for _ in 0..len {
+            let access_path = deserializer.decode_struct::<AccessPath>()?;
+            let write_op = deserializer.decode_struct::<WriteOp>()?;
+            write_set.push((access_path, write_op));
+        }
+        write_set.freeze()
+    }
+}
+
 /// A mutable version of `WriteSet`.
 ///
 /// This is separate because it goes through validation before becoming an immutable `WriteSet`.
@@ -23,6 +23,7 @@ use crate::vm_runtime::{
         struct_def::StructDef,
         types::Type,
     },
+    vm_runtime_types::native_structs::resolve_native_struct,
 };
 use log::error;
 use std::marker::PhantomData;
@@ -50,7 +51,8 @@ pub trait ModuleCache<'alloc> {
         idx: FunctionHandleIndex,
     ) -> VMResult<Option<FunctionRef<'alloc>>>;
 
-    /// Resolve a StructDefinitionIndex into a StructDef. This process will be recursive so we may
+    /// Resolve a StructDefinitionIndex into a StructDef, with the type parameters of the struct
+    /// instantiated with the layouts `type_actuals`. This process will be recursive so we may
     /// charge gas on each recursive step.
     ///
     /// Returns:
@@ -63,6 +65,7 @@ pub trait ModuleCache<'alloc> {
         &self,
         module: &LoadedModule,
         idx: StructDefinitionIndex,
+        type_actuals: &[Type],
         gas_meter: &GasMeter,
     ) -> VMResult<Option<StructDef>>;
 
@@ -100,9 +103,10 @@ where
         &self,
         module: &LoadedModule,
         idx: StructDefinitionIndex,
+        type_actuals: &[Type],
         gas_meter: &GasMeter,
     ) -> VMResult<Option<StructDef>> {
-        (*self).resolve_struct_def(module, idx, gas_meter)
+        (*self).resolve_struct_def(module, idx, type_actuals, gas_meter)
     }
 
     fn get_loaded_module(&self, id: &ModuleId) -> VMResult<Option<&'alloc LoadedModule>> {
@@ -215,14 +219,16 @@ impl<'alloc> VMModuleCache<'alloc> {
         }
     }
 
-    /// Resolve a StructHandle into a StructDef recursively in either the cache or the `fetcher`.
+    /// Resolve a StructHandle instantiated with `type_actuals` into a Type recursively in
+    /// either the cache or the `fetcher`. Native structs are resolved to their native layouts.
     pub fn resolve_struct_handle_with_fetcher<F: ModuleFetcher>(
         &self,
         module: &LoadedModule,
         idx: StructHandleIndex,
+        type_actuals: &[Type],
         gas_meter: &GasMeter,
         fetcher: &F,
-    ) -> VMResult<Option<StructDef>> {
+    ) -> VMResult<Option<Type>> {
         let struct_handle = module.struct_handle_at(idx);
         let struct_name = module.string_at(struct_handle.name);
         let struct_def_module_id = StructHandleView::new(module, struct_handle).module_id();
@@ -232,60 +238,110 @@ impl<'alloc> VMModuleCache<'alloc> {
                     .struct_defs_table
                     .get(struct_name)
                     .ok_or(VMInvariantViolation::LinkerError)?;
-                self.resolve_struct_def_with_fetcher(module, *struct_def_idx, gas_meter, fetcher)
+                if let StructFieldInformation::Native =
+                    module.struct_def_at(*struct_def_idx).field_information
+                {
+                    let ty = resolve_native_struct(module.name(), struct_name, type_actuals)
+                        .ok_or(VMInvariantViolation::LinkerError)?;
+                    return Ok(Ok(Some(ty)));
+                }
+                let struct_def = try_runtime!(self.resolve_struct_def_with_fetcher(
+                    module,
+                    *struct_def_idx,
+                    type_actuals,
+                    gas_meter,
+                    fetcher,
+                ));
+                Ok(Ok(struct_def.map(Type::Struct)))
             }
             Ok(None) => Ok(Ok(None)),
             Err(errors) => Ok(Err(errors)),
         }
     }
 
-    /// Resolve a SignatureToken into a Type recursively in either the cache or the `fetcher`.
+    /// Resolve a SignatureToken into a Type recursively in either the cache or the `fetcher`,
+    /// where the type parameters in scope are instantiated with the layouts `type_actuals`.
     pub fn resolve_signature_token_with_fetcher<'txn, F: ModuleFetcher>(
         &'txn self,
         module: &LoadedModule,
         tok: &SignatureToken,
+        type_actuals: &[Type],
         gas_meter: &GasMeter,
         fetcher: &F,
     ) -> VMResult<Option<Type>> {
         match tok {
             SignatureToken::Bool => Ok(Ok(Some(Type::Bool))),
+            SignatureToken::U8 => Ok(Ok(Some(Type::U8))),
             SignatureToken::U64 => Ok(Ok(Some(Type::U64))),
+            SignatureToken::U128 => Ok(Ok(Some(Type::U128))),
             SignatureToken::String => Ok(Ok(Some(Type::String))),
             SignatureToken::ByteArray => Ok(Ok(Some(Type::ByteArray))),
             SignatureToken::Address => Ok(Ok(Some(Type::Address))),
-            SignatureToken::TypeParameter(_) => unimplemented!(),
-            SignatureToken::Struct(sh_idx, _) => {
-                let struct_def =
-                    try_runtime!(self
-                        .resolve_struct_handle_with_fetcher(module, *sh_idx, gas_meter, fetcher));
-                Ok(Ok(struct_def.map(Type::Struct)))
+            SignatureToken::TypeParameter(idx) => {
+                let ty = type_actuals
+                    .get(*idx as usize)
+                    .ok_or(VMInvariantViolation::InternalTypeError)?;
+                Ok(Ok(Some(ty.clone())))
+            }
+            SignatureToken::Struct(sh_idx, sub_toks) => {
+                let mut struct_type_actuals = vec![];
+                for sub_tok in sub_toks {
+                    match try_runtime!(self.resolve_signature_token_with_fetcher(
+                        module,
+                        sub_tok,
+                        type_actuals,
+                        gas_meter,
+                        fetcher
+                    )) {
+                        Some(ty) => struct_type_actuals.push(ty),
+                        None => return Ok(Ok(None)),
+                    }
+                }
+                self.resolve_struct_handle_with_fetcher(
+                    module,
+                    *sh_idx,
+                    &struct_type_actuals,
+                    gas_meter,
+                    fetcher,
+                )
             }
             SignatureToken::Reference(sub_tok) => {
-                let inner_ty =
-                    try_runtime!(self
-                        .resolve_signature_token_with_fetcher(module, sub_tok, gas_meter, fetcher));
+                let inner_ty = try_runtime!(self.resolve_signature_token_with_fetcher(
+                    module,
+                    sub_tok,
+                    type_actuals,
+                    gas_meter,
+                    fetcher
+                ));
                 Ok(Ok(inner_ty.map(|t| Type::Reference(Box::new(t)))))
             }
             SignatureToken::MutableReference(sub_tok) => {
-                let inner_ty =
-                    try_runtime!(self
-                        .resolve_signature_token_with_fetcher(module, sub_tok, gas_meter, fetcher));
+                let inner_ty = try_runtime!(self.resolve_signature_token_with_fetcher(
+                    module,
+                    sub_tok,
+                    type_actuals,
+                    gas_meter,
+                    fetcher
+                ));
                 Ok(Ok(inner_ty.map(|t| Type::MutableReference(Box::new(t)))))
             }
         }
     }
 
-    /// Resolve a StructDefinition into a StructDef recursively in either the cache or the
-    /// `fetcher`.
+    /// Resolve a StructDefinition instantiated with `type_actuals` into a StructDef recursively in
+    /// either the cache or the `fetcher`. Only the layouts of non-generic structs are cached.
     pub fn resolve_struct_def_with_fetcher<'txn, F: ModuleFetcher>(
         &'txn self,
         module: &LoadedModule,
         idx: StructDefinitionIndex,
+        type_actuals: &[Type],
         gas_meter: &GasMeter,
         fetcher: &F,
     ) -> VMResult<Option<StructDef>> {
-        if let Some(def) = module.cached_struct_def_at(idx) {
-            return Ok(Ok(Some(def)));
+        if type_actuals.is_empty() {
+            if let Some(def) = module.cached_struct_def_at(idx) {
+                return Ok(Ok(Some(def)));
+            }
         }
         let def = {
             let struct_def = module.struct_def_at(idx);
@@ -301,6 +357,7 @@ impl<'alloc> VMModuleCache<'alloc> {
                         let ty = try_runtime!(self.resolve_signature_token_with_fetcher(
                             module,
                             &module.type_signature_at(field.signature).0,
+                            type_actuals,
                             gas_meter,
                             fetcher
                         ));
@@ -317,7 +374,9 @@ impl<'alloc> VMModuleCache<'alloc> {
         // If multiple writers write to def at the same time, the last one will win. It's possible
         // to have multiple copies of a struct def floating around, but that probably isn't going
         // to be a big deal.
-        module.cache_struct_def(idx, def.clone());
+        if type_actuals.is_empty() {
+            module.cache_struct_def(idx, def.clone());
+        }
         Ok(Ok(Some(def)))
     }
 }
@@ -335,9 +394,10 @@ impl<'alloc> ModuleCache<'alloc> for VMModuleCache<'alloc> {
         &self,
         module: &LoadedModule,
         idx: StructDefinitionIndex,
+        type_actuals: &[Type],
         gas_meter: &GasMeter,
     ) -> VMResult<Option<StructDef>> {
-        self.resolve_struct_def_with_fetcher(module, idx, gas_meter, &NullFetcher())
+        self.resolve_struct_def_with_fetcher(module, idx, type_actuals, gas_meter, &NullFetcher())
     }
 
     fn get_loaded_module(&self, id: &ModuleId) -> VMResult<Option<&'alloc LoadedModule>> {
@@ -403,10 +463,16 @@ impl<'alloc, 'blk, F: ModuleFetcher> ModuleCache<'alloc> for BlockModuleCache<'a
         &self,
         module: &LoadedModule,
         idx: StructDefinitionIndex,
+        type_actuals: &[Type],
         gas_meter: &GasMeter,
     ) -> VMResult<Option<StructDef>> {
-        self.vm_cache
-            .resolve_struct_def_with_fetcher(module, idx, gas_meter, &self.storage)
+        self.vm_cache.resolve_struct_def_with_fetcher(
+            module,
+            idx,
+            type_actuals,
+            gas_meter,
+            &self.storage,
+        )
     }
 
     fn get_loaded_module(&self, id: &ModuleId) -> VMResult<Option<&'alloc LoadedModule>> {
@@ -474,12 +540,18 @@ where
         &self,
         module: &LoadedModule,
         idx: StructDefinitionIndex,
+        type_actuals: &[Type],
         gas_meter: &GasMeter,
     ) -> VMResult<Option<StructDef>> {
-        if let Some(f) = try_runtime!(self.local_cache.resolve_struct_def(module, idx, gas_meter)) {
+        if let Some(f) =
+            try_runtime!(self
+                .local_cache
+                .resolve_struct_def(module, idx, type_actuals, gas_meter))
+        {
             Ok(Ok(Some(f)))
         } else {
-            self.block_cache.resolve_struct_def(module, idx, gas_meter)
+            self.block_cache
+                .resolve_struct_def(module, idx, type_actuals, gas_meter)
         }
     }
 
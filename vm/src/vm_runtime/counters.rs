@@ -62,7 +62,7 @@ fn inc_counter(prefix: &str, status: &VMStatus) {
             // all serialization error are lumped into one bucket
             VM_COUNTERS.inc(&format!("{}.deserialization", prefix));
         }
-        VMStatus::Execution(status) => {
+        VMStatus::Execution(status) | VMStatus::ExecutionFailure { status, .. } => {
             // counters for ExecutionStatus are as granular as the enum
             VM_COUNTERS.inc(&format!("{}.{:?}", prefix, status));
         }
@@ -8,6 +8,7 @@ use super::{
     loaded_data::function::{FunctionRef, FunctionReference},
 };
 use crate::def::errors::*;
+use crate::types::TypeTag;
 use std::{fmt, marker::PhantomData};
 
 pub struct ExecutionStack<'alloc, 'txn, P>
@@ -37,10 +38,16 @@ where
         }
     }
 
-    pub fn push_call(&mut self, function: FunctionRef<'txn>) -> VMResult<()> {
+    /// Calls `function`, with its type parameters instantiated with `type_actuals`.
+    pub fn push_call(
+        &mut self,
+        function: FunctionRef<'txn>,
+        type_actuals: Vec<TypeTag>,
+    ) -> VMResult<()> {
         let callee_arg_size = function.arg_count();
         let args = self.popn(callee_arg_size as u16)?;
-        self.function_stack.push(Frame::new(function, args));
+        self.function_stack
+            .push(Frame::new(function, type_actuals, args));
         Ok(Ok(()))
     }
 
@@ -71,8 +78,19 @@ where
         self.function_stack.is_empty()
     }
 
+    /// The call stack at the current instruction, innermost call first.
     pub fn location(&self) -> Result<Location, VMInvariantViolation> {
-        Ok(self.top_frame()?.into())
+        if self.function_stack.is_empty() {
+            return Err(VMInvariantViolation::EmptyCallStack);
+        }
+        Ok(Location {
+            call_stack: self
+                .function_stack
+                .iter()
+                .rev()
+                .map(Frame::code_location)
+                .collect(),
+        })
     }
 
     pub fn push(&mut self, value: Local) {
@@ -135,7 +153,7 @@ where
     }
 
     pub fn push_frame(&mut self, func: FunctionRef<'txn>) {
-        self.function_stack.push(Frame::new(func, vec![]));
+        self.function_stack.push(Frame::new(func, vec![], vec![]));
     }
 }
 
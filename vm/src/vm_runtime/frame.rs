@@ -7,16 +7,19 @@ use super::{
     loaded_data::{function::FunctionReference, loaded_module::LoadedModule},
 };
 use crate::def::{
-    errors::{Location, VMInvariantViolation, VMResult},
+    access::ModuleAccess,
+    errors::{CodeLocation, VMInvariantViolation, VMResult},
     file_format::{Bytecode, CodeOffset, LocalIndex},
     IndexKind,
 };
+use crate::types::TypeTag;
 use std::{fmt, marker::PhantomData, mem::replace};
 
 pub struct Frame<'txn, F: 'txn> {
     pc: u16,
     locals: Vec<Local>,
     function: F,
+    type_actuals: Vec<TypeTag>,
     phantom: PhantomData<&'txn F>,
 }
 
@@ -24,12 +27,13 @@ impl<'txn, F> Frame<'txn, F>
 where
     F: FunctionReference<'txn>,
 {
-    pub fn new(function: F, mut args: Vec<Local>) -> Self {
+    pub fn new(function: F, type_actuals: Vec<TypeTag>, mut args: Vec<Local>) -> Self {
         args.resize(function.local_count(), Local::Invalid);
         Frame {
             pc: 0,
             locals: args,
             function,
+            type_actuals,
             phantom: PhantomData,
         }
     }
@@ -82,11 +86,20 @@ where
     pub fn module(&self) -> &'txn LoadedModule {
         self.function.module()
     }
-}
 
-impl<'txn, F> Into<Location> for &Frame<'txn, F> {
-    fn into(self) -> Location {
-        Location::new()
+    /// The types the type parameters of the function are instantiated with in this call.
+    pub fn type_actuals(&self) -> &[TypeTag] {
+        &self.type_actuals
+    }
+
+    /// The instruction this frame is at: the one being executed for the top frame, the call for
+    /// the others.
+    pub fn code_location(&self) -> CodeLocation {
+        CodeLocation {
+            module: self.module().self_id(),
+            function: self.function.name().to_string(),
+            offset: self.pc,
+        }
     }
 }
 
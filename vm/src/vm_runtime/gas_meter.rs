@@ -117,6 +117,11 @@ impl GasMeter {
             | Bytecode::BitOr
             | Bytecode::BitAnd
             | Bytecode::Xor
+            | Bytecode::Shl
+            | Bytecode::Shr
+            | Bytecode::CastU8
+            | Bytecode::CastU64
+            | Bytecode::CastU128
             | Bytecode::Or
             | Bytecode::And
             | Bytecode::Not
@@ -128,6 +133,8 @@ impl GasMeter {
             | Bytecode::LdTrue
             | Bytecode::LdFalse
             | Bytecode::LdConst(_)
+            | Bytecode::LdU8(_)
+            | Bytecode::LdU128(_)
             | Bytecode::Branch(_)
             | Bytecode::Abort
             | Bytecode::Pop
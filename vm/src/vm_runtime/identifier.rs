@@ -2,13 +2,30 @@
 // SPDX-License-Identifier: Apache-2.0
 //! A bunch of helper functions to fetch the storage key for move resources and values.
 
-use crate::def::{access::ModuleAccess, file_format::StructDefinitionIndex};
-use crate::types::{AccessPath, Accesses, AccountAddress, ResourceKey, StructTag};
+use crate::def::{
+    access::ModuleAccess,
+    file_format::{SignatureToken, StructDefinitionIndex, StructHandleIndex},
+};
+use crate::types::{AccessPath, Accesses, AccountAddress, ResourceKey, StructTag, TypeTag};
 
-/// Get the StructTag for a StructDefinition defined in a published module.
-pub fn resource_storage_key(module: &impl ModuleAccess, idx: StructDefinitionIndex) -> StructTag {
+/// Get the StructTag for a StructDefinition defined in a published module, instantiated with
+/// `type_params`.
+pub fn resource_storage_key(
+    module: &impl ModuleAccess,
+    idx: StructDefinitionIndex,
+    type_params: Vec<TypeTag>,
+) -> StructTag {
     let resource = module.struct_def_at(idx);
-    let res_handle = module.struct_handle_at(resource.struct_handle);
+    struct_tag(module, resource.struct_handle, type_params)
+}
+
+/// Get the StructTag for a StructHandle of `module`, instantiated with `type_params`.
+fn struct_tag(
+    module: &impl ModuleAccess,
+    idx: StructHandleIndex,
+    type_params: Vec<TypeTag>,
+) -> StructTag {
+    let res_handle = module.struct_handle_at(idx);
     let res_module = module.module_handle_at(res_handle.module);
     let res_name = module.string_at(res_handle.name);
     let res_mod_addr = module.address_at(res_module.address);
@@ -17,7 +34,35 @@ pub fn resource_storage_key(module: &impl ModuleAccess, idx: StructDefinitionInd
         module: res_mod_name.to_string(),
         address: *res_mod_addr,
         name: res_name.to_string(),
-        type_params: vec![],
+        type_params,
+    }
+}
+
+/// Get the TypeTag for a type of `module`, where the type parameters in scope are instantiated
+/// with `type_actuals`. Returns `None` for references, and for type parameters without a type
+/// actual, neither of which can instantiate a type parameter.
+pub fn type_tag(
+    module: &impl ModuleAccess,
+    token: &SignatureToken,
+    type_actuals: &[TypeTag],
+) -> Option<TypeTag> {
+    match token {
+        SignatureToken::Bool => Some(TypeTag::Bool),
+        SignatureToken::U8 => Some(TypeTag::U8),
+        SignatureToken::U64 => Some(TypeTag::U64),
+        SignatureToken::U128 => Some(TypeTag::U128),
+        SignatureToken::String => Some(TypeTag::String),
+        SignatureToken::ByteArray => Some(TypeTag::ByteArray),
+        SignatureToken::Address => Some(TypeTag::Address),
+        SignatureToken::Struct(sh_idx, actuals) => {
+            let type_params = actuals
+                .iter()
+                .map(|actual| type_tag(module, actual, type_actuals))
+                .collect::<Option<Vec<_>>>()?;
+            Some(TypeTag::Struct(struct_tag(module, *sh_idx, type_params)))
+        }
+        SignatureToken::TypeParameter(idx) => type_actuals.get(*idx as usize).cloned(),
+        SignatureToken::Reference(_) | SignatureToken::MutableReference(_) => None,
     }
 }
 
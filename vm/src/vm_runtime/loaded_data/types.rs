@@ -10,13 +10,16 @@ use failure::prelude::*;
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Type {
     Bool,
+    U8,
     U64,
+    U128,
     String,
     ByteArray,
     Address,
     Struct(StructDef),
     Reference(Box<Type>),
     MutableReference(Box<Type>),
+    Vector(Box<Type>),
 }
 
 /// This isn't used by any normal code at the moment, but is used by the fuzzer to serialize types
@@ -47,6 +50,13 @@ impl CanonicalSerialize for Type {
                 ty.serialize(serializer)?;
                 serializer
             }
+            Vector(ty) => {
+                serializer.encode_u8(0x09)?;
+                ty.serialize(serializer)?;
+                serializer
+            }
+            U8 => serializer.encode_u8(0x0a)?,
+            U128 => serializer.encode_u8(0x0b)?,
         };
         Ok(())
     }
@@ -68,6 +78,9 @@ impl CanonicalDeserialize for Type {
             0x06 => Struct(StructDef::deserialize(deserializer)?),
             0x07 => Reference(Box::new(Type::deserialize(deserializer)?)),
             0x08 => MutableReference(Box::new(Type::deserialize(deserializer)?)),
+            0x09 => Vector(Box::new(Type::deserialize(deserializer)?)),
+            0x0a => U8,
+            0x0b => U128,
             other => bail!(
                 "Error while deserializing type: found unexpected tag {:#x}",
                 other
@@ -249,7 +249,9 @@ fn verify_actuals(signature: &FunctionSignature, args: &[TransactionArgument]) -
     }
     for (ty, arg) in signature.arg_types.iter().zip(args.iter()) {
         match (ty, arg) {
+            (SignatureToken::U8, TransactionArgument::U8(_)) => (),
             (SignatureToken::U64, TransactionArgument::U64(_)) => (),
+            (SignatureToken::U128, TransactionArgument::U128(_)) => (),
             (SignatureToken::Address, TransactionArgument::Address(_)) => (),
             (SignatureToken::ByteArray, TransactionArgument::ByteArray(_)) => (),
             (SignatureToken::String, TransactionArgument::String(_)) => (),
@@ -6,7 +6,10 @@ use crate::bytecode_verifier::{VerifiedModule, VerifiedScript};
 use crate::def::{
     access::ModuleAccess,
     errors::*,
-    file_format::{Bytecode, CodeOffset, CompiledScript, StructDefinitionIndex},
+    file_format::{
+        Bytecode, CodeOffset, CompiledScript, LocalsSignatureIndex, StructDefinitionIndex,
+        StructFieldInformation,
+    },
     gas_schedule::{AbstractMemorySize, GasAlgebra, GasUnits},
     transaction_metadata::TransactionMetadata,
 };
@@ -17,25 +20,28 @@ use crate::types::{
     transaction::{TransactionArgument, TransactionOutput, TransactionStatus},
     vm_error::{ExecutionStatus, VMStatus},
     write_set::WriteSet,
-    AccessPath, AccountAddress, ByteArray, ModuleId,
+    AccessPath, AccountAddress, ByteArray, ModuleId, TypeTag,
 };
 use crate::vm_runtime::vm_runtime_types::{
     native_functions::dispatch::{dispatch_native_function, NativeReturnStatus},
-    value::{Local, MutVal, Reference, Value},
+    native_structs::resolve_native_struct,
+    value::{IntegerValue, Local, MutVal, Reference, Value},
 };
 use crate::vm_runtime::{
     code_cache::module_cache::{ModuleCache, VMModuleCache},
     data_cache::{RemoteCache, TransactionDataCache},
     execution_stack::ExecutionStack,
     gas_meter::GasMeter,
-    identifier::{create_access_path, resource_storage_key},
+    identifier::{create_access_path, resource_storage_key, type_tag},
     loaded_data::{
         function::{FunctionRef, FunctionReference},
         loaded_module::LoadedModule,
+        struct_def::StructDef,
+        types::Type,
     },
 };
 use lazy_static::lazy_static;
-use std::collections::VecDeque;
+use std::{cmp::Ordering, collections::VecDeque};
 use vm_cache_map::Arena;
 
 // #[cfg(test)]
@@ -60,9 +66,10 @@ const ACCOUNT_STRUCT_NAME: &str = "T";
 fn make_access_path(
     module: &impl ModuleAccess,
     idx: StructDefinitionIndex,
+    type_actuals: Vec<TypeTag>,
     address: AccountAddress,
 ) -> AccessPath {
-    let struct_tag = resource_storage_key(module, idx);
+    let struct_tag = resource_storage_key(module, idx, type_actuals);
     create_access_path(&address, struct_tag)
 }
 
@@ -126,7 +133,11 @@ where
     {
         let rhs = try_runtime!(self.execution_stack.pop_as::<T>());
         let lhs = try_runtime!(self.execution_stack.pop_as::<T>());
-        let result = f(lhs, rhs);
+        self.push_result(f(lhs, rhs))
+    }
+
+    /// Push the result of an operation, or fail with an arithmetic error if there is none.
+    fn push_result(&mut self, result: Option<Local>) -> VMResult<()> {
         if let Some(v) = result {
             self.execution_stack.push(v);
             Ok(Ok(()))
@@ -138,12 +149,20 @@ where
         }
     }
 
-    fn binop_int<F, T>(&mut self, f: F) -> VMResult<()>
+    fn binop_int<F>(&mut self, f: F) -> VMResult<()>
     where
-        Option<T>: From<MutVal>,
-        F: FnOnce(T, T) -> Option<u64>,
+        F: FnOnce(IntegerValue, IntegerValue) -> Option<IntegerValue>,
+    {
+        self.binop(|lhs, rhs| f(lhs, rhs).map(IntegerValue::into_local))
+    }
+
+    /// Compare the two integers at the top of the stack and push whether their ordering is the
+    /// one `f` accepts.
+    fn binop_cmp<F>(&mut self, f: F) -> VMResult<()>
+    where
+        F: FnOnce(Ordering) -> bool,
     {
-        self.binop(|lhs, rhs| f(lhs, rhs).map(Local::u64))
+        self.binop(|lhs: IntegerValue, rhs| lhs.compare(rhs).map(|ord| Local::bool(f(ord))))
     }
 
     fn binop_bool<F, T>(&mut self, f: F) -> VMResult<()>
@@ -154,18 +173,56 @@ where
         self.binop(|lhs, rhs| Some(Local::bool(f(lhs, rhs))))
     }
 
+    /// Shift the integer below the top of the stack by the number of bits at the top of the stack.
+    fn shift<F>(&mut self, f: F) -> VMResult<()>
+    where
+        F: FnOnce(IntegerValue, u8) -> Option<IntegerValue>,
+    {
+        let rhs = try_runtime!(self.execution_stack.pop_as::<u8>());
+        let lhs = try_runtime!(self.execution_stack.pop_as::<IntegerValue>());
+        self.push_result(f(lhs, rhs).map(IntegerValue::into_local))
+    }
+
+    /// Convert the integer at the top of the stack to another width.
+    fn cast<F>(&mut self, f: F) -> VMResult<()>
+    where
+        F: FnOnce(IntegerValue) -> Option<IntegerValue>,
+    {
+        let value = try_runtime!(self.execution_stack.pop_as::<IntegerValue>());
+        self.push_result(f(value).map(IntegerValue::into_local))
+    }
+
     /// This function will execute the code sequence starting from the beginning_offset, and return
     /// Ok(Ok(offset)) when the instruction sequence hit a branch, either by calling into a new
     /// function, branches, function return, etc. The return value will be the pc for the next
     /// instruction to be executed.
-    #[allow(clippy::cognitive_complexity)]
     pub fn execute_block(
         &mut self,
         code: &[Bytecode],
         beginning_offset: CodeOffset,
     ) -> VMResult<CodeOffset> {
         let mut pc = beginning_offset;
-        for instruction in &code[beginning_offset as usize..] {
+        match self.execute_instructions(code, &mut pc)? {
+            Ok(offset) => Ok(Ok(offset)),
+            // Frames only record their pc on calls, so errors are located at the instruction
+            // that raised them once they get here.
+            Err(mut err) => {
+                self.execution_stack.top_frame_mut()?.jump(pc);
+                err.loc = self.execution_stack.location()?;
+                Ok(Err(err))
+            }
+        }
+    }
+
+    /// Executes the instructions of `code` from `pc` for `execute_block`, leaving `pc` on the
+    /// instruction that stopped the block.
+    #[allow(clippy::cognitive_complexity)]
+    fn execute_instructions(
+        &mut self,
+        code: &[Bytecode],
+        pc: &mut CodeOffset,
+    ) -> VMResult<CodeOffset> {
+        for instruction in &code[*pc as usize..] {
             // FIXME: Once we add in memory ops, we will need to pass in the current memory size to
             // this function.
             try_runtime!(self.gas_meter.calculate_and_consume(
@@ -201,6 +258,12 @@ where
                 Bytecode::LdConst(int_const) => {
                     self.execution_stack.push(Local::u64(int_const));
                 }
+                Bytecode::LdU8(int_const) => {
+                    self.execution_stack.push(Local::u8(int_const));
+                }
+                Bytecode::LdU128(int_const) => {
+                    self.execution_stack.push(Local::u128(int_const));
+                }
                 Bytecode::LdAddr(idx) => {
                     let top_frame = self.execution_stack.top_frame()?;
                     let addr_ref = top_frame.module().address_at(idx);
@@ -242,7 +305,8 @@ where
                         .top_frame_mut()?
                         .store_local(idx, stack_top));
                 }
-                Bytecode::Call(idx, _) => {
+                Bytecode::Call(idx, type_actuals_idx) => {
+                    let type_actuals = try_runtime!(self.type_actuals(type_actuals_idx));
                     let self_module = &self.execution_stack.top_frame()?.module();
                     let callee_function_ref = try_runtime!(self
                         .execution_stack
@@ -290,8 +354,10 @@ where
                     // Call stack is not reconstructed for a native call, so we just
                     // proceed on to next instruction.
                     } else {
-                        self.execution_stack.top_frame_mut()?.jump(pc);
-                        try_runtime!(self.execution_stack.push_call(callee_function_ref));
+                        self.execution_stack.top_frame_mut()?.jump(*pc);
+                        try_runtime!(self
+                            .execution_stack
+                            .push_call(callee_function_ref, type_actuals));
                         // Call stack is reconstructed, the next instruction to execute will be the
                         // first instruction of the callee function. Thus we should break here to
                         // restart the instruction sequence from there.
@@ -418,20 +484,25 @@ where
                     }
                 }
                 // Arithmetic Operations
-                Bytecode::Add => try_runtime!(self.binop_int(u64::checked_add)),
-                Bytecode::Sub => try_runtime!(self.binop_int(u64::checked_sub)),
-                Bytecode::Mul => try_runtime!(self.binop_int(u64::checked_mul)),
-                Bytecode::Mod => try_runtime!(self.binop_int(u64::checked_rem)),
-                Bytecode::Div => try_runtime!(self.binop_int(u64::checked_div)),
-                Bytecode::BitOr => try_runtime!(self.binop_int(|l: u64, r| Some(l | r))),
-                Bytecode::BitAnd => try_runtime!(self.binop_int(|l: u64, r| Some(l & r))),
-                Bytecode::Xor => try_runtime!(self.binop_int(|l: u64, r| Some(l ^ r))),
+                Bytecode::Add => try_runtime!(self.binop_int(IntegerValue::checked_add)),
+                Bytecode::Sub => try_runtime!(self.binop_int(IntegerValue::checked_sub)),
+                Bytecode::Mul => try_runtime!(self.binop_int(IntegerValue::checked_mul)),
+                Bytecode::Mod => try_runtime!(self.binop_int(IntegerValue::checked_rem)),
+                Bytecode::Div => try_runtime!(self.binop_int(IntegerValue::checked_div)),
+                Bytecode::BitOr => try_runtime!(self.binop_int(IntegerValue::bit_or)),
+                Bytecode::BitAnd => try_runtime!(self.binop_int(IntegerValue::bit_and)),
+                Bytecode::Xor => try_runtime!(self.binop_int(IntegerValue::bit_xor)),
+                Bytecode::Shl => try_runtime!(self.shift(IntegerValue::checked_shl)),
+                Bytecode::Shr => try_runtime!(self.shift(IntegerValue::checked_shr)),
+                Bytecode::CastU8 => try_runtime!(self.cast(IntegerValue::cast_u8)),
+                Bytecode::CastU64 => try_runtime!(self.cast(IntegerValue::cast_u64)),
+                Bytecode::CastU128 => try_runtime!(self.cast(IntegerValue::cast_u128)),
                 Bytecode::Or => try_runtime!(self.binop_bool(|l, r| l || r)),
                 Bytecode::And => try_runtime!(self.binop_bool(|l, r| l && r)),
-                Bytecode::Lt => try_runtime!(self.binop_bool(|l: u64, r| l < r)),
-                Bytecode::Gt => try_runtime!(self.binop_bool(|l: u64, r| l > r)),
-                Bytecode::Le => try_runtime!(self.binop_bool(|l: u64, r| l <= r)),
-                Bytecode::Ge => try_runtime!(self.binop_bool(|l: u64, r| l >= r)),
+                Bytecode::Lt => try_runtime!(self.binop_cmp(|ord| ord == Ordering::Less)),
+                Bytecode::Gt => try_runtime!(self.binop_cmp(|ord| ord == Ordering::Greater)),
+                Bytecode::Le => try_runtime!(self.binop_cmp(|ord| ord != Ordering::Greater)),
+                Bytecode::Ge => try_runtime!(self.binop_cmp(|ord| ord != Ordering::Less)),
                 Bytecode::Abort => {
                     let error_code = try_runtime!(self.execution_stack.pop_as::<u64>());
                     return Ok(Err(VMRuntimeError {
@@ -474,95 +545,62 @@ where
                         self.txn_data.public_key().to_slice().to_vec(),
                     )));
                 }
-                Bytecode::BorrowGlobal(idx, _) => {
+                Bytecode::BorrowGlobal(idx, type_actuals_idx) => {
                     let address = try_runtime!(self.execution_stack.pop_as::<AccountAddress>());
-                    let curr_module = self.execution_stack.top_frame()?.module();
-                    let ap = make_access_path(curr_module, idx, address);
-                    if let Some(struct_def) = try_runtime!(self
-                        .execution_stack
-                        .module_cache
-                        .resolve_struct_def(curr_module, idx, &self.gas_meter))
-                    {
-                        let global_ref =
-                            try_runtime!(self.data_view.borrow_global(&ap, struct_def));
-                        try_runtime!(self.gas_meter.calculate_and_consume(
-                            &instruction,
-                            &self.execution_stack,
-                            global_ref.size()
-                        ));
-                        self.execution_stack.push(Local::GlobalRef(global_ref));
-                    } else {
-                        return Err(VMInvariantViolation::LinkerError);
-                    }
-                }
-                Bytecode::Exists(idx, _) => {
+                    let (ap, struct_def) =
+                        try_runtime!(self.resolve_resource(idx, type_actuals_idx, address));
+                    let global_ref = try_runtime!(self.data_view.borrow_global(&ap, struct_def));
+                    try_runtime!(self.gas_meter.calculate_and_consume(
+                        &instruction,
+                        &self.execution_stack,
+                        global_ref.size()
+                    ));
+                    self.execution_stack.push(Local::GlobalRef(global_ref));
+                }
+                Bytecode::Exists(idx, type_actuals_idx) => {
                     let address = try_runtime!(self.execution_stack.pop_as::<AccountAddress>());
-                    let curr_module = self.execution_stack.top_frame()?.module();
-                    let ap = make_access_path(curr_module, idx, address);
-                    if let Some(struct_def) = try_runtime!(self
-                        .execution_stack
-                        .module_cache
-                        .resolve_struct_def(curr_module, idx, &self.gas_meter))
-                    {
-                        let (exists, mem_size) = self.data_view.resource_exists(&ap, struct_def)?;
-                        try_runtime!(self.gas_meter.calculate_and_consume(
-                            &instruction,
-                            &self.execution_stack,
-                            mem_size
-                        ));
-                        self.execution_stack.push(Local::bool(exists));
-                    } else {
-                        return Err(VMInvariantViolation::LinkerError);
-                    }
-                }
-                Bytecode::MoveFrom(idx, _) => {
+                    let (ap, struct_def) =
+                        try_runtime!(self.resolve_resource(idx, type_actuals_idx, address));
+                    let (exists, mem_size) = self.data_view.resource_exists(&ap, struct_def)?;
+                    try_runtime!(self.gas_meter.calculate_and_consume(
+                        &instruction,
+                        &self.execution_stack,
+                        mem_size
+                    ));
+                    self.execution_stack.push(Local::bool(exists));
+                }
+                Bytecode::MoveFrom(idx, type_actuals_idx) => {
                     let address = try_runtime!(self.execution_stack.pop_as::<AccountAddress>());
-                    let curr_module = self.execution_stack.top_frame()?.module();
-                    let ap = make_access_path(curr_module, idx, address);
-                    if let Some(struct_def) = try_runtime!(self
-                        .execution_stack
-                        .module_cache
-                        .resolve_struct_def(curr_module, idx, &self.gas_meter))
-                    {
-                        let resource =
-                            try_runtime!(self.data_view.move_resource_from(&ap, struct_def));
+                    let (ap, struct_def) =
+                        try_runtime!(self.resolve_resource(idx, type_actuals_idx, address));
+                    let resource = try_runtime!(self.data_view.move_resource_from(&ap, struct_def));
+                    try_runtime!(self.gas_meter.calculate_and_consume(
+                        &instruction,
+                        &self.execution_stack,
+                        resource.size()
+                    ));
+                    self.execution_stack.push(resource);
+                }
+                Bytecode::MoveToSender(idx, type_actuals_idx) => {
+                    let (ap, struct_def) = try_runtime!(self.resolve_resource(
+                        idx,
+                        type_actuals_idx,
+                        self.txn_data.sender()
+                    ));
+                    let local = self.execution_stack.pop()?;
+
+                    if let Some(resource) = local.value() {
                         try_runtime!(self.gas_meter.calculate_and_consume(
                             &instruction,
                             &self.execution_stack,
                             resource.size()
                         ));
-                        self.execution_stack.push(resource);
-                    } else {
-                        return Err(VMInvariantViolation::LinkerError);
-                    }
-                }
-                Bytecode::MoveToSender(idx, _) => {
-                    let curr_module = self.execution_stack.top_frame()?.module();
-                    let ap = make_access_path(curr_module, idx, self.txn_data.sender());
-                    if let Some(struct_def) = try_runtime!(self
-                        .execution_stack
-                        .module_cache
-                        .resolve_struct_def(curr_module, idx, &self.gas_meter))
-                    {
-                        let local = self.execution_stack.pop()?;
-
-                        if let Some(resource) = local.value() {
-                            try_runtime!(self.gas_meter.calculate_and_consume(
-                                &instruction,
-                                &self.execution_stack,
-                                resource.size()
-                            ));
-                            try_runtime!(self
-                                .data_view
-                                .move_resource_to(&ap, struct_def, resource));
-                        } else {
-                            return Ok(Err(VMRuntimeError {
-                                loc: Location::new(),
-                                err: VMErrorKind::TypeError,
-                            }));
-                        }
+                        try_runtime!(self.data_view.move_resource_to(&ap, struct_def, resource));
                     } else {
-                        return Err(VMInvariantViolation::LinkerError);
+                        return Ok(Err(VMRuntimeError {
+                            loc: Location::new(),
+                            err: VMErrorKind::TypeError,
+                        }));
                     }
                 }
                 Bytecode::CreateAccount => {
@@ -599,7 +637,7 @@ where
                         .push(Local::u64(self.gas_meter.remaining_gas().get()));
                 }
             }
-            pc += 1;
+            *pc += 1;
         }
 
         if cfg!(test) || cfg!(feature = "instruction_synthesis") {
@@ -615,7 +653,9 @@ where
     pub(crate) fn setup_main_args(&mut self, args: Vec<TransactionArgument>) {
         for arg in args.into_iter() {
             self.execution_stack.push(match arg {
+                TransactionArgument::U8(i) => Local::u8(i),
                 TransactionArgument::U64(i) => Local::u64(i),
+                TransactionArgument::U128(i) => Local::u128(i),
                 TransactionArgument::Address(a) => Local::address(a),
                 TransactionArgument::ByteArray(b) => Local::bytearray(b),
                 TransactionArgument::String(s) => Local::string(s),
@@ -651,15 +691,97 @@ where
         let account_struct_def = try_runtime!(self
             .execution_stack
             .module_cache
-            .resolve_struct_def(account_module, *account_struct_id, &self.gas_meter))
+            .resolve_struct_def(account_module, *account_struct_id, &[], &self.gas_meter))
         .ok_or(VMInvariantViolation::LinkerError)?;
 
         // TODO: Adding the freshly created account's expiration date to the TransactionOutput here.
-        let account_path = make_access_path(account_module, *account_struct_id, addr);
+        let account_path = make_access_path(account_module, *account_struct_id, vec![], addr);
         self.data_view
             .move_resource_to(&account_path, account_struct_def, account_resource)
     }
 
+    /// Returns the type actuals `idx` of an instruction of the current function, with the type
+    /// parameters of the function replaced by the types of this call.
+    fn type_actuals(&self, idx: LocalsSignatureIndex) -> VMResult<Vec<TypeTag>> {
+        let frame = self.execution_stack.top_frame()?;
+        let module = frame.module();
+        let type_actuals = module
+            .locals_signature_at(idx)
+            .0
+            .iter()
+            .map(|token| type_tag(module, token, frame.type_actuals()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(VMInvariantViolation::InternalTypeError)?;
+        Ok(Ok(type_actuals))
+    }
+
+    /// Resolves the layout of a fully instantiated type.
+    fn resolve_type_tag(&self, tag: &TypeTag) -> VMResult<Type> {
+        let ty = match tag {
+            TypeTag::Bool => Type::Bool,
+            TypeTag::U8 => Type::U8,
+            TypeTag::U64 => Type::U64,
+            TypeTag::U128 => Type::U128,
+            TypeTag::String => Type::String,
+            TypeTag::ByteArray => Type::ByteArray,
+            TypeTag::Address => Type::Address,
+            TypeTag::Struct(struct_tag) => {
+                let module_id = ModuleId::new(struct_tag.address, struct_tag.module.clone());
+                let module = try_runtime!(self
+                    .execution_stack
+                    .module_cache
+                    .get_loaded_module(&module_id))
+                .ok_or(VMInvariantViolation::LinkerError)?;
+                let idx = module
+                    .struct_defs_table
+                    .get(&struct_tag.name)
+                    .ok_or(VMInvariantViolation::LinkerError)?;
+                let mut type_actuals = vec![];
+                for type_param in &struct_tag.type_params {
+                    type_actuals.push(try_runtime!(self.resolve_type_tag(type_param)));
+                }
+                if let StructFieldInformation::Native = module.struct_def_at(*idx).field_information
+                {
+                    let ty = resolve_native_struct(module.name(), &struct_tag.name, &type_actuals)
+                        .ok_or(VMInvariantViolation::LinkerError)?;
+                    return Ok(Ok(ty));
+                }
+                let struct_def = try_runtime!(self
+                    .execution_stack
+                    .module_cache
+                    .resolve_struct_def(module, *idx, &type_actuals, &self.gas_meter))
+                .ok_or(VMInvariantViolation::LinkerError)?;
+                Type::Struct(struct_def)
+            }
+        };
+        Ok(Ok(ty))
+    }
+
+    /// Resolves the access path under `address` and the layout of the resource `idx` of the
+    /// current module, instantiated with the type actuals `type_actuals_idx`.
+    fn resolve_resource(
+        &self,
+        idx: StructDefinitionIndex,
+        type_actuals_idx: LocalsSignatureIndex,
+        address: AccountAddress,
+    ) -> VMResult<(AccessPath, StructDef)> {
+        let type_actuals = try_runtime!(self.type_actuals(type_actuals_idx));
+        let mut layouts = vec![];
+        for type_actual in &type_actuals {
+            layouts.push(try_runtime!(self.resolve_type_tag(type_actual)));
+        }
+        let curr_module = self.execution_stack.top_frame()?.module();
+        let struct_def = try_runtime!(self.execution_stack.module_cache.resolve_struct_def(
+            curr_module,
+            idx,
+            &layouts,
+            &self.gas_meter
+        ))
+        .ok_or(VMInvariantViolation::LinkerError)?;
+        let ap = make_access_path(curr_module, idx, type_actuals, address);
+        Ok(Ok((ap, struct_def)))
+    }
+
     /// Run the prologue of a transaction by calling into `PROLOGUE_NAME` function stored
     /// in the `ACCOUNT_MODULE` on chain.
     pub(crate) fn run_prologue(&mut self) -> VMResult<()> {
@@ -733,7 +855,7 @@ where
             .gas_meter
             .charge_transaction_gas(self.txn_data.transaction_size, &self.execution_stack));
         let beginning_height = self.execution_stack.call_stack_height();
-        try_runtime!(self.execution_stack.push_call(func));
+        try_runtime!(self.execution_stack.push_call(func, vec![]));
         // We always start execution from the first instruction.
         let mut pc = 0;
 
@@ -9,4 +9,5 @@ mod value_serializer;
 // mod proptest_types;
 
 pub mod native_functions;
+pub mod native_structs;
 pub mod value;
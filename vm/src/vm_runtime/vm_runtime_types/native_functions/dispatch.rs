@@ -1,8 +1,8 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{hash, primitive_helpers, signature, vector};
-use crate::def::file_format::{FunctionSignature, SignatureToken, StructHandleIndex};
+use super::{hash, primitive_helpers, signature, string, vector};
+use crate::def::file_format::{FunctionSignature, Kind, SignatureToken, StructHandleIndex};
 use crate::vm_runtime::vm_runtime_types::value::Local;
 use lazy_static::lazy_static;
 use std::collections::{HashMap, VecDeque};
@@ -52,10 +52,13 @@ pub fn dispatch_native_function(
 
 macro_rules! add {
     ($m:ident, $module:expr, $name:expr, $dis:expr, $args:expr, $ret:expr) => {{
+        add!($m, $module, $name, $dis, vec![], $args, $ret)
+    }};
+    ($m:ident, $module:expr, $name:expr, $dis:expr, $kinds:expr, $args:expr, $ret:expr) => {{
         let expected_signature = FunctionSignature {
             return_types: $ret,
             arg_types: $args,
-            kind_constraints: vec![],
+            kind_constraints: $kinds,
         };
         let f = NativeFunction {
             dispatch: $dis,
@@ -123,12 +126,70 @@ lazy_static! {
             vec![ByteArray, ByteArray],
             vec![ByteArray]
         );
-        // BytearrayUtil
+        // StringUtil
+        add!(m, "StringUtil", "length",
+            string::native_length,
+            vec![String],
+            vec![U64]
+        );
+        add!(m, "StringUtil", "concat",
+            string::native_concat,
+            vec![String, String],
+            vec![String]
+        );
+        add!(m, "StringUtil", "to_bytes",
+            string::native_to_bytes,
+            vec![String],
+            vec![ByteArray]
+        );
+        add!(m, "StringUtil", "from_utf8",
+            string::native_from_utf8,
+            vec![ByteArray],
+            vec![String]
+        );
+        add!(m, "StringUtil", "substring",
+            string::native_substring,
+            vec![String, U64, U64],
+            vec![String]
+        );
+        // Vector
+        let vector_type = || Struct(StructHandleIndex(0), vec![TypeParameter(0)]);
+        add!(m, "Vector", "empty",
+            vector::native_empty,
+            vec![Kind::Resource],
+            vec![],
+            vec![vector_type()]
+        );
         add!(m, "Vector", "length",
             vector::native_length,
-            vec![Reference(Box::new(Struct(StructHandleIndex(0), vec![])))],
+            vec![Kind::Resource],
+            vec![Reference(Box::new(vector_type()))],
             vec![U64]
         );
+        add!(m, "Vector", "push_back",
+            vector::native_push_back,
+            vec![Kind::Resource],
+            vec![MutableReference(Box::new(vector_type())), TypeParameter(0)],
+            vec![]
+        );
+        add!(m, "Vector", "pop_back",
+            vector::native_pop_back,
+            vec![Kind::Resource],
+            vec![MutableReference(Box::new(vector_type()))],
+            vec![TypeParameter(0)]
+        );
+        add!(m, "Vector", "borrow",
+            vector::native_borrow,
+            vec![Kind::Resource],
+            vec![Reference(Box::new(vector_type())), U64],
+            vec![Reference(Box::new(TypeParameter(0)))]
+        );
+        add!(m, "Vector", "borrow_mut",
+            vector::native_borrow,
+            vec![Kind::Resource],
+            vec![MutableReference(Box::new(vector_type())), U64],
+            vec![MutableReference(Box::new(TypeParameter(0)))]
+        );
         m
     };
 }
@@ -6,4 +6,5 @@ pub mod dispatch;
 pub mod hash;
 pub mod primitive_helpers;
 pub mod signature;
+pub mod string;
 pub mod vector;
@@ -0,0 +1,119 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::dispatch::NativeReturnStatus;
+use crate::types::ByteArray;
+use crate::vm_runtime::vm_runtime_types::value::Local;
+use std::{collections::VecDeque, convert::TryFrom};
+
+// TODO: determine these costs experimentally
+const LENGTH_COST: u64 = 30;
+/// Base cost of the natives that read or build strings, on top of `COST_PER_BYTE`
+const BASE_COST: u64 = 30;
+/// Cost of each byte read or written, so that large strings cost more
+const COST_PER_BYTE: u64 = 1;
+
+/// Starting error code number
+const DEFAULT_ERROR_CODE: u64 = 0x0571_0000;
+/// `from_utf8` of bytes that are not valid UTF-8
+const INVALID_UTF8: u64 = DEFAULT_ERROR_CODE + 1;
+/// `substring` bounds that are out of range, reversed or not on a character boundary
+const INVALID_SUBSTRING_RANGE: u64 = DEFAULT_ERROR_CODE + 2;
+
+fn cost_of(len: usize) -> u64 {
+    BASE_COST + COST_PER_BYTE * len as u64
+}
+
+/// The length of a string in bytes of its UTF-8 encoding.
+pub fn native_length(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 1 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let s = pop_arg!(arguments, String);
+
+    let cost = LENGTH_COST;
+    let return_values = vec![Local::u64(s.len() as u64)];
+    NativeReturnStatus::Success {
+        cost,
+        return_values,
+    }
+}
+
+pub fn native_concat(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 2 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let s2 = pop_arg!(arguments, String);
+    let mut s1 = pop_arg!(arguments, String);
+    s1.push_str(&s2);
+
+    let cost = cost_of(s1.len());
+    let return_values = vec![Local::string(s1)];
+    NativeReturnStatus::Success {
+        cost,
+        return_values,
+    }
+}
+
+pub fn native_to_bytes(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 1 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let s = pop_arg!(arguments, String);
+
+    let cost = cost_of(s.len());
+    let return_values = vec![Local::bytearray(ByteArray::new(s.into_bytes()))];
+    NativeReturnStatus::Success {
+        cost,
+        return_values,
+    }
+}
+
+pub fn native_from_utf8(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 1 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let bytes = pop_arg!(arguments, ByteArray);
+
+    let cost = cost_of(bytes.len());
+    match String::from_utf8(bytes.as_bytes().to_vec()) {
+        Ok(s) => NativeReturnStatus::Success {
+            cost,
+            return_values: vec![Local::string(s)],
+        },
+        Err(_) => NativeReturnStatus::Aborted {
+            cost,
+            error_code: INVALID_UTF8,
+        },
+    }
+}
+
+/// The bytes of a string from `start` up to, but excluding, `end`, both counted in bytes.
+pub fn native_substring(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 3 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let end = pop_arg!(arguments, u64);
+    let start = pop_arg!(arguments, u64);
+    let s = pop_arg!(arguments, String);
+
+    let range = match (usize::try_from(start), usize::try_from(end)) {
+        (Ok(start), Ok(end)) => start..end,
+        _ => {
+            return NativeReturnStatus::Aborted {
+                cost: BASE_COST,
+                error_code: INVALID_SUBSTRING_RANGE,
+            }
+        }
+    };
+    match s.get(range) {
+        Some(substring) => NativeReturnStatus::Success {
+            cost: cost_of(substring.len()),
+            return_values: vec![Local::string(substring.to_string())],
+        },
+        None => NativeReturnStatus::Aborted {
+            cost: BASE_COST,
+            error_code: INVALID_SUBSTRING_RANGE,
+        },
+    }
+}
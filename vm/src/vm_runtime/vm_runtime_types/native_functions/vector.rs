@@ -2,16 +2,137 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::dispatch::NativeReturnStatus;
+use crate::def::gas_schedule::{AbstractMemorySize, GasAlgebra, GasCarrier};
 use crate::vm_runtime::vm_runtime_types::value::Local;
 use std::collections::VecDeque;
 
-const LENGTH_COST: u64 = 30; // TODO: determine experimentally
+// TODO: determine these costs experimentally
+const EMPTY_COST: u64 = 30;
+const LENGTH_COST: u64 = 30;
+const PUSH_BACK_COST: u64 = 50;
+const POP_BACK_COST: u64 = 50;
+const BORROW_COST: u64 = 30;
+/// Cost of each word of the element pushed, popped or borrowed, on top of the costs above
+const COST_PER_WORD: u64 = 1;
+
+/// Starting error code number
+const DEFAULT_ERROR_CODE: u64 = 0x0EC7_0000;
+/// Index of `borrow` or `borrow_mut` is out of the range of the vector
+const INDEX_OUT_OF_BOUNDS: u64 = DEFAULT_ERROR_CODE + 1;
+/// `pop_back` on an empty vector
+const POP_EMPTY_VECTOR: u64 = DEFAULT_ERROR_CODE + 2;
+
+fn cost_of(base: u64, size: AbstractMemorySize<GasCarrier>) -> u64 {
+    base + COST_PER_WORD * size.get()
+}
+
+pub fn native_empty(arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if !arguments.is_empty() {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let cost = EMPTY_COST;
+    let return_values = vec![Local::vector(vec![])];
+    NativeReturnStatus::Success {
+        cost,
+        return_values,
+    }
+}
+
+pub fn native_length(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 1 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let vector = arguments.pop_back().unwrap();
+    let len = match vector.vector_len() {
+        Some(len) => len,
+        None => return NativeReturnStatus::InvalidArguments,
+    };
+    if vector.release_reference().is_err() {
+        return NativeReturnStatus::InvalidArguments;
+    }
 
-#[allow(unreachable_code)]
-pub fn native_length(_arguments: VecDeque<Local>) -> NativeReturnStatus {
-    unimplemented!("Computing length of a vector collection");
     let cost = LENGTH_COST;
-    let return_values = vec![Local::u64(0)];
+    let return_values = vec![Local::u64(len)];
+    NativeReturnStatus::Success {
+        cost,
+        return_values,
+    }
+}
+
+pub fn native_push_back(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 2 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let elem = arguments.pop_back().unwrap();
+    let cost = cost_of(PUSH_BACK_COST, elem.size());
+    let elem = match elem.value() {
+        Some(elem) => elem,
+        None => return NativeReturnStatus::InvalidArguments,
+    };
+    let vector = arguments.pop_back().unwrap();
+    if vector.push_elem(elem).is_none() {
+        return NativeReturnStatus::InvalidArguments;
+    }
+
+    NativeReturnStatus::Success {
+        cost,
+        return_values: vec![],
+    }
+}
+
+pub fn native_pop_back(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 1 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let vector = arguments.pop_back().unwrap();
+    match vector.vector_len() {
+        Some(0) => {
+            return NativeReturnStatus::Aborted {
+                cost: POP_BACK_COST,
+                error_code: POP_EMPTY_VECTOR,
+            }
+        }
+        Some(_) => (),
+        None => return NativeReturnStatus::InvalidArguments,
+    }
+    let elem = match vector.pop_elem() {
+        Some(elem) => Local::Value(elem),
+        None => return NativeReturnStatus::InvalidArguments,
+    };
+
+    let cost = cost_of(POP_BACK_COST, elem.size());
+    let return_values = vec![elem];
+    NativeReturnStatus::Success {
+        cost,
+        return_values,
+    }
+}
+
+/// Implements both `borrow` and `borrow_mut`, the verifier checks the mutability of the
+/// references.
+pub fn native_borrow(mut arguments: VecDeque<Local>) -> NativeReturnStatus {
+    if arguments.len() != 2 {
+        return NativeReturnStatus::InvalidArguments;
+    }
+    let idx = pop_arg!(arguments, u64);
+    let vector = arguments.pop_back().unwrap();
+    match vector.vector_len() {
+        Some(len) if idx < len => (),
+        Some(_) => {
+            return NativeReturnStatus::Aborted {
+                cost: BORROW_COST,
+                error_code: INDEX_OUT_OF_BOUNDS,
+            }
+        }
+        None => return NativeReturnStatus::InvalidArguments,
+    }
+    let elem = match vector.borrow_elem(idx) {
+        Some(elem) => elem,
+        None => return NativeReturnStatus::InvalidArguments,
+    };
+
+    let cost = cost_of(BORROW_COST, elem.value_size());
+    let return_values = vec![elem];
     NativeReturnStatus::Success {
         cost,
         return_values,
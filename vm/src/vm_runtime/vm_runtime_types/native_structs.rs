@@ -0,0 +1,19 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Layouts of the structs declared native in Move modules.
+
+use crate::vm_runtime::loaded_data::types::Type;
+
+/// Returns the layout of the native struct `struct_name` of the module `module_name`,
+/// instantiated with the layouts `type_actuals`, or `None` if there is no such native struct.
+pub fn resolve_native_struct(
+    module_name: &str,
+    struct_name: &str,
+    type_actuals: &[Type],
+) -> Option<Type> {
+    match (module_name, struct_name, type_actuals) {
+        ("Vector", "T", [elem_type]) => Some(Type::Vector(Box::new(elem_type.clone()))),
+        _ => None,
+    }
+}
@@ -14,7 +14,9 @@ impl Value {
     pub fn single_value_strategy() -> impl Strategy<Value = Self> {
         prop_oneof![
             any::<AccountAddress>().prop_map(Value::Address),
+            any::<u8>().prop_map(Value::U8),
             any::<u64>().prop_map(Value::U64),
+            any::<u128>().prop_map(Value::U128),
             any::<bool>().prop_map(Value::Bool),
             ".*".prop_map(Value::String),
             any::<ByteArray>().prop_map(Value::ByteArray),
@@ -71,7 +73,9 @@ impl Type {
 
         prop_oneof![
             Just(Bool),
+            Just(U8),
             Just(U64),
+            Just(U128),
             Just(String),
             Just(ByteArray),
             Just(Address),
@@ -91,6 +95,7 @@ impl Type {
             prop_oneof![
                 inner.clone().prop_map(|t| Reference(Box::new(t))),
                 inner.clone().prop_map(|t| MutableReference(Box::new(t))),
+                inner.clone().prop_map(|t| Vector(Box::new(t))),
                 vec(inner, 0..10).prop_map(|defs| Struct(StructDef::new(defs))),
             ]
         })
@@ -15,6 +15,8 @@ use crate::types::{
 use crate::vm_runtime::loaded_data::{struct_def::StructDef, types::Type};
 use std::{
     cell::{Ref, RefCell},
+    cmp::Ordering,
+    convert::TryFrom,
     ops::Add,
     rc::Rc,
 };
@@ -32,17 +34,20 @@ use std::{
 #[derive(Debug, Clone)]
 pub enum Value {
     Address(AccountAddress),
+    U8(u8),
     U64(u64),
+    U128(u128),
     Bool(bool),
     String(String),
     Struct(Vec<MutVal>),
     ByteArray(ByteArray),
+    Vector(Vec<MutVal>),
 }
 
 impl Value {
     fn size(&self) -> AbstractMemorySize<GasCarrier> {
         match self {
-            Value::U64(_) | Value::Bool(_) => *CONST_SIZE,
+            Value::U8(_) | Value::U64(_) | Value::U128(_) | Value::Bool(_) => *CONST_SIZE,
             Value::Address(_) => AbstractMemorySize::new(ADDRESS_LENGTH as u64),
             // Possible debate topic: Should we charge based upon the size of the string.
             // At this moment, we take the view that you should be charged as though you are
@@ -55,6 +60,9 @@ impl Value {
                 .iter()
                 .fold(*STRUCT_SIZE, |acc, vl| acc.map2(vl.size(), Add::add)),
             Value::ByteArray(key) => AbstractMemorySize::new(key.len() as u64),
+            Value::Vector(vals) => vals
+                .iter()
+                .fold(*STRUCT_SIZE, |acc, vl| acc.map2(vl.size(), Add::add)),
         }
     }
 
@@ -70,21 +78,30 @@ impl Value {
 
         let fields = values
             .iter()
-            .map(|mut_val| {
-                let val = &*mut_val.peek();
-                match val {
-                    Value::Bool(_) => Type::Bool,
-                    Value::Address(_) => Type::Address,
-                    Value::U64(_) => Type::U64,
-                    Value::String(_) => Type::String,
-                    Value::ByteArray(_) => Type::ByteArray,
-                    Value::Struct(_) => Type::Struct(val.to_struct_def_FOR_TESTING()),
-                }
-            })
+            .map(|mut_val| mut_val.peek().to_type_FOR_TESTING())
             .collect();
         StructDef::new(fields)
     }
 
+    #[allow(non_snake_case)]
+    fn to_type_FOR_TESTING(&self) -> Type {
+        match self {
+            Value::Bool(_) => Type::Bool,
+            Value::Address(_) => Type::Address,
+            Value::U8(_) => Type::U8,
+            Value::U64(_) => Type::U64,
+            Value::U128(_) => Type::U128,
+            Value::String(_) => Type::String,
+            Value::ByteArray(_) => Type::ByteArray,
+            Value::Struct(_) => Type::Struct(self.to_struct_def_FOR_TESTING()),
+            // The element type of an empty vector does not affect its serialization
+            Value::Vector(vals) => Type::Vector(Box::new(
+                vals.first()
+                    .map_or(Type::U64, |mut_val| mut_val.peek().to_type_FOR_TESTING()),
+            )),
+        }
+    }
+
     // Structural equality for Move values
     // Cannot use Rust's equality due to:
     // - Collections possibly having different representations but still being "equal" semantically
@@ -92,7 +109,9 @@ impl Value {
         Ok(match (self, v2) {
             (Value::Bool(b1), Value::Bool(b2)) => b1 == b2,
             (Value::Address(a1), Value::Address(a2)) => a1 == a2,
+            (Value::U8(u1), Value::U8(u2)) => u1 == u2,
             (Value::U64(u1), Value::U64(u2)) => u1 == u2,
+            (Value::U128(u1), Value::U128(u2)) => u1 == u2,
             (Value::String(s1), Value::String(s2)) => s1 == s2,
             (Value::Struct(s1), Value::Struct(s2)) => {
                 if s1.len() != s2.len() {
@@ -106,6 +125,17 @@ impl Value {
                 true
             }
             (Value::ByteArray(ba1), Value::ByteArray(ba2)) => ba1 == ba2,
+            (Value::Vector(v1), Value::Vector(v2)) => {
+                if v1.len() != v2.len() {
+                    return Ok(false);
+                }
+                for (mv1, mv2) in v1.iter().zip(v2) {
+                    if !MutVal::equals(mv1, mv2)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
             _ => return Err(VMInvariantViolation::InternalTypeError),
         })
     }
@@ -116,7 +146,9 @@ impl Value {
         Ok(match (self, v2) {
             (Value::Bool(b1), Value::Bool(b2)) => b1 != b2,
             (Value::Address(a1), Value::Address(a2)) => a1 != a2,
+            (Value::U8(u1), Value::U8(u2)) => u1 != u2,
             (Value::U64(u1), Value::U64(u2)) => u1 != u2,
+            (Value::U128(u1), Value::U128(u2)) => u1 != u2,
             (Value::String(s1), Value::String(s2)) => s1 != s2,
             (Value::Struct(s1), Value::Struct(s2)) => {
                 if s1.len() != s2.len() {
@@ -130,19 +162,191 @@ impl Value {
                 false
             }
             (Value::ByteArray(ba1), Value::ByteArray(ba2)) => ba1 != ba2,
+            (Value::Vector(v1), Value::Vector(v2)) => {
+                if v1.len() != v2.len() {
+                    return Ok(true);
+                }
+                for (mv1, mv2) in v1.iter().zip(v2) {
+                    if MutVal::not_equals(mv1, mv2)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
             _ => return Err(VMInvariantViolation::InternalTypeError),
         })
     }
 }
 
+/// An unsigned integer of any of the widths the VM supports.
+///
+/// The arithmetic instructions operate on integers of the same width, which the bytecode verifier
+/// guarantees. Operations on integers of different widths fail like operations that overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegerValue {
+    U8(u8),
+    U64(u64),
+    U128(u128),
+}
+
+impl IntegerValue {
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => IntegerValue::U8(l.checked_add(r)?),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => IntegerValue::U64(l.checked_add(r)?),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => {
+                IntegerValue::U128(l.checked_add(r)?)
+            }
+            _ => return None,
+        })
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => IntegerValue::U8(l.checked_sub(r)?),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => IntegerValue::U64(l.checked_sub(r)?),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => {
+                IntegerValue::U128(l.checked_sub(r)?)
+            }
+            _ => return None,
+        })
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => IntegerValue::U8(l.checked_mul(r)?),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => IntegerValue::U64(l.checked_mul(r)?),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => {
+                IntegerValue::U128(l.checked_mul(r)?)
+            }
+            _ => return None,
+        })
+    }
+
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => IntegerValue::U8(l.checked_div(r)?),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => IntegerValue::U64(l.checked_div(r)?),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => {
+                IntegerValue::U128(l.checked_div(r)?)
+            }
+            _ => return None,
+        })
+    }
+
+    pub fn checked_rem(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => IntegerValue::U8(l.checked_rem(r)?),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => IntegerValue::U64(l.checked_rem(r)?),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => {
+                IntegerValue::U128(l.checked_rem(r)?)
+            }
+            _ => return None,
+        })
+    }
+
+    pub fn bit_or(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => IntegerValue::U8(l | r),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => IntegerValue::U64(l | r),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => IntegerValue::U128(l | r),
+            _ => return None,
+        })
+    }
+
+    pub fn bit_and(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => IntegerValue::U8(l & r),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => IntegerValue::U64(l & r),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => IntegerValue::U128(l & r),
+            _ => return None,
+        })
+    }
+
+    pub fn bit_xor(self, other: Self) -> Option<Self> {
+        Some(match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => IntegerValue::U8(l ^ r),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => IntegerValue::U64(l ^ r),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => IntegerValue::U128(l ^ r),
+            _ => return None,
+        })
+    }
+
+    /// Shifts left by `n` bits, or fails if `n` is not less than the width of the integer.
+    pub fn checked_shl(self, n: u8) -> Option<Self> {
+        let n = u32::from(n);
+        Some(match self {
+            IntegerValue::U8(i) => IntegerValue::U8(i.checked_shl(n)?),
+            IntegerValue::U64(i) => IntegerValue::U64(i.checked_shl(n)?),
+            IntegerValue::U128(i) => IntegerValue::U128(i.checked_shl(n)?),
+        })
+    }
+
+    /// Shifts right by `n` bits, or fails if `n` is not less than the width of the integer.
+    pub fn checked_shr(self, n: u8) -> Option<Self> {
+        let n = u32::from(n);
+        Some(match self {
+            IntegerValue::U8(i) => IntegerValue::U8(i.checked_shr(n)?),
+            IntegerValue::U64(i) => IntegerValue::U64(i.checked_shr(n)?),
+            IntegerValue::U128(i) => IntegerValue::U128(i.checked_shr(n)?),
+        })
+    }
+
+    /// Compares two integers of the same width.
+    pub fn compare(self, other: Self) -> Option<Ordering> {
+        match (self, other) {
+            (IntegerValue::U8(l), IntegerValue::U8(r)) => Some(l.cmp(&r)),
+            (IntegerValue::U64(l), IntegerValue::U64(r)) => Some(l.cmp(&r)),
+            (IntegerValue::U128(l), IntegerValue::U128(r)) => Some(l.cmp(&r)),
+            _ => None,
+        }
+    }
+
+    fn into_u128(self) -> u128 {
+        match self {
+            IntegerValue::U8(i) => u128::from(i),
+            IntegerValue::U64(i) => u128::from(i),
+            IntegerValue::U128(i) => i,
+        }
+    }
+
+    /// Converts to a u8, or fails if the value does not fit.
+    pub fn cast_u8(self) -> Option<Self> {
+        u8::try_from(self.into_u128()).ok().map(IntegerValue::U8)
+    }
+
+    /// Converts to a u64, or fails if the value does not fit.
+    pub fn cast_u64(self) -> Option<Self> {
+        u64::try_from(self.into_u128()).ok().map(IntegerValue::U64)
+    }
+
+    /// Converts to a u128.
+    pub fn cast_u128(self) -> Option<Self> {
+        Some(IntegerValue::U128(self.into_u128()))
+    }
+
+    pub fn into_local(self) -> Local {
+        match self {
+            IntegerValue::U8(i) => Local::u8(i),
+            IntegerValue::U64(i) => Local::u64(i),
+            IntegerValue::U128(i) => Local::u128(i),
+        }
+    }
+}
+
 pub trait Reference
 where
     Self: std::marker::Sized + Clone,
 {
     fn borrow_field(&self, idx: u32) -> Option<Self>;
+    fn borrow_elem(&self, idx: u64) -> Option<Self>;
     fn read_reference(self) -> MutVal;
     fn mutate_reference(self, v: MutVal);
 
+    fn vector_len(&self) -> Option<u64>;
+    fn push_elem(self, v: MutVal) -> Option<()>;
+    fn pop_elem(self) -> Option<MutVal>;
+
     fn size(&self) -> AbstractMemorySize<GasCarrier>;
 }
 
@@ -229,10 +433,18 @@ impl MutVal {
         MutVal::new(Value::Address(addr))
     }
 
+    fn u8(i: u8) -> Self {
+        MutVal::new(Value::U8(i))
+    }
+
     fn u64(i: u64) -> Self {
         MutVal::new(Value::U64(i))
     }
 
+    fn u128(i: u128) -> Self {
+        MutVal::new(Value::U128(i))
+    }
+
     fn bool(b: bool) -> Self {
         MutVal::new(Value::Bool(b))
     }
@@ -249,6 +461,10 @@ impl MutVal {
         MutVal::new(Value::ByteArray(v))
     }
 
+    fn vector(v: Vec<MutVal>) -> Self {
+        MutVal::new(Value::Vector(v))
+    }
+
     fn size(&self) -> AbstractMemorySize<GasCarrier> {
         self.peek().size()
     }
@@ -275,6 +491,13 @@ impl Reference for MutVal {
         }
     }
 
+    fn borrow_elem(&self, idx: u64) -> Option<Self> {
+        match &*self.peek() {
+            Value::Vector(ref vec) => vec.get(idx as usize).map(MutVal::shallow_clone),
+            _ => None,
+        }
+    }
+
     fn read_reference(self) -> MutVal {
         self.clone()
     }
@@ -283,6 +506,32 @@ impl Reference for MutVal {
         self.0.replace(v.peek().clone());
     }
 
+    fn vector_len(&self) -> Option<u64> {
+        match &*self.peek() {
+            Value::Vector(ref vec) => Some(vec.len() as u64),
+            _ => None,
+        }
+    }
+
+    fn push_elem(self, v: MutVal) -> Option<()> {
+        let mut value = self.0.borrow_mut();
+        match &mut *value {
+            Value::Vector(ref mut vec) => {
+                vec.push(v);
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn pop_elem(self) -> Option<MutVal> {
+        let mut value = self.0.borrow_mut();
+        match &mut *value {
+            Value::Vector(ref mut vec) => vec.pop(),
+            _ => None,
+        }
+    }
+
     fn size(&self) -> AbstractMemorySize<GasCarrier> {
         words_in(*REFERENCE_SIZE)
     }
@@ -293,10 +542,18 @@ impl Local {
         Local::Value(MutVal::address(addr))
     }
 
+    pub fn u8(i: u8) -> Self {
+        Local::Value(MutVal::u8(i))
+    }
+
     pub fn u64(i: u64) -> Self {
         Local::Value(MutVal::u64(i))
     }
 
+    pub fn u128(i: u128) -> Self {
+        Local::Value(MutVal::u128(i))
+    }
+
     pub fn bool(b: bool) -> Self {
         Local::Value(MutVal::bool(b))
     }
@@ -313,6 +570,10 @@ impl Local {
         Local::Value(MutVal::bytearray(v))
     }
 
+    pub fn vector(v: Vec<MutVal>) -> Self {
+        Local::Value(MutVal::vector(v))
+    }
+
     pub fn borrow_local(&self) -> Option<Self> {
         match self {
             Local::Value(v) => Some(Local::Ref(v.shallow_clone())),
@@ -328,6 +589,38 @@ impl Local {
         }
     }
 
+    pub fn borrow_elem(&self, idx: u64) -> Option<Self> {
+        match self {
+            Local::Ref(v) => v.borrow_elem(idx).map(Local::Ref),
+            Local::GlobalRef(v) => v.borrow_elem(idx).map(Local::GlobalRef),
+            _ => None,
+        }
+    }
+
+    pub fn vector_len(&self) -> Option<u64> {
+        match self {
+            Local::Ref(v) => v.vector_len(),
+            Local::GlobalRef(v) => v.vector_len(),
+            _ => None,
+        }
+    }
+
+    pub fn push_elem(self, v: MutVal) -> Option<()> {
+        match self {
+            Local::Ref(r) => r.push_elem(v),
+            Local::GlobalRef(r) => r.push_elem(v),
+            _ => None,
+        }
+    }
+
+    pub fn pop_elem(self) -> Option<MutVal> {
+        match self {
+            Local::Ref(r) => r.pop_elem(),
+            Local::GlobalRef(r) => r.pop_elem(),
+            _ => None,
+        }
+    }
+
     pub fn read_reference(self) -> Option<Self> {
         match self {
             Local::Ref(r) => Some(Local::Value(r.read_reference())),
@@ -383,6 +676,15 @@ impl Local {
         }
     }
 
+    /// The size of the value a reference points to, or of the value itself otherwise.
+    pub fn value_size(&self) -> AbstractMemorySize<GasCarrier> {
+        match self {
+            Local::Ref(v) | Local::Value(v) => v.peek().size(),
+            Local::GlobalRef(v) => v.reference.peek().size(),
+            Local::Invalid => *CONST_SIZE,
+        }
+    }
+
     // Structural equality for Move values
     // Cannot use Rust's equality due to:
     // - Internal representation of references
@@ -577,6 +879,19 @@ impl Reference for GlobalRef {
         }
     }
 
+    fn borrow_elem(&self, idx: u64) -> Option<Self> {
+        match &*self.reference.peek() {
+            Value::Vector(ref vec) => match vec.get(idx as usize) {
+                Some(elem_ref) => {
+                    self.root.borrow_mut().dec_ref_count();
+                    Some(GlobalRef::new_ref(self, elem_ref.shallow_clone()))
+                }
+                None => None,
+            },
+            _ => None,
+        }
+    }
+
     fn read_reference(self) -> MutVal {
         self.root.borrow_mut().dec_ref_count();
         self.reference.clone()
@@ -588,6 +903,22 @@ impl Reference for GlobalRef {
         self.reference.mutate_reference(v);
     }
 
+    fn vector_len(&self) -> Option<u64> {
+        self.reference.vector_len()
+    }
+
+    fn push_elem(self, v: MutVal) -> Option<()> {
+        self.root.borrow_mut().dec_ref_count();
+        self.root.borrow_mut().mark_dirty();
+        self.reference.push_elem(v)
+    }
+
+    fn pop_elem(self) -> Option<MutVal> {
+        self.root.borrow_mut().dec_ref_count();
+        self.root.borrow_mut().mark_dirty();
+        self.reference.pop_elem()
+    }
+
     fn size(&self) -> AbstractMemorySize<GasCarrier> {
         words_in(*REFERENCE_SIZE)
     }
@@ -597,6 +928,15 @@ impl Reference for GlobalRef {
 // Conversion routines for the interpreter
 //
 
+impl From<MutVal> for Option<u8> {
+    fn from(value: MutVal) -> Option<u8> {
+        match &*value.peek() {
+            Value::U8(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
 impl From<MutVal> for Option<u64> {
     fn from(value: MutVal) -> Option<u64> {
         match &*value.peek() {
@@ -606,6 +946,26 @@ impl From<MutVal> for Option<u64> {
     }
 }
 
+impl From<MutVal> for Option<u128> {
+    fn from(value: MutVal) -> Option<u128> {
+        match &*value.peek() {
+            Value::U128(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+impl From<MutVal> for Option<IntegerValue> {
+    fn from(value: MutVal) -> Option<IntegerValue> {
+        match &*value.peek() {
+            Value::U8(i) => Some(IntegerValue::U8(*i)),
+            Value::U64(i) => Some(IntegerValue::U64(*i)),
+            Value::U128(i) => Some(IntegerValue::U128(*i)),
+            _ => None,
+        }
+    }
+}
+
 impl From<MutVal> for Option<bool> {
     fn from(value: MutVal) -> Option<bool> {
         match &*value.peek() {
@@ -624,6 +984,15 @@ impl From<MutVal> for Option<AccountAddress> {
     }
 }
 
+impl From<MutVal> for Option<String> {
+    fn from(value: MutVal) -> Option<String> {
+        match &*value.peek() {
+            Value::String(s) => Some(s.clone()),
+            _ => None,
+        }
+    }
+}
+
 impl From<MutVal> for Option<ByteArray> {
     fn from(value: MutVal) -> Option<ByteArray> {
         match &*value.peek() {